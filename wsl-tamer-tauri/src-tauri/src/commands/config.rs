@@ -1,9 +1,24 @@
 //! Configuration command handlers
 
-use crate::models::{Theme, WslConfig};
-use crate::services::WslService;
+use crate::commands::{get_profile_manager, get_wsl_service};
+use crate::error::{TamerError, TamerResult};
+use crate::models::{
+    CompressionSettings, ConfigDiagnostic, ConfigOrigin, ConfigTarget, ConfigVersion, DiffLine,
+    DistroConfig, OperationTimeouts, Theme, WslConfig, WslConfigSetting, WSLCONFIG_SCHEMA,
+};
+use crate::services::ConfigValidator;
+use crate::utils::validate_distro_name;
+use std::sync::OnceLock;
 use tauri_plugin_autostart::ManagerExt;
 
+/// Global config validator instance
+static CONFIG_VALIDATOR: OnceLock<ConfigValidator> = OnceLock::new();
+
+/// Get or initialize the config validator
+pub fn get_config_validator() -> &'static ConfigValidator {
+    CONFIG_VALIDATOR.get_or_init(ConfigValidator::default)
+}
+
 /// Get app settings - check if autostart is enabled
 #[tauri::command]
 pub fn get_start_with_windows(app: tauri::AppHandle) -> bool {
@@ -12,16 +27,16 @@ pub fn get_start_with_windows(app: tauri::AppHandle) -> bool {
 
 /// Set start with Windows using autostart plugin
 #[tauri::command]
-pub fn set_start_with_windows(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+pub fn set_start_with_windows(app: tauri::AppHandle, enabled: bool) -> TamerResult<()> {
     let manager = app.autolaunch();
     if enabled {
         manager
             .enable()
-            .map_err(|e| format!("Failed to enable autostart: {}", e))
+            .map_err(|e| TamerError::Internal(format!("Failed to enable autostart: {}", e)))
     } else {
         manager
             .disable()
-            .map_err(|e| format!("Failed to disable autostart: {}", e))
+            .map_err(|e| TamerError::Internal(format!("Failed to disable autostart: {}", e)))
     }
 }
 
@@ -33,25 +48,113 @@ pub fn get_theme() -> Theme {
 
 /// Set theme
 #[tauri::command]
-pub fn set_theme(_theme: Theme) -> Result<(), String> {
+pub fn set_theme(_theme: Theme) -> TamerResult<()> {
     // Theme is persisted via tauri-plugin-store in frontend
     Ok(())
 }
 
+/// Update time limits for export/import/install operations
+#[tauri::command]
+pub fn set_operation_timeouts(timeouts: OperationTimeouts) -> TamerResult<()> {
+    get_wsl_service().set_operation_timeouts(timeouts.clone());
+    get_profile_manager().set_operation_timeouts(timeouts)
+}
+
+/// Get the default export format and compression levels
+#[tauri::command]
+pub fn get_compression_settings() -> CompressionSettings {
+    get_profile_manager().get_config().export_compression
+}
+
+/// Update the default export format and compression levels
+#[tauri::command]
+pub fn set_compression_settings(settings: CompressionSettings) -> TamerResult<()> {
+    settings.validate()?;
+    get_wsl_service().set_compression_settings(settings.clone());
+    get_profile_manager().set_compression_settings(settings)
+}
+
 // === WSL Configuration Commands (typed variants with validation) ===
 
+/// Get the schema of every .wslconfig key, for generating the editor
+#[tauri::command]
+pub fn get_wslconfig_schema() -> Vec<WslConfigSetting> {
+    WSLCONFIG_SCHEMA.to_vec()
+}
+
 /// Get global .wslconfig as typed struct
 #[tauri::command]
-pub fn get_wslconfig_typed() -> Result<WslConfig, String> {
-    let content = WslService::read_wslconfig()?;
-    WslConfig::from_ini(&content)
+pub fn get_wslconfig_typed() -> TamerResult<WslConfig> {
+    let doc = get_wsl_service().read_wslconfig_document()?;
+    Ok(WslConfig::from_document(&doc))
+}
+
+/// Check a .wslconfig against the schema and this machine
+#[tauri::command]
+pub fn validate_wslconfig(config: WslConfig) -> Vec<ConfigDiagnostic> {
+    get_config_validator().validate_wslconfig(&config)
 }
 
 /// Save global .wslconfig from typed struct — validates before writing
+///
+/// Only keys whose value changed are rewritten; comments, unknown keys and
+/// other sections are kept. Diagnostics don't block the save.
+#[tauri::command]
+pub fn save_wslconfig_typed(config: WslConfig) -> TamerResult<Vec<ConfigDiagnostic>> {
+    let diagnostics = get_config_validator().validate_wslconfig(&config);
+    let service = get_wsl_service();
+    let mut doc = service.read_wslconfig_document()?;
+    config.apply_to(&mut doc);
+    service.write_wslconfig(&doc.to_string(), ConfigOrigin::Manual)?;
+    Ok(diagnostics)
+}
+
+/// Get a distribution's wsl.conf as typed struct
+#[tauri::command]
+pub fn get_distro_config_typed(name: String) -> TamerResult<DistroConfig> {
+    let name = validate_distro_name(&name)?;
+    let doc = get_wsl_service()
+        .read_distro_config_document(name)?
+        .unwrap_or_default();
+    Ok(DistroConfig::from_document(&doc))
+}
+
+/// Save a distribution's wsl.conf from typed struct — validates before writing
+///
+/// Like [`save_wslconfig_typed`], only changed keys are rewritten and
+/// diagnostics don't block the save.
+#[tauri::command]
+pub fn save_distro_config_typed(
+    name: String,
+    config: DistroConfig,
+) -> TamerResult<Vec<ConfigDiagnostic>> {
+    let name = validate_distro_name(&name)?;
+    let diagnostics = config.diagnostics();
+    let service = get_wsl_service();
+    let mut doc = service
+        .read_distro_config_document(name)?
+        .unwrap_or_default();
+    config.apply_to(&mut doc);
+    service.write_distro_config(name, &doc.to_string(), ConfigOrigin::Manual)?;
+    Ok(diagnostics)
+}
+
+// === Configuration History Commands ===
+
+/// Earlier versions of .wslconfig and wsl.conf files, newest first
+#[tauri::command]
+pub fn get_config_history(target: Option<ConfigTarget>) -> Vec<ConfigVersion> {
+    get_wsl_service().config_history(target.as_ref())
+}
+
+/// Line diff between two versions, or a version and the current file
+#[tauri::command]
+pub fn diff_config_versions(from_id: String, to_id: Option<String>) -> TamerResult<Vec<DiffLine>> {
+    get_wsl_service().diff_config_versions(&from_id, to_id.as_deref())
+}
+
+/// Restore a configuration file to an earlier version
 #[tauri::command]
-pub fn save_wslconfig_typed(config: WslConfig) -> Result<Vec<String>, String> {
-    let warnings = config.validate();
-    let content = config.to_ini();
-    WslService::write_wslconfig(&content)?;
-    Ok(warnings)
+pub fn rollback_config(version_id: String) -> TamerResult<()> {
+    get_wsl_service().rollback_config(&version_id)
 }
@@ -0,0 +1,63 @@
+//! Virtual disk maintenance command handlers
+
+use crate::commands::{get_job_manager, get_monitoring_service, get_wsl_service};
+use crate::error::TamerResult;
+use crate::models::{CompactionReport, DiskResize, Job, JobKind};
+use crate::services::DiskService;
+use crate::utils::validate_distro_name;
+use std::sync::OnceLock;
+
+/// Global disk service instance
+static DISK_SERVICE: OnceLock<DiskService> = OnceLock::new();
+
+/// Get or initialize the disk service
+pub fn get_disk_service() -> &'static DiskService {
+    DISK_SERVICE.get_or_init(DiskService::default)
+}
+
+/// Trim, stop and compact a distribution's virtual disk (background job)
+#[tauri::command]
+pub fn compact_distribution_disk(name: String) -> TamerResult<Job> {
+    validate_distro_name(&name)?;
+    Ok(
+        get_job_manager().start(JobKind::Compact, name.clone(), move |job| {
+            let result = get_disk_service().compact(get_wsl_service(), &name, job);
+            get_monitoring_service().disks().invalidate(Some(&name));
+            result.map(|_| ())
+        }),
+    )
+}
+
+/// Before/after sizes of the last compaction of a distribution
+#[tauri::command]
+pub fn get_compaction_report(name: String) -> Option<CompactionReport> {
+    get_disk_service().last_compaction(&name)
+}
+
+/// Grow a distribution's virtual disk to `new_size` bytes
+///
+/// Validates the size and returns the plan; unless `dry_run`, also starts a
+/// background job that carries it out.
+#[tauri::command]
+pub fn resize_distribution_disk(
+    name: String,
+    new_size: u64,
+    dry_run: Option<bool>,
+) -> TamerResult<DiskResize> {
+    validate_distro_name(&name)?;
+    let plan = get_disk_service().plan_resize(get_wsl_service(), &name, new_size)?;
+    if dry_run.unwrap_or(false) {
+        return Ok(DiskResize { plan, job: None });
+    }
+
+    let job_plan = plan.clone();
+    let job = get_job_manager().start(JobKind::Resize, name.clone(), move |job| {
+        let result = get_disk_service().resize(get_wsl_service(), &job_plan, job);
+        get_monitoring_service().disks().invalidate(Some(&name));
+        result
+    });
+    Ok(DiskResize {
+        plan,
+        job: Some(job),
+    })
+}
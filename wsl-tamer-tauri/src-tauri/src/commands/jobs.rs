@@ -0,0 +1,43 @@
+//! Background job command handlers
+
+use crate::error::TamerResult;
+use crate::models::Job;
+use crate::services::JobManager;
+use std::sync::{Arc, OnceLock};
+use tauri::Emitter;
+
+/// Event emitted with the full job snapshot on every job update
+pub const JOB_PROGRESS_EVENT: &str = "job-progress";
+
+/// Global job manager instance
+static JOB_MANAGER: OnceLock<JobManager> = OnceLock::new();
+
+/// Get or initialize the job manager
+pub fn get_job_manager() -> &'static JobManager {
+    JOB_MANAGER.get_or_init(JobManager::new)
+}
+
+/// Forward job updates to the frontend as events
+pub fn init_job_events(app: tauri::AppHandle) {
+    get_job_manager().set_listener(Arc::new(move |job: &Job| {
+        let _ = app.emit(JOB_PROGRESS_EVENT, job);
+    }));
+}
+
+/// List running and recently finished jobs
+#[tauri::command]
+pub fn list_jobs() -> Vec<Job> {
+    get_job_manager().list()
+}
+
+/// Get a job by ID
+#[tauri::command]
+pub fn get_job(id: String) -> Option<Job> {
+    get_job_manager().get(&id)
+}
+
+/// Request cancellation of a running job
+#[tauri::command]
+pub fn cancel_job(id: String) -> TamerResult<()> {
+    get_job_manager().cancel(&id)
+}
@@ -5,9 +5,17 @@ mod profiles;
 mod hardware;
 mod config;
 mod monitoring;
+mod jobs;
+mod snapshots;
+mod disk;
+mod network;
 
 pub use wsl::*;
 pub use profiles::*;
 pub use hardware::*;
 pub use config::*;
 pub use monitoring::*;
+pub use jobs::*;
+pub use snapshots::*;
+pub use disk::*;
+pub use network::*;
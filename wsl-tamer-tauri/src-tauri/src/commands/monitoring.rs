@@ -1,258 +1,268 @@
 //! Monitoring command handlers for real-time WSL stats
 
-use crate::models::{DistroMetrics, SystemMetrics, WslMemoryBreakdown};
-use std::process::Command;
-
-/// Get real-time system metrics using Windows APIs + WSL query
-#[tauri::command]
-pub fn get_system_metrics() -> Result<SystemMetrics, String> {
-    use windows::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+use crate::commands::{activate_profile, get_profile_manager, get_wsl_service};
+use crate::error::TamerResult;
+use crate::models::{
+    AlertAction, AlertEvent, AlertMetric, AutomationRule, ConfigOrigin, DistributionState,
+    DistroMetrics, ListeningPort, MetricsHistory, MetricsHistorySettings, MetricsPoint,
+    MetricsResolution, ProcessNode, ProcessSignal, SystemMetrics, TriggerType, WslConfig,
+    WslDistribution,
+};
+use crate::services::{
+    show_notification, AlertEngine, MetricsHistoryStore, MonitoringService, PortScanner,
+    ProcessExplorer,
+};
+use crate::utils::{app_data_dir, system_runner, validate_distro_name};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Event carrying an [`AlertEvent`] when a resource alert fires
+pub const ALERT_FIRED_EVENT: &str = "alert-fired";
+
+/// Global monitoring service instance
+static MONITORING_SERVICE: OnceLock<MonitoringService> = OnceLock::new();
+
+/// Global metrics history, filled by the background collector
+static METRICS_HISTORY: OnceLock<MetricsHistoryStore> = OnceLock::new();
+
+/// Global alert state, fed by the background collector
+static ALERT_ENGINE: OnceLock<AlertEngine> = OnceLock::new();
+
+/// Global process explorer instance
+static PROCESS_EXPLORER: OnceLock<ProcessExplorer> = OnceLock::new();
+
+/// Global port scanner instance
+static PORT_SCANNER: OnceLock<PortScanner> = OnceLock::new();
+
+/// Get or initialize the monitoring service
+pub fn get_monitoring_service() -> &'static MonitoringService {
+    MONITORING_SERVICE.get_or_init(MonitoringService::default)
+}
 
-    // Get system memory using Windows API (instant)
-    let (total_mem, avail_mem) = unsafe {
-        let mut mem_info = MEMORYSTATUSEX::default();
-        mem_info.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
-        if GlobalMemoryStatusEx(&mut mem_info).is_ok() {
-            let total_mb = mem_info.ullTotalPhys as f64 / (1024.0 * 1024.0);
-            let avail_mb = mem_info.ullAvailPhys as f64 / (1024.0 * 1024.0);
-            (total_mb, avail_mb)
+/// Get or initialize the metrics history, restoring the saved one if
+/// persistence is enabled
+pub fn get_metrics_history_store() -> &'static MetricsHistoryStore {
+    METRICS_HISTORY.get_or_init(|| {
+        let path = app_data_dir().join("metrics-history.json");
+        if get_profile_manager().get_config().metrics_history.persist {
+            MetricsHistoryStore::load(path)
         } else {
-            (0.0, 0.0)
+            MetricsHistoryStore::new(path)
         }
-    };
-
-    // Get vmmem process memory (host-side view of WSL memory commitment)
-    let vmmem_memory = get_vmmem_memory_mb();
-
-    // Get memory limit from .wslconfig (file read is fast)
-    let memory_limit = get_wsl_memory_limit().unwrap_or(total_mem / 2.0);
-
-    // Get detailed memory breakdown from inside WSL (if running)
-    let wsl_memory = if vmmem_memory > 0.0 {
-        get_wsl_memory_breakdown().ok()
-    } else {
-        None
-    };
-
-    Ok(SystemMetrics {
-        vmmem_memory_mb: vmmem_memory,
-        wsl_memory_limit_mb: memory_limit,
-        wsl_memory,
-        wsl_cpu_percent: 0.0, // CPU tracking requires more complex setup
-        total_system_memory_mb: total_mem,
-        available_system_memory_mb: avail_mem,
-        timestamp: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs(),
     })
 }
 
-/// Get vmmem process memory in MB
-fn get_vmmem_memory_mb() -> f64 {
-    // Use PowerShell - more reliable since it handles the memory value directly
-    // Checks for both vmmem and vmmemWSL process names
-    let output = Command::new("powershell")
-        .args([
-            "-NoProfile",
-            "-Command",
-            "(Get-Process -Name 'vmmem*' -ErrorAction SilentlyContinue | Measure-Object WorkingSet64 -Sum).Sum / 1MB"
-        ])
-        .output();
-
-    if let Ok(output) = output {
-        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if let Ok(mb) = stdout.parse::<f64>() {
-            if mb > 0.0 {
-                return mb;
-            }
-        }
-    }
-
-    0.0
+/// Get or initialize the alert engine
+pub fn get_alert_engine() -> &'static AlertEngine {
+    ALERT_ENGINE.get_or_init(AlertEngine::new)
 }
 
-/// Query /proc/meminfo inside WSL for detailed memory breakdown
-fn get_wsl_memory_breakdown() -> Result<WslMemoryBreakdown, String> {
-    let output = Command::new("wsl")
-        .args(["cat", "/proc/meminfo"])
-        .output()
-        .map_err(|e| format!("Failed to run wsl: {}", e))?;
-
-    if !output.status.success() {
-        return Err("WSL command failed".to_string());
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    parse_meminfo(&stdout)
+/// Get or initialize the process explorer
+pub fn get_process_explorer() -> &'static ProcessExplorer {
+    PROCESS_EXPLORER.get_or_init(ProcessExplorer::default)
 }
 
-/// Parse /proc/meminfo output into WslMemoryBreakdown
-fn parse_meminfo(content: &str) -> Result<WslMemoryBreakdown, String> {
-    let mut breakdown = WslMemoryBreakdown::default();
-
-    for line in content.lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 2 {
-            continue;
-        }
-
-        let key = parts[0].trim_end_matches(':');
-        let value_kb: f64 = parts[1].parse().unwrap_or(0.0);
-        let value_mb = value_kb / 1024.0;
+/// Get or initialize the port scanner
+pub fn get_port_scanner() -> &'static PortScanner {
+    PORT_SCANNER.get_or_init(PortScanner::default)
+}
 
-        match key {
-            "MemTotal" => breakdown.total_mb = value_mb,
-            "MemFree" => breakdown.free_mb = value_mb,
-            "MemAvailable" => breakdown.available_mb = value_mb,
-            "Buffers" => breakdown.buffers_mb = value_mb,
-            "Cached" => breakdown.cached_mb = value_mb,
-            "SwapTotal" => breakdown.swap_total_mb = value_mb,
-            "SwapFree" => breakdown.swap_used_mb = breakdown.swap_total_mb - value_mb,
-            _ => {}
-        }
-    }
+/// Get real-time system metrics using Windows APIs + WSL query
+#[tauri::command]
+pub fn get_system_metrics() -> TamerResult<SystemMetrics> {
+    // The guest is only read while a distribution already runs
+    let distros = get_wsl_service().get_distributions().unwrap_or_default();
+    Ok(get_monitoring_service().get_system_metrics(running_distro(&distros)))
+}
 
-    // Calculate used memory: total - free - buffers - cached
-    breakdown.used_mb =
-        breakdown.total_mb - breakdown.free_mb - breakdown.buffers_mb - breakdown.cached_mb;
-    if breakdown.used_mb < 0.0 {
-        breakdown.used_mb = breakdown.total_mb - breakdown.available_mb;
-    }
+/// Get per-distribution disk metrics (VHDX file and virtual size, guest usage)
+#[tauri::command]
+pub fn get_distro_metrics() -> TamerResult<Vec<DistroMetrics>> {
+    let distros = get_wsl_service().get_distributions()?;
 
-    Ok(breakdown)
+    Ok(get_monitoring_service().get_distro_metrics(distros, true))
 }
 
-/// Get memory limit from .wslconfig
-fn get_wsl_memory_limit() -> Option<f64> {
-    let home = std::env::var("USERPROFILE").ok()?;
-    let path = std::path::Path::new(&home).join(".wslconfig");
-    let content = std::fs::read_to_string(path).ok()?;
+/// Get the metrics recorded over the last `range` seconds
+///
+/// Without a `resolution`, up to 10 minutes are returned per second and
+/// longer ranges per minute.
+#[tauri::command]
+pub fn get_metrics_history(
+    range: u64,
+    resolution: Option<MetricsResolution>,
+) -> TamerResult<MetricsHistory> {
+    get_metrics_history_store().history(range, resolution)
+}
 
-    for line in content.lines() {
-        let line = line.trim().to_lowercase();
-        if line.starts_with("memory=") || line.starts_with("memory =") {
-            let value = line.split('=').nth(1)?.trim();
-            return parse_memory_value(value);
-        }
-    }
-    None
+/// Get how metrics history is sampled and kept
+#[tauri::command]
+pub fn get_metrics_history_settings() -> MetricsHistorySettings {
+    get_profile_manager().get_config().metrics_history
 }
 
-/// Parse memory value like "8GB" or "4096MB" to MB
-fn parse_memory_value(value: &str) -> Option<f64> {
-    let value = value.to_uppercase();
-    if value.ends_with("GB") {
-        let num: f64 = value.trim_end_matches("GB").trim().parse().ok()?;
-        Some(num * 1024.0)
-    } else if value.ends_with("MB") {
-        value.trim_end_matches("MB").trim().parse().ok()
-    } else if value.ends_with("G") {
-        let num: f64 = value.trim_end_matches("G").trim().parse().ok()?;
-        Some(num * 1024.0)
-    } else if value.ends_with("M") {
-        value.trim_end_matches("M").trim().parse().ok()
+/// Update how metrics history is sampled and kept
+#[tauri::command]
+pub fn set_metrics_history_settings(settings: MetricsHistorySettings) -> TamerResult<()> {
+    settings.validate()?;
+    let store = get_metrics_history_store();
+    if settings.persist {
+        store.save()?;
     } else {
-        // Assume bytes, convert to MB
-        let bytes: f64 = value.parse().ok()?;
-        Some(bytes / (1024.0 * 1024.0))
+        store.discard_saved()?;
     }
+    get_profile_manager().set_metrics_history_settings(settings)
 }
 
-/// Get per-distribution disk metrics (lightweight version - skips expensive disk lookup)
+/// Get resource alerts that have fired and not cleared yet
 #[tauri::command]
-pub fn get_distro_metrics() -> Result<Vec<DistroMetrics>, String> {
-    use crate::services::WslService;
-
-    let distros = WslService::get_distributions()
-        .map_err(|e| format!("Failed to get distributions: {}", e))?;
-
-    let mut metrics = Vec::new();
-
-    for distro in distros {
-        // Skip expensive VHDX lookup for now - just report running state
-        metrics.push(DistroMetrics {
-            name: distro.name,
-            disk_usage_mb: 0.0, // Skip slow PowerShell call
-            disk_size_mb: 0.0,
-            is_running: distro.state == crate::models::DistributionState::Running,
-        });
-    }
-
-    Ok(metrics)
+pub fn get_active_alerts() -> Vec<AlertEvent> {
+    get_alert_engine().active()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    // --- parse_memory_value tests ---
+/// Get the process tree of a running distribution
+#[tauri::command]
+pub fn get_distro_processes(name: String) -> TamerResult<Vec<ProcessNode>> {
+    validate_distro_name(&name)?;
+    get_process_explorer().list(get_wsl_service(), &name)
+}
 
-    #[test]
-    fn test_parse_memory_gb() {
-        assert_eq!(parse_memory_value("8GB"), Some(8192.0));
-        assert_eq!(parse_memory_value("4gb"), Some(4096.0));
-    }
+/// Send a signal to a process of a running distribution
+#[tauri::command]
+pub fn kill_distro_process(name: String, pid: u32, signal: ProcessSignal) -> TamerResult<()> {
+    validate_distro_name(&name)?;
+    get_process_explorer().kill(get_wsl_service(), &name, pid, signal)
+}
 
-    #[test]
-    fn test_parse_memory_mb() {
-        assert_eq!(parse_memory_value("512MB"), Some(512.0));
-        assert_eq!(parse_memory_value("1024mb"), Some(1024.0));
-    }
+/// Get listening TCP/UDP ports of all running distributions and whether
+/// Windows can reach them under the current .wslconfig
+#[tauri::command]
+pub fn get_listening_ports() -> TamerResult<Vec<ListeningPort>> {
+    // No (or an unreadable) .wslconfig means WSL's defaults
+    let config = get_wsl_service()
+        .read_wslconfig()
+        .and_then(|content| WslConfig::from_ini(&content))
+        .unwrap_or_default();
+    get_port_scanner().list(get_wsl_service(), &config)
+}
 
-    #[test]
-    fn test_parse_memory_short_suffix() {
-        assert_eq!(parse_memory_value("4G"), Some(4096.0));
-        assert_eq!(parse_memory_value("256M"), Some(256.0));
-    }
+/// Start the background thread that records metrics history and
+/// evaluates resource alerts
+///
+/// Settings and rules are re-read before every sample, so changes apply
+/// without a restart.
+pub fn start_metrics_collector(app: AppHandle, shutdown: Arc<AtomicBool>) {
+    std::thread::spawn(move || {
+        while !shutdown.load(Ordering::Relaxed) {
+            let config = get_profile_manager().get_config();
+            let settings = config.metrics_history;
+            let alerting = config
+                .rules
+                .iter()
+                .any(|r| r.is_enabled && matches!(r.trigger_type, TriggerType::Metric));
+            if settings.enabled || alerting {
+                sample_metrics(&app, &settings, &config.rules);
+            }
+            for _ in 0..settings.interval_secs.max(1) {
+                if shutdown.load(Ordering::Relaxed) {
+                    return;
+                }
+                std::thread::sleep(Duration::from_secs(1));
+            }
+        }
+    });
+}
 
-    #[test]
-    fn test_parse_memory_bytes() {
-        // 1 GB in bytes
-        assert_eq!(parse_memory_value("1073741824"), Some(1024.0));
+/// Take one sample, record it (saving the history whenever a minute
+/// completes) and check it against the alert rules
+///
+/// Samples stay on the host so they neither start a distribution nor keep
+/// the VM from going idle. Running distributions are only read while an
+/// enabled rule watches guest CPU or disk usage.
+fn sample_metrics(app: &AppHandle, settings: &MetricsHistorySettings, rules: &[AutomationRule]) {
+    let monitoring = get_monitoring_service();
+    let distros = get_wsl_service()
+        .get_distributions()
+        .map_err(|e| log::debug!("Metrics sample without distributions: {}", e))
+        .unwrap_or_default();
+    let guest = running_distro(&distros).filter(|_| watches(rules, AlertMetric::GuestCpuPercent));
+    let system = monitoring.get_system_metrics(guest);
+    // Guest usage is only measured in distributions that already run
+    let distros =
+        monitoring.get_distro_metrics(distros, watches(rules, AlertMetric::DistroDiskPercent));
+
+    if settings.enabled {
+        let store = get_metrics_history_store();
+        if store.record(MetricsPoint::new(&system, &distros)) && settings.persist {
+            if let Err(e) = store.save() {
+                log::warn!("Failed to save metrics history: {}", e);
+            }
+        }
     }
 
-    #[test]
-    fn test_parse_memory_invalid() {
-        assert_eq!(parse_memory_value("notanumber"), None);
-        assert_eq!(parse_memory_value(""), None);
+    let engine = get_alert_engine();
+    for event in engine.evaluate(rules, &system, &distros, system.timestamp) {
+        log::info!("Alert '{}': {}", event.rule_name, event.message);
+        let _ = app.emit(ALERT_FIRED_EVENT, &event);
+        if let Some(rule) = rules.iter().find(|r| r.id == event.rule_id) {
+            run_alert_actions(&event, &rule.actions);
+        }
     }
+    update_tray_alerts(app, &engine.active());
+}
 
-    // --- parse_meminfo tests ---
-
-    #[test]
-    fn test_parse_meminfo_typical() {
-        let input = "\
-MemTotal:        8048596 kB
-MemFree:          524288 kB
-MemAvailable:    6291456 kB
-Buffers:          131072 kB
-Cached:          4194304 kB
-SwapTotal:       2097152 kB
-SwapFree:        1048576 kB";
+/// Whether an enabled rule alerts on `metric`
+fn watches(rules: &[AutomationRule], metric: AlertMetric) -> bool {
+    rules.iter().any(|r| {
+        r.is_enabled
+            && matches!(r.trigger_type, TriggerType::Metric)
+            && r.alert.as_ref().is_some_and(|a| a.metric == metric)
+    })
+}
 
-        let b = parse_meminfo(input).unwrap();
-        assert!((b.total_mb - 7860.0).abs() < 1.0);
-        assert!((b.free_mb - 512.0).abs() < 1.0);
-        assert!((b.cached_mb - 4096.0).abs() < 1.0);
-        assert!((b.buffers_mb - 128.0).abs() < 1.0);
-        assert!((b.swap_total_mb - 2048.0).abs() < 1.0);
-        assert!((b.swap_used_mb - 1024.0).abs() < 1.0);
-        assert!(b.used_mb > 0.0);
-    }
+/// Name of a distribution that is already running, if any
+fn running_distro(distros: &[WslDistribution]) -> Option<&str> {
+    distros
+        .iter()
+        .find(|d| d.state == DistributionState::Running)
+        .map(|d| d.name.as_str())
+}
 
-    #[test]
-    fn test_parse_meminfo_empty() {
-        let b = parse_meminfo("").unwrap();
-        assert_eq!(b.total_mb, 0.0);
-        assert_eq!(b.free_mb, 0.0);
+/// Carry out what a fired rule asks for, logging failures
+fn run_alert_actions(event: &AlertEvent, actions: &[AlertAction]) {
+    for action in actions {
+        let result = match action {
+            AlertAction::Notify => show_notification(
+                system_runner().as_ref(),
+                &format!("WSL Tamer: {}", event.rule_name),
+                &event.message,
+            ),
+            AlertAction::ReclaimMemory => get_wsl_service().reclaim_memory(),
+            AlertAction::ApplyProfile { profile_id } => activate_profile(
+                profile_id,
+                Some(ConfigOrigin::AutomationRule {
+                    rule_id: event.rule_id.clone(),
+                    rule_name: event.rule_name.clone(),
+                }),
+            ),
+            AlertAction::StopDistro { name } => get_wsl_service().stop_distribution(name),
+        };
+        if let Err(e) = result {
+            log::warn!("Alert '{}': {:?} failed: {}", event.rule_name, action, e);
+        }
     }
+}
 
-    #[test]
-    fn test_parse_meminfo_partial() {
-        let input = "MemTotal: 4096 kB\nMemFree: 2048 kB";
-        let b = parse_meminfo(input).unwrap();
-        assert!((b.total_mb - 4.0).abs() < 0.01);
-        assert!((b.free_mb - 2.0).abs() < 0.01);
+/// List active alerts in the tray tooltip
+fn update_tray_alerts(app: &AppHandle, active: &[AlertEvent]) {
+    let Some(tray) = app.tray_by_id("main") else {
+        return;
+    };
+    let mut tooltip = String::from("WSL Tamer");
+    for event in active {
+        tooltip.push_str(&format!("\n⚠ {}", event.message));
     }
+    let _ = tray.set_tooltip(Some(tooltip));
 }
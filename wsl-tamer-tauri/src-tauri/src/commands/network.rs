@@ -0,0 +1,106 @@
+//! Port forwarding command handlers
+
+use crate::commands::{get_profile_manager, get_wsl_service};
+use crate::error::{TamerError, TamerResult};
+use crate::models::{NetworkingMode, PortForwardRule, PortForwardStatus, WslConfig};
+use crate::services::PortForwardService;
+use crate::utils::validate_distro_name;
+use std::sync::OnceLock;
+
+/// Global port forward service instance
+static PORT_FORWARD_SERVICE: OnceLock<PortForwardService> = OnceLock::new();
+
+/// Get or initialize the port forward service
+pub fn get_port_forward_service() -> &'static PortForwardService {
+    PORT_FORWARD_SERVICE.get_or_init(PortForwardService::default)
+}
+
+/// Get all port forwarding rules
+#[tauri::command]
+pub fn get_port_forwards() -> Vec<PortForwardRule> {
+    get_profile_manager().get_config().port_forwards
+}
+
+/// Add or replace a port forwarding rule and apply all rules
+#[tauri::command]
+pub fn save_port_forward(rule: PortForwardRule) -> TamerResult<Vec<PortForwardStatus>> {
+    rule.validate()?;
+    validate_distro_name(&rule.distro)?;
+    let manager = get_profile_manager();
+    let rules = manager.get_config().port_forwards;
+    if let Some(other) = rules.iter().find(|r| {
+        r.id != rule.id
+            && r.listen_address == rule.listen_address
+            && r.listen_port == rule.listen_port
+    }) {
+        return Err(TamerError::InvalidInput(format!(
+            "{}:{} is already forwarded to {}",
+            rule.listen_address, rule.listen_port, other.distro
+        )));
+    }
+    // Proxies are matched by what they listen on, so one on the old
+    // endpoint would otherwise be left behind
+    if let Some(previous) = rules.iter().find(|r| {
+        r.id == rule.id
+            && (r.listen_address != rule.listen_address || r.listen_port != rule.listen_port)
+    }) {
+        get_port_forward_service().remove(previous)?;
+    }
+    manager.save_port_forward(rule)?;
+    apply_port_forwards()
+}
+
+/// Remove a port forwarding rule along with its proxy and firewall rule
+#[tauri::command]
+pub fn delete_port_forward(id: String) -> TamerResult<()> {
+    let manager = get_profile_manager();
+    let Some(rule) = manager
+        .get_config()
+        .port_forwards
+        .into_iter()
+        .find(|r| r.id == id)
+    else {
+        return Err(TamerError::NotFound {
+            what: format!("Port forward '{}'", id),
+        });
+    };
+    get_port_forward_service().remove(&rule)?;
+    manager.delete_port_forward(&id)
+}
+
+/// Point all proxies at the distributions' current addresses (one elevation)
+#[tauri::command]
+pub fn apply_port_forwards() -> TamerResult<Vec<PortForwardStatus>> {
+    let rules = get_profile_manager().get_config().port_forwards;
+    get_port_forward_service().sync(get_wsl_service(), &rules)
+}
+
+/// Get where each rule's proxy currently points
+#[tauri::command]
+pub fn get_port_forward_status() -> TamerResult<Vec<PortForwardStatus>> {
+    let rules = get_profile_manager().get_config().port_forwards;
+    get_port_forward_service().status(get_wsl_service(), &rules)
+}
+
+/// Re-apply port forwards when a distribution's address changed
+/// (called by the status polling thread)
+///
+/// Only NAT networking needs proxies; mirrored networking shares the
+/// host's ports.
+pub fn reapply_port_forwards() {
+    let rules = get_profile_manager().get_config().port_forwards;
+    if !rules.iter().any(|r| r.enabled) {
+        return;
+    }
+    let mode = get_wsl_service()
+        .read_wslconfig()
+        .and_then(|content| WslConfig::from_ini(&content))
+        .map(|config| config.networking_mode.unwrap_or_default())
+        .unwrap_or_default();
+    if mode != NetworkingMode::Nat {
+        return;
+    }
+    if let Err(e) = get_port_forward_service().reapply_if_changed(get_wsl_service(), &rules) {
+        log::warn!("Could not re-apply port forwards: {}", e);
+    }
+}
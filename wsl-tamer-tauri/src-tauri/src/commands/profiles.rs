@@ -1,7 +1,10 @@
 //! Profile command handlers
 
-use crate::models::{WslProfile, AutomationRule, AppConfig};
-use crate::services::ProfileManager;
+use crate::error::{TamerError, TamerResult};
+use crate::models::{WslProfile, AutomationRule, AppConfig, ConfigOrigin};
+use crate::services::{AlertEngine, ProfileManager};
+use crate::commands::get_wsl_service;
+use crate::utils::app_data_dir;
 use std::sync::OnceLock;
 
 /// Global profile manager instance
@@ -10,7 +13,7 @@ static PROFILE_MANAGER: OnceLock<ProfileManager> = OnceLock::new();
 /// Get or initialize the profile manager
 pub fn get_profile_manager() -> &'static ProfileManager {
     PROFILE_MANAGER.get_or_init(|| {
-        let manager = ProfileManager::new();
+        let manager = ProfileManager::with_config_file(app_data_dir().join("config.json"));
         manager.init_defaults();
         manager
     })
@@ -36,36 +39,48 @@ pub fn get_current_profile() -> Option<WslProfile> {
 
 /// Save (create/update) a profile
 #[tauri::command]
-pub fn save_profile(profile: WslProfile) -> Result<(), String> {
+pub fn save_profile(profile: WslProfile) -> TamerResult<()> {
     get_profile_manager().save_profile(profile)
 }
 
 /// Delete a profile
 #[tauri::command]
-pub fn delete_profile(id: String) -> Result<(), String> {
+pub fn delete_profile(id: String) -> TamerResult<()> {
     get_profile_manager().delete_profile(&id)
 }
 
 /// Set default profile
 #[tauri::command]
-pub fn set_default_profile(id: String) -> Result<(), String> {
+pub fn set_default_profile(id: String) -> TamerResult<()> {
     get_profile_manager().set_default_profile(&id)
 }
 
 /// Set current profile and apply to .wslconfig
 #[tauri::command]
-pub fn apply_profile(id: String) -> Result<(), String> {
+pub fn apply_profile(id: String) -> TamerResult<()> {
+    activate_profile(&id, None)
+}
+
+/// Apply a profile to .wslconfig and make it the current one
+///
+/// The history records the change as made by `origin`, or by the profile
+/// itself when there is none.
+pub fn activate_profile(id: &str, origin: Option<ConfigOrigin>) -> TamerResult<()> {
     let manager = get_profile_manager();
     
     // Get the profile
-    let profile = manager.get_profile(&id)
-        .ok_or("Profile not found")?;
+    let profile = manager.get_profile(id)
+        .ok_or_else(|| TamerError::NotFound { what: format!("Profile '{}'", id) })?;
     
     // Apply to .wslconfig
-    crate::services::WslService::apply_profile(&profile)?;
+    let origin = origin.unwrap_or_else(|| ConfigOrigin::Profile {
+        profile_id: profile.id.clone(),
+        profile_name: profile.name.clone(),
+    });
+    get_wsl_service().apply_profile(&profile, origin)?;
     
     // Update current profile
-    manager.set_current_profile(&id)?;
+    manager.set_current_profile(id)?;
     
     Ok(())
 }
@@ -80,19 +95,20 @@ pub fn get_automation_rules() -> Vec<AutomationRule> {
 
 /// Save an automation rule
 #[tauri::command]
-pub fn save_automation_rule(rule: AutomationRule) -> Result<(), String> {
+pub fn save_automation_rule(rule: AutomationRule) -> TamerResult<()> {
+    AlertEngine::validate_rule(&rule)?;
     get_profile_manager().save_rule(rule)
 }
 
 /// Delete an automation rule
 #[tauri::command]
-pub fn delete_automation_rule(id: String) -> Result<(), String> {
+pub fn delete_automation_rule(id: String) -> TamerResult<()> {
     get_profile_manager().delete_rule(&id)
 }
 
 /// Toggle automation rule
 #[tauri::command]
-pub fn toggle_automation_rule(id: String) -> Result<bool, String> {
+pub fn toggle_automation_rule(id: String) -> TamerResult<bool> {
     get_profile_manager().toggle_rule(&id)
 }
 
@@ -104,6 +120,7 @@ pub fn get_app_config() -> AppConfig {
 
 /// Load configuration from storage
 #[tauri::command]
-pub fn load_app_config(config: AppConfig) -> Result<(), String> {
+pub fn load_app_config(config: AppConfig) -> TamerResult<()> {
+    crate::commands::get_wsl_service().set_operation_timeouts(config.operation_timeouts.clone());
     get_profile_manager().load_config(config)
 }
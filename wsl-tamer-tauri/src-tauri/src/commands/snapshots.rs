@@ -0,0 +1,182 @@
+//! Snapshot command handlers
+
+use crate::commands::{get_job_manager, get_profile_manager, get_wsl_service};
+use crate::error::TamerResult;
+use crate::models::{Job, JobKind, JobStatus, Snapshot, SnapshotFormat, SnapshotPolicy};
+use crate::services::automation_engine::{AutomationEngine, CronSchedule};
+use crate::services::{SnapshotOptions, SnapshotService};
+use crate::utils::{validate_distro_name, validate_windows_path};
+use chrono::{Local, TimeZone};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+/// Global snapshot service instance
+static SNAPSHOT_SERVICE: OnceLock<SnapshotService> = OnceLock::new();
+
+/// How often the scheduler checks for due snapshots
+const SCHEDULER_INTERVAL_SECS: u64 = 30;
+
+/// Get or initialize the snapshot service
+pub fn get_snapshot_service() -> &'static SnapshotService {
+    SNAPSHOT_SERVICE.get_or_init(SnapshotService::default)
+}
+
+/// Take a snapshot of a distribution (background job)
+#[tauri::command]
+pub fn create_snapshot(
+    distro: String,
+    name: String,
+    format: Option<SnapshotFormat>,
+    notes: Option<String>,
+) -> TamerResult<Job> {
+    validate_distro_name(&distro)?;
+    let options = SnapshotOptions {
+        format: format.unwrap_or_default(),
+        notes,
+        automatic: false,
+    };
+    Ok(
+        get_job_manager().start(JobKind::Snapshot, distro.clone(), move |job| {
+            get_snapshot_service()
+                .create(get_wsl_service(), &distro, &name, options, job)
+                .map(|_| ())
+        }),
+    )
+}
+
+/// List snapshots, newest first, optionally for a single distribution
+#[tauri::command]
+pub fn list_snapshots(distro: Option<String>) -> Vec<Snapshot> {
+    get_snapshot_service().list(distro.as_deref())
+}
+
+/// Delete a snapshot and its data
+#[tauri::command]
+pub fn delete_snapshot(id: String) -> TamerResult<()> {
+    get_snapshot_service().delete(&id)
+}
+
+/// Replace a distribution with one of its snapshots (background job)
+#[tauri::command]
+pub fn restore_snapshot(id: String) -> TamerResult<Job> {
+    let target = get_snapshot_service().get(&id)?.distro;
+    Ok(
+        get_job_manager().start(JobKind::Restore, target, move |job| {
+            get_snapshot_service().restore_in_place(get_wsl_service(), &id, job)
+        }),
+    )
+}
+
+/// Register a snapshot as a new distribution (background job)
+#[tauri::command]
+pub fn restore_snapshot_as_new(id: String, new_name: String, location: String) -> TamerResult<Job> {
+    validate_distro_name(&new_name)?;
+    validate_windows_path(&location)?;
+    get_snapshot_service().get(&id)?;
+    Ok(
+        get_job_manager().start(JobKind::Restore, new_name.clone(), move |job| {
+            get_snapshot_service().restore_as_new(get_wsl_service(), &id, &new_name, &location, job)
+        }),
+    )
+}
+
+// === Snapshot Policies ===
+
+/// Get all snapshot schedules and retention policies
+#[tauri::command]
+pub fn get_snapshot_policies() -> Vec<SnapshotPolicy> {
+    get_profile_manager().get_snapshot_policies()
+}
+
+/// Add or replace the snapshot policy of a distribution
+#[tauri::command]
+pub fn save_snapshot_policy(policy: SnapshotPolicy) -> TamerResult<()> {
+    validate_distro_name(&policy.distro)?;
+    CronSchedule::parse(&policy.schedule)?;
+    get_profile_manager().save_snapshot_policy(policy)
+}
+
+/// Remove the snapshot policy of a distribution (its snapshots are kept)
+#[tauri::command]
+pub fn delete_snapshot_policy(distro: String) -> TamerResult<()> {
+    get_profile_manager().delete_snapshot_policy(&distro)
+}
+
+/// Apply a distribution's retention policy now, returning the deleted snapshots
+#[tauri::command]
+pub fn prune_snapshots(distro: String) -> TamerResult<Vec<Snapshot>> {
+    let Some(policy) = get_profile_manager()
+        .get_snapshot_policies()
+        .into_iter()
+        .find(|p| p.distro.eq_ignore_ascii_case(&distro))
+    else {
+        return Ok(Vec::new());
+    };
+    get_snapshot_service().apply_retention(&policy.distro, &policy.retention, &Local::now())
+}
+
+/// Start the background thread that takes scheduled snapshots
+pub fn start_snapshot_scheduler(shutdown: Arc<AtomicBool>) {
+    std::thread::spawn(move || {
+        while !shutdown.load(Ordering::Relaxed) {
+            run_due_snapshots();
+            for _ in 0..SCHEDULER_INTERVAL_SECS {
+                if shutdown.load(Ordering::Relaxed) {
+                    return;
+                }
+                std::thread::sleep(Duration::from_secs(1));
+            }
+        }
+    });
+}
+
+/// Start a snapshot job for every enabled policy whose schedule is due
+fn run_due_snapshots() {
+    let now = Local::now();
+    let manager = get_profile_manager();
+
+    for policy in manager
+        .get_snapshot_policies()
+        .into_iter()
+        .filter(|p| p.enabled)
+    {
+        let schedule = match CronSchedule::parse(&policy.schedule) {
+            Ok(schedule) => schedule,
+            Err(e) => {
+                log::warn!("Skipping snapshot policy for '{}': {}", policy.distro, e);
+                continue;
+            }
+        };
+        let last_run = policy
+            .last_run
+            .and_then(|t| Local.timestamp_opt(t as i64, 0).single());
+        if !AutomationEngine::is_schedule_due(&schedule, last_run.as_ref(), &now) {
+            continue;
+        }
+
+        // Don't pile up snapshots of a distribution that is still being exported
+        let busy = get_job_manager().list().iter().any(|job| {
+            job.kind == JobKind::Snapshot
+                && job.status == JobStatus::Running
+                && job.target.eq_ignore_ascii_case(&policy.distro)
+        });
+        if busy {
+            continue;
+        }
+
+        if let Err(e) = manager.record_snapshot_run(&policy.distro, now.timestamp() as u64) {
+            log::warn!(
+                "Failed to record snapshot run for '{}': {}",
+                policy.distro,
+                e
+            );
+            continue;
+        }
+        get_job_manager().start(JobKind::Snapshot, policy.distro.clone(), move |job| {
+            get_snapshot_service()
+                .run_policy(get_wsl_service(), &policy, job)
+                .map(|_| ())
+        });
+    }
+}
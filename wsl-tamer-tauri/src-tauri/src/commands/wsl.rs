@@ -1,163 +1,239 @@
 //! WSL command handlers
 
-use crate::models::{WslDistribution, WslStatus, OnlineDistribution, WslProfile};
+use crate::commands::get_job_manager;
+use crate::error::TamerResult;
+use crate::models::{ExportManifest, ExportOptions, Job, JobKind, MoveJournal, WslDistribution, WslStatus, OnlineDistribution, WslProfile, ConfigOrigin};
 use crate::services::WslService;
-use crate::utils::{validate_distro_name, validate_windows_path};
+use crate::utils::{read_export_manifest, validate_distro_name, validate_windows_path};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Global WSL service instance
+static WSL_SERVICE: OnceLock<WslService> = OnceLock::new();
+
+/// Get or initialize the WSL service
+pub fn get_wsl_service() -> &'static WslService {
+    WSL_SERVICE.get_or_init(WslService::default)
+}
 
 /// Get list of installed WSL distributions
 #[tauri::command]
-pub fn get_distributions() -> Result<Vec<WslDistribution>, String> {
-    WslService::get_distributions()
+pub fn get_distributions() -> TamerResult<Vec<WslDistribution>> {
+    get_wsl_service().get_distributions()
 }
 
 /// Check if WSL is currently running
 #[tauri::command]
 pub fn is_wsl_running() -> bool {
-    WslService::is_wsl_running()
+    get_wsl_service().is_wsl_running()
 }
 
 /// Get WSL status information
 #[tauri::command]
 pub fn get_wsl_status() -> WslStatus {
-    WslService::get_status()
+    get_wsl_service().get_status()
 }
 
 /// Start a distribution in terminal
 #[tauri::command]
-pub fn start_distribution(name: String) -> Result<(), String> {
+pub fn start_distribution(name: String) -> TamerResult<()> {
     let name = validate_distro_name(&name)?;
-    WslService::start_distribution(name)
+    get_wsl_service().start_distribution(name)
 }
 
 /// Start a distribution in background
 #[tauri::command]
-pub fn start_distribution_background(name: String) -> Result<(), String> {
+pub fn start_distribution_background(name: String) -> TamerResult<()> {
     let name = validate_distro_name(&name)?;
-    WslService::start_distribution_background(name)
+    get_wsl_service().start_distribution_background(name)
 }
 
 /// Stop a distribution
 #[tauri::command]
-pub fn stop_distribution(name: String) -> Result<(), String> {
+pub fn stop_distribution(name: String) -> TamerResult<()> {
     let name = validate_distro_name(&name)?;
-    WslService::stop_distribution(name)
+    get_wsl_service().stop_distribution(name)
 }
 
 /// Shutdown all WSL
 #[tauri::command]
-pub fn shutdown_wsl() -> Result<(), String> {
-    WslService::shutdown_all()
+pub fn shutdown_wsl() -> TamerResult<()> {
+    get_wsl_service().shutdown_all()
 }
 
 /// Kill all WSL processes
 #[tauri::command]
-pub fn kill_all_wsl() -> Result<(), String> {
-    WslService::kill_all()
+pub fn kill_all_wsl() -> TamerResult<()> {
+    get_wsl_service().kill_all()
 }
 
 /// Set default distribution
 #[tauri::command]
-pub fn set_default_distribution(name: String) -> Result<(), String> {
+pub fn set_default_distribution(name: String) -> TamerResult<()> {
     let name = validate_distro_name(&name)?;
-    WslService::set_default(name)
+    get_wsl_service().set_default(name)
 }
 
 /// Reclaim memory
 #[tauri::command]
-pub fn reclaim_memory() -> Result<(), String> {
-    WslService::reclaim_memory()
+pub fn reclaim_memory() -> TamerResult<()> {
+    get_wsl_service().reclaim_memory()
 }
 
-/// Export a distribution
+/// Export a distribution (background job)
+///
+/// Compression defaults to the file extension; `options` can override it
+/// and the level.
 #[tauri::command]
-pub fn export_distribution(name: String, path: String) -> Result<(), String> {
-    let name = validate_distro_name(&name)?;
-    let path = validate_windows_path(&path)?;
-    WslService::export_distribution(name, path)
+pub fn export_distribution(
+    name: String,
+    path: String,
+    options: Option<ExportOptions>,
+) -> TamerResult<Job> {
+    validate_distro_name(&name)?;
+    validate_windows_path(&path)?;
+    let options = options.unwrap_or_default();
+    Ok(get_job_manager().start(JobKind::Export, name.clone(), move |job| {
+        get_wsl_service()
+            .export_distribution(&name, &path, &options, job)
+            .map(|_| ())
+    }))
 }
 
-/// Import a distribution
+/// Import a distribution (background job)
 #[tauri::command]
-pub fn import_distribution(name: String, location: String, tar_path: String) -> Result<(), String> {
-    let name = validate_distro_name(&name)?;
-    let location = validate_windows_path(&location)?;
-    let tar_path = validate_windows_path(&tar_path)?;
-    WslService::import_distribution(name, location, tar_path)
+pub fn import_distribution(name: String, location: String, tar_path: String) -> TamerResult<Job> {
+    validate_distro_name(&name)?;
+    validate_windows_path(&location)?;
+    validate_windows_path(&tar_path)?;
+    Ok(get_job_manager().start(JobKind::Import, name.clone(), move |job| {
+        get_wsl_service().import_distribution(&name, &location, &tar_path, job)
+    }))
 }
 
-/// Clone a distribution
+/// Check an exported tarball's checksum and structure (background job)
 #[tauri::command]
-pub fn clone_distribution(source: String, new_name: String, location: String) -> Result<(), String> {
-    let source = validate_distro_name(&source)?;
-    let new_name = validate_distro_name(&new_name)?;
-    let location = validate_windows_path(&location)?;
-    WslService::clone_distribution(source, new_name, location)
+pub fn verify_export(path: String) -> TamerResult<Job> {
+    validate_windows_path(&path)?;
+    Ok(get_job_manager().start(JobKind::Verify, path.clone(), move |job| {
+        get_wsl_service().verify_export(&path, job).map(|_| ())
+    }))
 }
 
-/// Move a distribution
+/// Get the sidecar manifest written alongside an export, if any
 #[tauri::command]
-pub fn move_distribution(name: String, new_location: String) -> Result<(), String> {
-    let name = validate_distro_name(&name)?;
-    let new_location = validate_windows_path(&new_location)?;
-    WslService::move_distribution(name, new_location)
+pub fn get_export_manifest(path: String) -> TamerResult<Option<ExportManifest>> {
+    validate_windows_path(&path)?;
+    read_export_manifest(Path::new(&path))
+}
+
+/// Clone a distribution (background job)
+#[tauri::command]
+pub fn clone_distribution(source: String, new_name: String, location: String) -> TamerResult<Job> {
+    validate_distro_name(&source)?;
+    validate_distro_name(&new_name)?;
+    validate_windows_path(&location)?;
+    Ok(get_job_manager().start(JobKind::Clone, source.clone(), move |job| {
+        get_wsl_service().clone_distribution(&source, &new_name, &location, job)
+    }))
+}
+
+/// Move a distribution (background job)
+#[tauri::command]
+pub fn move_distribution(name: String, new_location: String) -> TamerResult<Job> {
+    validate_distro_name(&name)?;
+    validate_windows_path(&new_location)?;
+    Ok(get_job_manager().start(JobKind::Move, name.clone(), move |job| {
+        get_wsl_service().move_distribution(&name, &new_location, job)
+    }))
+}
+
+/// Get moves interrupted by a crash or shutdown
+#[tauri::command]
+pub fn get_pending_moves() -> Vec<MoveJournal> {
+    get_wsl_service().pending_moves()
+}
+
+/// Finish an interrupted move (background job)
+#[tauri::command]
+pub fn resume_move(id: String) -> TamerResult<Job> {
+    let target = get_wsl_service().pending_move(&id)?.distro;
+    Ok(get_job_manager().start(JobKind::Move, target, move |job| {
+        get_wsl_service().resume_move(&id, job)
+    }))
+}
+
+/// Undo an interrupted move, restoring the original location (background job)
+#[tauri::command]
+pub fn rollback_move(id: String) -> TamerResult<Job> {
+    let target = get_wsl_service().pending_move(&id)?.distro;
+    Ok(get_job_manager().start(JobKind::MoveRollback, target, move |job| {
+        get_wsl_service().rollback_move(&id, job)
+    }))
 }
 
 /// Unregister a distribution
 #[tauri::command]
-pub fn unregister_distribution(name: String) -> Result<(), String> {
+pub fn unregister_distribution(name: String) -> TamerResult<()> {
     let name = validate_distro_name(&name)?;
-    WslService::unregister_distribution(name)
+    get_wsl_service().unregister_distribution(name)
 }
 
 /// Get online distributions
 #[tauri::command]
-pub fn get_online_distributions() -> Result<Vec<OnlineDistribution>, String> {
-    WslService::get_online_distributions()
+pub fn get_online_distributions() -> TamerResult<Vec<OnlineDistribution>> {
+    get_wsl_service().get_online_distributions()
 }
 
-/// Install online distribution
+/// Install online distribution (background job)
 #[tauri::command]
-pub fn install_distribution(name: String) -> Result<(), String> {
-    let name = validate_distro_name(&name)?;
-    WslService::install_distribution(name)
+pub fn install_distribution(name: String) -> TamerResult<Job> {
+    validate_distro_name(&name)?;
+    Ok(get_job_manager().start(JobKind::Install, name.clone(), move |job| {
+        get_wsl_service().install_distribution(&name, job)
+    }))
 }
 
 /// Open Explorer to WSL path
 #[tauri::command]
-pub fn open_wsl_explorer(name: String) -> Result<(), String> {
+pub fn open_wsl_explorer(name: String) -> TamerResult<()> {
     let name = validate_distro_name(&name)?;
-    WslService::open_explorer(name)
+    get_wsl_service().open_explorer(name)
 }
 
 /// Read .wslconfig
 #[tauri::command]
-pub fn read_wslconfig() -> Result<String, String> {
-    WslService::read_wslconfig()
+pub fn read_wslconfig() -> TamerResult<String> {
+    get_wsl_service().read_wslconfig()
 }
 
 /// Write .wslconfig
 #[tauri::command]
-pub fn write_wslconfig(content: String) -> Result<(), String> {
-    WslService::write_wslconfig(&content)
+pub fn write_wslconfig(content: String) -> TamerResult<()> {
+    get_wsl_service().write_wslconfig(&content, ConfigOrigin::Manual)
 }
 
 /// Apply a profile to .wslconfig
 #[tauri::command]
-pub fn apply_wsl_profile(profile: WslProfile) -> Result<(), String> {
-    WslService::apply_profile(&profile)
+pub fn apply_wsl_profile(profile: WslProfile) -> TamerResult<()> {
+    let origin = ConfigOrigin::Profile {
+        profile_id: profile.id.clone(),
+        profile_name: profile.name.clone(),
+    };
+    get_wsl_service().apply_profile(&profile, origin)
 }
 
 /// Read distro wsl.conf
 #[tauri::command]
-pub fn read_distro_config(name: String) -> Result<String, String> {
+pub fn read_distro_config(name: String) -> TamerResult<String> {
     let name = validate_distro_name(&name)?;
-    WslService::read_distro_config(name)
+    get_wsl_service().read_distro_config(name)
 }
 
 /// Write distro wsl.conf
 #[tauri::command]
-pub fn write_distro_config(name: String, content: String) -> Result<(), String> {
+pub fn write_distro_config(name: String, content: String) -> TamerResult<()> {
     let name = validate_distro_name(&name)?;
-    WslService::write_distro_config(name, &content)
+    get_wsl_service().write_distro_config(name, &content, ConfigOrigin::Manual)
 }
 
@@ -0,0 +1,213 @@
+//! Structured error type shared by services and IPC commands
+//!
+//! Every error carries a stable machine-readable code so the frontend can
+//! branch on the kind of failure instead of matching message strings.
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// Result type used by services and commands
+pub type TamerResult<T> = Result<T, TamerError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TamerError {
+    /// WSL itself is missing (wsl.exe could not be started)
+    #[error("WSL is not installed")]
+    WslNotInstalled,
+
+    /// An external program could not be started
+    #[error("Failed to execute {program}: {message}")]
+    CommandUnavailable { program: String, message: String },
+
+    /// An external program ran but exited unsuccessfully
+    #[error("{command} failed: {stderr}")]
+    CommandFailed {
+        command: String,
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+
+    /// The named distribution is not registered
+    #[error("Distribution '{name}' not found")]
+    DistroNotFound { name: String },
+
+    /// A process was killed after exceeding its time limit
+    #[error("{command} timed out after {timeout_secs}s")]
+    TimedOut { command: String, timeout_secs: u64 },
+
+    /// The operation was cancelled by the user
+    #[error("Operation was cancelled")]
+    Cancelled,
+
+    /// The UAC prompt was dismissed
+    #[error("Elevated command failed or was cancelled")]
+    ElevationCancelled,
+
+    /// User-supplied input failed validation
+    #[error("{0}")]
+    InvalidInput(String),
+
+    /// A profile, rule or other stored item does not exist
+    #[error("{what} not found")]
+    NotFound { what: String },
+
+    /// The request is valid but not allowed in the current state
+    #[error("{0}")]
+    InvalidOperation(String),
+
+    /// The resource is busy or rate limited; retrying later may succeed
+    #[error("{0}")]
+    Busy(String),
+
+    /// A filesystem operation failed
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// Output or file content could not be parsed
+    #[error("{0}")]
+    Parse(String),
+
+    /// A multi-step operation failed part-way; `source` is the underlying cause
+    #[error("{context}: {source}")]
+    OperationFailed {
+        context: String,
+        #[source]
+        source: Box<TamerError>,
+    },
+
+    /// Internal failure (poisoned lock, invariant violation)
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl TamerError {
+    /// Filesystem error with a description of what was being attempted
+    pub fn io(context: impl Into<String>, source: std::io::Error) -> Self {
+        Self::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    /// Wrap this error with a description of the step that failed
+    pub fn context(self, context: impl Into<String>) -> Self {
+        Self::OperationFailed {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Stable machine-readable error code
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::WslNotInstalled => "WSL_NOT_INSTALLED",
+            Self::CommandUnavailable { .. } => "COMMAND_UNAVAILABLE",
+            Self::CommandFailed { .. } => "COMMAND_FAILED",
+            Self::DistroNotFound { .. } => "DISTRO_NOT_FOUND",
+            Self::TimedOut { .. } => "TIMED_OUT",
+            Self::Cancelled => "CANCELLED",
+            Self::ElevationCancelled => "ELEVATION_CANCELLED",
+            Self::InvalidInput(_) => "INVALID_INPUT",
+            Self::NotFound { .. } => "NOT_FOUND",
+            Self::InvalidOperation(_) => "INVALID_OPERATION",
+            Self::Busy(_) => "BUSY",
+            Self::Io { .. } => "IO_ERROR",
+            Self::Parse(_) => "PARSE_ERROR",
+            Self::OperationFailed { .. } => "OPERATION_FAILED",
+            Self::Internal(_) => "INTERNAL",
+        }
+    }
+}
+
+/// Serialized for IPC as `{ code, message, ...details }`
+impl Serialize for TamerError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("code", self.code())?;
+        map.serialize_entry("message", &self.to_string())?;
+
+        match self {
+            Self::CommandUnavailable { program, .. } => {
+                map.serialize_entry("program", program)?;
+            }
+            Self::CommandFailed {
+                command,
+                exit_code,
+                stderr,
+            } => {
+                map.serialize_entry("command", command)?;
+                map.serialize_entry("exitCode", exit_code)?;
+                map.serialize_entry("stderr", stderr)?;
+            }
+            Self::TimedOut {
+                command,
+                timeout_secs,
+            } => {
+                map.serialize_entry("command", command)?;
+                map.serialize_entry("timeoutSecs", timeout_secs)?;
+            }
+            Self::DistroNotFound { name } => {
+                map.serialize_entry("name", name)?;
+            }
+            Self::OperationFailed { source, .. } => {
+                map.serialize_entry("cause", source)?;
+            }
+            _ => {}
+        }
+
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_failed_serializes_details() {
+        let err = TamerError::CommandFailed {
+            command: "wsl --export Ubuntu out.tar".to_string(),
+            exit_code: Some(1),
+            stderr: "Access is denied.".to_string(),
+        };
+        let json = serde_json::to_value(&err).unwrap();
+
+        assert_eq!(json["code"], "COMMAND_FAILED");
+        assert_eq!(json["exitCode"], 1);
+        assert_eq!(json["stderr"], "Access is denied.");
+        assert_eq!(
+            json["message"],
+            "wsl --export Ubuntu out.tar failed: Access is denied."
+        );
+    }
+
+    #[test]
+    fn test_simple_variants_serialize_code_and_message() {
+        let json = serde_json::to_value(TamerError::ElevationCancelled).unwrap();
+        assert_eq!(json["code"], "ELEVATION_CANCELLED");
+        assert_eq!(json["message"], "Elevated command failed or was cancelled");
+
+        let json = serde_json::to_value(TamerError::DistroNotFound {
+            name: "Arch".to_string(),
+        })
+        .unwrap();
+        assert_eq!(json["code"], "DISTRO_NOT_FOUND");
+        assert_eq!(json["name"], "Arch");
+    }
+
+    #[test]
+    fn test_context_keeps_cause() {
+        let err = TamerError::ElevationCancelled.context("Failed to attach device");
+        assert_eq!(
+            err.to_string(),
+            "Failed to attach device: Elevated command failed or was cancelled"
+        );
+
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["code"], "OPERATION_FAILED");
+        assert_eq!(json["cause"]["code"], "ELEVATION_CANCELLED");
+    }
+}
@@ -4,10 +4,12 @@
 //! hardware passthrough, and automation.
 
 pub mod commands;
+pub mod error;
 pub mod models;
 pub mod services;
 pub mod tray;
 pub mod utils;
+pub mod vhdx;
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -18,7 +20,6 @@ use tauri::{
 };
 
 use crate::commands::*;
-use crate::services::WslService;
 use crate::tray::{build_tray_menu, generate_status_icon, handle_tray_menu_event};
 
 /// Application entry point
@@ -58,8 +59,13 @@ pub fn run() {
             reclaim_memory,
             export_distribution,
             import_distribution,
+            verify_export,
+            get_export_manifest,
             clone_distribution,
             move_distribution,
+            get_pending_moves,
+            resume_move,
+            rollback_move,
             unregister_distribution,
             get_online_distributions,
             install_distribution,
@@ -98,11 +104,52 @@ pub fn run() {
             set_start_with_windows,
             get_theme,
             set_theme,
+            set_operation_timeouts,
+            get_compression_settings,
+            set_compression_settings,
+            get_wslconfig_schema,
             get_wslconfig_typed,
             save_wslconfig_typed,
+            validate_wslconfig,
+            get_distro_config_typed,
+            save_distro_config_typed,
+            get_config_history,
+            diff_config_versions,
+            rollback_config,
             // Monitoring commands
             get_system_metrics,
             get_distro_metrics,
+            get_metrics_history,
+            get_metrics_history_settings,
+            set_metrics_history_settings,
+            get_active_alerts,
+            get_distro_processes,
+            kill_distro_process,
+            get_listening_ports,
+            // Disk commands
+            compact_distribution_disk,
+            get_compaction_report,
+            resize_distribution_disk,
+            // Port forwarding commands
+            get_port_forwards,
+            save_port_forward,
+            delete_port_forward,
+            apply_port_forwards,
+            get_port_forward_status,
+            // Job commands
+            list_jobs,
+            get_job,
+            cancel_job,
+            // Snapshot commands
+            create_snapshot,
+            list_snapshots,
+            delete_snapshot,
+            restore_snapshot,
+            restore_snapshot_as_new,
+            get_snapshot_policies,
+            save_snapshot_policy,
+            delete_snapshot_policy,
+            prune_snapshots,
             // Automation commands
             services::automation_engine::get_system_state,
             services::automation_engine::evaluate_automation_rule,
@@ -111,14 +158,43 @@ pub fn run() {
         ])
         // Setup application
         .setup(|app| {
-            // Initialize profile manager with defaults
-            let _ = get_profile_manager();
+            // Initialize profile manager with defaults and apply saved time limits and compression levels
+            let config = get_profile_manager().get_config();
+            get_wsl_service().set_operation_timeouts(config.operation_timeouts);
+            get_wsl_service().set_compression_settings(config.export_compression);
+
+            // Tell the user their unreadable settings file was kept, not overwritten
+            if let Some(backup) = get_profile_manager().corrupt_backup() {
+                let _ = services::show_notification(
+                    utils::system_runner().as_ref(),
+                    "WSL Tamer: settings could not be read",
+                    &format!(
+                        "Defaults are in use. Your previous settings were saved to {}",
+                        backup.display()
+                    ),
+                );
+            }
+
+            // Forward background job progress to the frontend
+            init_job_events(app.handle().clone());
+
+            // Log moves interrupted by a crash; the Distributions page offers
+            // to resume or roll them back
+            for journal in get_wsl_service().pending_moves() {
+                log::warn!(
+                    "Interrupted move of '{}' to '{}' found (step {:?}, id {})",
+                    journal.distro,
+                    journal.new_location,
+                    journal.step,
+                    journal.id
+                );
+            }
 
             // Build initial tray menu
             let menu = build_tray_menu(app.handle())?;
 
             // Create system tray
-            let is_running = WslService::is_wsl_running();
+            let is_running = get_wsl_service().is_wsl_running();
             let icon = generate_status_icon(is_running);
 
             let _tray = TrayIconBuilder::with_id("main")
@@ -129,20 +205,18 @@ pub fn run() {
                     handle_tray_menu_event(app, event.id().as_ref());
                 })
                 .on_tray_icon_event(|tray, event| {
-                    match event {
-                        TrayIconEvent::Click {
-                            button: MouseButton::Left,
-                            button_state: MouseButtonState::Up,
-                            ..
-                        } => {
-                            // Show settings window on left click
-                            let app = tray.app_handle();
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
+                    if let TrayIconEvent::Click {
+                        button: MouseButton::Left,
+                        button_state: MouseButtonState::Up,
+                        ..
+                    } = event
+                    {
+                        // Show settings window on left click
+                        let app = tray.app_handle();
+                        if let Some(window) = app.get_webview_window("main") {
+                            let _ = window.show();
+                            let _ = window.set_focus();
                         }
-                        _ => {}
                     }
                 })
                 .build(app)?;
@@ -169,7 +243,7 @@ pub fn run() {
 
             // Spawn polling thread
             std::thread::spawn(move || {
-                let mut last_running = WslService::is_wsl_running();
+                let mut last_running = get_wsl_service().is_wsl_running();
 
                 while !shutdown_for_thread.load(Ordering::Relaxed) {
                     // Use shorter sleep intervals for responsive shutdown
@@ -180,7 +254,7 @@ pub fn run() {
                         std::thread::sleep(Duration::from_secs(1));
                     }
 
-                    let is_running = WslService::is_wsl_running();
+                    let is_running = get_wsl_service().is_wsl_running();
                     if is_running != last_running {
                         last_running = is_running;
 
@@ -195,9 +269,20 @@ pub fn run() {
                             }
                         }
                     }
+
+                    // WSL hands out new addresses on every start
+                    if is_running {
+                        reapply_port_forwards();
+                    }
                 }
             });
 
+            // Take scheduled snapshots in the background
+            start_snapshot_scheduler(shutdown_flag.clone());
+
+            // Record metrics history for the dashboard graphs and check resource alerts
+            start_metrics_collector(app.handle().clone(), shutdown_flag.clone());
+
             // Store shutdown flag for cleanup on app exit
             app.manage(shutdown_flag);
 
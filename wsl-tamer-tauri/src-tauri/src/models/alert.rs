@@ -0,0 +1,109 @@
+//! Resource alert models (conditions and actions of `Metric` automation rules)
+
+use serde::{Deserialize, Serialize};
+
+/// Value an alert watches
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertMetric {
+    /// vmmem memory as a percentage of the .wslconfig limit
+    VmmemPercentOfLimit,
+    /// vmmem memory in MB
+    VmmemMb,
+    /// Available host memory in MB
+    HostAvailableMb,
+    /// Share of all host cores used by the VM
+    CpuPercent,
+    /// Busy share of the VM's cores
+    GuestCpuPercent,
+    /// Guest usage as a percentage of a distribution's maximum disk size
+    DistroDiskPercent,
+}
+
+impl AlertMetric {
+    /// Whether the value is per distribution
+    pub fn is_per_distro(self) -> bool {
+        matches!(self, Self::DistroDiskPercent)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::VmmemPercentOfLimit => "WSL memory",
+            Self::VmmemMb => "WSL memory",
+            Self::HostAvailableMb => "Available host memory",
+            Self::CpuPercent => "WSL CPU",
+            Self::GuestCpuPercent => "Guest CPU",
+            Self::DistroDiskPercent => "Disk usage",
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            Self::VmmemMb | Self::HostAvailableMb => " MB",
+            _ => "%",
+        }
+    }
+}
+
+/// Which side of the threshold triggers the alert
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertComparison {
+    Above,
+    Below,
+}
+
+/// When a `Metric` rule fires
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertCondition {
+    pub metric: AlertMetric,
+    pub comparison: AlertComparison,
+    pub threshold: f64,
+    /// How long the threshold must stay crossed before the rule fires
+    #[serde(default)]
+    pub for_secs: u64,
+    /// How far back across the threshold the value must go before the
+    /// alert clears and can fire again
+    #[serde(default)]
+    pub hysteresis: f64,
+    /// Minimum time between two firings
+    #[serde(default)]
+    pub cooldown_secs: u64,
+    /// Distribution for per-distribution metrics (any of them if unset)
+    #[serde(default)]
+    pub distro: Option<String>,
+}
+
+/// What a `Metric` rule does when it fires
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AlertAction {
+    /// Desktop notification
+    Notify,
+    /// Drop the VM's page cache
+    ReclaimMemory,
+    #[serde(rename_all = "camelCase")]
+    ApplyProfile {
+        profile_id: String,
+    },
+    StopDistro {
+        name: String,
+    },
+}
+
+/// A rule that fired
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertEvent {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub metric: AlertMetric,
+    pub value: f64,
+    pub threshold: f64,
+    /// Distribution the value belongs to (per-distribution metrics)
+    pub distro: Option<String>,
+    /// Unix timestamp (seconds)
+    pub fired_at: u64,
+    pub message: String,
+}
@@ -0,0 +1,81 @@
+//! Configuration file history models
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration file a version belongs to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ConfigTarget {
+    /// The global `%USERPROFILE%\.wslconfig`
+    Wslconfig,
+    /// `/etc/wsl.conf` of a distribution
+    DistroConfig { distro: String },
+}
+
+impl std::fmt::Display for ConfigTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigTarget::Wslconfig => write!(f, ".wslconfig"),
+            ConfigTarget::DistroConfig { distro } => write!(f, "{}:/etc/wsl.conf", distro),
+        }
+    }
+}
+
+/// What made a change to a configuration file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ConfigOrigin {
+    /// Edited in the app
+    Manual,
+    #[serde(rename_all = "camelCase")]
+    Profile {
+        profile_id: String,
+        profile_name: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    AutomationRule { rule_id: String, rule_name: String },
+    #[serde(rename_all = "camelCase")]
+    Rollback { version_id: String },
+}
+
+/// An earlier version of a configuration file, saved before a write
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigVersion {
+    /// Sortable id: newer versions compare greater
+    pub id: String,
+    pub target: ConfigTarget,
+    /// When the write replaced this version (Unix seconds)
+    pub replaced_at: u64,
+    /// What made the write that replaced it
+    pub replaced_by: ConfigOrigin,
+    /// `false` when the file didn't exist before the write
+    pub existed: bool,
+    pub size_bytes: u64,
+}
+
+/// A version together with the file content it saved
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredConfigVersion {
+    #[serde(flatten)]
+    pub version: ConfigVersion,
+    pub content: String,
+}
+
+/// How a line differs between two versions
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffKind {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+/// One line of a diff
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+}
@@ -0,0 +1,55 @@
+//! Configuration diagnostic models
+
+use serde::{Deserialize, Serialize};
+
+/// How serious a configuration problem is
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    /// Worth knowing, e.g. a setting that has no effect in the current mode
+    Info,
+    /// WSL starts but ignores or clamps the setting
+    Warning,
+    /// WSL rejects the setting or fails to start with it
+    Error,
+}
+
+/// A problem found in a configuration file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigDiagnostic {
+    pub severity: DiagnosticSeverity,
+    /// Key the problem is about (`None` for the file as a whole)
+    pub key: Option<String>,
+    pub message: String,
+    /// What to change to fix it
+    pub suggestion: Option<String>,
+}
+
+impl ConfigDiagnostic {
+    pub fn error(key: &str, message: impl Into<String>) -> Self {
+        Self::new(DiagnosticSeverity::Error, key, message)
+    }
+
+    pub fn warning(key: &str, message: impl Into<String>) -> Self {
+        Self::new(DiagnosticSeverity::Warning, key, message)
+    }
+
+    pub fn info(key: &str, message: impl Into<String>) -> Self {
+        Self::new(DiagnosticSeverity::Info, key, message)
+    }
+
+    pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+
+    fn new(severity: DiagnosticSeverity, key: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            key: Some(key.to_string()),
+            message: message.into(),
+            suggestion: None,
+        }
+    }
+}
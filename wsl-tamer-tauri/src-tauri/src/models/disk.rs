@@ -0,0 +1,71 @@
+//! Virtual disk maintenance models
+
+use crate::models::Job;
+use serde::{Deserialize, Serialize};
+
+/// Tool used to compact a VHDX
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum CompactionMethod {
+    /// `Optimize-VHD` from the Hyper-V PowerShell module
+    OptimizeVhd,
+    /// `compact vdisk` in a diskpart script (any Windows edition)
+    Diskpart,
+}
+
+/// Outcome of compacting a distribution's virtual disk
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactionReport {
+    pub distro: String,
+    pub vhd_path: String,
+    pub method: CompactionMethod,
+    /// `fstrim` succeeded, so freed guest blocks could be released
+    pub trimmed: bool,
+    /// Size of the VHDX file before and after, in bytes
+    pub size_before: u64,
+    pub size_after: u64,
+    pub reclaimed_bytes: u64,
+    /// Unix timestamps (seconds)
+    pub started_at: u64,
+    pub finished_at: u64,
+}
+
+/// Tool used to grow a VHDX
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ResizeMethod {
+    /// `Resize-VHD` from the Hyper-V PowerShell module
+    ResizeVhd,
+    /// `expand vdisk` in a diskpart script (any Windows edition)
+    Diskpart,
+}
+
+/// What resizing a distribution's virtual disk will do, after validation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResizePlan {
+    pub distro: String,
+    pub vhd_path: String,
+    pub method: ResizeMethod,
+    /// Maximum size of the disk now and afterwards, in bytes
+    pub current_size: u64,
+    pub new_size: u64,
+    /// Size of the VHDX file on the host
+    pub file_size: u64,
+    /// Free space on the volume holding the VHDX, if it could be read
+    pub host_free_bytes: Option<u64>,
+    /// Steps in the order they run
+    pub steps: Vec<String>,
+    /// Things worth knowing that don't prevent the resize
+    pub warnings: Vec<String>,
+}
+
+/// Result of a resize request: the plan, and the job carrying it out
+/// unless this was a dry run
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskResize {
+    pub plan: ResizePlan,
+    pub job: Option<Job>,
+}
@@ -1,6 +1,7 @@
 //! WSL Distribution models
 
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 /// Represents a WSL distribution
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,23 +11,74 @@ pub struct WslDistribution {
     pub state: DistributionState,
     pub version: String,
     pub is_default: bool,
+    /// Metadata from the Lxss registry key, when it could be read
+    #[serde(default)]
+    pub registration: Option<DistroRegistration>,
 }
 
+/// A distribution's entry under `HKCU\Software\Microsoft\Windows\CurrentVersion\Lxss`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DistroRegistration {
+    /// GUID of the registry key, including braces
+    pub id: String,
+    pub name: String,
+    /// Folder holding the distribution's files (without the `\\?\` prefix)
+    pub base_path: String,
+    /// Virtual disk file name inside `base_path` (WSL2 only)
+    pub vhd_file_name: Option<String>,
+    pub default_uid: Option<u32>,
+    /// Raw `Flags` bitmask (see the `FLAG_*` constants)
+    pub flags: Option<u32>,
+    /// 1 or 2
+    pub version: Option<u32>,
+    pub package_family_name: Option<String>,
+    pub default_environment: Vec<String>,
+}
+
+impl DistroRegistration {
+    pub const FLAG_ENABLE_INTEROP: u32 = 0x1;
+    pub const FLAG_APPEND_NT_PATH: u32 = 0x2;
+    pub const FLAG_ENABLE_DRIVE_MOUNTING: u32 = 0x4;
+    pub const FLAG_VERSION_2: u32 = 0x8;
+
+    pub fn is_wsl2(&self) -> bool {
+        match self.version {
+            Some(version) => version == 2,
+            None => self.has_flag(Self::FLAG_VERSION_2),
+        }
+    }
+
+    pub fn has_flag(&self, flag: u32) -> bool {
+        self.flags.is_some_and(|flags| flags & flag != 0)
+    }
+
+    /// Full path of the virtual disk, for WSL2 distributions
+    pub fn vhd_path(&self) -> Option<PathBuf> {
+        if !self.is_wsl2() {
+            return None;
+        }
+        let file = self
+            .vhd_file_name
+            .as_deref()
+            .unwrap_or(DEFAULT_VHD_FILE_NAME);
+        Some(Path::new(&self.base_path).join(file))
+    }
+}
+
+/// Virtual disk name WSL uses when the registry doesn't specify one
+pub const DEFAULT_VHD_FILE_NAME: &str = "ext4.vhdx";
+
 /// Distribution running state
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub enum DistributionState {
     Running,
     Stopped,
     Installing,
+    #[default]
     Unknown,
 }
 
-impl Default for DistributionState {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
-
 impl From<&str> for DistributionState {
     fn from(s: &str) -> Self {
         match s.to_lowercase().as_str() {
@@ -0,0 +1,279 @@
+//! Per-distribution `/etc/wsl.conf` model
+
+use super::diagnostic::ConfigDiagnostic;
+use super::wslconfig::{read_setting, write_setting};
+use crate::utils::IniDocument;
+use serde::{Deserialize, Serialize};
+
+/// Typed /etc/wsl.conf representation with validation
+///
+/// Fields are flattened as `<section><Key>` where the key name alone would
+/// be ambiguous (several sections have `enabled`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DistroConfig {
+    // [boot] section
+    pub systemd: Option<bool>,
+    pub boot_command: Option<String>,
+    // [automount] section
+    pub automount_enabled: Option<bool>,
+    pub automount_root: Option<String>,
+    pub automount_options: Option<String>,
+    pub mount_fs_tab: Option<bool>,
+    // [network] section
+    pub hostname: Option<String>,
+    pub generate_hosts: Option<bool>,
+    pub generate_resolv_conf: Option<bool>,
+    // [interop] section
+    pub interop_enabled: Option<bool>,
+    pub append_windows_path: Option<bool>,
+    // [user] section
+    pub default_user: Option<String>,
+    // [gpu] section
+    pub gpu_enabled: Option<bool>,
+}
+
+impl DistroConfig {
+    /// Parse from INI-format wsl.conf content
+    pub fn from_ini(content: &str) -> Self {
+        Self::from_document(&IniDocument::parse(content))
+    }
+
+    /// Read the known keys of a wsl.conf document
+    pub fn from_document(doc: &IniDocument) -> Self {
+        Self {
+            systemd: read_setting(doc, "boot", "systemd"),
+            boot_command: read_setting(doc, "boot", "command"),
+            automount_enabled: read_setting(doc, "automount", "enabled"),
+            automount_root: read_setting(doc, "automount", "root"),
+            automount_options: read_setting(doc, "automount", "options"),
+            mount_fs_tab: read_setting(doc, "automount", "mountFsTab"),
+            hostname: read_setting(doc, "network", "hostname"),
+            generate_hosts: read_setting(doc, "network", "generateHosts"),
+            generate_resolv_conf: read_setting(doc, "network", "generateResolvConf"),
+            interop_enabled: read_setting(doc, "interop", "enabled"),
+            append_windows_path: read_setting(doc, "interop", "appendWindowsPath"),
+            default_user: read_setting(doc, "user", "default"),
+            gpu_enabled: read_setting(doc, "gpu", "enabled"),
+        }
+    }
+
+    /// Write the known keys into a wsl.conf document
+    ///
+    /// Unset fields remove their key; everything else in the file is kept.
+    pub fn apply_to(&self, doc: &mut IniDocument) {
+        write_setting(doc, "boot", "systemd", self.systemd.as_ref());
+        write_setting(doc, "boot", "command", self.boot_command.as_ref());
+        write_setting(doc, "automount", "enabled", self.automount_enabled.as_ref());
+        write_setting(doc, "automount", "root", self.automount_root.as_ref());
+        write_setting(doc, "automount", "options", self.automount_options.as_ref());
+        write_setting(doc, "automount", "mountFsTab", self.mount_fs_tab.as_ref());
+        write_setting(doc, "network", "hostname", self.hostname.as_ref());
+        write_setting(
+            doc,
+            "network",
+            "generateHosts",
+            self.generate_hosts.as_ref(),
+        );
+        write_setting(
+            doc,
+            "network",
+            "generateResolvConf",
+            self.generate_resolv_conf.as_ref(),
+        );
+        write_setting(doc, "interop", "enabled", self.interop_enabled.as_ref());
+        write_setting(
+            doc,
+            "interop",
+            "appendWindowsPath",
+            self.append_windows_path.as_ref(),
+        );
+        write_setting(doc, "user", "default", self.default_user.as_ref());
+        write_setting(doc, "gpu", "enabled", self.gpu_enabled.as_ref());
+    }
+
+    /// Check values and their combinations
+    ///
+    /// Keys are reported as `section.key`.
+    pub fn diagnostics(&self) -> Vec<ConfigDiagnostic> {
+        let mut diagnostics = Vec::new();
+
+        if let Some(hostname) = &self.hostname {
+            if !is_valid_hostname(hostname) {
+                diagnostics.push(
+                    ConfigDiagnostic::error(
+                        "network.hostname",
+                        format!("'{}' is not a valid hostname", hostname),
+                    )
+                    .with_suggestion("Use letters, digits and hyphens, at most 63 per label"),
+                );
+            }
+        }
+
+        if let Some(user) = &self.default_user {
+            if !is_valid_user_name(user) {
+                diagnostics.push(
+                    ConfigDiagnostic::error(
+                        "user.default",
+                        format!("'{}' is not a valid Linux user name", user),
+                    )
+                    .with_suggestion("Use lowercase letters, digits, '_' and '-'"),
+                );
+            }
+        }
+
+        if let Some(root) = &self.automount_root {
+            if !root.starts_with('/') {
+                diagnostics.push(
+                    ConfigDiagnostic::error(
+                        "automount.root",
+                        format!("Mount root '{}' must be an absolute path", root),
+                    )
+                    .with_suggestion("Use a path such as /mnt/"),
+                );
+            }
+        }
+
+        if let Some(options) = &self.automount_options {
+            // wsl.conf allows the value to be quoted
+            let malformed = options
+                .trim_matches('"')
+                .split(',')
+                .any(|o| o.is_empty() || o.chars().any(char::is_whitespace));
+            if malformed {
+                diagnostics.push(
+                    ConfigDiagnostic::error(
+                        "automount.options",
+                        format!("Mount options '{}' are malformed", options),
+                    )
+                    .with_suggestion(
+                        "Separate options with commas and no spaces, e.g. metadata,uid=1000",
+                    ),
+                );
+            }
+        }
+
+        if self.automount_enabled == Some(false)
+            && (self.automount_root.is_some() || self.automount_options.is_some())
+        {
+            diagnostics.push(
+                ConfigDiagnostic::info(
+                    "automount.enabled",
+                    "root and options have no effect while automount is disabled",
+                )
+                .with_suggestion("Enable automount or remove root and options"),
+            );
+        }
+
+        if self.interop_enabled == Some(false) && self.append_windows_path == Some(true) {
+            diagnostics.push(
+                ConfigDiagnostic::info(
+                    "interop.appendWindowsPath",
+                    "appendWindowsPath has no effect while interop is disabled",
+                )
+                .with_suggestion("Enable interop or remove appendWindowsPath"),
+            );
+        }
+
+        diagnostics
+    }
+}
+
+/// RFC 1123 host name: dot-separated labels of letters, digits and hyphens
+fn is_valid_hostname(hostname: &str) -> bool {
+    hostname.len() <= 253
+        && hostname.split('.').all(|label| {
+            (1..=63).contains(&label.len())
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// User name as accepted by useradd's default NAME_REGEX
+fn is_valid_user_name(user: &str) -> bool {
+    let mut chars = user.chars();
+    let first_ok = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_');
+    first_ok
+        && user.len() <= 32
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WSL_CONF: &str = "\
+# Managed by hand\n\
+[boot]\n\
+systemd = true\n\
+command = service docker start\n\
+\n\
+[automount]\n\
+root = /win/\n\
+options = \"metadata,umask=22\"\n\
+\n\
+[user]\n\
+default=dev\n\
+\n\
+[time]\n\
+useWindowsTimezone=true\n";
+
+    #[test]
+    fn parse_wsl_conf() {
+        let config = DistroConfig::from_ini(WSL_CONF);
+        assert_eq!(config.systemd, Some(true));
+        assert_eq!(config.boot_command.as_deref(), Some("service docker start"));
+        assert_eq!(config.automount_root.as_deref(), Some("/win/"));
+        assert_eq!(config.default_user.as_deref(), Some("dev"));
+        assert_eq!(config.interop_enabled, None);
+    }
+
+    #[test]
+    fn apply_keeps_comments_and_unknown_sections() {
+        let mut doc = IniDocument::parse(WSL_CONF);
+        let mut config = DistroConfig::from_document(&doc);
+        config.boot_command = None;
+        config.hostname = Some("devbox".into());
+        config.gpu_enabled = Some(false);
+        config.apply_to(&mut doc);
+
+        let expected = WSL_CONF.replace("command = service docker start\n", "")
+            + "\n[network]\nhostname=devbox\n\n[gpu]\nenabled=false\n";
+        assert_eq!(doc.to_string(), expected);
+        assert_eq!(DistroConfig::from_document(&doc), config);
+    }
+
+    #[test]
+    fn diagnostics_report_bad_values() {
+        let config = DistroConfig {
+            hostname: Some("my_box".into()),
+            default_user: Some("Dev".into()),
+            automount_root: Some("mnt".into()),
+            automount_options: Some("metadata, uid=1000".into()),
+            interop_enabled: Some(false),
+            append_windows_path: Some(true),
+            ..Default::default()
+        };
+        let keys: Vec<_> = config
+            .diagnostics()
+            .into_iter()
+            .filter_map(|d| d.key)
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "network.hostname",
+                "user.default",
+                "automount.root",
+                "automount.options",
+                "interop.appendWindowsPath"
+            ]
+        );
+
+        let config = DistroConfig::from_ini(WSL_CONF);
+        assert!(config.diagnostics().is_empty());
+    }
+}
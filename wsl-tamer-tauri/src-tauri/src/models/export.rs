@@ -0,0 +1,197 @@
+//! Export integrity models
+
+use crate::error::{TamerError, TamerResult};
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// Compression applied to an exported tarball
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl Compression {
+    /// Detect from the file extension (`.tar.gz`/`.tgz`, `.tar.zst`/`.tzst`,
+    /// `.tar.xz`/`.txz`); anything else is treated as a plain tar
+    pub fn from_path(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        let ends_with_any = |suffixes: &[&str]| suffixes.iter().any(|s| name.ends_with(s));
+        if ends_with_any(&[".tar.gz", ".tgz", ".gz"]) {
+            Self::Gzip
+        } else if ends_with_any(&[".tar.zst", ".tzst", ".zst"]) {
+            Self::Zstd
+        } else if ends_with_any(&[".tar.xz", ".txz", ".xz"]) {
+            Self::Xz
+        } else {
+            Self::None
+        }
+    }
+
+    /// Conventional file extension, without the leading dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::None => "tar",
+            Self::Gzip => "tar.gz",
+            Self::Zstd => "tar.zst",
+            Self::Xz => "tar.xz",
+        }
+    }
+
+    /// Compression levels the codec accepts
+    pub fn level_range(self) -> RangeInclusive<i32> {
+        match self {
+            Self::None => 0..=0,
+            Self::Gzip | Self::Xz => 0..=9,
+            Self::Zstd => 1..=22,
+        }
+    }
+
+    pub fn check_level(self, level: i32) -> TamerResult<()> {
+        let range = self.level_range();
+        if !range.contains(&level) {
+            return Err(TamerError::InvalidInput(format!(
+                "Compression level {} is out of range for {:?} ({}-{})",
+                level,
+                self,
+                range.start(),
+                range.end()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Default format and per-codec levels for exports
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CompressionSettings {
+    /// Format suggested for new exports
+    pub default_format: Compression,
+    pub gzip_level: i32,
+    pub zstd_level: i32,
+    pub xz_level: i32,
+}
+
+impl Default for CompressionSettings {
+    fn default() -> Self {
+        Self {
+            default_format: Compression::Zstd,
+            gzip_level: 6,
+            zstd_level: 3,
+            xz_level: 6,
+        }
+    }
+}
+
+impl CompressionSettings {
+    /// Configured level for `compression`
+    pub fn level(&self, compression: Compression) -> i32 {
+        match compression {
+            Compression::None => 0,
+            Compression::Gzip => self.gzip_level,
+            Compression::Zstd => self.zstd_level,
+            Compression::Xz => self.xz_level,
+        }
+    }
+
+    pub fn validate(&self) -> TamerResult<()> {
+        for compression in [Compression::Gzip, Compression::Zstd, Compression::Xz] {
+            compression.check_level(self.level(compression))?;
+        }
+        Ok(())
+    }
+}
+
+/// Per-export overrides (both default to the extension and the settings)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportOptions {
+    pub compression: Option<Compression>,
+    pub level: Option<i32>,
+}
+
+/// Sidecar manifest written next to an exported tarball
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportManifest {
+    /// Distribution the export was taken from
+    pub distro: String,
+    /// Export file name (without directory)
+    pub file_name: String,
+    pub size_bytes: u64,
+    /// Hex-encoded SHA-256 of the export file
+    pub sha256: String,
+    /// Unix timestamp (seconds)
+    pub created_at: u64,
+    #[serde(default)]
+    pub compression: Compression,
+    /// Size of the tar before compression
+    #[serde(default)]
+    pub uncompressed_bytes: u64,
+}
+
+impl ExportManifest {
+    /// Uncompressed size divided by file size (e.g. 4.0 for a 4x reduction)
+    pub fn compression_ratio(&self) -> f64 {
+        if self.size_bytes == 0 {
+            return 1.0;
+        }
+        self.uncompressed_bytes as f64 / self.size_bytes as f64
+    }
+}
+
+/// Result of a successful `verify_export`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportVerification {
+    pub size_bytes: u64,
+    pub sha256: String,
+    /// Whether a sidecar manifest was found and its checksum matched
+    pub checksum_verified: bool,
+    /// Number of entries in the archive
+    pub entries: u64,
+    pub compression: Compression,
+    /// Size of the tar after decompression
+    pub uncompressed_bytes: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_from_extension() {
+        let detect = |name: &str| Compression::from_path(Path::new(name));
+        assert_eq!(detect("D:\\Backups\\Ubuntu.tar"), Compression::None);
+        assert_eq!(detect("Ubuntu.TAR.GZ"), Compression::Gzip);
+        assert_eq!(detect("Ubuntu.tgz"), Compression::Gzip);
+        assert_eq!(detect("Ubuntu.tar.zst"), Compression::Zstd);
+        assert_eq!(detect("Ubuntu.txz"), Compression::Xz);
+        for compression in [
+            Compression::None,
+            Compression::Gzip,
+            Compression::Zstd,
+            Compression::Xz,
+        ] {
+            let name = format!("Ubuntu.{}", compression.extension());
+            assert_eq!(detect(&name), compression);
+        }
+    }
+
+    #[test]
+    fn test_manifest_without_compression_fields_is_plain_tar() {
+        let json = r#"{"distro":"Ubuntu","fileName":"Ubuntu.tar","sizeBytes":10,"sha256":"ab","createdAt":0}"#;
+        let manifest: ExportManifest = serde_json::from_str(json).unwrap();
+        assert_eq!(manifest.compression, Compression::None);
+        assert_eq!(manifest.uncompressed_bytes, 0);
+    }
+}
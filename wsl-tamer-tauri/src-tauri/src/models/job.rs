@@ -0,0 +1,105 @@
+//! Background job models
+
+use crate::error::TamerError;
+use serde::Serialize;
+
+/// Kind of long-running operation a job performs
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum JobKind {
+    Export,
+    Import,
+    Clone,
+    Move,
+    /// Undo an interrupted move, restoring the original location
+    MoveRollback,
+    Install,
+    Snapshot,
+    Restore,
+    /// Integrity check of an exported tarball
+    Verify,
+    /// Shrink a distribution's virtual disk
+    Compact,
+    /// Grow a distribution's virtual disk and file system
+    Resize,
+}
+
+/// Step a job is currently working on
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum JobPhase {
+    #[default]
+    Queued,
+    Exporting,
+    Importing,
+    Installing,
+    Verifying,
+    /// Computing a checksum of written data
+    Checksumming,
+    /// Removing a distribution that is about to be replaced
+    Unregistering,
+    /// Releasing unused blocks inside the guest (`fstrim`)
+    Trimming,
+    /// Shutting a distribution down before touching its disk
+    Stopping,
+    /// Compacting a virtual disk
+    Compacting,
+    /// Changing the maximum size of a virtual disk
+    Resizing,
+    /// Growing the guest file system to fill its disk (`resize2fs`)
+    ExpandingFilesystem,
+    CleaningUp,
+}
+
+/// Overall state of a job
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    #[default]
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Whether the job has stopped for good
+    pub fn is_finished(&self) -> bool {
+        *self != JobStatus::Running
+    }
+}
+
+/// Error recorded on a failed job
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JobError {
+    pub code: String,
+    pub message: String,
+}
+
+impl From<&TamerError> for JobError {
+    fn from(err: &TamerError) -> Self {
+        Self {
+            code: err.code().to_string(),
+            message: err.to_string(),
+        }
+    }
+}
+
+/// Snapshot of a background job, sent to the frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Job {
+    pub id: String,
+    pub kind: JobKind,
+    /// Distribution the job operates on
+    pub target: String,
+    pub status: JobStatus,
+    pub phase: JobPhase,
+    /// Size of the file currently being written (temp or destination tar)
+    pub bytes_written: u64,
+    /// Unix timestamp (seconds)
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+    pub error: Option<JobError>,
+}
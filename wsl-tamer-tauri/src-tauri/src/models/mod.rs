@@ -1,13 +1,37 @@
 //! Data models for WSL Tamer
 
+mod alert;
 mod config;
+mod config_history;
+mod diagnostic;
+mod disk;
 mod distribution;
+mod distro_config;
+mod export;
 mod hardware;
+mod job;
 mod monitoring;
+mod move_journal;
+mod network;
+mod process;
 mod profile;
+mod snapshot;
+mod wslconfig;
 
+pub use alert::*;
 pub use config::*;
+pub use config_history::*;
+pub use diagnostic::*;
+pub use disk::*;
 pub use distribution::*;
+pub use distro_config::*;
+pub use export::*;
 pub use hardware::*;
+pub use job::*;
 pub use monitoring::*;
+pub use move_journal::*;
+pub use network::*;
+pub use process::*;
 pub use profile::*;
+pub use snapshot::*;
+pub use wslconfig::*;
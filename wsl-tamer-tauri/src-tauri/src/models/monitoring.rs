@@ -1,6 +1,7 @@
 //! Monitoring-related data models
 
-use serde::Serialize;
+use crate::error::{TamerError, TamerResult};
+use serde::{Deserialize, Serialize};
 
 /// Detailed memory breakdown from inside WSL
 #[derive(Debug, Clone, Serialize, Default)]
@@ -26,8 +27,11 @@ pub struct SystemMetrics {
     pub wsl_memory_limit_mb: f64,
     /// Detailed breakdown from inside WSL (if running)
     pub wsl_memory: Option<WslMemoryBreakdown>,
-    /// CPU usage (currently not implemented)
+    /// Host-side: share of all logical cores used by vmmem since the
+    /// previous sample (0–100)
     pub wsl_cpu_percent: f64,
+    /// Busy share of the VM's cores from /proc/stat (if running)
+    pub guest_cpu_percent: Option<f64>,
     /// Total host system memory
     pub total_system_memory_mb: f64,
     /// Available host system memory
@@ -40,7 +44,116 @@ pub struct SystemMetrics {
 #[serde(rename_all = "camelCase")]
 pub struct DistroMetrics {
     pub name: String,
-    pub disk_usage_mb: f64,
+    /// Space used inside the guest file system (last known if stopped;
+    /// `None` if it was never measured)
+    pub disk_usage_mb: Option<f64>,
+    /// Size of the VHDX file on the host
     pub disk_size_mb: f64,
+    /// Maximum size of the virtual disk
+    pub virtual_size_mb: f64,
+    /// Host space a compaction could give back (file size minus guest usage)
+    pub reclaimable_mb: Option<f64>,
+    /// Location of the VHDX (WSL2 only)
+    pub vhd_path: Option<String>,
     pub is_running: bool,
 }
+
+/// Background sampling of metrics into the history
+///
+/// Off by default: sampling starts a PowerShell query every interval.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MetricsHistorySettings {
+    pub enabled: bool,
+    /// Seconds between samples
+    pub interval_secs: u64,
+    /// Keep the history across restarts
+    pub persist: bool,
+}
+
+impl Default for MetricsHistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 5,
+            persist: false,
+        }
+    }
+}
+
+impl MetricsHistorySettings {
+    pub fn validate(&self) -> TamerResult<()> {
+        if !(1..=600).contains(&self.interval_secs) {
+            return Err(TamerError::InvalidInput(
+                "Metrics interval must be between 1 and 600 seconds".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Granularity of a metrics history query
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MetricsResolution {
+    /// One point per second, kept for 10 minutes
+    Second,
+    /// Per-minute averages, kept for 24 hours
+    Minute,
+}
+
+/// Per-distribution values of one history point
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DistroMetricsPoint {
+    pub name: String,
+    pub disk_usage_mb: Option<f64>,
+    pub disk_size_mb: f64,
+    pub is_running: bool,
+}
+
+/// System and distribution metrics at one point in time (or averaged over
+/// a minute)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsPoint {
+    /// Unix timestamp (seconds) of the sample, or of the start of its minute
+    pub timestamp: u64,
+    pub vmmem_memory_mb: f64,
+    /// Memory used inside the VM (if it was running)
+    pub wsl_memory_used_mb: Option<f64>,
+    pub wsl_cpu_percent: f64,
+    pub guest_cpu_percent: Option<f64>,
+    pub available_system_memory_mb: f64,
+    pub distros: Vec<DistroMetricsPoint>,
+}
+
+impl MetricsPoint {
+    pub fn new(system: &SystemMetrics, distros: &[DistroMetrics]) -> Self {
+        Self {
+            timestamp: system.timestamp,
+            vmmem_memory_mb: system.vmmem_memory_mb,
+            wsl_memory_used_mb: system.wsl_memory.as_ref().map(|m| m.used_mb),
+            wsl_cpu_percent: system.wsl_cpu_percent,
+            guest_cpu_percent: system.guest_cpu_percent,
+            available_system_memory_mb: system.available_system_memory_mb,
+            distros: distros
+                .iter()
+                .map(|d| DistroMetricsPoint {
+                    name: d.name.clone(),
+                    disk_usage_mb: d.disk_usage_mb,
+                    disk_size_mb: d.disk_size_mb,
+                    is_running: d.is_running,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Result of a history query, oldest point first
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsHistory {
+    pub resolution: MetricsResolution,
+    pub points: Vec<MetricsPoint>,
+}
@@ -0,0 +1,37 @@
+//! Write-ahead journal for distribution moves
+
+use serde::{Deserialize, Serialize};
+
+/// Last step of a move that is known to have completed
+///
+/// Steps are persisted *after* they succeed, so on recovery the recorded
+/// step tells which state the distribution is guaranteed to be in.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum MoveStep {
+    /// Export to the tar has started; the original is untouched
+    Exporting,
+    /// The tar is complete; the original is still registered
+    Exported,
+    /// The original has been unregistered; the tar is the only copy
+    Unregistered,
+    /// The distribution is registered at the new location; only cleanup remains
+    Imported,
+}
+
+/// Persisted state of an in-flight move
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveJournal {
+    pub id: String,
+    pub distro: String,
+    pub new_location: String,
+    /// Where the distribution lived before the move, used for rollback
+    pub original_location: Option<String>,
+    /// Intermediate export
+    pub tar_path: String,
+    pub step: MoveStep,
+    /// Unix timestamp (seconds)
+    pub started_at: u64,
+    pub updated_at: u64,
+}
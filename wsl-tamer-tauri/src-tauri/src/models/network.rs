@@ -0,0 +1,101 @@
+//! Networking models (guest sockets and Windows port forwarding)
+
+use crate::error::{TamerError, TamerResult};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr};
+
+/// Transport of a socket
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum SocketProtocol {
+    Tcp,
+    Udp,
+}
+
+/// How Windows can reach a port opened inside a distribution
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WindowsReachability {
+    /// At `localhost:<port>` (localhost forwarding, mirrored networking or WSL 1)
+    Localhost,
+    /// Only at the VM's (or, bridged, the distribution's) own IP address
+    VmAddress,
+    /// Bound to the guest's loopback only
+    Unreachable,
+}
+
+/// A socket accepting connections (TCP) or datagrams (UDP) inside a distribution
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ListeningPort {
+    /// Distribution owning the socket
+    ///
+    /// WSL 2 distributions share one network namespace, so this is the
+    /// distribution whose process holds the socket; for sockets no visible
+    /// process holds, the first distribution that listed it.
+    pub distro: String,
+    pub protocol: SocketProtocol,
+    /// Local address (`0.0.0.0` / `::` when bound to all interfaces)
+    pub address: IpAddr,
+    pub port: u16,
+    pub pid: Option<u32>,
+    /// Executable name of the owning process
+    pub process: Option<String>,
+    pub reachability: WindowsReachability,
+}
+
+/// A Windows port proxied to a port inside a distribution
+/// (`netsh interface portproxy`), for NAT networking
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PortForwardRule {
+    pub id: String,
+    pub enabled: bool,
+    /// Windows address to listen on (`0.0.0.0` for all interfaces)
+    pub listen_address: Ipv4Addr,
+    pub listen_port: u16,
+    pub distro: String,
+    pub target_port: u16,
+    /// Also allow inbound connections to `listen_port` in Windows Firewall
+    #[serde(default)]
+    pub open_firewall: bool,
+}
+
+impl PortForwardRule {
+    pub fn validate(&self) -> TamerResult<()> {
+        // Ends up in a firewall rule name and an elevated script
+        if self.id.is_empty()
+            || !self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(TamerError::InvalidInput(format!(
+                "Invalid port forward id '{}'",
+                self.id
+            )));
+        }
+        if self.listen_port == 0 || self.target_port == 0 {
+            return Err(TamerError::InvalidInput(
+                "Ports must be between 1 and 65535".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Name of the Windows Firewall rule opened for this forward
+    pub fn firewall_rule_name(&self) -> String {
+        format!("WSL-Tamer-{}", self.id)
+    }
+}
+
+/// Where a forwarding rule currently points
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PortForwardStatus {
+    pub rule_id: String,
+    /// Address the Windows port is proxied to, if a proxy exists
+    pub connect_address: Option<Ipv4Addr>,
+    /// The proxy points at the distribution's current address
+    pub active: bool,
+}
@@ -0,0 +1,63 @@
+//! Guest process models
+
+use serde::{Deserialize, Serialize};
+
+/// A process inside a distribution, read from /proc
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DistroProcess {
+    pub pid: u32,
+    pub ppid: u32,
+    /// User name, or the numeric UID if it isn't in /etc/passwd
+    pub user: String,
+    /// Executable name (`comm`)
+    pub name: String,
+    /// Full command line, or `[name]` when it is empty
+    pub command: String,
+    /// Single-letter state (R, S, D, Z, ...)
+    pub state: String,
+    /// CPU time over the process's lifetime, as `ps` reports it
+    pub cpu_percent: f64,
+    /// Resident memory in bytes
+    pub rss_bytes: u64,
+    pub threads: u32,
+    /// Unix timestamp (seconds)
+    pub start_time: u64,
+    /// Unified (or first) cgroup path
+    pub cgroup: Option<String>,
+}
+
+/// A process with the processes it started
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessNode {
+    #[serde(flatten)]
+    pub process: DistroProcess,
+    pub children: Vec<ProcessNode>,
+}
+
+/// Signals `kill_distro_process` may send
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ProcessSignal {
+    Term,
+    Kill,
+    Int,
+    Hup,
+    Stop,
+    Cont,
+}
+
+impl ProcessSignal {
+    /// Name as `kill -s` takes it
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Term => "TERM",
+            Self::Kill => "KILL",
+            Self::Int => "INT",
+            Self::Hup => "HUP",
+            Self::Stop => "STOP",
+            Self::Cont => "CONT",
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! WSL Profile models
 
+use super::alert::{AlertAction, AlertCondition};
+use crate::utils::IniDocument;
 use serde::{Deserialize, Serialize};
 
 /// Resource profile for WSL configuration
@@ -39,46 +41,26 @@ impl Default for WslProfile {
 impl WslProfile {
     /// Generate .wslconfig content from this profile
     pub fn to_wslconfig(&self) -> String {
-        let mut config = String::from("[wsl2]\n");
-        
-        if !self.memory.is_empty() {
-            config.push_str(&format!("memory={}\n", self.memory));
-        }
-        
-        if self.processors > 0 {
-            config.push_str(&format!("processors={}\n", self.processors));
-        }
-        
-        if !self.swap.is_empty() {
-            config.push_str(&format!("swap={}\n", self.swap));
-        }
-        
-        config.push_str(&format!(
-            "localhostForwarding={}\n",
-            if self.localhost_forwarding { "true" } else { "false" }
-        ));
-        
-        if let Some(ref kernel) = self.kernel_path {
-            if !kernel.is_empty() {
-                config.push_str(&format!("kernel={}\n", kernel));
-            }
-        }
-        
-        if !self.networking_mode.is_empty() {
-            config.push_str(&format!("networkingMode={}\n", self.networking_mode));
-        }
-        
-        config.push_str(&format!(
-            "guiApplications={}\n",
-            if self.gui_applications { "true" } else { "false" }
-        ));
-        
-        config.push_str(&format!(
-            "debugConsole={}\n",
-            if self.debug_console { "true" } else { "false" }
-        ));
-        
-        config
+        let mut doc = IniDocument::default();
+        self.apply_to(&mut doc);
+        doc.to_string()
+    }
+
+    /// Write this profile's settings into a .wslconfig document
+    ///
+    /// Settings the profile leaves empty remove their key; everything else
+    /// in the document is kept.
+    pub fn apply_to(&self, doc: &mut IniDocument) {
+        let non_empty = |v: &str| Some(v.to_string()).filter(|v| !v.is_empty());
+
+        doc.set_opt("wsl2", "memory", non_empty(&self.memory));
+        doc.set_opt("wsl2", "processors", Some(self.processors).filter(|p| *p > 0));
+        doc.set_opt("wsl2", "swap", non_empty(&self.swap));
+        doc.set_opt("wsl2", "localhostForwarding", Some(self.localhost_forwarding));
+        doc.set_opt("wsl2", "kernel", self.kernel_path.as_deref().and_then(non_empty));
+        doc.set_opt("wsl2", "networkingMode", non_empty(&self.networking_mode));
+        doc.set_opt("wsl2", "guiApplications", Some(self.gui_applications));
+        doc.set_opt("wsl2", "debugConsole", Some(self.debug_console));
     }
 }
 
@@ -89,6 +71,8 @@ pub enum TriggerType {
     Process,
     PowerState,
     Network,
+    /// Resource alert, evaluated against collected metrics
+    Metric,
 }
 
 /// Automation rule
@@ -101,4 +85,10 @@ pub struct AutomationRule {
     pub trigger_type: TriggerType,
     pub trigger_value: String,
     pub target_profile_id: String,
+    /// Condition of a `Metric` trigger
+    #[serde(default)]
+    pub alert: Option<AlertCondition>,
+    /// What a `Metric` rule does when it fires
+    #[serde(default)]
+    pub actions: Vec<AlertAction>,
 }
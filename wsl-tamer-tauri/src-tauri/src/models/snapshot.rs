@@ -0,0 +1,84 @@
+//! Distribution snapshot models
+
+use serde::{Deserialize, Serialize};
+
+/// How a snapshot's data is stored
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotFormat {
+    /// Root filesystem tarball (`wsl --export`)
+    #[default]
+    Tar,
+    /// Copy of the virtual disk (`wsl --export --vhd`, WSL2 only)
+    Vhd,
+}
+
+impl SnapshotFormat {
+    /// File name of the data file inside the snapshot folder
+    pub fn data_file_name(&self) -> &'static str {
+        match self {
+            SnapshotFormat::Tar => "rootfs.tar",
+            SnapshotFormat::Vhd => "ext4.vhdx",
+        }
+    }
+}
+
+/// Manifest of a named point-in-time snapshot of a distribution
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub id: String,
+    pub name: String,
+    /// Distribution the snapshot was taken from
+    pub distro: String,
+    /// Unix timestamp (seconds)
+    pub created_at: u64,
+    pub format: SnapshotFormat,
+    /// Data file name within the snapshot folder
+    pub file_name: String,
+    pub size_bytes: u64,
+    /// Hex-encoded SHA-256 of the data file
+    pub sha256: String,
+    /// WSL version of the source distribution ("1" or "2")
+    pub wsl_version: String,
+    pub notes: Option<String>,
+    /// Taken by the scheduler; only automatic snapshots are pruned
+    #[serde(default)]
+    pub automatic: bool,
+}
+
+/// Which automatic snapshots of a distribution to keep
+///
+/// A snapshot is kept if any of the count rules keeps it; with no count rule
+/// set, all are kept. `max_total_bytes` is applied afterwards, dropping the
+/// oldest first, but never drops the newest snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetentionPolicy {
+    /// Keep the N most recent snapshots
+    pub keep_last: Option<u32>,
+    /// Keep the newest snapshot of each of the last N days
+    pub keep_daily: Option<u32>,
+    /// Keep the newest snapshot of each of the last N weeks
+    pub keep_weekly: Option<u32>,
+    /// Cap on the combined size of the snapshots kept
+    pub max_total_bytes: Option<u64>,
+}
+
+/// Automatic snapshot schedule and retention for one distribution
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotPolicy {
+    pub distro: String,
+    pub enabled: bool,
+    /// Cron expression (`minute hour day-of-month month day-of-week`),
+    /// e.g. `0 3 * * *` for every night at 03:00
+    pub schedule: String,
+    #[serde(default)]
+    pub format: SnapshotFormat,
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    /// Unix timestamp (seconds) of the last scheduled snapshot
+    #[serde(default)]
+    pub last_run: Option<u64>,
+}
@@ -0,0 +1,832 @@
+//! `.wslconfig` model and the schema describing every documented key
+//!
+//! Each key is listed once in the `wslconfig_schema!` invocation below. That
+//! single table generates the [`WslConfig`] fields, the [`WSLCONFIG_SCHEMA`]
+//! served to the UI editor, reading from and writing to an [`IniDocument`],
+//! and validation, so a key can't be supported in one place and missing in
+//! another.
+
+use super::diagnostic::ConfigDiagnostic;
+use crate::utils::IniDocument;
+use serde::{Deserialize, Serialize};
+
+/// Kind of value a setting takes
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SettingType {
+    Bool,
+    Integer,
+    /// Byte size with an optional unit, e.g. `8GB`
+    Size,
+    /// Windows path; backslashes must be escaped in the file
+    Path,
+    /// One of [`WslConfigSetting::values`]
+    Enum,
+    /// Comma-separated values
+    List,
+    /// Milliseconds
+    Duration,
+    String,
+}
+
+/// Schema entry for one `.wslconfig` key
+///
+/// `key` doubles as the field name of [`WslConfig`] in JSON.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WslConfigSetting {
+    pub section: &'static str,
+    pub key: &'static str,
+    #[serde(rename = "type")]
+    pub kind: SettingType,
+    /// What WSL uses when the key is absent, if it's a fixed value
+    pub default: Option<&'static str>,
+    /// Allowed values of an enum setting
+    pub values: &'static [&'static str],
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+    /// First WSL release that reads the key (`None` = all WSL 2 releases)
+    pub min_wsl_version: Option<&'static str>,
+    /// Editor grouping
+    pub category: &'static str,
+    pub description: &'static str,
+}
+
+/// Conversion between a typed setting and its text in the file
+pub trait SettingValue: Sized {
+    /// Allowed values, for enums
+    const VALUES: &'static [&'static str] = &[];
+
+    /// `None` when the text isn't a valid value of this type
+    fn parse_setting(raw: &str) -> Option<Self>;
+
+    fn render_setting(&self) -> String;
+}
+
+impl SettingValue for bool {
+    fn parse_setting(raw: &str) -> Option<Self> {
+        match raw.to_ascii_lowercase().as_str() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        }
+    }
+
+    fn render_setting(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl SettingValue for u32 {
+    fn parse_setting(raw: &str) -> Option<Self> {
+        raw.parse().ok()
+    }
+
+    fn render_setting(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl SettingValue for u64 {
+    fn parse_setting(raw: &str) -> Option<Self> {
+        raw.parse().ok()
+    }
+
+    fn render_setting(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Sizes and paths stay text so the user's spelling (`8GB` vs `8192MB`,
+/// escaped backslashes) survives; validation checks their format.
+impl SettingValue for String {
+    fn parse_setting(raw: &str) -> Option<Self> {
+        Some(raw.to_string())
+    }
+
+    fn render_setting(&self) -> String {
+        self.clone()
+    }
+}
+
+impl SettingValue for Vec<u16> {
+    fn parse_setting(raw: &str) -> Option<Self> {
+        raw.split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(|p| p.parse().ok())
+            .collect()
+    }
+
+    fn render_setting(&self) -> String {
+        self.iter()
+            .map(u16::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Read a typed setting; `None` when the key is missing or its value invalid
+pub fn read_setting<T: SettingValue>(doc: &IniDocument, section: &str, key: &str) -> Option<T> {
+    doc.get(section, key).and_then(T::parse_setting)
+}
+
+/// Write a typed setting
+///
+/// `None` removes the key, unless its current value isn't valid for the type:
+/// that value was never read, so it is left as it is.
+pub fn write_setting<T: SettingValue>(
+    doc: &mut IniDocument,
+    section: &str,
+    key: &str,
+    value: Option<&T>,
+) {
+    match value {
+        Some(value) => doc.set(section, key, &value.render_setting()),
+        None if read_setting::<T>(doc, section, key).is_some() => doc.remove(section, key),
+        None => {}
+    }
+}
+
+/// Parse a size such as `8GB`, `512MB` or `1073741824` into bytes
+///
+/// Units are binary (`1GB` = 1024 MB), matching WSL.
+pub fn parse_size(raw: &str) -> Option<u64> {
+    let raw = raw.trim();
+    let digits = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+    let (number, unit) = raw.split_at(digits);
+    let number: u64 = number.parse().ok()?;
+    let shift = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" => 10,
+        "M" | "MB" => 20,
+        "G" | "GB" => 30,
+        "T" | "TB" => 40,
+        _ => return None,
+    };
+    number.checked_mul(1 << shift)
+}
+
+/// Networking mode for WSL2
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkingMode {
+    #[default]
+    Nat,
+    Mirrored,
+    Bridged,
+}
+
+impl std::fmt::Display for NetworkingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkingMode::Nat => write!(f, "nat"),
+            NetworkingMode::Mirrored => write!(f, "mirrored"),
+            NetworkingMode::Bridged => write!(f, "bridged"),
+        }
+    }
+}
+
+impl NetworkingMode {
+    fn from_str(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "mirrored" => NetworkingMode::Mirrored,
+            "bridged" => NetworkingMode::Bridged,
+            _ => NetworkingMode::Nat,
+        }
+    }
+}
+
+impl SettingValue for NetworkingMode {
+    const VALUES: &'static [&'static str] = &["nat", "mirrored", "bridged"];
+
+    fn parse_setting(raw: &str) -> Option<Self> {
+        Self::VALUES
+            .iter()
+            .any(|v| v.eq_ignore_ascii_case(raw))
+            .then(|| Self::from_str(raw))
+    }
+
+    fn render_setting(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// How the VM hands cached memory back to Windows
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoMemoryReclaim {
+    Disabled,
+    Gradual,
+    Dropcache,
+}
+
+impl SettingValue for AutoMemoryReclaim {
+    const VALUES: &'static [&'static str] = &["disabled", "gradual", "dropcache"];
+
+    fn parse_setting(raw: &str) -> Option<Self> {
+        match raw.to_ascii_lowercase().as_str() {
+            "disabled" => Some(Self::Disabled),
+            "gradual" => Some(Self::Gradual),
+            "dropcache" => Some(Self::Dropcache),
+            _ => None,
+        }
+    }
+
+    fn render_setting(&self) -> String {
+        match self {
+            Self::Disabled => "disabled",
+            Self::Gradual => "gradual",
+            Self::Dropcache => "dropcache",
+        }
+        .to_string()
+    }
+}
+
+macro_rules! wslconfig_schema {
+    ($(
+        [$section:literal] $key:literal => $field:ident: $ty:ty, $kind:ident {
+            category: $category:literal,
+            default: $default:expr,
+            range: $min:expr, $max:expr,
+            since: $since:expr,
+            $description:literal
+        }
+    )*) => {
+        /// Typed .wslconfig representation with validation
+        #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+        #[serde(rename_all = "camelCase")]
+        pub struct WslConfig {
+            $(
+                #[doc = $description]
+                pub $field: Option<$ty>,
+            )*
+        }
+
+        /// Every documented `.wslconfig` key, in editor order
+        pub static WSLCONFIG_SCHEMA: &[WslConfigSetting] = &[$(
+            WslConfigSetting {
+                section: $section,
+                key: $key,
+                kind: SettingType::$kind,
+                default: $default,
+                values: <$ty as SettingValue>::VALUES,
+                min: $min,
+                max: $max,
+                min_wsl_version: $since,
+                category: $category,
+                description: $description,
+            },
+        )*];
+
+        impl WslConfig {
+            /// Read the known keys of a .wslconfig document
+            ///
+            /// Values that don't parse as their type are left unset.
+            pub fn from_document(doc: &IniDocument) -> Self {
+                Self {
+                    $($field: read_setting(doc, $section, $key),)*
+                }
+            }
+
+            /// Write the known keys into a .wslconfig document
+            ///
+            /// Unset fields remove their key; keys whose value doesn't
+            /// change, values this struct couldn't read, and everything it
+            /// doesn't model are left exactly as they are.
+            pub fn apply_to(&self, doc: &mut IniDocument) {
+                $(write_setting(doc, $section, $key, self.$field.as_ref());)*
+            }
+
+            /// Set values with their schema entry, as written to the file
+            pub fn set_values(&self) -> Vec<(&'static WslConfigSetting, String)> {
+                let mut rendered = Vec::new();
+                let mut schema = WSLCONFIG_SCHEMA.iter();
+                $(
+                    let setting = schema.next().expect("one schema entry per field");
+                    if let Some(value) = &self.$field {
+                        rendered.push((setting, value.render_setting()));
+                    }
+                )*
+                rendered
+            }
+        }
+    };
+}
+
+wslconfig_schema! {
+    ["wsl2"] "memory" => memory: String, Size {
+        category: "Resources",
+        default: None,
+        range: None, None,
+        since: None,
+        "Memory limit of the VM, e.g. 8GB. Defaults to 50% of Windows memory."
+    }
+    ["wsl2"] "processors" => processors: u32, Integer {
+        category: "Resources",
+        default: None,
+        range: Some(1), Some(128),
+        since: None,
+        "Number of virtual processors. Defaults to all logical processors."
+    }
+    ["wsl2"] "swap" => swap: String, Size {
+        category: "Resources",
+        default: None,
+        range: None, None,
+        since: None,
+        "Swap space added to the VM; 0 disables swap. Defaults to 25% of Windows memory."
+    }
+    ["wsl2"] "swapFile" => swap_file: String, Path {
+        category: "Resources",
+        default: None,
+        range: None, None,
+        since: None,
+        "Absolute Windows path of the swap virtual disk. Defaults to %USERPROFILE%\\AppData\\Local\\Temp\\swap.vhdx."
+    }
+    ["wsl2"] "vmIdleTimeout" => vm_idle_timeout: u64, Duration {
+        category: "Resources",
+        default: Some("60000"),
+        range: None, None,
+        since: None,
+        "Milliseconds the VM stays idle before it shuts down."
+    }
+    ["wsl2"] "pageReporting" => page_reporting: bool, Bool {
+        category: "Resources",
+        default: Some("true"),
+        range: None, None,
+        since: None,
+        "Let Windows reclaim memory the VM has freed."
+    }
+    ["experimental"] "autoMemoryReclaim" => auto_memory_reclaim: AutoMemoryReclaim, Enum {
+        category: "Resources",
+        default: Some("dropcache"),
+        range: None, None,
+        since: Some("2.0.0"),
+        "Hand cached memory back to Windows after the VM goes idle: gradually, by dropping the cache at once, or not at all."
+    }
+    ["wsl2"] "networkingMode" => networking_mode: NetworkingMode, Enum {
+        category: "Networking",
+        default: Some("nat"),
+        range: None, None,
+        since: Some("2.0.0"),
+        "Network mode: NAT, mirrored (share the host's interfaces) or bridged (requires vmSwitch)."
+    }
+    ["wsl2"] "vmSwitch" => vm_switch: String, String {
+        category: "Networking",
+        default: None,
+        range: None, None,
+        since: None,
+        "Hyper-V virtual switch used by bridged networking."
+    }
+    ["wsl2"] "localhostForwarding" => localhost_forwarding: bool, Bool {
+        category: "Networking",
+        default: Some("true"),
+        range: None, None,
+        since: None,
+        "Make ports bound to wildcard or localhost in the VM reachable from Windows via localhost (NAT mode)."
+    }
+    ["wsl2"] "firewall" => firewall: bool, Bool {
+        category: "Networking",
+        default: Some("true"),
+        range: None, None,
+        since: Some("2.0.0"),
+        "Apply Windows Firewall and Hyper-V firewall rules to WSL traffic."
+    }
+    ["wsl2"] "dnsTunneling" => dns_tunneling: bool, Bool {
+        category: "Networking",
+        default: Some("true"),
+        range: None, None,
+        since: Some("2.0.0"),
+        "Answer DNS requests through a virtualization feature instead of a network packet."
+    }
+    ["wsl2"] "dnsProxy" => dns_proxy: bool, Bool {
+        category: "Networking",
+        default: Some("true"),
+        range: None, None,
+        since: None,
+        "Use the NAT host as the VM's DNS server (NAT mode without DNS tunneling)."
+    }
+    ["wsl2"] "ipv6" => ipv6: bool, Bool {
+        category: "Networking",
+        default: Some("false"),
+        range: None, None,
+        since: None,
+        "Enable IPv6 networking in the VM."
+    }
+    ["experimental"] "hostAddressLoopback" => host_address_loopback: bool, Bool {
+        category: "Networking",
+        default: Some("false"),
+        range: None, None,
+        since: Some("2.0.0"),
+        "Let the host and the VM reach each other through the host's own IP addresses (mirrored mode)."
+    }
+    ["experimental"] "ignoredPorts" => ignored_ports: Vec<u16>, List {
+        category: "Networking",
+        default: None,
+        range: None, None,
+        since: Some("2.0.0"),
+        "Comma-separated ports Linux applications may bind even when Windows uses them (mirrored mode)."
+    }
+    ["experimental"] "bestEffortDnsParsing" => best_effort_dns_parsing: bool, Bool {
+        category: "Networking",
+        default: Some("false"),
+        range: None, None,
+        since: Some("2.0.0"),
+        "Strip unknown records from DNS responses instead of failing them (DNS tunneling)."
+    }
+    ["experimental"] "dnsTunnelingIpAddress" => dns_tunneling_ip_address: String, String {
+        category: "Networking",
+        default: Some("10.255.255.254"),
+        range: None, None,
+        since: Some("2.2.1"),
+        "Nameserver address written to resolv.conf when DNS tunneling is on."
+    }
+    ["experimental"] "autoProxy" => auto_proxy: bool, Bool {
+        category: "Networking",
+        default: Some("true"),
+        range: None, None,
+        since: Some("2.0.0"),
+        "Use the Windows HTTP proxy settings inside WSL."
+    }
+    ["experimental"] "initialAutoProxyTimeout" => initial_auto_proxy_timeout: u64, Duration {
+        category: "Networking",
+        default: Some("1000"),
+        range: None, None,
+        since: Some("2.0.0"),
+        "Milliseconds to wait for the proxy settings when a distribution starts."
+    }
+    ["wsl2"] "guiApplications" => gui_applications: bool, Bool {
+        category: "Features",
+        default: Some("true"),
+        range: None, None,
+        since: None,
+        "Enable GUI applications (WSLg)."
+    }
+    ["wsl2"] "nestedVirtualization" => nested_virtualization: bool, Bool {
+        category: "Features",
+        default: Some("true"),
+        range: None, None,
+        since: None,
+        "Allow VMs to run inside WSL (Windows 11 only)."
+    }
+    ["wsl2"] "defaultVhdSize" => default_vhd_size: String, Size {
+        category: "Disk",
+        default: Some("1TB"),
+        range: None, None,
+        since: Some("2.0.0"),
+        "Maximum size of the virtual disk of newly created distributions."
+    }
+    ["experimental"] "sparseVhd" => sparse_vhd: bool, Bool {
+        category: "Disk",
+        default: Some("false"),
+        range: None, None,
+        since: Some("2.0.0"),
+        "Create virtual disks of new distributions as sparse so they shrink automatically."
+    }
+    ["wsl2"] "kernel" => kernel: String, Path {
+        category: "Kernel",
+        default: None,
+        range: None, None,
+        since: None,
+        "Absolute Windows path of a custom Linux kernel. Defaults to the inbox kernel."
+    }
+    ["wsl2"] "kernelModules" => kernel_modules: String, Path {
+        category: "Kernel",
+        default: None,
+        range: None, None,
+        since: Some("2.3.11"),
+        "Absolute Windows path of a VHD with modules for a custom kernel."
+    }
+    ["wsl2"] "kernelCommandLine" => kernel_command_line: String, String {
+        category: "Kernel",
+        default: None,
+        range: None, None,
+        since: None,
+        "Additional kernel command line arguments."
+    }
+    ["wsl2"] "safeMode" => safe_mode: bool, Bool {
+        category: "Kernel",
+        default: Some("false"),
+        range: None, None,
+        since: None,
+        "Start WSL in safe mode, disabling many features to recover a broken distribution."
+    }
+    ["wsl2"] "debugConsole" => debug_console: bool, Bool {
+        category: "Kernel",
+        default: Some("false"),
+        range: None, None,
+        since: None,
+        "Show a console window with dmesg output while the VM runs."
+    }
+    ["wsl2"] "maxCrashDumpCount" => max_crash_dump_count: u32, Integer {
+        category: "Kernel",
+        default: Some("10"),
+        range: None, None,
+        since: Some("2.0.0"),
+        "Maximum number of crash dumps kept in %TEMP%\\wsl-crashes."
+    }
+}
+
+impl WslConfig {
+    /// Parse from INI-format .wslconfig content
+    pub fn from_ini(content: &str) -> crate::error::TamerResult<Self> {
+        Ok(Self::from_document(&IniDocument::parse(content)))
+    }
+
+    /// Serialize to fresh INI-format .wslconfig content
+    ///
+    /// To change an existing file, use [`WslConfig::apply_to`] on its
+    /// parsed document instead.
+    pub fn to_ini(&self) -> String {
+        let mut doc = IniDocument::default();
+        self.apply_to(&mut doc);
+        doc.to_string().trim().to_string()
+    }
+
+    /// Validate config values, returning a list of warnings
+    pub fn validate(&self) -> Vec<String> {
+        self.diagnostics().into_iter().map(|d| d.message).collect()
+    }
+
+    /// Check values against the schema and each other
+    ///
+    /// Checks that need the host (memory, paths, Windows build) are done by
+    /// `ConfigValidator`.
+    pub fn diagnostics(&self) -> Vec<ConfigDiagnostic> {
+        let mut diagnostics = Vec::new();
+
+        for (setting, value) in self.set_values() {
+            match setting.kind {
+                SettingType::Size if parse_size(&value).is_none() => {
+                    diagnostics.push(
+                        ConfigDiagnostic::error(
+                            setting.key,
+                            format!(
+                                "Invalid {} format '{}': expected a size such as '4GB' or '512MB'",
+                                setting.key, value
+                            ),
+                        )
+                        .with_suggestion("Use a whole number followed by MB or GB"),
+                    );
+                }
+                SettingType::Path if value.trim().is_empty() => {
+                    diagnostics.push(
+                        ConfigDiagnostic::error(
+                            setting.key,
+                            format!("{} is set to an empty path", setting.key),
+                        )
+                        .with_suggestion(format!("Remove {} or set an absolute path", setting.key)),
+                    );
+                }
+                SettingType::Integer | SettingType::Duration => {
+                    let number: u64 = value.parse().unwrap_or_default();
+                    let min = setting.min.unwrap_or(0);
+                    let below = number < min;
+                    let above = setting.max.is_some_and(|max| number > max);
+                    if below || above {
+                        let max = setting.max.map_or("∞".to_string(), |m| m.to_string());
+                        diagnostics.push(
+                            ConfigDiagnostic::error(
+                                setting.key,
+                                format!(
+                                    "{} {} out of range ({}-{})",
+                                    setting.key, number, min, max
+                                ),
+                            )
+                            .with_suggestion(format!("Use a value from {} to {}", min, max)),
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if self.memory.as_deref().and_then(parse_size) == Some(0) {
+            diagnostics.push(
+                ConfigDiagnostic::error("memory", "memory must be larger than 0")
+                    .with_suggestion("Remove memory to use half of Windows memory"),
+            );
+        }
+
+        let mode = self.networking_mode.clone().unwrap_or_default();
+        if mode == NetworkingMode::Bridged && self.vm_switch.is_none() {
+            diagnostics.push(
+                ConfigDiagnostic::error(
+                    "networkingMode",
+                    "Bridged networking needs a Hyper-V switch in vmSwitch",
+                )
+                .with_suggestion("Set vmSwitch to the name of an external virtual switch"),
+            );
+        }
+        if mode != NetworkingMode::Mirrored {
+            let mirrored_only = [
+                ("hostAddressLoopback", self.host_address_loopback.is_some()),
+                ("ignoredPorts", self.ignored_ports.is_some()),
+            ];
+            for (key, _) in mirrored_only.into_iter().filter(|(_, set)| *set) {
+                diagnostics.push(
+                    ConfigDiagnostic::info(
+                        key,
+                        format!("{} only applies to mirrored networking", key),
+                    )
+                    .with_suggestion("Set networkingMode to mirrored or remove the key"),
+                );
+            }
+        }
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DiagnosticSeverity;
+
+    #[test]
+    fn parse_basic_wslconfig() {
+        let content = "[wsl2]\nmemory=8GB\nprocessors=4\nlocalhostForwarding=true\n\n[experimental]\nsparseVhd=true";
+        let config = WslConfig::from_ini(content).unwrap();
+        assert_eq!(config.memory.as_deref(), Some("8GB"));
+        assert_eq!(config.processors, Some(4));
+        assert_eq!(config.localhost_forwarding, Some(true));
+        assert_eq!(config.sparse_vhd, Some(true));
+    }
+
+    #[test]
+    fn parse_empty_config() {
+        let config = WslConfig::from_ini("").unwrap();
+        assert!(config.memory.is_none());
+        assert!(config.processors.is_none());
+    }
+
+    #[test]
+    fn roundtrip_config() {
+        let config = WslConfig {
+            memory: Some("4GB".into()),
+            processors: Some(2),
+            networking_mode: Some(NetworkingMode::Mirrored),
+            sparse_vhd: Some(true),
+            ..Default::default()
+        };
+
+        let ini_str = config.to_ini();
+        let reparsed = WslConfig::from_ini(&ini_str).unwrap();
+        assert_eq!(reparsed.memory.as_deref(), Some("4GB"));
+        assert_eq!(reparsed.processors, Some(2));
+        assert_eq!(reparsed.networking_mode, Some(NetworkingMode::Mirrored));
+        assert_eq!(reparsed.sparse_vhd, Some(true));
+    }
+
+    #[test]
+    fn roundtrip_new_keys() {
+        let content = "[wsl2]\nkernel=C:\\\\wsl\\\\bzImage\nkernelModules=C:\\\\wsl\\\\modules.vhdx\nvmIdleTimeout=120000\ndefaultVhdSize=512GB\nmaxCrashDumpCount=3\nnetworkingMode=mirrored\n\n[experimental]\nautoMemoryReclaim=Gradual\nhostAddressLoopback=true\nignoredPorts=3000, 8080\nbestEffortDnsParsing=true\ndnsTunnelingIpAddress=10.0.0.53\ninitialAutoProxyTimeout=5000\n";
+        let config = WslConfig::from_ini(content).unwrap();
+        assert_eq!(config.kernel.as_deref(), Some("C:\\\\wsl\\\\bzImage"));
+        assert_eq!(config.vm_idle_timeout, Some(120_000));
+        assert_eq!(config.max_crash_dump_count, Some(3));
+        assert_eq!(config.auto_memory_reclaim, Some(AutoMemoryReclaim::Gradual));
+        assert_eq!(config.ignored_ports, Some(vec![3000, 8080]));
+        assert_eq!(config.initial_auto_proxy_timeout, Some(5000));
+        assert!(config.validate().is_empty());
+
+        assert_eq!(WslConfig::from_ini(&config.to_ini()).unwrap(), config);
+    }
+
+    #[test]
+    fn apply_keeps_unknown_keys_and_comments() {
+        let content = "# tuned for builds\n[wsl2]\nmemory = 8GB\nvmIdleTimeout=60000\nswap=2GB\nfuture=1\n\n[experimental]\nautoMemoryReclaim=gradual\n";
+        let mut doc = IniDocument::parse(content);
+        let mut config = WslConfig::from_document(&doc);
+        assert_eq!(config.memory.as_deref(), Some("8GB"));
+
+        config.memory = Some("12GB".into());
+        config.swap = None;
+        config.sparse_vhd = Some(true);
+        config.apply_to(&mut doc);
+        assert_eq!(
+            doc.to_string(),
+            "# tuned for builds\n[wsl2]\nmemory = 12GB\nvmIdleTimeout=60000\nfuture=1\n\n[experimental]\nautoMemoryReclaim=gradual\nsparseVhd=true\n"
+        );
+    }
+
+    #[test]
+    fn apply_keeps_values_it_could_not_read() {
+        let content = "[wsl2]\nnetworkingMode=virtioproxy\nprocessors=many\n";
+        let mut doc = IniDocument::parse(content);
+        let config = WslConfig::from_document(&doc);
+        assert_eq!(config.networking_mode, None);
+        assert_eq!(config.processors, None);
+
+        config.apply_to(&mut doc);
+        assert_eq!(doc.to_string(), content);
+    }
+
+    #[test]
+    fn schema_covers_every_field() {
+        let fields = serde_json::to_value(WslConfig::default()).unwrap();
+        let fields = fields.as_object().unwrap();
+        assert_eq!(fields.len(), WSLCONFIG_SCHEMA.len());
+        for setting in WSLCONFIG_SCHEMA {
+            assert!(fields.contains_key(setting.key), "{}", setting.key);
+            assert_eq!(
+                setting.kind == SettingType::Enum,
+                !setting.values.is_empty(),
+                "{}",
+                setting.key
+            );
+        }
+
+        let json = serde_json::to_value(&WSLCONFIG_SCHEMA[0]).unwrap();
+        assert_eq!(json["type"], "size");
+        assert_eq!(json["minWslVersion"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn parse_sizes() {
+        assert_eq!(parse_size("8GB"), Some(8 << 30));
+        assert_eq!(parse_size("512mb"), Some(512 << 20));
+        assert_eq!(parse_size("1TB"), Some(1 << 40));
+        assert_eq!(parse_size("1024"), Some(1024));
+        assert_eq!(parse_size("0"), Some(0));
+        assert_eq!(parse_size("lots"), None);
+        assert_eq!(parse_size("4.5GB"), None);
+    }
+
+    #[test]
+    fn validate_good_config() {
+        let config = WslConfig {
+            memory: Some("8GB".into()),
+            processors: Some(4),
+            swap: Some("2GB".into()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn validate_bad_memory() {
+        let config = WslConfig {
+            memory: Some("lots".into()),
+            ..Default::default()
+        };
+        let warnings = config.validate();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Invalid memory format"));
+    }
+
+    #[test]
+    fn validate_bad_processors() {
+        let mut config = WslConfig {
+            processors: Some(0),
+            ..Default::default()
+        };
+        assert!(!config.validate().is_empty());
+        config.processors = Some(200);
+        assert!(!config.validate().is_empty());
+    }
+
+    #[test]
+    fn validate_swap_zero() {
+        let config = WslConfig {
+            swap: Some("0".into()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn diagnostics_check_related_keys() {
+        let config = WslConfig {
+            networking_mode: Some(NetworkingMode::Bridged),
+            host_address_loopback: Some(true),
+            ..Default::default()
+        };
+        let diagnostics = config.diagnostics();
+        let keys: Vec<_> = diagnostics.iter().map(|d| d.key.as_deref()).collect();
+        assert_eq!(
+            keys,
+            vec![Some("networkingMode"), Some("hostAddressLoopback")]
+        );
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Error);
+        assert!(diagnostics[0].suggestion.is_some());
+
+        let config = WslConfig {
+            networking_mode: Some(NetworkingMode::Bridged),
+            vm_switch: Some("External".into()),
+            ..Default::default()
+        };
+        assert!(config.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn networking_mode_parsing() {
+        assert_eq!(
+            NetworkingMode::from_str("mirrored"),
+            NetworkingMode::Mirrored
+        );
+        assert_eq!(NetworkingMode::from_str("BRIDGED"), NetworkingMode::Bridged);
+        assert_eq!(NetworkingMode::from_str("anything"), NetworkingMode::Nat);
+        assert_eq!(NetworkingMode::parse_setting("anything"), None);
+    }
+}
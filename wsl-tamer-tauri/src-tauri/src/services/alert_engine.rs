@@ -0,0 +1,430 @@
+//! Alert Engine - Resource alerts from collected metrics
+//!
+//! `Metric` automation rules compare a value from each metrics sample with a
+//! threshold. A rule fires once the threshold has been crossed for
+//! `for_secs`, and fires again only after the value has come back past the
+//! threshold by `hysteresis` and `cooldown_secs` have passed since it last
+//! fired. That keeps a value hovering around the threshold from producing a
+//! stream of notifications.
+
+use crate::error::{TamerError, TamerResult};
+use crate::models::{
+    AlertAction, AlertComparison, AlertCondition, AlertEvent, AlertMetric, AutomationRule,
+    DistroMetrics, SystemMetrics, TriggerType,
+};
+use crate::utils::{run_powershell_command, validate_distro_name, CommandRunner};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Evaluation state of one rule
+#[derive(Debug, Default)]
+struct RuleState {
+    /// When the threshold was first crossed in the current breach
+    breached_since: Option<u64>,
+    /// Set while the alert is firing, until the value clears
+    active: Option<AlertEvent>,
+    last_fired: Option<u64>,
+}
+
+#[derive(Default)]
+pub struct AlertEngine {
+    states: Mutex<HashMap<String, RuleState>>,
+}
+
+impl AlertEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check that a rule can be evaluated (only `Metric` rules are checked)
+    pub fn validate_rule(rule: &AutomationRule) -> TamerResult<()> {
+        if !matches!(rule.trigger_type, TriggerType::Metric) {
+            return Ok(());
+        }
+        let condition = rule.alert.as_ref().ok_or_else(|| {
+            TamerError::InvalidInput("Metric rules need an alert condition".to_string())
+        })?;
+        if !condition.threshold.is_finite() {
+            return Err(TamerError::InvalidInput(
+                "Alert threshold must be a number".to_string(),
+            ));
+        }
+        if !condition.hysteresis.is_finite() || condition.hysteresis < 0.0 {
+            return Err(TamerError::InvalidInput(
+                "Alert hysteresis must not be negative".to_string(),
+            ));
+        }
+        if let Some(distro) = &condition.distro {
+            if !condition.metric.is_per_distro() {
+                return Err(TamerError::InvalidInput(format!(
+                    "{} is not a per-distribution metric",
+                    condition.metric.label()
+                )));
+            }
+            validate_distro_name(distro)?;
+        }
+        for action in &rule.actions {
+            match action {
+                AlertAction::ApplyProfile { profile_id } if profile_id.is_empty() => {
+                    return Err(TamerError::InvalidInput(
+                        "Apply profile action needs a profile".to_string(),
+                    ));
+                }
+                AlertAction::StopDistro { name } => {
+                    validate_distro_name(name)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluate enabled `Metric` rules against a sample taken at `now`,
+    /// returning the rules that fired
+    ///
+    /// State of rules that are gone or disabled is dropped.
+    pub fn evaluate(
+        &self,
+        rules: &[AutomationRule],
+        system: &SystemMetrics,
+        distros: &[DistroMetrics],
+        now: u64,
+    ) -> Vec<AlertEvent> {
+        let Ok(mut states) = self.states.lock() else {
+            return Vec::new();
+        };
+        let rules: Vec<(&AutomationRule, &AlertCondition)> = rules
+            .iter()
+            .filter(|r| r.is_enabled && matches!(r.trigger_type, TriggerType::Metric))
+            .filter_map(|r| Some((r, r.alert.as_ref()?)))
+            .collect();
+        states.retain(|id, _| rules.iter().any(|(r, _)| &r.id == id));
+
+        let mut fired = Vec::new();
+        for (rule, condition) in rules {
+            let state = states.entry(rule.id.clone()).or_default();
+            let Some((value, distro)) = metric_value(condition, system, distros) else {
+                // No value (e.g. the VM stopped): the breach is over
+                state.breached_since = None;
+                state.active = None;
+                continue;
+            };
+
+            if state.active.is_some() {
+                if is_cleared(condition, value) {
+                    state.active = None;
+                    state.breached_since = None;
+                }
+                continue;
+            }
+            if !is_breached(condition, value) {
+                state.breached_since = None;
+                continue;
+            }
+            let since = *state.breached_since.get_or_insert(now);
+            let cooled_down = state
+                .last_fired
+                .is_none_or(|last| now.saturating_sub(last) >= condition.cooldown_secs);
+            if now.saturating_sub(since) < condition.for_secs || !cooled_down {
+                continue;
+            }
+
+            let event = AlertEvent {
+                rule_id: rule.id.clone(),
+                rule_name: rule.name.clone(),
+                metric: condition.metric,
+                value,
+                threshold: condition.threshold,
+                message: alert_message(condition, value, distro.as_deref()),
+                distro,
+                fired_at: now,
+            };
+            state.active = Some(event.clone());
+            state.last_fired = Some(now);
+            fired.push(event);
+        }
+        fired
+    }
+
+    /// Alerts that have fired and not cleared yet
+    pub fn active(&self) -> Vec<AlertEvent> {
+        let Ok(states) = self.states.lock() else {
+            return Vec::new();
+        };
+        let mut active: Vec<AlertEvent> =
+            states.values().filter_map(|s| s.active.clone()).collect();
+        active.sort_by_key(|e| e.fired_at);
+        active
+    }
+}
+
+/// Current value of the watched metric, with the distribution it belongs to
+///
+/// For a per-distribution metric without a distribution the highest value
+/// of any distribution is used.
+fn metric_value(
+    condition: &AlertCondition,
+    system: &SystemMetrics,
+    distros: &[DistroMetrics],
+) -> Option<(f64, Option<String>)> {
+    let value = match condition.metric {
+        AlertMetric::VmmemPercentOfLimit => (system.wsl_memory_limit_mb > 0.0)
+            .then(|| system.vmmem_memory_mb / system.wsl_memory_limit_mb * 100.0)?,
+        AlertMetric::VmmemMb => system.vmmem_memory_mb,
+        AlertMetric::HostAvailableMb => {
+            (system.total_system_memory_mb > 0.0).then_some(system.available_system_memory_mb)?
+        }
+        AlertMetric::CpuPercent => system.wsl_cpu_percent,
+        AlertMetric::GuestCpuPercent => system.guest_cpu_percent?,
+        AlertMetric::DistroDiskPercent => {
+            return distros
+                .iter()
+                .filter(|d| {
+                    condition
+                        .distro
+                        .as_ref()
+                        .is_none_or(|name| d.name.eq_ignore_ascii_case(name))
+                })
+                // Unknown usage is no value, not an empty disk
+                .filter(|d| d.virtual_size_mb > 0.0)
+                .filter_map(|d| {
+                    Some((
+                        d.disk_usage_mb? / d.virtual_size_mb * 100.0,
+                        Some(d.name.clone()),
+                    ))
+                })
+                .max_by(|a, b| a.0.total_cmp(&b.0));
+        }
+    };
+    Some((value, None))
+}
+
+fn is_breached(condition: &AlertCondition, value: f64) -> bool {
+    match condition.comparison {
+        AlertComparison::Above => value > condition.threshold,
+        AlertComparison::Below => value < condition.threshold,
+    }
+}
+
+fn is_cleared(condition: &AlertCondition, value: f64) -> bool {
+    match condition.comparison {
+        AlertComparison::Above => value <= condition.threshold - condition.hysteresis,
+        AlertComparison::Below => value >= condition.threshold + condition.hysteresis,
+    }
+}
+
+fn alert_message(condition: &AlertCondition, value: f64, distro: Option<&str>) -> String {
+    let subject = match distro {
+        Some(distro) => format!("{} of {}", condition.metric.label(), distro),
+        None => condition.metric.label().to_string(),
+    };
+    let unit = condition.metric.unit();
+    let direction = match condition.comparison {
+        AlertComparison::Above => "above",
+        AlertComparison::Below => "below",
+    };
+    format!(
+        "{} is {:.0}{}, {} {:.0}{}",
+        subject, value, unit, direction, condition.threshold, unit
+    )
+}
+
+/// Windows toast showing `title` and `message`, via the PowerShell app ID
+const TOAST_SCRIPT: &str = r#"[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null
+$xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$text = $xml.GetElementsByTagName('text')
+$text.Item(0).AppendChild($xml.CreateTextNode('{title}')) | Out-Null
+$text.Item(1).AppendChild($xml.CreateTextNode('{message}')) | Out-Null
+$toast = [Windows.UI.Notifications.ToastNotification]::new($xml)
+[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe').Show($toast)"#;
+
+/// Show a desktop notification
+pub fn show_notification(
+    runner: &dyn CommandRunner,
+    title: &str,
+    message: &str,
+) -> TamerResult<()> {
+    let quote = |s: &str| s.replace('\'', "''");
+    let script = TOAST_SCRIPT
+        .replace("{title}", &quote(title))
+        .replace("{message}", &quote(message));
+    run_powershell_command(runner, &script).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::ScriptedRunner;
+
+    fn system(vmmem_mb: f64) -> SystemMetrics {
+        SystemMetrics {
+            vmmem_memory_mb: vmmem_mb,
+            wsl_memory_limit_mb: 1000.0,
+            wsl_memory: None,
+            wsl_cpu_percent: 0.0,
+            guest_cpu_percent: None,
+            total_system_memory_mb: 16000.0,
+            available_system_memory_mb: 8000.0,
+            timestamp: 0,
+        }
+    }
+
+    fn rule(condition: AlertCondition) -> AutomationRule {
+        AutomationRule {
+            id: "mem".to_string(),
+            name: "Memory".to_string(),
+            is_enabled: true,
+            trigger_type: TriggerType::Metric,
+            trigger_value: String::new(),
+            target_profile_id: String::new(),
+            alert: Some(condition),
+            actions: vec![AlertAction::Notify],
+        }
+    }
+
+    fn memory_above_90(for_secs: u64, cooldown_secs: u64) -> AutomationRule {
+        rule(AlertCondition {
+            metric: AlertMetric::VmmemPercentOfLimit,
+            comparison: AlertComparison::Above,
+            threshold: 90.0,
+            for_secs,
+            hysteresis: 5.0,
+            cooldown_secs,
+            distro: None,
+        })
+    }
+
+    #[test]
+    fn test_fires_after_sustained_breach() {
+        let engine = AlertEngine::new();
+        let rules = [memory_above_90(120, 0)];
+
+        assert!(engine.evaluate(&rules, &system(950.0), &[], 0).is_empty());
+        assert!(engine.evaluate(&rules, &system(950.0), &[], 60).is_empty());
+        let fired = engine.evaluate(&rules, &system(950.0), &[], 120);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].value, 95.0);
+        assert_eq!(fired[0].message, "WSL memory is 95%, above 90%");
+        assert_eq!(engine.active().len(), 1);
+
+        // A dip resets the timer
+        let rules = [memory_above_90(120, 0)];
+        let engine = AlertEngine::new();
+        engine.evaluate(&rules, &system(950.0), &[], 0);
+        engine.evaluate(&rules, &system(800.0), &[], 60);
+        assert!(engine.evaluate(&rules, &system(950.0), &[], 150).is_empty());
+    }
+
+    #[test]
+    fn test_hysteresis_and_cooldown() {
+        let engine = AlertEngine::new();
+        let rules = [memory_above_90(0, 300)];
+
+        assert_eq!(engine.evaluate(&rules, &system(950.0), &[], 0).len(), 1);
+        // Within the hysteresis band: still active, no new alert
+        assert!(engine.evaluate(&rules, &system(880.0), &[], 10).is_empty());
+        assert!(engine.evaluate(&rules, &system(950.0), &[], 20).is_empty());
+        // Cleared, but the cooldown holds the next one back
+        engine.evaluate(&rules, &system(800.0), &[], 30);
+        assert!(engine.active().is_empty());
+        assert!(engine.evaluate(&rules, &system(950.0), &[], 40).is_empty());
+        assert_eq!(engine.evaluate(&rules, &system(950.0), &[], 300).len(), 1);
+    }
+
+    #[test]
+    fn test_distro_disk_uses_fullest_distro() {
+        let distro = |name: &str, used: f64| DistroMetrics {
+            name: name.to_string(),
+            disk_usage_mb: Some(used),
+            disk_size_mb: used,
+            virtual_size_mb: 1000.0,
+            reclaimable_mb: None,
+            vhd_path: None,
+            is_running: true,
+        };
+        let engine = AlertEngine::new();
+        let rules = [rule(AlertCondition {
+            metric: AlertMetric::DistroDiskPercent,
+            comparison: AlertComparison::Above,
+            threshold: 85.0,
+            for_secs: 0,
+            hysteresis: 0.0,
+            cooldown_secs: 0,
+            distro: None,
+        })];
+
+        let fired = engine.evaluate(
+            &rules,
+            &system(0.0),
+            &[distro("Debian", 100.0), distro("Ubuntu", 900.0)],
+            0,
+        );
+        assert_eq!(fired[0].distro.as_deref(), Some("Ubuntu"));
+        assert_eq!(fired[0].message, "Disk usage of Ubuntu is 90%, above 85%");
+    }
+
+    #[test]
+    fn test_distro_disk_unknown_usage_is_no_value() {
+        let unknown = DistroMetrics {
+            name: "Ubuntu".to_string(),
+            disk_usage_mb: None,
+            disk_size_mb: 500.0,
+            virtual_size_mb: 1000.0,
+            reclaimable_mb: None,
+            vhd_path: None,
+            is_running: false,
+        };
+        let engine = AlertEngine::new();
+        let rules = [rule(AlertCondition {
+            metric: AlertMetric::DistroDiskPercent,
+            comparison: AlertComparison::Below,
+            threshold: 10.0,
+            for_secs: 0,
+            hysteresis: 0.0,
+            cooldown_secs: 0,
+            distro: None,
+        })];
+
+        // Not read as an empty disk
+        assert!(engine
+            .evaluate(&rules, &system(0.0), std::slice::from_ref(&unknown), 0)
+            .is_empty());
+
+        // Nor does it outweigh a distribution that was measured
+        let measured = DistroMetrics {
+            name: "Debian".to_string(),
+            disk_usage_mb: Some(50.0),
+            ..unknown.clone()
+        };
+        let fired = engine.evaluate(&rules, &system(0.0), &[unknown, measured], 10);
+        assert_eq!(fired[0].distro.as_deref(), Some("Debian"));
+    }
+
+    #[test]
+    fn test_validate_rule() {
+        let mut bad = memory_above_90(0, 0);
+        bad.alert.as_mut().unwrap().distro = Some("Ubuntu".to_string());
+        assert!(AlertEngine::validate_rule(&bad).is_err());
+
+        let mut bad = memory_above_90(0, 0);
+        bad.actions = vec![AlertAction::ApplyProfile {
+            profile_id: String::new(),
+        }];
+        assert!(AlertEngine::validate_rule(&bad).is_err());
+
+        let mut bad = memory_above_90(0, 0);
+        bad.alert = None;
+        assert!(AlertEngine::validate_rule(&bad).is_err());
+
+        assert!(AlertEngine::validate_rule(&memory_above_90(120, 600)).is_ok());
+    }
+
+    #[test]
+    fn test_notification_script_quotes_text() {
+        let runner = ScriptedRunner::new();
+        let _ = show_notification(&runner, "Disk", "Ubuntu's disk is full");
+        let calls = runner.calls();
+        let script = calls[0].args.last().unwrap();
+        assert!(script.contains("CreateTextNode('Ubuntu''s disk is full')"));
+    }
+}
@@ -1,9 +1,15 @@
 // Automation Engine - Process watcher, power monitor, and rule evaluation
 
+use chrono::{DateTime, Datelike, DurationRound, TimeDelta, TimeZone, Timelike};
 use serde::Serialize;
-use std::process::Command;
+use std::sync::{Arc, OnceLock};
 
+use crate::error::{TamerError, TamerResult};
 use crate::models::{AutomationRule, TriggerType};
+use crate::utils::{system_runner, CommandRunner};
+
+/// How far back a schedule looks for runs missed while the app was closed
+const SCHEDULE_CATCH_UP_DAYS: i64 = 7;
 
 /// Power state
 #[derive(Debug, Clone, PartialEq, Serialize)]
@@ -22,59 +28,61 @@ pub struct SystemState {
     pub network_connected: bool,
 }
 
-/// Stateless automation engine — all methods are associated functions
-pub struct AutomationEngine;
+/// Automation engine — queries system state through its runner; rule
+/// evaluation itself is pure
+pub struct AutomationEngine {
+    runner: Arc<dyn CommandRunner>,
+}
+
+impl Default for AutomationEngine {
+    fn default() -> Self {
+        Self::new(system_runner())
+    }
+}
 
 impl AutomationEngine {
+    pub fn new(runner: Arc<dyn CommandRunner>) -> Self {
+        Self { runner }
+    }
+
     /// Get current system state
-    pub fn get_system_state() -> SystemState {
+    pub fn get_system_state(&self) -> SystemState {
         SystemState {
-            running_processes: Self::get_running_processes(),
-            power_state: Self::get_power_state(),
+            running_processes: self.get_running_processes(),
+            power_state: self.get_power_state(),
             current_time: Self::get_current_time(),
-            network_connected: Self::check_network(),
+            network_connected: self.check_network(),
         }
     }
 
+    /// Run a PowerShell snippet and return its trimmed stdout (None if it could not run)
+    fn powershell(&self, script: &str) -> Option<String> {
+        self.runner
+            .output("powershell", &["-NoProfile", "-Command", script])
+            .ok()
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+    }
+
     /// Get list of running process names
-    fn get_running_processes() -> Vec<String> {
-        let output = Command::new("powershell")
-            .args([
-                "-NoProfile",
-                "-Command",
-                "Get-Process | Select-Object -ExpandProperty ProcessName -Unique | ConvertTo-Json",
-            ])
-            .output();
+    fn get_running_processes(&self) -> Vec<String> {
+        let output = self.powershell(
+            "Get-Process | Select-Object -ExpandProperty ProcessName -Unique | ConvertTo-Json",
+        );
 
         match output {
-            Ok(out) => {
-                let stdout = String::from_utf8_lossy(&out.stdout);
-                serde_json::from_str::<Vec<String>>(&stdout).unwrap_or_default()
-            }
-            Err(_) => Vec::new(),
+            Some(stdout) => serde_json::from_str::<Vec<String>>(&stdout).unwrap_or_default(),
+            None => Vec::new(),
         }
     }
 
     /// Get current power state (AC or Battery)
-    fn get_power_state() -> PowerState {
-        let output = Command::new("powershell")
-            .args([
-                "-NoProfile",
-                "-Command",
-                "(Get-CimInstance -ClassName Win32_Battery).BatteryStatus",
-            ])
-            .output();
+    fn get_power_state(&self) -> PowerState {
+        let output = self.powershell("(Get-CimInstance -ClassName Win32_Battery).BatteryStatus");
 
-        match output {
-            Ok(out) => {
-                let stdout = String::from_utf8_lossy(&out.stdout).trim().to_string();
-                match stdout.as_str() {
-                    "1" => PowerState::Battery, // Discharging
-                    "2" => PowerState::AC,      // AC Power
-                    _ => PowerState::Unknown,
-                }
-            }
-            Err(_) => PowerState::Unknown,
+        match output.as_deref() {
+            Some("1") => PowerState::Battery, // Discharging
+            Some("2") => PowerState::AC,      // AC Power
+            _ => PowerState::Unknown,
         }
     }
 
@@ -84,19 +92,12 @@ impl AutomationEngine {
     }
 
     /// Check if network is connected
-    fn check_network() -> bool {
-        let output = Command::new("powershell")
-            .args(["-NoProfile", "-Command",
-                   "(Get-NetConnectionProfile | Where-Object {$_.IPv4Connectivity -eq 'Internet'}).Count -gt 0"])
-            .output();
-
-        match output {
-            Ok(out) => {
-                let stdout = String::from_utf8_lossy(&out.stdout).trim().to_lowercase();
-                stdout == "true"
-            }
-            Err(_) => false,
-        }
+    fn check_network(&self) -> bool {
+        self.powershell(
+            "(Get-NetConnectionProfile | Where-Object {$_.IPv4Connectivity -eq 'Internet'}).Count -gt 0",
+        )
+        .map(|stdout| stdout.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
     }
 
     /// Evaluate a single rule against current system state
@@ -118,6 +119,8 @@ impl AutomationEngine {
             TriggerType::Network => {
                 Self::evaluate_network_trigger(&rule.trigger_value, state.network_connected)
             }
+            // Resource alerts need metrics, see `AlertEngine`
+            TriggerType::Metric => false,
         }
     }
 
@@ -163,24 +166,175 @@ impl AutomationEngine {
             _ => false,
         }
     }
+
+    /// Whether a schedule has fired since `last_run`
+    ///
+    /// Checks every minute after `last_run` up to and including `now`, so a
+    /// run missed while the app was closed fires once on the next check
+    /// (looking back at most `SCHEDULE_CATCH_UP_DAYS`). With no previous run
+    /// only the current minute counts.
+    pub fn is_schedule_due<Tz: TimeZone>(
+        schedule: &CronSchedule,
+        last_run: Option<&DateTime<Tz>>,
+        now: &DateTime<Tz>,
+    ) -> bool {
+        let minute = TimeDelta::minutes(1);
+        let Ok(now) = now.clone().duration_trunc(minute) else {
+            return false;
+        };
+
+        let mut at = match last_run.and_then(|t| t.clone().duration_trunc(minute).ok()) {
+            Some(last) if last >= now => return false,
+            Some(last) => {
+                (last + minute).max(now.clone() - TimeDelta::days(SCHEDULE_CATCH_UP_DAYS))
+            }
+            None => now.clone(),
+        };
+        while at <= now {
+            if schedule.matches(&at) {
+                return true;
+            }
+            at += minute;
+        }
+        false
+    }
+}
+
+/// Cron-style schedule: `minute hour day-of-month month day-of-week`
+///
+/// Each field accepts `*`, numbers, ranges (`1-5`), lists (`1,15`) and steps
+/// (`*/15`, `0-30/10`). Day-of-week runs 0-7 with both 0 and 7 meaning Sunday.
+/// As in cron, when both day fields are restricted a day matching either one
+/// matches. The shortcuts `@hourly`, `@daily`, `@weekly` and `@monthly` are
+/// also accepted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> TamerResult<Self> {
+        let expr = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields[..] else {
+            return Err(TamerError::InvalidInput(format!(
+                "Invalid schedule '{}': expected 5 fields (minute hour day month weekday)",
+                expr
+            )));
+        };
+
+        let mut days_of_week = parse_cron_field(dow, 0, 7)?;
+        // 7 is an alias for Sunday
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+
+        Ok(Self {
+            minutes: parse_cron_field(minute, 0, 59)?,
+            hours: parse_cron_field(hour, 0, 23)?,
+            days_of_month: parse_cron_field(dom, 1, 31)?,
+            months: parse_cron_field(month, 1, 12)?,
+            days_of_week,
+            day_of_month_restricted: dom != "*",
+            day_of_week_restricted: dow != "*",
+        })
+    }
+
+    /// Whether the schedule fires in the minute containing `at`
+    pub fn matches<Tz: TimeZone>(&self, at: &DateTime<Tz>) -> bool {
+        let bit = |mask: u64, value: u32| mask & (1 << value) != 0;
+
+        let dom = bit(self.days_of_month, at.day());
+        let dow = bit(self.days_of_week, at.weekday().num_days_from_sunday());
+        let day = match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => dom || dow,
+            _ => dom && dow,
+        };
+
+        day && bit(self.minutes, at.minute())
+            && bit(self.hours, at.hour())
+            && bit(self.months, at.month())
+    }
+}
+
+/// Parse one cron field into a bitmask of allowed values
+fn parse_cron_field(field: &str, min: u32, max: u32) -> TamerResult<u64> {
+    let invalid = || TamerError::InvalidInput(format!("Invalid schedule field '{}'", field));
+    let mut mask = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                start.parse().map_err(|_| invalid())?,
+                end.parse().map_err(|_| invalid())?,
+            )
+        } else {
+            let value = range.parse().map_err(|_| invalid())?;
+            // `5/15` means "from 5 to the end, every 15"
+            (value, if part.contains('/') { max } else { value })
+        };
+        if start < min || end > max || start > end {
+            return Err(TamerError::InvalidInput(format!(
+                "Invalid schedule field '{}': values must be within {}-{}",
+                field, min, max
+            )));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
 }
 
 // Tauri commands for automation
 
+/// Global automation engine instance
+static AUTOMATION_ENGINE: OnceLock<AutomationEngine> = OnceLock::new();
+
+/// Get or initialize the automation engine
+pub fn get_automation_engine() -> &'static AutomationEngine {
+    AUTOMATION_ENGINE.get_or_init(AutomationEngine::default)
+}
+
 #[tauri::command]
 pub fn get_system_state() -> SystemState {
-    AutomationEngine::get_system_state()
+    get_automation_engine().get_system_state()
 }
 
 #[tauri::command]
 pub fn evaluate_automation_rule(rule: AutomationRule) -> bool {
-    let state = AutomationEngine::get_system_state();
+    let state = get_automation_engine().get_system_state();
     AutomationEngine::evaluate_rule(&rule, &state)
 }
 
 #[tauri::command]
 pub fn get_power_state() -> String {
-    match AutomationEngine::get_system_state().power_state {
+    match get_automation_engine().get_system_state().power_state {
         PowerState::AC => "AC".to_string(),
         PowerState::Battery => "Battery".to_string(),
         PowerState::Unknown => "Unknown".to_string(),
@@ -189,12 +343,13 @@ pub fn get_power_state() -> String {
 
 #[tauri::command]
 pub fn get_running_processes() -> Vec<String> {
-    AutomationEngine::get_system_state().running_processes
+    get_automation_engine().get_system_state().running_processes
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::{CommandOutput, ScriptedRunner};
 
     fn make_state(
         time: &str,
@@ -218,6 +373,8 @@ mod tests {
             trigger_type,
             trigger_value: trigger_value.to_string(),
             target_profile_id: "profile1".to_string(),
+            alert: None,
+            actions: Vec::new(),
         }
     }
 
@@ -317,4 +474,102 @@ mod tests {
 
         assert!(!AutomationEngine::evaluate_rule(&rule, &state));
     }
+
+    fn at(date: &str) -> DateTime<chrono::Utc> {
+        DateTime::parse_from_rfc3339(date).unwrap().to_utc()
+    }
+
+    #[test]
+    fn test_cron_schedule_matching() {
+        let nightly = CronSchedule::parse("0 3 * * *").unwrap();
+        assert!(nightly.matches(&at("2026-10-17T03:00:30Z")));
+        assert!(!nightly.matches(&at("2026-10-17T03:01:00Z")));
+
+        // Every 15 minutes on weekdays during working hours
+        let work = CronSchedule::parse("*/15 9-17 * * 1-5").unwrap();
+        assert!(work.matches(&at("2026-10-16T09:45:00Z"))); // Friday
+        assert!(!work.matches(&at("2026-10-17T09:45:00Z"))); // Saturday
+        assert!(!work.matches(&at("2026-10-16T09:50:00Z")));
+
+        // Sunday as 7, and day-of-month OR day-of-week when both are set
+        let weekly = CronSchedule::parse("0 0 1 * 7").unwrap();
+        assert!(weekly.matches(&at("2026-10-18T00:00:00Z"))); // Sunday
+        assert!(weekly.matches(&at("2026-10-01T00:00:00Z"))); // Thursday the 1st
+
+        assert_eq!(
+            CronSchedule::parse("@daily").unwrap(),
+            CronSchedule::parse("0 0 * * *").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_cron_schedule_rejects_bad_expressions() {
+        for expr in [
+            "",
+            "0 3 * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+        ] {
+            let err = CronSchedule::parse(expr).unwrap_err();
+            assert_eq!(err.code(), "INVALID_INPUT", "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_schedule_due_catches_up_once() {
+        let nightly = CronSchedule::parse("0 3 * * *").unwrap();
+        let last = at("2026-10-16T03:00:00Z");
+
+        // Same night: already ran
+        assert!(!AutomationEngine::is_schedule_due(
+            &nightly,
+            Some(&last),
+            &at("2026-10-16T12:00:00Z")
+        ));
+        // App was closed at 03:00 the next night; fires on the next check
+        assert!(AutomationEngine::is_schedule_due(
+            &nightly,
+            Some(&last),
+            &at("2026-10-17T08:30:00Z")
+        ));
+        // Never ran: only fires at the scheduled minute
+        assert!(!AutomationEngine::is_schedule_due(
+            &nightly,
+            None,
+            &at("2026-10-17T08:30:00Z")
+        ));
+        assert!(AutomationEngine::is_schedule_due(
+            &nightly,
+            None,
+            &at("2026-10-17T03:00:10Z")
+        ));
+    }
+
+    #[test]
+    fn test_get_system_state_from_scripted_runner() {
+        let ps = |script: &'static str| ["-NoProfile", "-Command", script];
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on(
+                    "powershell",
+                    &ps("Get-Process | Select-Object -ExpandProperty ProcessName -Unique | ConvertTo-Json"),
+                    CommandOutput::ok("[\"code\",\"explorer\"]"),
+                )
+                .on(
+                    "powershell",
+                    &ps("(Get-CimInstance -ClassName Win32_Battery).BatteryStatus"),
+                    CommandOutput::ok("1\r\n"),
+                ),
+        );
+        let engine = AutomationEngine::new(runner);
+
+        let state = engine.get_system_state();
+        assert_eq!(state.running_processes, vec!["code", "explorer"]);
+        assert_eq!(state.power_state, PowerState::Battery);
+        // Network query was not scripted, so it reads as disconnected
+        assert!(!state.network_connected);
+    }
 }
@@ -0,0 +1,234 @@
+//! Config History - Versioned backups of configuration files
+//!
+//! Every write of .wslconfig or a distribution's wsl.conf first stores the
+//! content it replaces here, one JSON file per version.
+
+use crate::error::{TamerError, TamerResult};
+use crate::models::{ConfigOrigin, ConfigTarget, ConfigVersion, StoredConfigVersion};
+use crate::utils::{app_data_dir, unix_timestamp};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Versions kept per file; older ones are pruned
+const MAX_VERSIONS_PER_TARGET: usize = 100;
+
+/// Last millisecond stamp handed out, so ids stay ordered within a millisecond
+static LAST_STAMP: AtomicU64 = AtomicU64::new(0);
+
+pub struct ConfigHistory {
+    dir: PathBuf,
+}
+
+impl Default for ConfigHistory {
+    fn default() -> Self {
+        Self::new(app_data_dir().join("config-history"))
+    }
+}
+
+impl ConfigHistory {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Store the content a write is about to replace
+    ///
+    /// `prior` is `None` when the file doesn't exist yet, `new_content` when
+    /// the write deletes it. Nothing is stored when the write wouldn't change
+    /// the file.
+    pub fn record(
+        &self,
+        target: ConfigTarget,
+        prior: Option<&str>,
+        new_content: Option<&str>,
+        origin: ConfigOrigin,
+    ) -> TamerResult<Option<ConfigVersion>> {
+        if prior == new_content {
+            return Ok(None);
+        }
+
+        let content = prior.unwrap_or_default().to_string();
+        let version = ConfigVersion {
+            id: format!(
+                "{:013}-{}",
+                next_stamp(),
+                &uuid::Uuid::new_v4().simple().to_string()[..8]
+            ),
+            target,
+            replaced_at: unix_timestamp(),
+            replaced_by: origin,
+            existed: prior.is_some(),
+            size_bytes: content.len() as u64,
+        };
+        self.save(&StoredConfigVersion {
+            version: version.clone(),
+            content,
+        })?;
+        self.prune(&version.target);
+        Ok(Some(version))
+    }
+
+    /// Stored versions, newest first, optionally of one file only
+    ///
+    /// Unreadable entries are skipped.
+    pub fn list(&self, target: Option<&ConfigTarget>) -> Vec<ConfigVersion> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut versions: Vec<ConfigVersion> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                let content = fs::read_to_string(&path).ok()?;
+                match serde_json::from_str::<StoredConfigVersion>(&content) {
+                    Ok(stored) => Some(stored.version),
+                    Err(e) => {
+                        log::warn!("Skipping unreadable config version {:?}: {}", path, e);
+                        None
+                    }
+                }
+            })
+            .filter(|v| target.is_none_or(|t| &v.target == t))
+            .collect();
+        versions.sort_by(|a, b| b.id.cmp(&a.id));
+        versions
+    }
+
+    /// A version with its content
+    pub fn get(&self, id: &str) -> TamerResult<StoredConfigVersion> {
+        let not_found = || TamerError::NotFound {
+            what: format!("Config version '{}'", id),
+        };
+        // Ids name files in the history directory
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(not_found());
+        }
+
+        let content = fs::read_to_string(self.path(id)).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                not_found()
+            } else {
+                TamerError::io("Failed to read config version", e)
+            }
+        })?;
+        serde_json::from_str(&content)
+            .map_err(|e| TamerError::Parse(format!("Corrupt config version '{}': {}", id, e)))
+    }
+
+    /// Persist a version atomically (write to a temp file, then rename over)
+    fn save(&self, stored: &StoredConfigVersion) -> TamerResult<()> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| TamerError::io("Failed to create config history directory", e))?;
+
+        let json = serde_json::to_string_pretty(stored).map_err(|e| {
+            TamerError::Internal(format!("Failed to serialize config version: {}", e))
+        })?;
+        let path = self.path(&stored.version.id);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, json).map_err(|e| TamerError::io("Failed to write config version", e))?;
+        fs::rename(&tmp, &path).map_err(|e| TamerError::io("Failed to write config version", e))
+    }
+
+    /// Drop the oldest versions of a file beyond the retention limit
+    fn prune(&self, target: &ConfigTarget) {
+        for old in self.list(Some(target)).iter().skip(MAX_VERSIONS_PER_TARGET) {
+            if let Err(e) = fs::remove_file(self.path(&old.id)) {
+                log::warn!("Failed to prune config version {}: {}", old.id, e);
+            }
+        }
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+/// Current time in milliseconds, later than any stamp handed out before
+fn next_stamp() -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let previous = LAST_STAMP
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |last| {
+            Some(now.max(last + 1))
+        })
+        .unwrap_or_default();
+    now.max(previous + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history() -> ConfigHistory {
+        ConfigHistory::new(
+            std::env::temp_dir().join(format!("wsl-tamer-test-{}", uuid::Uuid::new_v4())),
+        )
+    }
+
+    #[test]
+    fn test_record_list_and_get() {
+        let history = history();
+        let ubuntu = ConfigTarget::DistroConfig {
+            distro: "Ubuntu".into(),
+        };
+
+        let first = history
+            .record(
+                ConfigTarget::Wslconfig,
+                None,
+                Some("[wsl2]\nmemory=8GB\n"),
+                ConfigOrigin::Manual,
+            )
+            .unwrap()
+            .unwrap();
+        assert!(!first.existed);
+        let rule = ConfigOrigin::AutomationRule {
+            rule_id: "r1".into(),
+            rule_name: "Low memory".into(),
+        };
+        let second = history
+            .record(
+                ConfigTarget::Wslconfig,
+                Some("[wsl2]\nmemory=8GB\n"),
+                Some("[wsl2]\nmemory=4GB\n"),
+                rule.clone(),
+            )
+            .unwrap()
+            .unwrap();
+        history
+            .record(
+                ubuntu.clone(),
+                Some(""),
+                Some("[boot]\nsystemd=true\n"),
+                ConfigOrigin::Manual,
+            )
+            .unwrap();
+        // Unchanged content is not a new version
+        let unchanged = history
+            .record(ubuntu.clone(), Some("x"), Some("x"), ConfigOrigin::Manual)
+            .unwrap();
+        assert!(unchanged.is_none());
+
+        let ids: Vec<_> = history
+            .list(Some(&ConfigTarget::Wslconfig))
+            .into_iter()
+            .map(|v| v.id)
+            .collect();
+        assert_eq!(ids, vec![second.id.clone(), first.id.clone()]);
+        assert_eq!(history.list(None).len(), 3);
+        assert_eq!(history.list(Some(&ubuntu)).len(), 1);
+
+        let stored = history.get(&second.id).unwrap();
+        assert_eq!(stored.content, "[wsl2]\nmemory=8GB\n");
+        assert_eq!(stored.version.replaced_by, rule);
+        assert!(matches!(
+            history.get("../secrets"),
+            Err(TamerError::NotFound { .. })
+        ));
+    }
+}
@@ -0,0 +1,497 @@
+//! Config Validator - Checks .wslconfig against the machine it runs on
+//!
+//! [`WslConfig::diagnostics`] catches values that are wrong anywhere; this
+//! adds the ones that are only wrong on this host: more memory or processors
+//! than it has, paths that don't exist, and settings its Windows build or
+//! WSL release doesn't support. Host facts come from a [`HostFacts`] so the
+//! checks can be tested with a [`StaticHostFacts`].
+
+use crate::error::TamerError;
+use crate::models::{parse_size, ConfigDiagnostic, NetworkingMode, SettingType, WslConfig};
+use crate::services::host_memory_mb;
+use crate::utils::{run_wsl_command, system_runner, CommandRunner};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+/// First Windows 11 build
+const WINDOWS_11_BUILD: u32 = 22000;
+
+/// Windows 11 22H2, needed by mirrored networking and its companions
+const WINDOWS_11_22H2_BUILD: u32 = 22621;
+
+/// What validation needs to know about the host
+///
+/// `None` means unknown; checks that depend on an unknown fact are skipped.
+pub trait HostFacts: Send + Sync {
+    /// Physical memory in bytes
+    fn total_memory_bytes(&self) -> Option<u64>;
+
+    fn logical_processors(&self) -> Option<u32>;
+
+    /// Windows build number, e.g. 22631
+    fn windows_build(&self) -> Option<u32>;
+
+    /// WSL release, e.g. `2.3.24.0`; `0` for the inbox WSL that predates
+    /// `wsl --version`
+    fn wsl_version(&self) -> Option<String>;
+
+    /// Whether a Windows path exists
+    fn path_exists(&self, path: &Path) -> bool;
+}
+
+/// Facts about the machine the app runs on
+pub struct SystemHostFacts {
+    runner: Arc<dyn CommandRunner>,
+    windows_build: OnceLock<Option<u32>>,
+}
+
+impl Default for SystemHostFacts {
+    fn default() -> Self {
+        Self::new(system_runner())
+    }
+}
+
+impl SystemHostFacts {
+    pub fn new(runner: Arc<dyn CommandRunner>) -> Self {
+        Self {
+            runner,
+            windows_build: OnceLock::new(),
+        }
+    }
+}
+
+impl HostFacts for SystemHostFacts {
+    fn total_memory_bytes(&self) -> Option<u64> {
+        let (total_mb, _) = host_memory_mb();
+        (total_mb > 0.0).then_some((total_mb * 1024.0 * 1024.0) as u64)
+    }
+
+    fn logical_processors(&self) -> Option<u32> {
+        std::thread::available_parallelism()
+            .ok()
+            .map(|n| n.get() as u32)
+    }
+
+    fn windows_build(&self) -> Option<u32> {
+        *self.windows_build.get_or_init(|| {
+            let output = self.runner.output("cmd", &["/c", "ver"]).ok()?;
+            parse_windows_build(&String::from_utf8_lossy(&output.stdout))
+        })
+    }
+
+    fn wsl_version(&self) -> Option<String> {
+        match run_wsl_command(self.runner.as_ref(), &["--version"]) {
+            Ok(output) => parse_wsl_version(&output),
+            // The inbox wsl.exe rejects the flag
+            Err(TamerError::CommandFailed { .. }) => Some("0".to_string()),
+            Err(_) => None,
+        }
+    }
+
+    fn path_exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+}
+
+/// Host facts given up front
+#[derive(Debug, Clone, Default)]
+pub struct StaticHostFacts {
+    pub total_memory_bytes: Option<u64>,
+    pub logical_processors: Option<u32>,
+    pub windows_build: Option<u32>,
+    pub wsl_version: Option<String>,
+    pub existing_paths: HashSet<PathBuf>,
+}
+
+impl HostFacts for StaticHostFacts {
+    fn total_memory_bytes(&self) -> Option<u64> {
+        self.total_memory_bytes
+    }
+
+    fn logical_processors(&self) -> Option<u32> {
+        self.logical_processors
+    }
+
+    fn windows_build(&self) -> Option<u32> {
+        self.windows_build
+    }
+
+    fn wsl_version(&self) -> Option<String> {
+        self.wsl_version.clone()
+    }
+
+    fn path_exists(&self, path: &Path) -> bool {
+        self.existing_paths.contains(path)
+    }
+}
+
+pub struct ConfigValidator {
+    host: Arc<dyn HostFacts>,
+}
+
+impl Default for ConfigValidator {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemHostFacts::default()))
+    }
+}
+
+impl ConfigValidator {
+    pub fn new(host: Arc<dyn HostFacts>) -> Self {
+        Self { host }
+    }
+
+    /// All problems with a .wslconfig, most severe first
+    pub fn validate_wslconfig(&self, config: &WslConfig) -> Vec<ConfigDiagnostic> {
+        let mut diagnostics = config.diagnostics();
+        self.check_resources(config, &mut diagnostics);
+        self.check_paths(config, &mut diagnostics);
+        self.check_windows_build(config, &mut diagnostics);
+        self.check_wsl_version(config, &mut diagnostics);
+        diagnostics.sort_by_key(|d| std::cmp::Reverse(d.severity));
+        diagnostics
+    }
+
+    fn check_resources(&self, config: &WslConfig, diagnostics: &mut Vec<ConfigDiagnostic>) {
+        let memory = config.memory.as_deref().and_then(parse_size);
+        if let (Some(memory), Some(total)) = (memory, self.host.total_memory_bytes()) {
+            if memory > total {
+                diagnostics.push(
+                    ConfigDiagnostic::warning(
+                        "memory",
+                        format!(
+                            "memory is more than the {} of physical memory; the VM can't use it all",
+                            format_gb(total)
+                        ),
+                    )
+                    .with_suggestion(format!(
+                        "Set memory to at most {}GB",
+                        (total >> 30).max(1)
+                    )),
+                );
+            }
+        }
+
+        if let (Some(processors), Some(cores)) = (config.processors, self.host.logical_processors())
+        {
+            if processors > cores {
+                diagnostics.push(
+                    ConfigDiagnostic::warning(
+                        "processors",
+                        format!(
+                            "processors is {} but the host has {} logical processors; WSL uses at most {}",
+                            processors, cores, cores
+                        ),
+                    )
+                    .with_suggestion(format!("Set processors to {} or less", cores)),
+                );
+            }
+        }
+    }
+
+    fn check_paths(&self, config: &WslConfig, diagnostics: &mut Vec<ConfigDiagnostic>) {
+        for (setting, value) in config.set_values() {
+            if setting.kind != SettingType::Path || value.trim().is_empty() || value.contains('%') {
+                // Empty paths are reported by WslConfig; environment
+                // variables are expanded by WSL and can't be checked here
+                continue;
+            }
+            let path = unescape_path(&value);
+            if !is_absolute_windows_path(&path) {
+                diagnostics.push(
+                    ConfigDiagnostic::error(
+                        setting.key,
+                        format!("{} must be an absolute Windows path", setting.key),
+                    )
+                    .with_suggestion(
+                        "Use a full path such as C:\\\\wsl\\\\file, with doubled backslashes",
+                    ),
+                );
+                continue;
+            }
+
+            // The swap file is created on demand; only its folder must exist
+            let (checked, what) = if setting.key == "swapFile" {
+                let parent = path.rsplit_once(['\\', '/']).map_or("", |(dir, _)| dir);
+                (parent.to_string(), "folder of swapFile")
+            } else {
+                (path.clone(), setting.key)
+            };
+            if !self.host.path_exists(Path::new(&checked)) {
+                diagnostics.push(
+                    ConfigDiagnostic::error(
+                        setting.key,
+                        format!("The {} does not exist: {}", what, checked),
+                    )
+                    .with_suggestion(format!("Fix the path or remove {}", setting.key)),
+                );
+            }
+        }
+    }
+
+    fn check_windows_build(&self, config: &WslConfig, diagnostics: &mut Vec<ConfigDiagnostic>) {
+        let Some(build) = self.host.windows_build() else {
+            return;
+        };
+
+        if config.networking_mode == Some(NetworkingMode::Mirrored) && build < WINDOWS_11_22H2_BUILD
+        {
+            diagnostics.push(
+                ConfigDiagnostic::error(
+                    "networkingMode",
+                    format!(
+                        "Mirrored networking needs Windows 11 22H2 (build {}) or later; this is build {} and WSL falls back to NAT",
+                        WINDOWS_11_22H2_BUILD, build
+                    ),
+                )
+                .with_suggestion("Use nat networking or update Windows"),
+            );
+        }
+
+        let needs_22h2 = [
+            ("dnsTunneling", config.dns_tunneling),
+            ("firewall", config.firewall),
+            ("autoProxy", config.auto_proxy),
+        ];
+        for (key, _) in needs_22h2
+            .into_iter()
+            .filter(|(_, value)| *value == Some(true))
+        {
+            if build < WINDOWS_11_22H2_BUILD {
+                diagnostics.push(
+                    ConfigDiagnostic::warning(
+                        key,
+                        format!(
+                            "{} needs Windows 11 22H2 (build {}) or later and is ignored on build {}",
+                            key, WINDOWS_11_22H2_BUILD, build
+                        ),
+                    )
+                    .with_suggestion(format!("Remove {} or update Windows", key)),
+                );
+            }
+        }
+
+        if config.nested_virtualization == Some(true) && build < WINDOWS_11_BUILD {
+            diagnostics.push(
+                ConfigDiagnostic::warning(
+                    "nestedVirtualization",
+                    format!(
+                        "Nested virtualization needs Windows 11 and is ignored on build {}",
+                        build
+                    ),
+                )
+                .with_suggestion("Remove nestedVirtualization or update Windows"),
+            );
+        }
+    }
+
+    fn check_wsl_version(&self, config: &WslConfig, diagnostics: &mut Vec<ConfigDiagnostic>) {
+        let Some(installed) = self.host.wsl_version() else {
+            return;
+        };
+        let installed_name = if installed == "0" {
+            "the inbox WSL".to_string()
+        } else {
+            format!("WSL {}", installed)
+        };
+
+        for (setting, _) in config.set_values() {
+            let Some(required) = setting.min_wsl_version else {
+                continue;
+            };
+            if version_parts(&installed) < version_parts(required) {
+                diagnostics.push(
+                    ConfigDiagnostic::warning(
+                        setting.key,
+                        format!(
+                            "{} needs WSL {} or later and is ignored by {}",
+                            setting.key, required, installed_name
+                        ),
+                    )
+                    .with_suggestion("Run 'wsl --update' to get the latest WSL"),
+                );
+            }
+        }
+    }
+}
+
+/// Build number from `cmd /c ver`, e.g. `Microsoft Windows [Version 10.0.22631.4317]`
+fn parse_windows_build(output: &str) -> Option<u32> {
+    let start = output.find('[')?;
+    let end = output[start..].find(']')? + start;
+    let version = output[start + 1..end].split_whitespace().last()?;
+    version.split('.').nth(2)?.parse().ok()
+}
+
+/// Release from the first line of `wsl --version`, e.g. `WSL version: 2.3.24.0`
+///
+/// The label is localized, so only the value after the colon is used.
+fn parse_wsl_version(output: &str) -> Option<String> {
+    let value = output.lines().next()?.rsplit(':').next()?.trim();
+    let valid = !value.is_empty()
+        && value
+            .split('.')
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+    valid.then(|| value.to_string())
+}
+
+/// Dotted version as comparable numbers
+fn version_parts(version: &str) -> Vec<u32> {
+    version.split('.').map(|p| p.parse().unwrap_or(0)).collect()
+}
+
+/// .wslconfig paths escape backslashes
+fn unescape_path(value: &str) -> String {
+    value.trim().replace("\\\\", "\\")
+}
+
+fn is_absolute_windows_path(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    let drive = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'\\' | b'/');
+    drive || path.starts_with("\\\\")
+}
+
+fn format_gb(bytes: u64) -> String {
+    format!("{:.1}GB", bytes as f64 / (1u64 << 30) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DiagnosticSeverity;
+    use crate::utils::{CommandOutput, ScriptedRunner};
+
+    fn host() -> StaticHostFacts {
+        StaticHostFacts {
+            total_memory_bytes: Some(16 << 30),
+            logical_processors: Some(8),
+            windows_build: Some(22631),
+            wsl_version: Some("2.3.24.0".into()),
+            existing_paths: HashSet::from([
+                PathBuf::from("C:\\wsl\\bzImage"),
+                PathBuf::from("D:\\swap"),
+            ]),
+        }
+    }
+
+    fn validate(host: StaticHostFacts, config: &WslConfig) -> Vec<ConfigDiagnostic> {
+        ConfigValidator::new(Arc::new(host)).validate_wslconfig(config)
+    }
+
+    fn keys(diagnostics: &[ConfigDiagnostic]) -> Vec<&str> {
+        diagnostics
+            .iter()
+            .filter_map(|d| d.key.as_deref())
+            .collect()
+    }
+
+    #[test]
+    fn test_fitting_config_has_no_diagnostics() {
+        let config = WslConfig::from_ini(
+            "[wsl2]\nmemory=12GB\nprocessors=8\nkernel=C:\\\\wsl\\\\bzImage\nswapFile=D:\\\\swap\\\\swap.vhdx\nnetworkingMode=mirrored\ndnsTunneling=true\n",
+        )
+        .unwrap();
+        assert_eq!(validate(host(), &config), vec![]);
+    }
+
+    #[test]
+    fn test_resources_above_host_are_flagged() {
+        let config = WslConfig {
+            memory: Some("32GB".into()),
+            processors: Some(16),
+            ..Default::default()
+        };
+        let diagnostics = validate(host(), &config);
+        assert_eq!(keys(&diagnostics), vec!["memory", "processors"]);
+        assert_eq!(
+            diagnostics[0].suggestion.as_deref(),
+            Some("Set memory to at most 16GB")
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.severity == DiagnosticSeverity::Warning));
+    }
+
+    #[test]
+    fn test_missing_and_relative_paths_are_errors() {
+        let config = WslConfig {
+            kernel: Some("C:\\\\wsl\\\\missing".into()),
+            kernel_modules: Some("modules.vhdx".into()),
+            swap_file: Some("E:\\\\nowhere\\\\swap.vhdx".into()),
+            ..Default::default()
+        };
+        let diagnostics = validate(host(), &config);
+        assert_eq!(
+            keys(&diagnostics),
+            vec!["swapFile", "kernel", "kernelModules"]
+        );
+        assert!(diagnostics[0].message.contains("E:\\nowhere"));
+        assert!(diagnostics[1].message.contains("C:\\wsl\\missing"));
+        assert!(diagnostics[2].message.contains("absolute"));
+    }
+
+    #[test]
+    fn test_old_windows_and_wsl_releases() {
+        let config = WslConfig {
+            networking_mode: Some(NetworkingMode::Mirrored),
+            dns_tunneling: Some(true),
+            nested_virtualization: Some(true),
+            ..Default::default()
+        };
+        let old = StaticHostFacts {
+            windows_build: Some(19045),
+            wsl_version: Some("1.2.5.0".into()),
+            ..host()
+        };
+        let diagnostics = validate(old, &config);
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Error);
+        assert_eq!(diagnostics[0].key.as_deref(), Some("networkingMode"));
+        assert_eq!(
+            keys(&diagnostics),
+            vec![
+                "networkingMode",
+                "dnsTunneling",
+                "nestedVirtualization",
+                "networkingMode",
+                "dnsTunneling"
+            ]
+        );
+        assert!(diagnostics[4].message.contains("WSL 1.2.5.0"));
+
+        // Unknown facts skip the checks that need them
+        let unknown = StaticHostFacts::default();
+        assert_eq!(validate(unknown, &config), vec![]);
+    }
+
+    #[test]
+    fn test_system_facts_parse_commands() {
+        let runner = ScriptedRunner::new()
+            .on(
+                "cmd",
+                &["/c", "ver"],
+                CommandOutput::ok("\r\nMicrosoft Windows [Version 10.0.22631.4317]\r\n"),
+            )
+            .on(
+                "wsl",
+                &["--version"],
+                CommandOutput::ok_utf16(
+                    "WSL version: 2.3.24.0\r\nKernel version: 5.15.153.1-2\r\n",
+                ),
+            );
+        let facts = SystemHostFacts::new(Arc::new(runner));
+        assert_eq!(facts.windows_build(), Some(22631));
+        assert_eq!(facts.wsl_version().as_deref(), Some("2.3.24.0"));
+
+        let inbox = ScriptedRunner::new().on(
+            "wsl",
+            &["--version"],
+            CommandOutput::failed(-1, "Invalid command line option: --version"),
+        );
+        let facts = SystemHostFacts::new(Arc::new(inbox));
+        assert_eq!(facts.wsl_version().as_deref(), Some("0"));
+        assert_eq!(facts.windows_build(), None);
+    }
+}
@@ -0,0 +1,352 @@
+//! CPU Sampler - WSL VM CPU usage from successive readings
+//!
+//! CPU usage is a rate, so a single reading of the counters means nothing on
+//! its own. The sampler keeps the previous reading of each source and reports
+//! the usage between it and the current one:
+//!
+//! - host side: kernel + user time of the vmmem/vmmemWSL process over wall
+//!   time, normalized by the number of logical cores
+//! - guest side: busy vs. total jiffies of the aggregate `cpu` line of
+//!   `/proc/stat` inside the VM
+//!
+//! The guest is only read from a distribution that is already running, so
+//! sampling never boots one.
+//!
+//! Without a previous reading (first call, or after the VM restarted) the
+//! sampler takes one, waits briefly and reads again, so every call returns a
+//! meaningful percentage.
+
+use crate::error::{TamerError, TamerResult};
+use crate::utils::{run_wsl_command_to_writer, system_runner, CommandRunner, RunControl};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Total processor time of the vmmem/vmmemWSL processes, in 100 ns ticks
+const VMMEM_CPU_SCRIPT: &str =
+    "(Get-Process -Name 'vmmem*' -ErrorAction SilentlyContinue | ForEach-Object { $_.TotalProcessorTime.Ticks } | Measure-Object -Sum).Sum";
+
+/// Processor time ticks per second (.NET `TimeSpan` ticks are 100 ns)
+const TICKS_PER_SECOND: f64 = 10_000_000.0;
+
+/// Wait between the two readings taken when there's no previous one
+const PRIME_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Time limit for reading /proc/stat in a distribution
+const PROC_STAT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// CPU time consumed by the VM process at a point in time
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct HostReading {
+    at: Instant,
+    ticks: u64,
+}
+
+/// Aggregate counters from the `cpu` line of `/proc/stat`, in jiffies
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct ProcStat {
+    busy: u64,
+    total: u64,
+}
+
+/// CPU usage of the WSL VM, as seen from both sides
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CpuSample {
+    /// Share of all host cores used by the VM process (0–100)
+    pub host_percent: f64,
+    /// Share of the VM's cores that were busy (0–100), if the guest could
+    /// be read
+    pub guest_percent: Option<f64>,
+}
+
+pub struct CpuSampler {
+    runner: Arc<dyn CommandRunner>,
+    cores: f64,
+    prime_interval: Duration,
+    host: Mutex<Option<HostReading>>,
+    guest: Mutex<Option<ProcStat>>,
+}
+
+impl Default for CpuSampler {
+    fn default() -> Self {
+        Self::new(system_runner())
+    }
+}
+
+impl CpuSampler {
+    pub fn new(runner: Arc<dyn CommandRunner>) -> Self {
+        let cores = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        Self {
+            runner,
+            cores: cores as f64,
+            prime_interval: PRIME_INTERVAL,
+            host: Mutex::new(None),
+            guest: Mutex::new(None),
+        }
+    }
+
+    /// Number of logical host cores the VM's usage is normalized by
+    pub fn with_cores(mut self, cores: usize) -> Self {
+        self.cores = cores.max(1) as f64;
+        self
+    }
+
+    pub fn with_prime_interval(mut self, interval: Duration) -> Self {
+        self.prime_interval = interval;
+        self
+    }
+
+    /// CPU usage since the previous call
+    ///
+    /// `/proc/stat` is only read from `guest`, a distribution that is
+    /// already running; without one only the host side is measured.
+    pub fn sample(&self, guest: Option<&str>) -> CpuSample {
+        let guest_percent = if let Some(distro) = guest {
+            self.guest_percent(distro)
+                .map_err(|e| log::debug!("Could not read /proc/stat: {}", e))
+                .ok()
+        } else {
+            self.clear(&self.guest);
+            None
+        };
+        CpuSample {
+            host_percent: self.host_percent(),
+            guest_percent,
+        }
+    }
+
+    /// Forget previous readings, e.g. after the VM shut down
+    pub fn reset(&self) {
+        self.clear(&self.host);
+        self.clear(&self.guest);
+    }
+
+    fn clear<T>(&self, slot: &Mutex<Option<T>>) {
+        if let Ok(mut previous) = slot.lock() {
+            *previous = None;
+        }
+    }
+
+    /// Host cores used by vmmem since the previous reading, in percent
+    fn host_percent(&self) -> f64 {
+        let read = || {
+            self.vmmem_ticks().map(|ticks| HostReading {
+                at: Instant::now(),
+                ticks,
+            })
+        };
+        let Some(reading) = read() else {
+            // Not running: the next process starts from zero again
+            self.clear(&self.host);
+            return 0.0;
+        };
+        if let Some(percent) = self.record_host(reading) {
+            return percent;
+        }
+        std::thread::sleep(self.prime_interval);
+        read()
+            .and_then(|reading| self.record_host(reading))
+            .unwrap_or(0.0)
+    }
+
+    /// Store `reading` and return the usage since the one it replaces
+    fn record_host(&self, reading: HostReading) -> Option<f64> {
+        let previous = self.host.lock().ok()?.replace(reading)?;
+        let wall = reading
+            .at
+            .checked_duration_since(previous.at)?
+            .as_secs_f64();
+        // A restarted VM process reports less time than its predecessor
+        let ticks = reading.ticks.checked_sub(previous.ticks)?;
+        if wall <= 0.0 {
+            return None;
+        }
+        let cpu = ticks as f64 / TICKS_PER_SECOND;
+        Some(clamp_percent(cpu / (wall * self.cores) * 100.0))
+    }
+
+    /// Busy share of the guest's jiffies since the previous reading
+    ///
+    /// All distributions share the VM's kernel, so any running one will do.
+    fn guest_percent(&self, distro: &str) -> TamerResult<f64> {
+        let reading = self.read_proc_stat(distro)?;
+        if let Some(percent) = self.record_guest(reading) {
+            return Ok(percent);
+        }
+        std::thread::sleep(self.prime_interval);
+        let reading = self.read_proc_stat(distro)?;
+        Ok(self.record_guest(reading).unwrap_or(0.0))
+    }
+
+    fn record_guest(&self, reading: ProcStat) -> Option<f64> {
+        let previous = self.guest.lock().ok()?.replace(reading)?;
+        let total = reading.total.checked_sub(previous.total)?;
+        let busy = reading.busy.saturating_sub(previous.busy);
+        if total == 0 {
+            return None;
+        }
+        Some(clamp_percent(busy as f64 / total as f64 * 100.0))
+    }
+
+    /// Total processor time of the VM process, `None` if it isn't running
+    fn vmmem_ticks(&self) -> Option<u64> {
+        let output = self
+            .runner
+            .output("powershell", &["-NoProfile", "-Command", VMMEM_CPU_SCRIPT])
+            .ok()?;
+        if !output.success() {
+            return None;
+        }
+        let ticks = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse::<u64>()
+            .ok()?;
+        (ticks > 0).then_some(ticks)
+    }
+
+    fn read_proc_stat(&self, distro: &str) -> TamerResult<ProcStat> {
+        // Guest output is UTF-8, unlike wsl.exe's own messages
+        let mut stdout = Vec::new();
+        run_wsl_command_to_writer(
+            self.runner.as_ref(),
+            &["-d", distro, "--", "cat", "/proc/stat"],
+            &mut stdout,
+            &RunControl::new(Some(PROC_STAT_TIMEOUT), Default::default()),
+        )?;
+        parse_proc_stat(&String::from_utf8_lossy(&stdout))
+    }
+}
+
+/// Parse the aggregate `cpu` line of /proc/stat
+///
+/// Columns are user, nice, system, idle, iowait, irq, softirq, steal, guest
+/// and guest_nice. Guest time is already counted in user/nice, and idle plus
+/// iowait is time the CPU had nothing to run.
+fn parse_proc_stat(content: &str) -> TamerResult<ProcStat> {
+    let line = content
+        .lines()
+        .find(|line| line.split_whitespace().next() == Some("cpu"))
+        .ok_or_else(|| TamerError::Parse("/proc/stat has no cpu line".to_string()))?;
+    let fields = line
+        .split_whitespace()
+        .skip(1)
+        .take(8)
+        .map(|field| field.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| TamerError::Parse(format!("Invalid /proc/stat cpu line: {}", e)))?;
+    if fields.len() < 4 {
+        return Err(TamerError::Parse(
+            "/proc/stat cpu line is too short".to_string(),
+        ));
+    }
+    let total: u64 = fields.iter().sum();
+    let idle = fields[3] + fields.get(4).copied().unwrap_or(0);
+    Ok(ProcStat {
+        busy: total - idle,
+        total,
+    })
+}
+
+fn clamp_percent(percent: f64) -> f64 {
+    percent.clamp(0.0, 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{CommandOutput, ScriptedRunner};
+
+    const VMMEM: [&str; 3] = ["-NoProfile", "-Command", VMMEM_CPU_SCRIPT];
+    const PROC_STAT: [&str; 5] = ["-d", "Ubuntu", "--", "cat", "/proc/stat"];
+
+    fn stat(user: u64, system: u64, idle: u64) -> String {
+        format!(
+            "cpu  {} 0 {} {} 0 0 0 0 0 0\ncpu0 {} 0 {} {} 0 0 0 0 0 0\nintr 0\n",
+            user, system, idle, user, system, idle
+        )
+    }
+
+    fn sampler(runner: ScriptedRunner) -> CpuSampler {
+        CpuSampler::new(Arc::new(runner))
+            .with_cores(4)
+            .with_prime_interval(Duration::from_millis(1))
+    }
+
+    #[test]
+    fn test_parse_proc_stat() {
+        let parsed = parse_proc_stat("cpu  100 20 30 800 50 0 0 0 10 0\ncpu0 1 2 3 4\n").unwrap();
+        assert_eq!(parsed.total, 1000);
+        assert_eq!(parsed.busy, 150);
+
+        assert!(parse_proc_stat("intr 0\n").is_err());
+        assert!(parse_proc_stat("cpu 1 2\n").is_err());
+    }
+
+    #[test]
+    fn test_host_percent_normalized_by_cores() {
+        let sampler = sampler(ScriptedRunner::new());
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert_eq!(
+            sampler.record_host(HostReading {
+                at: at(0),
+                ticks: 0
+            }),
+            None
+        );
+        // 2 s of CPU time over 1 s of wall time on 4 cores
+        let percent = sampler.record_host(HostReading {
+            at: at(1),
+            ticks: 20_000_000,
+        });
+        assert_eq!(percent, Some(50.0));
+
+        // The VM restarted: no usage until there's a new baseline
+        assert_eq!(
+            sampler.record_host(HostReading {
+                at: at(2),
+                ticks: 5
+            }),
+            None
+        );
+        let percent = sampler.record_host(HostReading {
+            at: at(4),
+            ticks: 5 + 80_000_000,
+        });
+        assert_eq!(percent, Some(100.0));
+    }
+
+    #[test]
+    fn test_first_sample_primes_with_a_second_reading() {
+        let runner = ScriptedRunner::new()
+            .on("powershell", &VMMEM, CommandOutput::ok("1000\r\n"))
+            .on("powershell", &VMMEM, CommandOutput::ok("2000\r\n"))
+            .on("wsl", &PROC_STAT, CommandOutput::ok(stat(100, 100, 800)))
+            .on("wsl", &PROC_STAT, CommandOutput::ok(stat(130, 120, 850)))
+            .on("wsl", &PROC_STAT, CommandOutput::ok(stat(130, 120, 950)));
+        let sampler = sampler(runner);
+
+        let first = sampler.sample(Some("Ubuntu"));
+        assert!(first.host_percent > 0.0);
+        // 50 busy out of 100 jiffies
+        assert_eq!(first.guest_percent, Some(50.0));
+
+        // Later calls compare against the previous call's reading
+        let second = sampler.sample(Some("Ubuntu"));
+        assert_eq!(second.guest_percent, Some(0.0));
+    }
+
+    #[test]
+    fn test_stopped_vm_reports_zero_and_skips_guest() {
+        let runner =
+            Arc::new(ScriptedRunner::new().on("powershell", &VMMEM, CommandOutput::ok("\r\n")));
+        let sampler = CpuSampler::new(runner.clone());
+
+        let sample = sampler.sample(None);
+        assert_eq!(sample, CpuSample::default());
+        assert!(!runner.was_called("wsl", &PROC_STAT));
+        assert_eq!(runner.calls().len(), 1);
+    }
+}
@@ -0,0 +1,293 @@
+//! Disk Metrics - Per-distribution virtual disk usage
+//!
+//! Combines the host's view of a WSL2 disk (size of the VHDX file and its
+//! maximum virtual size from the header) with the guest's view (`df` inside
+//! the running distribution). The difference between what the file occupies
+//! and what the guest uses is space a compaction would give back.
+
+use crate::error::{TamerError, TamerResult};
+use crate::models::{DistributionState, WslDistribution};
+use crate::utils::{run_wsl_command_to_writer, system_runner, CommandRunner, RunControl};
+use crate::vhdx;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a sample is reused before the disk is measured again
+const DISK_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Limit for `df` inside a distribution (it may have to boot the VM first)
+const DF_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sizes of one distribution's disk, in bytes (`None` when unknown)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiskSample {
+    pub vhd_path: Option<PathBuf>,
+    /// Size of the VHDX file on the host
+    pub allocated_bytes: Option<u64>,
+    /// Maximum size of the disk, from the VHDX metadata
+    pub virtual_bytes: Option<u64>,
+    /// Space used by the guest file system
+    pub used_bytes: Option<u64>,
+}
+
+impl DiskSample {
+    /// Host space the guest isn't using
+    pub fn reclaimable_bytes(&self) -> Option<u64> {
+        Some(self.allocated_bytes?.saturating_sub(self.used_bytes?))
+    }
+}
+
+struct CachedSample {
+    sampled_at: Instant,
+    running: bool,
+    sample: DiskSample,
+}
+
+pub struct DiskMetricsCollector {
+    runner: Arc<dyn CommandRunner>,
+    ttl: Duration,
+    cache: Mutex<HashMap<String, CachedSample>>,
+}
+
+impl Default for DiskMetricsCollector {
+    fn default() -> Self {
+        Self::new(system_runner())
+    }
+}
+
+impl DiskMetricsCollector {
+    pub fn new(runner: Arc<dyn CommandRunner>) -> Self {
+        Self {
+            runner,
+            ttl: DISK_CACHE_TTL,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Disk sizes of `distro`, measured at most once per TTL
+    ///
+    /// A distribution that starts or stops is measured again straight away.
+    /// Stopped distributions aren't booted just to run `df`; their last known
+    /// usage is kept instead.
+    pub fn collect(&self, distro: &WslDistribution) -> DiskSample {
+        let running = distro.state == DistributionState::Running;
+        let previous = match self.cache.lock() {
+            Ok(cache) => match cache.get(&distro.name) {
+                Some(cached)
+                    if cached.running == running && cached.sampled_at.elapsed() < self.ttl =>
+                {
+                    return cached.sample.clone();
+                }
+                Some(cached) => cached.sample.used_bytes,
+                None => None,
+            },
+            Err(_) => None,
+        };
+
+        let mut sample = Self::host_sample(distro);
+        sample.used_bytes = if running {
+            self.guest_used_bytes(&distro.name)
+                .map_err(|e| log::debug!("df failed in {}: {}", distro.name, e))
+                .ok()
+                .or(previous)
+        } else {
+            previous
+        };
+
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(
+                distro.name.clone(),
+                CachedSample {
+                    sampled_at: Instant::now(),
+                    running,
+                    sample: sample.clone(),
+                },
+            );
+        }
+        sample
+    }
+
+    /// Disk sizes of `distro` without running anything inside it
+    ///
+    /// The VHDX sizes are read from the host; guest usage is the last one
+    /// [`Self::collect`] measured, if any. Nothing is cached.
+    pub fn host_only(&self, distro: &WslDistribution) -> DiskSample {
+        let used_bytes = self
+            .cache
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(&distro.name).and_then(|c| c.sample.used_bytes));
+        DiskSample {
+            used_bytes,
+            ..Self::host_sample(distro)
+        }
+    }
+
+    /// VHDX path and sizes, as seen from the host
+    fn host_sample(distro: &WslDistribution) -> DiskSample {
+        let mut sample = DiskSample {
+            vhd_path: distro.registration.as_ref().and_then(|r| r.vhd_path()),
+            ..Default::default()
+        };
+        if let Some(path) = &sample.vhd_path {
+            match vhdx::read_info(path) {
+                Ok(info) => {
+                    sample.allocated_bytes = Some(info.file_size);
+                    sample.virtual_bytes = Some(info.virtual_size);
+                }
+                Err(e) => {
+                    log::debug!("Could not read {}: {}", path.display(), e);
+                    sample.allocated_bytes = std::fs::metadata(path).ok().map(|m| m.len());
+                }
+            }
+        }
+        sample
+    }
+
+    /// Drop cached samples, e.g. after a disk was compacted or resized
+    pub fn invalidate(&self, name: Option<&str>) {
+        if let Ok(mut cache) = self.cache.lock() {
+            match name {
+                Some(name) => {
+                    cache.remove(name);
+                }
+                None => cache.clear(),
+            }
+        }
+    }
+
+    /// Bytes used on the root file system, from `df` inside the distribution
+    fn guest_used_bytes(&self, name: &str) -> TamerResult<u64> {
+        // Guest output is UTF-8, unlike wsl.exe's own messages
+        let mut stdout = Vec::new();
+        run_wsl_command_to_writer(
+            self.runner.as_ref(),
+            &["-d", name, "--", "df", "-kP", "/"],
+            &mut stdout,
+            &RunControl::new(Some(DF_TIMEOUT), Default::default()),
+        )?;
+        parse_df_used_kb(&String::from_utf8_lossy(&stdout)).map(|kb| kb * 1024)
+    }
+}
+
+/// Used 1K-blocks from POSIX `df -kP` output
+fn parse_df_used_kb(output: &str) -> TamerResult<u64> {
+    output
+        .lines()
+        .skip(1)
+        .find_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            fields.get(2)?.parse().ok()
+        })
+        .ok_or_else(|| TamerError::Parse(format!("Unexpected df output: {}", output.trim())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DistroRegistration;
+    use crate::utils::{CommandOutput, ScriptedRunner};
+    use crate::vhdx::{fixture::VhdxBuilder, MIB};
+
+    const DF_OUTPUT: &str = "\
+Filesystem     1024-blocks    Used Available Capacity Mounted on
+/dev/sdc        1055762868 1024 996995708       1% /
+";
+
+    fn distro(state: DistributionState, base_path: &str) -> WslDistribution {
+        WslDistribution {
+            name: "Ubuntu".to_string(),
+            state,
+            version: "2".to_string(),
+            is_default: true,
+            registration: Some(DistroRegistration {
+                id: "{6d9bd9b3-9c6a-4c1a-8d3e-0f1b2c3d4e5f}".to_string(),
+                name: "Ubuntu".to_string(),
+                base_path: base_path.to_string(),
+                version: Some(2),
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn df_args() -> [&'static str; 6] {
+        ["-d", "Ubuntu", "--", "df", "-kP", "/"]
+    }
+
+    #[test]
+    fn test_parse_df_output() {
+        assert_eq!(parse_df_used_kb(DF_OUTPUT).unwrap(), 1024);
+        assert_eq!(
+            parse_df_used_kb("df: /: No such file\n")
+                .unwrap_err()
+                .code(),
+            "PARSE_ERROR"
+        );
+    }
+
+    #[test]
+    fn test_collects_host_and_guest_sizes_and_caches_them() {
+        let dir = std::env::temp_dir().join(format!("wsl-tamer-disk-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let image = VhdxBuilder::new(256 * MIB).build();
+        std::fs::write(dir.join("ext4.vhdx"), &image).unwrap();
+
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on("wsl", &df_args(), CommandOutput::ok(DF_OUTPUT))
+                .on("wsl", &df_args(), CommandOutput::ok(DF_OUTPUT)),
+        );
+        let collector = DiskMetricsCollector::new(runner.clone());
+        let running = distro(DistributionState::Running, &dir.to_string_lossy());
+
+        let sample = collector.collect(&running);
+        assert_eq!(sample.allocated_bytes, Some(image.len() as u64));
+        assert_eq!(sample.virtual_bytes, Some(256 * MIB));
+        assert_eq!(sample.used_bytes, Some(1024 * 1024));
+        assert_eq!(sample.reclaimable_bytes(), Some(image.len() as u64 - MIB));
+
+        // Cached: no second df
+        assert_eq!(collector.collect(&running), sample);
+        assert_eq!(runner.calls().len(), 1);
+
+        // Stopping is picked up at once, without booting the distro
+        let stopped = distro(DistributionState::Stopped, &dir.to_string_lossy());
+        assert_eq!(collector.collect(&stopped).used_bytes, Some(1024 * 1024));
+        assert_eq!(runner.calls().len(), 1);
+
+        // Host-only reads keep the last guest usage and run nothing
+        assert_eq!(collector.host_only(&running), sample);
+        assert_eq!(runner.calls().len(), 1);
+
+        collector.invalidate(None);
+        collector.collect(&running);
+        assert_eq!(runner.calls().len(), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_expired_samples_are_measured_again() {
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on("wsl", &df_args(), CommandOutput::ok(DF_OUTPUT))
+                .on("wsl", &df_args(), CommandOutput::failed(1, "boom")),
+        );
+        let collector = DiskMetricsCollector::new(runner.clone()).with_ttl(Duration::ZERO);
+        let running = distro(DistributionState::Running, "/nonexistent");
+
+        let first = collector.collect(&running);
+        assert_eq!(first.allocated_bytes, None);
+        assert_eq!(first.used_bytes, Some(1024 * 1024));
+
+        // A failed df keeps the last known usage
+        assert_eq!(collector.collect(&running).used_bytes, Some(1024 * 1024));
+        assert_eq!(runner.calls().len(), 2);
+    }
+}
@@ -0,0 +1,825 @@
+//! Disk Service - Maintenance of WSL2 virtual disks
+//!
+//! A VHDX grows as the guest writes but never shrinks on its own. Compaction
+//! first trims the guest file system so freed blocks are released, stops the
+//! distribution so the disk is detached, then has Windows compact the file.
+//! Resizing stops the distribution, raises the disk's maximum size, then
+//! grows the guest file system to match.
+//!
+//! Both use the Hyper-V cmdlets when installed and diskpart otherwise, run
+//! from a script in an elevated process.
+
+use crate::error::{TamerError, TamerResult};
+use crate::models::{
+    CompactionMethod, CompactionReport, DistributionState, JobPhase, ResizeMethod, ResizePlan,
+};
+use crate::services::{JobHandle, WslService};
+use crate::utils::{
+    app_data_dir, format_size, run_elevated, run_powershell_command, run_wsl_command_controlled,
+    run_wsl_command_to_writer, system_runner, unix_timestamp, CommandRunner, RunControl,
+};
+use crate::vhdx::{self, VhdxInfo, MIB};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Limit for `fstrim` (large, mostly empty disks take a while)
+const TRIM_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Limit for `resize2fs` and finding the root device
+const RESIZE2FS_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Largest virtual size the VHDX format allows (64 TiB)
+pub const MAX_VIRTUAL_SIZE: u64 = 64 * 1024 * 1024 * MIB;
+
+pub struct DiskService {
+    runner: Arc<dyn CommandRunner>,
+    /// Folder for the scripts handed to elevated processes
+    script_dir: PathBuf,
+    /// Last compaction of each distribution
+    reports: Mutex<HashMap<String, CompactionReport>>,
+}
+
+impl Default for DiskService {
+    fn default() -> Self {
+        Self::new(system_runner(), app_data_dir().join("scripts"))
+    }
+}
+
+impl DiskService {
+    pub fn new(runner: Arc<dyn CommandRunner>, script_dir: PathBuf) -> Self {
+        Self {
+            runner,
+            script_dir,
+            reports: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Trim, stop and compact the virtual disk of `name`
+    ///
+    /// Can be cancelled until compaction itself starts; interrupting diskpart
+    /// or `Optimize-VHD` halfway is not safe. A failed trim only makes the
+    /// compaction less effective, so it is logged and skipped. Compaction
+    /// needs elevation, so the user sees a UAC prompt.
+    pub fn compact(
+        &self,
+        wsl: &WslService,
+        name: &str,
+        job: &JobHandle,
+    ) -> TamerResult<CompactionReport> {
+        let started_at = unix_timestamp();
+        let (vhd, before) = self.disk_of(wsl, name, "compacted")?;
+        job.track_file(&vhd);
+
+        job.set_phase(JobPhase::Trimming);
+        let trimmed = match self.trim(name, job) {
+            Ok(()) => true,
+            Err(TamerError::Cancelled) => return Err(TamerError::Cancelled),
+            Err(e) => {
+                log::warn!("fstrim failed in {}, compacting anyway: {}", name, e);
+                false
+            }
+        };
+        if job.is_cancelled() {
+            return Err(TamerError::Cancelled);
+        }
+
+        job.set_phase(JobPhase::Stopping);
+        wsl.stop_distribution(name)?;
+        wsl.invalidate_distro_cache();
+        if job.is_cancelled() {
+            return Err(TamerError::Cancelled);
+        }
+
+        job.set_phase(JobPhase::Compacting);
+        let method = if self.has_cmdlet("Optimize-VHD") {
+            CompactionMethod::OptimizeVhd
+        } else {
+            CompactionMethod::Diskpart
+        };
+        self.run_script(
+            method == CompactionMethod::OptimizeVhd,
+            "compact",
+            name,
+            &compaction_script(method, &vhd),
+        )?;
+
+        // A failed compaction already failed the script; the disk must
+        // still be readable afterwards
+        job.set_phase(JobPhase::Verifying);
+        let after = vhdx::read_info(&vhd)
+            .map_err(|e| e.context("The disk could not be read after compaction"))?;
+
+        let report = CompactionReport {
+            distro: name.to_string(),
+            vhd_path: vhd.to_string_lossy().into_owned(),
+            method,
+            trimmed,
+            size_before: before.file_size,
+            size_after: after.file_size,
+            reclaimed_bytes: before.file_size.saturating_sub(after.file_size),
+            started_at,
+            finished_at: unix_timestamp(),
+        };
+        log::info!(
+            "Compacted {}: {} -> {} bytes",
+            name,
+            report.size_before,
+            report.size_after
+        );
+        if let Ok(mut reports) = self.reports.lock() {
+            reports.insert(name.to_string(), report.clone());
+        }
+        Ok(report)
+    }
+
+    /// Result of the last compaction of `name`, if any
+    pub fn last_compaction(&self, name: &str) -> Option<CompactionReport> {
+        self.reports.lock().ok()?.get(name).cloned()
+    }
+
+    /// Validate growing the disk of `name` to `new_size` bytes and describe
+    /// the steps, without changing anything
+    ///
+    /// Only growing is supported: shrinking ext4 safely needs the file system
+    /// shrunk first, offline. The added capacity must fit in the free space
+    /// of the host volume, since the file can grow by that much.
+    pub fn plan_resize(
+        &self,
+        wsl: &WslService,
+        name: &str,
+        new_size: u64,
+    ) -> TamerResult<ResizePlan> {
+        let (vhd, info) = self.disk_of(wsl, name, "resized")?;
+        if new_size <= info.virtual_size {
+            return Err(TamerError::InvalidInput(format!(
+                "The new size must be larger than the current {} (shrinking is not supported)",
+                format_size(info.virtual_size)
+            )));
+        }
+        if !new_size.is_multiple_of(MIB) {
+            return Err(TamerError::InvalidInput(
+                "The new size must be a whole number of megabytes".to_string(),
+            ));
+        }
+        if new_size > MAX_VIRTUAL_SIZE {
+            return Err(TamerError::InvalidInput(format!(
+                "The new size cannot exceed {}",
+                format_size(MAX_VIRTUAL_SIZE)
+            )));
+        }
+
+        let mut warnings = Vec::new();
+        let added = new_size - info.virtual_size;
+        let host_free_bytes = self.host_free_bytes(&vhd);
+        match host_free_bytes {
+            Some(free) if free < added => {
+                return Err(TamerError::InvalidInput(format!(
+                    "Only {} is free on the host volume, less than the {} being added",
+                    format_size(free),
+                    format_size(added)
+                )));
+            }
+            Some(free) if free < new_size - info.file_size => warnings.push(format!(
+                "If {} fills its disk, the file would outgrow the {} free on the host volume",
+                name,
+                format_size(free)
+            )),
+            Some(_) => {}
+            None => warnings.push("Free space on the host volume could not be read".to_string()),
+        }
+        let running = wsl
+            .get_distributions()
+            .map(|distros| {
+                distros.iter().any(|d| {
+                    d.name.eq_ignore_ascii_case(name) && d.state == DistributionState::Running
+                })
+            })
+            .unwrap_or(false);
+        if running {
+            warnings.push(format!(
+                "{} is running; its processes will be stopped",
+                name
+            ));
+        }
+
+        let method = if self.has_cmdlet("Resize-VHD") {
+            ResizeMethod::ResizeVhd
+        } else {
+            ResizeMethod::Diskpart
+        };
+        let path = vhd.to_string_lossy().into_owned();
+        let tool = match method {
+            ResizeMethod::ResizeVhd => "Resize-VHD".to_string(),
+            ResizeMethod::Diskpart => format!("diskpart (expand vdisk maximum={})", new_size / MIB),
+        };
+        let steps = vec![
+            format!("Stop {} (wsl --terminate {})", name, name),
+            format!(
+                "Expand {} from {} to {} with {}, as administrator",
+                path,
+                format_size(info.virtual_size),
+                format_size(new_size),
+                tool
+            ),
+            format!("Check that the disk reports {}", format_size(new_size)),
+            format!(
+                "Start {} as root and grow the file system on the device mounted at / with resize2fs",
+                name
+            ),
+        ];
+
+        Ok(ResizePlan {
+            distro: name.to_string(),
+            vhd_path: path,
+            method,
+            current_size: info.virtual_size,
+            new_size,
+            file_size: info.file_size,
+            host_free_bytes,
+            steps,
+            warnings,
+        })
+    }
+
+    /// Carry out `plan`: stop, expand the VHDX, then grow the file system
+    ///
+    /// Can be cancelled until the disk is expanded. If `resize2fs` fails the
+    /// disk is still larger, and running it again later is safe.
+    pub fn resize(&self, wsl: &WslService, plan: &ResizePlan, job: &JobHandle) -> TamerResult<()> {
+        let name = plan.distro.as_str();
+        let vhd = PathBuf::from(&plan.vhd_path);
+        if job.is_cancelled() {
+            return Err(TamerError::Cancelled);
+        }
+
+        job.set_phase(JobPhase::Stopping);
+        wsl.stop_distribution(name)?;
+        wsl.invalidate_distro_cache();
+        if job.is_cancelled() {
+            return Err(TamerError::Cancelled);
+        }
+
+        job.set_phase(JobPhase::Resizing);
+        self.run_script(
+            plan.method == ResizeMethod::ResizeVhd,
+            "resize",
+            name,
+            &resize_script(plan.method, &vhd, plan.new_size),
+        )?;
+
+        // Confirm the new size took effect
+        job.set_phase(JobPhase::Verifying);
+        let after = vhdx::read_info(&vhd)?;
+        if after.virtual_size != plan.new_size {
+            return Err(TamerError::CommandFailed {
+                command: match plan.method {
+                    ResizeMethod::ResizeVhd => "Resize-VHD".to_string(),
+                    ResizeMethod::Diskpart => "diskpart".to_string(),
+                },
+                exit_code: None,
+                stderr: format!(
+                    "The disk is still {} after resizing",
+                    format_size(after.virtual_size)
+                ),
+            });
+        }
+
+        job.set_phase(JobPhase::ExpandingFilesystem);
+        self.grow_filesystem(name, &job.uncancellable())?;
+        log::info!(
+            "Resized {} from {} to {} bytes",
+            name,
+            plan.current_size,
+            plan.new_size
+        );
+        Ok(())
+    }
+
+    /// VHDX of a WSL 2 distribution that can be `action` (not differencing)
+    fn disk_of(
+        &self,
+        wsl: &WslService,
+        name: &str,
+        action: &str,
+    ) -> TamerResult<(PathBuf, VhdxInfo)> {
+        let vhd = wsl
+            .distro_registration(name)?
+            .ok_or_else(|| TamerError::DistroNotFound {
+                name: name.to_string(),
+            })?
+            .vhd_path()
+            .ok_or_else(|| {
+                TamerError::InvalidOperation(format!(
+                    "{} is a WSL 1 distribution and has no virtual disk",
+                    name
+                ))
+            })?;
+        let info = vhdx::read_info(&vhd)?;
+        if info.has_parent {
+            return Err(TamerError::InvalidOperation(format!(
+                "Differencing disks cannot be {}",
+                action
+            )));
+        }
+        Ok((vhd, info))
+    }
+
+    /// Release unused blocks of the root file system
+    fn trim(&self, name: &str, job: &JobHandle) -> TamerResult<()> {
+        run_wsl_command_controlled(
+            self.runner.as_ref(),
+            &["-d", name, "-u", "root", "--", "fstrim", "/"],
+            &RunControl::new(Some(TRIM_TIMEOUT), job.cancel_token().clone()),
+        )
+        .map(|_| ())
+    }
+
+    /// Grow the root file system to fill its (just enlarged) device
+    fn grow_filesystem(&self, name: &str, job: &JobHandle) -> TamerResult<()> {
+        let control = RunControl::new(Some(RESIZE2FS_TIMEOUT), job.cancel_token().clone());
+        // Guest output is UTF-8, unlike wsl.exe's own messages
+        let mut source = Vec::new();
+        run_wsl_command_to_writer(
+            self.runner.as_ref(),
+            &[
+                "-d", name, "-u", "root", "--", "findmnt", "-n", "-o", "SOURCE", "/",
+            ],
+            &mut source,
+            &control,
+        )?;
+        let device = String::from_utf8_lossy(&source).trim().to_string();
+        if !device.starts_with("/dev/") {
+            return Err(TamerError::Parse(format!(
+                "Unexpected root device '{}' in {}",
+                device, name
+            )));
+        }
+        run_wsl_command_controlled(
+            self.runner.as_ref(),
+            &["-d", name, "-u", "root", "--", "resize2fs", &device],
+            &control,
+        )
+        .map(|_| ())
+    }
+
+    /// Whether a Hyper-V cmdlet is installed
+    fn has_cmdlet(&self, cmdlet: &str) -> bool {
+        matches!(
+            run_powershell_command(self.runner.as_ref(), &cmdlet_check(cmdlet)),
+            Ok(out) if out.trim() == "yes"
+        )
+    }
+
+    /// Free bytes on the volume holding `path`
+    fn host_free_bytes(&self, path: &Path) -> Option<u64> {
+        run_powershell_command(self.runner.as_ref(), &free_space_script(path))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    /// Run `script` elevated: with PowerShell if `powershell`, else diskpart
+    ///
+    /// Scripts are named after the action and distribution, so concurrent
+    /// jobs on different distributions don't clash.
+    fn run_script(
+        &self,
+        powershell: bool,
+        action: &str,
+        name: &str,
+        script: &str,
+    ) -> TamerResult<()> {
+        let extension = if powershell { "ps1" } else { "txt" };
+        let path = self
+            .script_dir
+            .join(format!("{}-{}.{}", action, name, extension));
+        fs::create_dir_all(&self.script_dir)
+            .map_err(|e| TamerError::io("Failed to create script folder", e))?;
+        fs::write(&path, script)
+            .map_err(|e| TamerError::io(format!("Failed to write {} script", action), e))?;
+
+        let path_arg = path.to_string_lossy();
+        let result = if powershell {
+            run_elevated(
+                self.runner.as_ref(),
+                "powershell",
+                &[
+                    "-NoProfile",
+                    "-ExecutionPolicy",
+                    "Bypass",
+                    "-File",
+                    &path_arg,
+                ],
+            )
+        } else {
+            run_elevated(self.runner.as_ref(), "diskpart", &["/s", &path_arg])
+        };
+        let _ = fs::remove_file(&path);
+        result
+    }
+}
+
+/// PowerShell printing "yes" when `cmdlet` is installed
+fn cmdlet_check(cmdlet: &str) -> String {
+    format!(
+        "if (Get-Command {} -ErrorAction SilentlyContinue) {{ 'yes' }}",
+        cmdlet
+    )
+}
+
+/// PowerShell printing the free bytes on the volume holding `path`
+fn free_space_script(path: &Path) -> String {
+    format!(
+        "(Get-Volume -FilePath '{}').SizeRemaining",
+        path.to_string_lossy().replace('\'', "''")
+    )
+}
+
+/// Script compacting `vhd` with `method`
+fn compaction_script(method: CompactionMethod, vhd: &Path) -> String {
+    let path = vhd.to_string_lossy();
+    match method {
+        CompactionMethod::OptimizeVhd => format!(
+            "Optimize-VHD -Path '{}' -Mode Full -ErrorAction Stop\r\n",
+            path.replace('\'', "''")
+        ),
+        CompactionMethod::Diskpart => format!(
+            "select vdisk file=\"{}\"\r\nattach vdisk readonly\r\ncompact vdisk\r\ndetach vdisk\r\nexit\r\n",
+            path
+        ),
+    }
+}
+
+/// Script growing `vhd` to `new_size` bytes with `method`
+fn resize_script(method: ResizeMethod, vhd: &Path, new_size: u64) -> String {
+    let path = vhd.to_string_lossy();
+    match method {
+        ResizeMethod::ResizeVhd => format!(
+            "Resize-VHD -Path '{}' -SizeBytes {} -ErrorAction Stop\r\n",
+            path.replace('\'', "''"),
+            new_size
+        ),
+        ResizeMethod::Diskpart => format!(
+            "select vdisk file=\"{}\"\r\nexpand vdisk maximum={}\r\nexit\r\n",
+            path,
+            new_size / MIB
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{DistroRegistry, LXSS_KEY};
+    use crate::utils::{
+        CancellationToken, CommandOutput, MemoryRegistry, RegistryValue, ScriptedRunner,
+    };
+    use crate::vhdx::{fixture::VhdxBuilder, MIB};
+
+    const TRIM_ARGS: [&str; 7] = ["-d", "Ubuntu", "-u", "root", "--", "fstrim", "/"];
+
+    struct Fixture {
+        dir: PathBuf,
+        runner: Arc<ScriptedRunner>,
+        wsl: WslService,
+        disks: DiskService,
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    /// Ubuntu (WSL2) registered at a temp folder holding a small VHDX, and
+    /// Debian (WSL1); `script` gets the folder to build the runner
+    fn fixture(script: impl FnOnce(&Path) -> ScriptedRunner) -> Fixture {
+        let dir = std::env::temp_dir().join(format!("wsl-tamer-disk-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("ext4.vhdx"),
+            VhdxBuilder::new(64 * MIB)
+                .block_size(MIB as u32)
+                .allocate(0)
+                .build(),
+        )
+        .unwrap();
+
+        let ubuntu = format!("{}\\{{ubuntu}}", LXSS_KEY);
+        let debian = format!("{}\\{{debian}}", LXSS_KEY);
+        let text = |s: &str| RegistryValue::String(s.to_string());
+        let registry = MemoryRegistry::new()
+            .with_value(&ubuntu, "DistributionName", text("Ubuntu"))
+            .with_value(&ubuntu, "BasePath", text(&dir.to_string_lossy()))
+            .with_value(&ubuntu, "Version", RegistryValue::Dword(2))
+            .with_value(&debian, "DistributionName", text("Debian"))
+            .with_value(&debian, "BasePath", text(r"D:\WSL\Debian"))
+            .with_value(&debian, "Version", RegistryValue::Dword(1));
+
+        let runner = Arc::new(script(&dir));
+        Fixture {
+            wsl: WslService::new(runner.clone())
+                .with_distro_registry(DistroRegistry::new(Arc::new(registry))),
+            disks: DiskService::new(runner.clone(), dir.join("scripts")),
+            runner,
+            dir,
+        }
+    }
+
+    /// PowerShell arguments `run_elevated` uses for `program args...`
+    fn elevated(program: &str, args: &[&str]) -> Vec<String> {
+        vec![
+            "-NoProfile".to_string(),
+            "-Command".to_string(),
+            format!(
+                "try {{ $p = Start-Process '{}' -ArgumentList '\"{}\"' -Verb RunAs -Wait -PassThru -ErrorAction Stop }} catch {{ exit 1223 }}; exit $p.ExitCode",
+                program,
+                args.join("\" \"").replace('\'', "''")
+            ),
+        ]
+    }
+
+    fn on(runner: ScriptedRunner, args: &[String]) -> ScriptedRunner {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        runner.on("powershell", &args, CommandOutput::ok(""))
+    }
+
+    #[test]
+    fn test_compaction_trims_stops_and_reports_sizes() {
+        let f = fixture(|dir| {
+            let script = dir.join("scripts").join("compact-Ubuntu.txt");
+            let runner = ScriptedRunner::new()
+                .on("wsl", &TRIM_ARGS, CommandOutput::ok(""))
+                .on("wsl", &["--terminate", "Ubuntu"], CommandOutput::ok(""))
+                .on(
+                    "powershell",
+                    &["-NoProfile", "-Command", &cmdlet_check("Optimize-VHD")],
+                    CommandOutput::ok(""),
+                );
+            on(
+                runner,
+                &elevated("diskpart", &["/s", &script.to_string_lossy()]),
+            )
+        });
+        let size = fs::metadata(f.dir.join("ext4.vhdx")).unwrap().len();
+
+        let job = JobHandle::detached(CancellationToken::new());
+        let report = f.disks.compact(&f.wsl, "Ubuntu", &job).unwrap();
+        assert_eq!(report.method, CompactionMethod::Diskpart);
+        assert!(report.trimmed);
+        assert_eq!(report.size_before, size);
+        assert_eq!(report.size_after, size);
+        assert_eq!(report.reclaimed_bytes, 0);
+        assert_eq!(f.disks.last_compaction("Ubuntu"), Some(report));
+
+        let programs: Vec<(String, String)> = f
+            .runner
+            .calls()
+            .into_iter()
+            .map(|c| (c.program, c.args.last().cloned().unwrap_or_default()))
+            .collect();
+        assert_eq!(programs[0], ("wsl".to_string(), "/".to_string()));
+        assert_eq!(programs[1], ("wsl".to_string(), "Ubuntu".to_string()));
+        assert_eq!(programs.len(), 4);
+        assert!(!f.dir.join("scripts").join("compact-Ubuntu.txt").exists());
+    }
+
+    #[test]
+    fn test_failed_trim_is_skipped_and_optimize_vhd_preferred() {
+        let f = fixture(|dir| {
+            let script = dir.join("scripts").join("compact-Ubuntu.ps1");
+            let runner = ScriptedRunner::new()
+                .on(
+                    "wsl",
+                    &TRIM_ARGS,
+                    CommandOutput::failed(1, "fstrim: /: the discard operation is not supported"),
+                )
+                .on("wsl", &["--terminate", "Ubuntu"], CommandOutput::ok(""))
+                .on(
+                    "powershell",
+                    &["-NoProfile", "-Command", &cmdlet_check("Optimize-VHD")],
+                    CommandOutput::ok("yes\r\n"),
+                );
+            let file = script.to_string_lossy();
+            on(
+                runner,
+                &elevated(
+                    "powershell",
+                    &["-NoProfile", "-ExecutionPolicy", "Bypass", "-File", &file],
+                ),
+            )
+        });
+
+        let job = JobHandle::detached(CancellationToken::new());
+        let report = f.disks.compact(&f.wsl, "Ubuntu", &job).unwrap();
+        assert_eq!(report.method, CompactionMethod::OptimizeVhd);
+        assert!(!report.trimmed);
+    }
+
+    #[test]
+    fn test_failed_compaction_fails_the_job() {
+        let f = fixture(|dir| {
+            let script = dir.join("scripts").join("compact-Ubuntu.ps1");
+            let file = script.to_string_lossy();
+            let args = elevated(
+                "powershell",
+                &["-NoProfile", "-ExecutionPolicy", "Bypass", "-File", &file],
+            );
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            ScriptedRunner::new()
+                .on("wsl", &TRIM_ARGS, CommandOutput::ok(""))
+                .on("wsl", &["--terminate", "Ubuntu"], CommandOutput::ok(""))
+                .on(
+                    "powershell",
+                    &["-NoProfile", "-Command", &cmdlet_check("Optimize-VHD")],
+                    CommandOutput::ok("yes\r\n"),
+                )
+                // Optimize-VHD threw, e.g. because the disk is still attached
+                .on("powershell", &args, CommandOutput::failed(1, ""))
+        });
+
+        let job = JobHandle::detached(CancellationToken::new());
+        let err = f.disks.compact(&f.wsl, "Ubuntu", &job).unwrap_err();
+        assert!(matches!(
+            err,
+            TamerError::CommandFailed {
+                exit_code: Some(1),
+                ..
+            }
+        ));
+        assert_eq!(f.disks.last_compaction("Ubuntu"), None);
+    }
+
+    #[test]
+    fn test_refuses_wsl1_and_stops_when_cancelled() {
+        let f = fixture(|_| ScriptedRunner::new().on("wsl", &TRIM_ARGS, CommandOutput::ok("")));
+        let job = JobHandle::detached(CancellationToken::new());
+
+        let err = f.disks.compact(&f.wsl, "Debian", &job).unwrap_err();
+        assert_eq!(err.code(), "INVALID_OPERATION");
+        let err = f.disks.compact(&f.wsl, "Arch", &job).unwrap_err();
+        assert_eq!(err.code(), "DISTRO_NOT_FOUND");
+        assert!(f.runner.calls().is_empty());
+
+        job.cancel_token().cancel();
+        let err = f.disks.compact(&f.wsl, "Ubuntu", &job).unwrap_err();
+        assert_eq!(err.code(), "CANCELLED");
+        assert!(!f.runner.was_called("wsl", &["--terminate", "Ubuntu"]));
+        assert_eq!(f.disks.last_compaction("Ubuntu"), None);
+    }
+
+    /// Runner for planning a resize of Ubuntu: `free` bytes on the host
+    /// volume, no Hyper-V, Ubuntu running
+    fn resize_runner(dir: &Path, free: u64) -> ScriptedRunner {
+        ScriptedRunner::new()
+            .on(
+                "powershell",
+                &[
+                    "-NoProfile",
+                    "-Command",
+                    &free_space_script(&dir.join("ext4.vhdx")),
+                ],
+                CommandOutput::ok(format!("{}\r\n", free)),
+            )
+            .on(
+                "powershell",
+                &["-NoProfile", "-Command", &cmdlet_check("Resize-VHD")],
+                CommandOutput::ok(""),
+            )
+            .on(
+                "wsl",
+                &["--list", "--verbose"],
+                CommandOutput::ok_utf16(
+                    "  NAME      STATE           VERSION\r\n* Ubuntu    Running         2\r\n",
+                ),
+            )
+    }
+
+    #[test]
+    fn test_resize_dry_run_explains_the_steps() {
+        let f = fixture(|dir| resize_runner(dir, 100 * 1024 * MIB));
+
+        let plan = f.disks.plan_resize(&f.wsl, "Ubuntu", 128 * MIB).unwrap();
+        assert_eq!(plan.method, ResizeMethod::Diskpart);
+        assert_eq!(plan.current_size, 64 * MIB);
+        assert_eq!(plan.new_size, 128 * MIB);
+        assert_eq!(plan.host_free_bytes, Some(100 * 1024 * MIB));
+        assert_eq!(plan.steps.len(), 4);
+        assert!(plan.steps[0].contains("wsl --terminate Ubuntu"));
+        assert!(plan.steps[1].contains("expand vdisk maximum=128"));
+        assert!(plan.steps[3].contains("resize2fs"));
+        assert_eq!(
+            plan.warnings,
+            vec!["Ubuntu is running; its processes will be stopped"]
+        );
+
+        // Nothing was changed
+        assert!(!f.runner.was_called("wsl", &["--terminate", "Ubuntu"]));
+        assert!(f
+            .runner
+            .calls()
+            .iter()
+            .all(|c| !c.args.iter().any(|a| a.contains("RunAs"))));
+    }
+
+    #[test]
+    fn test_resize_validates_the_new_size() {
+        let f = fixture(|dir| resize_runner(dir, 32 * MIB));
+
+        let err = f.disks.plan_resize(&f.wsl, "Ubuntu", 64 * MIB).unwrap_err();
+        assert!(err.to_string().contains("shrinking"), "{}", err);
+        let err = f
+            .disks
+            .plan_resize(&f.wsl, "Ubuntu", 96 * MIB + 1)
+            .unwrap_err();
+        assert_eq!(err.code(), "INVALID_INPUT");
+        let err = f
+            .disks
+            .plan_resize(&f.wsl, "Ubuntu", 2 * MAX_VIRTUAL_SIZE)
+            .unwrap_err();
+        assert_eq!(err.code(), "INVALID_INPUT");
+        // Adding 64 MiB with 32 MiB free
+        let err = f
+            .disks
+            .plan_resize(&f.wsl, "Ubuntu", 128 * MIB)
+            .unwrap_err();
+        assert!(err.to_string().contains("free on the host"), "{}", err);
+        // Adding 16 MiB fits, but a full disk wouldn't
+        let plan = f.disks.plan_resize(&f.wsl, "Ubuntu", 80 * MIB).unwrap();
+        assert!(plan.warnings[0].contains("outgrow"), "{:?}", plan.warnings);
+    }
+
+    #[test]
+    fn test_resize_checks_the_disk_before_growing_the_file_system() {
+        let f = fixture(|dir| {
+            let script = dir.join("scripts").join("resize-Ubuntu.txt");
+            let runner = resize_runner(dir, 100 * 1024 * MIB).on(
+                "wsl",
+                &["--terminate", "Ubuntu"],
+                CommandOutput::ok(""),
+            );
+            on(
+                runner,
+                &elevated("diskpart", &["/s", &script.to_string_lossy()]),
+            )
+        });
+        let plan = f.disks.plan_resize(&f.wsl, "Ubuntu", 128 * MIB).unwrap();
+
+        // The fake diskpart leaves the disk as it was
+        let job = JobHandle::detached(CancellationToken::new());
+        let err = f.disks.resize(&f.wsl, &plan, &job).unwrap_err();
+        assert_eq!(err.code(), "COMMAND_FAILED");
+        assert!(err.to_string().contains("still 64.00 MB"), "{}", err);
+        assert!(f.runner.was_called("wsl", &["--terminate", "Ubuntu"]));
+        assert!(!f
+            .runner
+            .calls()
+            .iter()
+            .any(|c| c.args.contains(&"resize2fs".to_string())));
+    }
+
+    #[test]
+    fn test_grows_the_root_file_system_as_root() {
+        let f = fixture(|_| {
+            ScriptedRunner::new()
+                .on(
+                    "wsl",
+                    &[
+                        "-d", "Ubuntu", "-u", "root", "--", "findmnt", "-n", "-o", "SOURCE", "/",
+                    ],
+                    CommandOutput::ok("/dev/sdc\n"),
+                )
+                .on(
+                    "wsl",
+                    &["-d", "Ubuntu", "-u", "root", "--", "resize2fs", "/dev/sdc"],
+                    CommandOutput::ok(""),
+                )
+        });
+
+        let job = JobHandle::detached(CancellationToken::new());
+        f.disks.grow_filesystem("Ubuntu", &job).unwrap();
+        assert!(f.runner.was_called(
+            "wsl",
+            &["-d", "Ubuntu", "-u", "root", "--", "resize2fs", "/dev/sdc"]
+        ));
+    }
+
+    #[test]
+    fn test_compaction_scripts_quote_the_path() {
+        let vhd = Path::new(r"C:\Users\o'brien\ext4.vhdx");
+        assert_eq!(
+            compaction_script(CompactionMethod::OptimizeVhd, vhd),
+            "Optimize-VHD -Path 'C:\\Users\\o''brien\\ext4.vhdx' -Mode Full -ErrorAction Stop\r\n"
+        );
+        assert!(compaction_script(CompactionMethod::Diskpart, vhd)
+            .starts_with("select vdisk file=\"C:\\Users\\o'brien\\ext4.vhdx\"\r\n"));
+        assert_eq!(
+            resize_script(ResizeMethod::ResizeVhd, vhd, 512 * MIB),
+            "Resize-VHD -Path 'C:\\Users\\o''brien\\ext4.vhdx' -SizeBytes 536870912 -ErrorAction Stop\r\n"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Distro Registry - Distribution metadata from the Lxss registry key
+//!
+//! WSL keeps one subkey per distribution under
+//! `HKCU\Software\Microsoft\Windows\CurrentVersion\Lxss`, named by GUID and
+//! holding its name, on-disk location, default user and flags.
+
+use crate::error::{TamerError, TamerResult};
+use crate::models::DistroRegistration;
+use crate::utils::{system_registry, RegistryReader, RegistryValue};
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+/// Registry key listing WSL distributions, relative to `HKEY_CURRENT_USER`
+pub const LXSS_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Lxss";
+
+pub struct DistroRegistry {
+    registry: Arc<dyn RegistryReader>,
+}
+
+impl Default for DistroRegistry {
+    fn default() -> Self {
+        Self::new(system_registry())
+    }
+}
+
+impl DistroRegistry {
+    pub fn new(registry: Arc<dyn RegistryReader>) -> Self {
+        Self { registry }
+    }
+
+    /// All registered distributions
+    ///
+    /// Empty if the Lxss key doesn't exist (WSL never used). Subkeys without
+    /// a `DistributionName` are skipped.
+    pub fn list(&self) -> TamerResult<Vec<DistroRegistration>> {
+        let ids = match self.registry.subkeys(LXSS_KEY) {
+            Ok(ids) => ids,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(TamerError::io("Failed to read the Lxss registry key", e)),
+        };
+
+        let mut distros = Vec::new();
+        for id in ids {
+            let values = match self.registry.values(&format!("{}\\{}", LXSS_KEY, id)) {
+                Ok(values) => values,
+                // Unregistered between listing and reading
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    return Err(TamerError::io(
+                        format!("Failed to read Lxss registry key {}", id),
+                        e,
+                    ))
+                }
+            };
+            if let Some(distro) = parse_registration(&id, &values) {
+                distros.push(distro);
+            }
+        }
+        Ok(distros)
+    }
+
+    /// Registration of `name` (case-insensitive), if it is registered
+    pub fn find(&self, name: &str) -> TamerResult<Option<DistroRegistration>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .find(|d| d.name.eq_ignore_ascii_case(name)))
+    }
+
+    /// GUID of the default distribution
+    pub fn default_id(&self) -> Option<String> {
+        let values = self.registry.values(LXSS_KEY).ok()?;
+        values
+            .get("DefaultDistribution")
+            .and_then(RegistryValue::as_str)
+            .map(String::from)
+    }
+}
+
+/// Build a registration from one Lxss subkey's values
+fn parse_registration(
+    id: &str,
+    values: &HashMap<String, RegistryValue>,
+) -> Option<DistroRegistration> {
+    let string = |name: &str| {
+        values
+            .get(name)
+            .and_then(RegistryValue::as_str)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+    };
+    let dword = |name: &str| values.get(name).and_then(RegistryValue::as_u32);
+
+    Some(DistroRegistration {
+        id: id.to_string(),
+        name: string("DistributionName")?,
+        base_path: string("BasePath")
+            .map(|p| p.trim_start_matches(r"\\?\").to_string())
+            .unwrap_or_default(),
+        vhd_file_name: string("VhdFileName"),
+        default_uid: dword("DefaultUid"),
+        flags: dword("Flags"),
+        version: dword("Version"),
+        package_family_name: string("PackageFamilyName"),
+        default_environment: values
+            .get("DefaultEnvironment")
+            .and_then(RegistryValue::as_strings)
+            .map(|env| env.to_vec())
+            .unwrap_or_default(),
+    })
+}
+
+/// In-memory Lxss key with an Ubuntu (WSL2, Store) and a Debian (WSL1, imported) entry
+#[cfg(test)]
+pub fn sample_lxss_registry() -> crate::utils::MemoryRegistry {
+    use crate::utils::MemoryRegistry;
+
+    let ubuntu = format!("{}\\{{6d9bd9b3-9c6a-4c1a-8d3e-0f1b2c3d4e5f}}", LXSS_KEY);
+    let debian = format!("{}\\{{1a2b3c4d-0000-4000-8000-abcdefabcdef}}", LXSS_KEY);
+    let text = |s: &str| RegistryValue::String(s.to_string());
+    MemoryRegistry::new()
+        .with_value(
+            LXSS_KEY,
+            "DefaultDistribution",
+            text("{6d9bd9b3-9c6a-4c1a-8d3e-0f1b2c3d4e5f}"),
+        )
+        .with_value(&ubuntu, "DistributionName", text("Ubuntu"))
+        .with_value(
+            &ubuntu,
+            "BasePath",
+            text(r"\\?\C:\Users\me\AppData\Local\Packages\CanonicalGroupLimited.Ubuntu_79rhkp1fndgsc\LocalState"),
+        )
+        .with_value(&ubuntu, "VhdFileName", text("ext4.vhdx"))
+        .with_value(&ubuntu, "DefaultUid", RegistryValue::Dword(1000))
+        .with_value(&ubuntu, "Flags", RegistryValue::Dword(15))
+        .with_value(&ubuntu, "Version", RegistryValue::Dword(2))
+        .with_value(
+            &ubuntu,
+            "PackageFamilyName",
+            text("CanonicalGroupLimited.Ubuntu_79rhkp1fndgsc"),
+        )
+        .with_value(
+            &ubuntu,
+            "DefaultEnvironment",
+            RegistryValue::MultiString(vec![
+                "HOSTTYPE=x86_64".to_string(),
+                "LANG=en_US.UTF-8".to_string(),
+                "TERM=xterm-256color".to_string(),
+            ]),
+        )
+        .with_value(&debian, "DistributionName", text("Debian"))
+        .with_value(&debian, "BasePath", text(r"D:\WSL\Debian"))
+        .with_value(&debian, "DefaultUid", RegistryValue::Dword(0))
+        .with_value(&debian, "Flags", RegistryValue::Dword(7))
+        .with_value(&debian, "Version", RegistryValue::Dword(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::MemoryRegistry;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parses_lxss_entries() {
+        let registry = DistroRegistry::new(Arc::new(sample_lxss_registry()));
+        let ubuntu = registry.find("ubuntu").unwrap().unwrap();
+
+        assert_eq!(ubuntu.id, "{6d9bd9b3-9c6a-4c1a-8d3e-0f1b2c3d4e5f}");
+        assert!(ubuntu.base_path.starts_with(r"C:\Users\me"));
+        assert_eq!(ubuntu.default_uid, Some(1000));
+        assert!(ubuntu.is_wsl2());
+        assert!(ubuntu.has_flag(DistroRegistration::FLAG_ENABLE_INTEROP));
+        assert_eq!(
+            ubuntu.package_family_name.as_deref(),
+            Some("CanonicalGroupLimited.Ubuntu_79rhkp1fndgsc")
+        );
+        assert_eq!(ubuntu.default_environment.len(), 3);
+        assert_eq!(
+            ubuntu.vhd_path(),
+            Some(PathBuf::from(&ubuntu.base_path).join("ext4.vhdx"))
+        );
+
+        let debian = registry.find("Debian").unwrap().unwrap();
+        assert!(!debian.is_wsl2());
+        assert_eq!(debian.vhd_path(), None);
+        assert_eq!(debian.package_family_name, None);
+        assert!(debian.default_environment.is_empty());
+
+        assert_eq!(registry.list().unwrap().len(), 2);
+        assert_eq!(registry.default_id(), Some(ubuntu.id));
+        assert!(registry.find("Arch").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_missing_lxss_key_means_no_distros() {
+        let registry = DistroRegistry::new(Arc::new(MemoryRegistry::new()));
+        assert!(registry.list().unwrap().is_empty());
+        assert_eq!(registry.default_id(), None);
+    }
+
+    #[test]
+    fn test_skips_entries_without_a_name() {
+        let registry = DistroRegistry::new(Arc::new(MemoryRegistry::new().with_value(
+            &format!("{}\\{{half-registered}}", LXSS_KEY),
+            "State",
+            RegistryValue::Dword(3),
+        )));
+        assert!(registry.list().unwrap().is_empty());
+    }
+}
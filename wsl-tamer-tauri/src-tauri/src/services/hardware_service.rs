@@ -1,36 +1,57 @@
 //! Hardware Service - USB and disk management
 
+use crate::error::{TamerError, TamerResult};
 use crate::models::{PhysicalDisk, UsbDevice};
-use crate::utils::{run_elevated, run_powershell_command};
+use crate::utils::{
+    format_command, format_size, run_elevated, run_powershell_command, run_wsl_command,
+    system_runner, CommandRunner,
+};
+use std::sync::Arc;
+
+pub struct HardwareService {
+    runner: Arc<dyn CommandRunner>,
+}
 
-pub struct HardwareService;
+impl Default for HardwareService {
+    fn default() -> Self {
+        Self::new(system_runner())
+    }
+}
 
 impl HardwareService {
+    pub fn new(runner: Arc<dyn CommandRunner>) -> Self {
+        Self { runner }
+    }
+
     /// Check if usbipd-win is installed
-    pub fn is_usbipd_installed() -> bool {
-        std::process::Command::new("where")
-            .arg("usbipd")
-            .output()
-            .map(|o| o.status.success())
+    pub fn is_usbipd_installed(&self) -> bool {
+        self.runner
+            .output("where", &["usbipd"])
+            .map(|o| o.success())
             .unwrap_or(false)
     }
 
     /// Get list of USB devices via usbipd
-    pub fn get_usb_devices() -> Result<Vec<UsbDevice>, String> {
-        if !Self::is_usbipd_installed() {
-            return Err("usbipd-win is not installed".to_string());
+    pub fn get_usb_devices(&self) -> TamerResult<Vec<UsbDevice>> {
+        if !self.is_usbipd_installed() {
+            return Err(TamerError::CommandUnavailable {
+                program: "usbipd".to_string(),
+                message: "usbipd-win is not installed".to_string(),
+            });
         }
 
-        let output = std::process::Command::new("usbipd")
-            .args(["list"])
-            .output()
-            .map_err(|e| format!("Failed to run usbipd: {}", e))?;
+        let output = self.runner.output("usbipd", &["list"]).map_err(|e| {
+            TamerError::CommandUnavailable {
+                program: "usbipd".to_string(),
+                message: e.to_string(),
+            }
+        })?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         Self::parse_usb_devices(&stdout)
     }
 
-    fn parse_usb_devices(output: &str) -> Result<Vec<UsbDevice>, String> {
+    fn parse_usb_devices(output: &str) -> TamerResult<Vec<UsbDevice>> {
         let mut devices = Vec::new();
 
         for line in output.lines().skip(2) {
@@ -80,7 +101,7 @@ impl HardwareService {
     }
 
     /// Attach a USB device to WSL (requires elevation)
-    pub fn attach_usb_device(bus_id: &str, distro: Option<&str>) -> Result<(), String> {
+    pub fn attach_usb_device(&self, bus_id: &str, distro: Option<&str>) -> TamerResult<()> {
         let mut args = vec!["attach", "--wsl", "--busid", bus_id];
 
         if let Some(d) = distro {
@@ -88,32 +109,40 @@ impl HardwareService {
             args.push(d);
         }
 
-        run_elevated("usbipd", &args)
+        run_elevated(self.runner.as_ref(), "usbipd", &args)
     }
 
     /// Detach a USB device from WSL
-    pub fn detach_usb_device(bus_id: &str) -> Result<(), String> {
-        let output = std::process::Command::new("usbipd")
-            .args(["detach", "--busid", bus_id])
-            .output()
-            .map_err(|e| format!("Failed to detach USB device: {}", e))?;
-
-        if !output.status.success() {
+    pub fn detach_usb_device(&self, bus_id: &str) -> TamerResult<()> {
+        let args = ["detach", "--busid", bus_id];
+        let output =
+            self.runner
+                .output("usbipd", &args)
+                .map_err(|e| TamerError::CommandUnavailable {
+                    program: "usbipd".to_string(),
+                    message: e.to_string(),
+                })?;
+
+        if !output.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("Failed to detach device: {}", stderr));
+            return Err(TamerError::CommandFailed {
+                command: format_command("usbipd", &args),
+                exit_code: output.exit_code,
+                stderr: stderr.trim().to_string(),
+            });
         }
 
         Ok(())
     }
 
     /// Get list of physical disks
-    pub fn get_physical_disks() -> Result<Vec<PhysicalDisk>, String> {
+    pub fn get_physical_disks(&self) -> TamerResult<Vec<PhysicalDisk>> {
         let script = r#"
             Get-PhysicalDisk | Select-Object DeviceId, Model, Size, SerialNumber | 
             ConvertTo-Json -Compress
         "#;
 
-        let output = run_powershell_command(script)?;
+        let output = run_powershell_command(self.runner.as_ref(), script)?;
 
         // Parse JSON output
         let disks: Vec<PhysicalDiskRaw> = serde_json::from_str(&output).unwrap_or_else(|_| {
@@ -124,7 +153,7 @@ impl HardwareService {
         });
 
         // Get mounted disks to check status
-        let mounted = Self::get_mounted_disks().unwrap_or_default();
+        let mounted = self.get_mounted_disks().unwrap_or_default();
 
         Ok(disks
             .into_iter()
@@ -142,16 +171,19 @@ impl HardwareService {
     }
 
     /// Get list of mounted disks in WSL
-    fn get_mounted_disks() -> Result<Vec<String>, String> {
-        let output = crate::utils::run_wsl_command(&[
-            "-u",
-            "root",
-            "--",
-            "lsblk",
-            "-o",
-            "NAME,MOUNTPOINT",
-            "--noheadings",
-        ])?;
+    fn get_mounted_disks(&self) -> TamerResult<Vec<String>> {
+        let output = run_wsl_command(
+            self.runner.as_ref(),
+            &[
+                "-u",
+                "root",
+                "--",
+                "lsblk",
+                "-o",
+                "NAME,MOUNTPOINT",
+                "--noheadings",
+            ],
+        )?;
 
         Ok(output
             .lines()
@@ -161,13 +193,17 @@ impl HardwareService {
     }
 
     /// Mount a disk to WSL (requires elevation)
-    pub fn mount_disk(device_path: &str) -> Result<(), String> {
-        run_elevated("wsl", &["--mount", device_path, "--bare"])
+    pub fn mount_disk(&self, device_path: &str) -> TamerResult<()> {
+        run_elevated(
+            self.runner.as_ref(),
+            "wsl",
+            &["--mount", device_path, "--bare"],
+        )
     }
 
     /// Unmount a disk from WSL  
-    pub fn unmount_disk(device_path: &str) -> Result<(), String> {
-        crate::utils::run_wsl_command(&["--unmount", device_path])?;
+    pub fn unmount_disk(&self, device_path: &str) -> TamerResult<()> {
+        run_wsl_command(self.runner.as_ref(), &["--unmount", device_path])?;
         Ok(())
     }
 
@@ -175,33 +211,44 @@ impl HardwareService {
     ///
     /// Uses positional shell args ($1/$2) instead of string interpolation
     /// to prevent shell injection, even if path validation is bypassed.
-    pub fn mount_folder(distro: &str, windows_path: &str, linux_path: &str) -> Result<(), String> {
+    pub fn mount_folder(
+        &self,
+        distro: &str,
+        windows_path: &str,
+        linux_path: &str,
+    ) -> TamerResult<()> {
         // Convert Windows path to WSL path
         let wsl_windows_path = windows_path.replace('\\', "/");
         let wsl_windows_path = wsl_windows_path.replace(":", "");
         let mount_source = format!("/mnt/{}", wsl_windows_path.to_lowercase());
 
         // Pass paths as positional args to sh — never interpolated into the script
-        crate::utils::run_wsl_command(&[
-            "-d",
-            distro,
-            "-u",
-            "root",
-            "--",
-            "sh",
-            "-c",
-            "mkdir -p \"$1\" && mount --bind \"$2\" \"$1\"",
-            "--",
-            linux_path,
-            &mount_source,
-        ])?;
+        run_wsl_command(
+            self.runner.as_ref(),
+            &[
+                "-d",
+                distro,
+                "-u",
+                "root",
+                "--",
+                "sh",
+                "-c",
+                "mkdir -p \"$1\" && mount --bind \"$2\" \"$1\"",
+                "--",
+                linux_path,
+                &mount_source,
+            ],
+        )?;
 
         Ok(())
     }
 
     /// Unmount a folder from a distribution
-    pub fn unmount_folder(distro: &str, linux_path: &str) -> Result<(), String> {
-        crate::utils::run_wsl_command(&["-d", distro, "-u", "root", "--", "umount", linux_path])?;
+    pub fn unmount_folder(&self, distro: &str, linux_path: &str) -> TamerResult<()> {
+        run_wsl_command(
+            self.runner.as_ref(),
+            &["-d", distro, "-u", "root", "--", "umount", linux_path],
+        )?;
         Ok(())
     }
 }
@@ -219,29 +266,10 @@ struct PhysicalDiskRaw {
     serial_number: String,
 }
 
-/// Format bytes to human readable size
-fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-    const TB: u64 = GB * 1024;
-
-    if bytes >= TB {
-        format!("{:.2} TB", bytes as f64 / TB as f64)
-    } else if bytes >= GB {
-        format!("{:.2} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.2} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.2} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::{CommandOutput, ScriptedRunner};
 
     #[test]
     fn test_parse_usb_devices_typical() {
@@ -278,4 +306,37 @@ BUSID\tVID:PID\tDEVICE\tSTATE\n\
         assert_eq!(devices.len(), 1);
         assert_eq!(devices[0].bus_id, "1-1");
     }
+
+    #[test]
+    fn test_get_usb_devices_requires_usbipd() {
+        let runner = Arc::new(ScriptedRunner::new().on(
+            "where",
+            &["usbipd"],
+            CommandOutput::failed(1, "INFO: Could not find files"),
+        ));
+        let service = HardwareService::new(runner.clone());
+
+        assert!(service.get_usb_devices().is_err());
+        assert!(!runner.was_called("usbipd", &["list"]));
+    }
+
+    #[test]
+    fn test_get_usb_devices_parses_usbipd_list() {
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on("where", &["usbipd"], CommandOutput::ok("C:\\usbipd.exe"))
+                .on(
+                    "usbipd",
+                    &["list"],
+                    CommandOutput::ok(
+                        "Connected:\nBUSID\tVID:PID\tDEVICE\tSTATE\n4-1\t1234:5678\tUSB Camera\tAttached",
+                    ),
+                ),
+        );
+        let service = HardwareService::new(runner);
+
+        let devices = service.get_usb_devices().unwrap();
+        assert_eq!(devices.len(), 1);
+        assert!(devices[0].is_attached);
+    }
 }
@@ -0,0 +1,401 @@
+//! Job Manager - Tracked background execution of long-running operations
+//!
+//! Export, import, clone, move and install run on worker threads as jobs.
+//! Each job reports its phase and the size of the file it is writing, and
+//! every change is pushed to an optional listener (the Tauri event bridge).
+
+use crate::error::{TamerError, TamerResult};
+use crate::models::{Job, JobError, JobKind, JobPhase, JobStatus};
+use crate::utils::{unix_timestamp, CancellationToken};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::Duration;
+
+/// How often the size of a tracked file is sampled
+const PROGRESS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Finished jobs kept for `list_jobs` before the oldest are dropped
+const MAX_FINISHED_JOBS: usize = 50;
+
+/// Callback invoked with a snapshot of a job whenever it changes
+pub type JobListener = Arc<dyn Fn(&Job) + Send + Sync>;
+
+struct JobEntry {
+    job: Job,
+    cancel: CancellationToken,
+    /// File whose size is reported as `bytes_written`
+    tracked_file: Option<PathBuf>,
+}
+
+#[derive(Default)]
+struct JobRegistry {
+    jobs: Mutex<HashMap<String, JobEntry>>,
+    listener: RwLock<Option<JobListener>>,
+}
+
+impl JobRegistry {
+    /// Apply a change to a job and notify the listener
+    fn update(&self, id: &str, change: impl FnOnce(&mut JobEntry)) {
+        let snapshot = match self.jobs.lock() {
+            Ok(mut jobs) => match jobs.get_mut(id) {
+                Some(entry) => {
+                    change(entry);
+                    entry.job.clone()
+                }
+                None => return,
+            },
+            Err(_) => return,
+        };
+        self.notify(&snapshot);
+    }
+
+    fn notify(&self, job: &Job) {
+        let listener = self.listener.read().ok().and_then(|l| l.clone());
+        if let Some(listener) = listener {
+            listener(job);
+        }
+    }
+
+    fn tracked_file(&self, id: &str) -> Option<(JobStatus, Option<PathBuf>)> {
+        let jobs = self.jobs.lock().ok()?;
+        let entry = jobs.get(id)?;
+        Some((entry.job.status, entry.tracked_file.clone()))
+    }
+
+    /// Drop the oldest finished jobs beyond the retention limit
+    fn prune(&self) {
+        if let Ok(mut jobs) = self.jobs.lock() {
+            let mut finished: Vec<(u64, String)> = jobs
+                .values()
+                .filter(|e| e.job.status.is_finished())
+                .map(|e| (e.job.finished_at.unwrap_or_default(), e.job.id.clone()))
+                .collect();
+            if finished.len() <= MAX_FINISHED_JOBS {
+                return;
+            }
+            finished.sort();
+            let excess = finished.len() - MAX_FINISHED_JOBS;
+            for (_, id) in finished.into_iter().take(excess) {
+                jobs.remove(&id);
+            }
+        }
+    }
+}
+
+/// Handle given to a running operation for cancellation and progress reporting
+///
+/// A detached handle (outside the job manager) only carries a cancellation
+/// token; progress calls are no-ops.
+#[derive(Clone, Default)]
+pub struct JobHandle {
+    id: String,
+    registry: Option<Arc<JobRegistry>>,
+    cancel: CancellationToken,
+}
+
+impl JobHandle {
+    /// Handle that is not tracked by any job manager
+    pub fn detached(cancel: CancellationToken) -> Self {
+        Self {
+            id: String::new(),
+            registry: None,
+            cancel,
+        }
+    }
+
+    /// Same job, but ignoring cancellation
+    ///
+    /// For steps that must run to completion once started, such as
+    /// re-registering a distribution that has already been unregistered.
+    pub fn uncancellable(&self) -> Self {
+        Self {
+            id: self.id.clone(),
+            registry: self.registry.clone(),
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    pub fn cancel_token(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Move the job to a new phase
+    pub fn set_phase(&self, phase: JobPhase) {
+        if let Some(registry) = &self.registry {
+            registry.update(&self.id, |entry| entry.job.phase = phase);
+        }
+    }
+
+    /// Report the size of `path` as bytes written until another file is tracked
+    pub fn track_file(&self, path: &Path) {
+        if let Some(registry) = &self.registry {
+            let path = path.to_path_buf();
+            registry.update(&self.id, |entry| {
+                entry.tracked_file = Some(path);
+                entry.job.bytes_written = 0;
+            });
+        }
+    }
+}
+
+pub struct JobManager {
+    registry: Arc<JobRegistry>,
+}
+
+impl Default for JobManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        Self {
+            registry: Arc::new(JobRegistry::default()),
+        }
+    }
+
+    /// Set the callback that receives every job update
+    pub fn set_listener(&self, listener: JobListener) {
+        if let Ok(mut current) = self.registry.listener.write() {
+            *current = Some(listener);
+        }
+    }
+
+    /// Start `op` on a worker thread and return the new job
+    pub fn start<F>(&self, kind: JobKind, target: String, op: F) -> Job
+    where
+        F: FnOnce(&JobHandle) -> TamerResult<()> + Send + 'static,
+    {
+        let id = uuid::Uuid::new_v4().to_string();
+        let cancel = CancellationToken::new();
+        let job = Job {
+            id: id.clone(),
+            kind,
+            target,
+            status: JobStatus::Running,
+            phase: JobPhase::Queued,
+            bytes_written: 0,
+            started_at: unix_timestamp(),
+            finished_at: None,
+            error: None,
+        };
+
+        if let Ok(mut jobs) = self.registry.jobs.lock() {
+            jobs.insert(
+                id.clone(),
+                JobEntry {
+                    job: job.clone(),
+                    cancel: cancel.clone(),
+                    tracked_file: None,
+                },
+            );
+        }
+        self.registry.notify(&job);
+
+        let handle = JobHandle {
+            id: id.clone(),
+            registry: Some(self.registry.clone()),
+            cancel,
+        };
+
+        let registry = self.registry.clone();
+        let progress_id = id.clone();
+        thread::spawn(move || watch_progress(&registry, &progress_id));
+
+        let registry = self.registry.clone();
+        thread::spawn(move || {
+            let result = op(&handle);
+            registry.update(&id, |entry| finish(&mut entry.job, result));
+            registry.prune();
+        });
+
+        job
+    }
+
+    /// All known jobs, newest first
+    pub fn list(&self) -> Vec<Job> {
+        let mut jobs: Vec<Job> = self
+            .registry
+            .jobs
+            .lock()
+            .map(|jobs| jobs.values().map(|e| e.job.clone()).collect())
+            .unwrap_or_default();
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.started_at));
+        jobs
+    }
+
+    pub fn get(&self, id: &str) -> Option<Job> {
+        let jobs = self.registry.jobs.lock().ok()?;
+        jobs.get(id).map(|e| e.job.clone())
+    }
+
+    /// Request cancellation of a running job
+    pub fn cancel(&self, id: &str) -> TamerResult<()> {
+        let jobs = self
+            .registry
+            .jobs
+            .lock()
+            .map_err(|_| TamerError::Internal("Job registry lock poisoned".to_string()))?;
+        let entry = jobs.get(id).ok_or_else(|| TamerError::NotFound {
+            what: format!("Job '{}'", id),
+        })?;
+        if entry.job.status.is_finished() {
+            return Err(TamerError::InvalidOperation(
+                "Job has already finished".to_string(),
+            ));
+        }
+        entry.cancel.cancel();
+        Ok(())
+    }
+}
+
+/// Sample the tracked file size until the job finishes
+fn watch_progress(registry: &JobRegistry, id: &str) {
+    loop {
+        thread::sleep(PROGRESS_POLL_INTERVAL);
+        let (status, path) = match registry.tracked_file(id) {
+            Some(state) => state,
+            None => return,
+        };
+        if status.is_finished() {
+            return;
+        }
+        let size = path
+            .and_then(|p| std::fs::metadata(p).ok())
+            .map(|m| m.len());
+        if let Some(size) = size {
+            let changed = registry
+                .jobs
+                .lock()
+                .ok()
+                .and_then(|jobs| jobs.get(id).map(|e| e.job.bytes_written != size))
+                .unwrap_or(false);
+            if changed {
+                registry.update(id, |entry| entry.job.bytes_written = size);
+            }
+        }
+    }
+}
+
+/// Record the final outcome of a job
+fn finish(job: &mut Job, result: TamerResult<()>) {
+    job.finished_at = Some(unix_timestamp());
+    match result {
+        Ok(()) => job.status = JobStatus::Completed,
+        Err(TamerError::Cancelled) => job.status = JobStatus::Cancelled,
+        Err(e) => {
+            job.status = JobStatus::Failed;
+            job.error = Some(JobError::from(&e));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn wait_until_finished(manager: &JobManager, id: &str) -> Job {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let job = manager.get(id).unwrap();
+            if job.status.is_finished() || Instant::now() > deadline {
+                return job;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[test]
+    fn test_successful_job_completes_and_notifies() {
+        let manager = JobManager::new();
+        let phases = Arc::new(Mutex::new(Vec::new()));
+        let seen = phases.clone();
+        manager.set_listener(Arc::new(move |job: &Job| {
+            seen.lock().unwrap().push(job.phase);
+        }));
+
+        let job = manager.start(JobKind::Clone, "Ubuntu".to_string(), |handle| {
+            handle.set_phase(JobPhase::Exporting);
+            handle.set_phase(JobPhase::Importing);
+            Ok(())
+        });
+
+        let job = wait_until_finished(&manager, &job.id);
+        assert_eq!(job.status, JobStatus::Completed);
+        assert!(job.finished_at.is_some());
+        let phases = phases.lock().unwrap();
+        assert!(phases.contains(&JobPhase::Exporting));
+        assert!(phases.contains(&JobPhase::Importing));
+    }
+
+    #[test]
+    fn test_failed_job_records_error() {
+        let manager = JobManager::new();
+        let job = manager.start(JobKind::Export, "Arch".to_string(), |_| {
+            Err(TamerError::DistroNotFound {
+                name: "Arch".to_string(),
+            })
+        });
+
+        let job = wait_until_finished(&manager, &job.id);
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.error.unwrap().code, "DISTRO_NOT_FOUND");
+    }
+
+    #[test]
+    fn test_cancel_running_job() {
+        let manager = JobManager::new();
+        let job = manager.start(JobKind::Move, "Ubuntu".to_string(), |handle| {
+            while !handle.is_cancelled() {
+                thread::sleep(Duration::from_millis(10));
+            }
+            Err(TamerError::Cancelled)
+        });
+
+        manager.cancel(&job.id).unwrap();
+        let job = wait_until_finished(&manager, &job.id);
+        assert_eq!(job.status, JobStatus::Cancelled);
+        assert!(job.error.is_none());
+
+        // Finished jobs can't be cancelled again
+        assert_eq!(
+            manager.cancel(&job.id).unwrap_err().code(),
+            "INVALID_OPERATION"
+        );
+        assert_eq!(manager.cancel("missing").unwrap_err().code(), "NOT_FOUND");
+    }
+
+    #[test]
+    fn test_tracked_file_size_is_reported() {
+        let path = std::env::temp_dir().join(format!("job_progress_{}.tar", uuid::Uuid::new_v4()));
+        std::fs::write(&path, vec![0u8; 4096]).unwrap();
+
+        let manager = JobManager::new();
+        let tracked = path.clone();
+        let job = manager.start(JobKind::Export, "Ubuntu".to_string(), move |handle| {
+            handle.track_file(&tracked);
+            while !handle.is_cancelled() {
+                thread::sleep(Duration::from_millis(10));
+            }
+            Err(TamerError::Cancelled)
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while manager.get(&job.id).unwrap().bytes_written != 4096 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(manager.get(&job.id).unwrap().bytes_written, 4096);
+
+        manager.cancel(&job.id).unwrap();
+        wait_until_finished(&manager, &job.id);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,375 @@
+//! Metrics History - Time series of system and distribution metrics
+//!
+//! Samples land in two tiers: every sample is kept for 10 minutes (at most
+//! one per second), and each minute is folded into an average kept for 24
+//! hours. Both tiers drop points by age, so their size is bounded no matter
+//! how often samples arrive. The history can be saved to a JSON file and
+//! picked up again on the next start.
+
+use crate::error::{TamerError, TamerResult};
+use crate::models::{DistroMetricsPoint, MetricsHistory, MetricsPoint, MetricsResolution};
+use crate::utils::unix_timestamp;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// How long per-second points are kept
+pub const SECOND_RETENTION_SECS: u64 = 10 * 60;
+
+/// How long per-minute averages are kept
+pub const MINUTE_RETENTION_SECS: u64 = 24 * 60 * 60;
+
+/// The two tiers of the history
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsRing {
+    seconds: VecDeque<MetricsPoint>,
+    minutes: VecDeque<MetricsPoint>,
+    /// Samples of the minute in progress
+    current: Vec<MetricsPoint>,
+}
+
+impl MetricsRing {
+    /// Add a sample, returning whether it completed a minute
+    pub fn record(&mut self, point: MetricsPoint) -> bool {
+        let now = point.timestamp;
+        let minute = minute_of(now);
+        let closed = self
+            .current
+            .first()
+            .is_some_and(|first| minute_of(first.timestamp) != minute);
+        if closed {
+            let start = minute_of(self.current[0].timestamp);
+            self.minutes.push_back(average(&self.current, start));
+            self.current.clear();
+        }
+
+        // Two samples within a second: the later one wins
+        if self
+            .seconds
+            .back()
+            .is_some_and(|last| last.timestamp == now)
+        {
+            self.seconds.pop_back();
+        }
+        self.seconds.push_back(point.clone());
+        self.current.push(point);
+        self.prune(now);
+        closed
+    }
+
+    /// Drop points that have aged out as of `now`
+    pub fn prune(&mut self, now: u64) {
+        while self
+            .seconds
+            .front()
+            .is_some_and(|p| p.timestamp + SECOND_RETENTION_SECS < now)
+        {
+            self.seconds.pop_front();
+        }
+        while self
+            .minutes
+            .front()
+            .is_some_and(|p| p.timestamp + MINUTE_RETENTION_SECS < now)
+        {
+            self.minutes.pop_front();
+        }
+        if self
+            .current
+            .first()
+            .is_some_and(|p| p.timestamp + MINUTE_RETENTION_SECS < now)
+        {
+            self.current.clear();
+        }
+    }
+
+    /// Points of the last `range_secs` before `now`, oldest first
+    ///
+    /// Minute resolution ends with the average of the minute in progress.
+    pub fn query(
+        &self,
+        now: u64,
+        range_secs: u64,
+        resolution: MetricsResolution,
+    ) -> Vec<MetricsPoint> {
+        let since = now.saturating_sub(range_secs);
+        match resolution {
+            MetricsResolution::Second => self
+                .seconds
+                .iter()
+                .filter(|p| p.timestamp >= since)
+                .cloned()
+                .collect(),
+            MetricsResolution::Minute => {
+                let since = minute_of(since);
+                let mut points: Vec<MetricsPoint> = self
+                    .minutes
+                    .iter()
+                    .filter(|p| p.timestamp >= since)
+                    .cloned()
+                    .collect();
+                if let Some(first) = self.current.first() {
+                    let start = minute_of(first.timestamp);
+                    if start >= since {
+                        points.push(average(&self.current, start));
+                    }
+                }
+                points
+            }
+        }
+    }
+}
+
+/// Shared history with optional persistence
+pub struct MetricsHistoryStore {
+    ring: Mutex<MetricsRing>,
+    path: PathBuf,
+}
+
+impl MetricsHistoryStore {
+    /// Empty history that saves to `path`
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            ring: Mutex::new(MetricsRing::default()),
+            path,
+        }
+    }
+
+    /// History saved at `path` by a previous run, without what has aged out
+    /// since (empty if there is none or it can't be read)
+    pub fn load(path: PathBuf) -> Self {
+        let store = Self::new(path);
+        let saved = fs::read_to_string(&store.path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<MetricsRing>(&json).ok());
+        if let (Some(mut ring), Ok(mut current)) = (saved, store.ring.lock()) {
+            ring.prune(unix_timestamp());
+            *current = ring;
+        }
+        store
+    }
+
+    /// Add a sample, returning whether it completed a minute
+    pub fn record(&self, point: MetricsPoint) -> bool {
+        self.ring
+            .lock()
+            .map(|mut ring| ring.record(point))
+            .unwrap_or(false)
+    }
+
+    /// Points of the last `range_secs`
+    ///
+    /// Without a `resolution`, ranges the per-second tier covers are
+    /// returned per second and longer ones per minute.
+    pub fn history(
+        &self,
+        range_secs: u64,
+        resolution: Option<MetricsResolution>,
+    ) -> TamerResult<MetricsHistory> {
+        if range_secs == 0 || range_secs > MINUTE_RETENTION_SECS {
+            return Err(TamerError::InvalidInput(format!(
+                "History range must be between 1 and {} seconds",
+                MINUTE_RETENTION_SECS
+            )));
+        }
+        let resolution = resolution.unwrap_or(if range_secs <= SECOND_RETENTION_SECS {
+            MetricsResolution::Second
+        } else {
+            MetricsResolution::Minute
+        });
+        let ring = self
+            .ring
+            .lock()
+            .map_err(|_| TamerError::Internal("Metrics history lock poisoned".to_string()))?;
+        Ok(MetricsHistory {
+            resolution,
+            points: ring.query(unix_timestamp(), range_secs, resolution),
+        })
+    }
+
+    /// Write the history to its file
+    pub fn save(&self) -> TamerResult<()> {
+        let json = {
+            let ring = self
+                .ring
+                .lock()
+                .map_err(|_| TamerError::Internal("Metrics history lock poisoned".to_string()))?;
+            serde_json::to_string(&*ring).map_err(|e| {
+                TamerError::Internal(format!("Failed to serialize metrics history: {}", e))
+            })?
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| TamerError::io("Failed to create metrics history directory", e))?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, json).map_err(|e| TamerError::io("Failed to save metrics history", e))?;
+        fs::rename(&tmp, &self.path)
+            .map_err(|e| TamerError::io("Failed to save metrics history", e))
+    }
+
+    /// Delete the saved history (the in-memory one is kept)
+    pub fn discard_saved(&self) -> TamerResult<()> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(TamerError::io("Failed to delete metrics history", e)),
+        }
+    }
+}
+
+fn minute_of(timestamp: u64) -> u64 {
+    timestamp - timestamp % 60
+}
+
+/// Mean of `points`, stamped with `timestamp`
+///
+/// Optional values are averaged over the points that have them, and a
+/// distribution counts as running if it was running in any point.
+fn average(points: &[MetricsPoint], timestamp: u64) -> MetricsPoint {
+    let n = points.len().max(1) as f64;
+    let mean = |value: fn(&MetricsPoint) -> f64| points.iter().map(value).sum::<f64>() / n;
+    let mean_of_some = |value: fn(&MetricsPoint) -> Option<f64>| {
+        let values: Vec<f64> = points.iter().filter_map(value).collect();
+        (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+    };
+
+    // Per distribution: sums, number of points, and the usages measured
+    let mut distros: Vec<(DistroMetricsPoint, usize, Vec<f64>)> = Vec::new();
+    for distro in points.iter().flat_map(|p| &p.distros) {
+        match distros.iter_mut().find(|(d, ..)| d.name == distro.name) {
+            Some((sum, count, used)) => {
+                sum.disk_size_mb += distro.disk_size_mb;
+                sum.is_running |= distro.is_running;
+                *count += 1;
+                used.extend(distro.disk_usage_mb);
+            }
+            None => distros.push((
+                distro.clone(),
+                1,
+                distro.disk_usage_mb.into_iter().collect(),
+            )),
+        }
+    }
+
+    MetricsPoint {
+        timestamp,
+        vmmem_memory_mb: mean(|p| p.vmmem_memory_mb),
+        wsl_memory_used_mb: mean_of_some(|p| p.wsl_memory_used_mb),
+        wsl_cpu_percent: mean(|p| p.wsl_cpu_percent),
+        guest_cpu_percent: mean_of_some(|p| p.guest_cpu_percent),
+        available_system_memory_mb: mean(|p| p.available_system_memory_mb),
+        distros: distros
+            .into_iter()
+            .map(|(mut sum, count, used)| {
+                sum.disk_usage_mb =
+                    (!used.is_empty()).then(|| used.iter().sum::<f64>() / used.len() as f64);
+                sum.disk_size_mb /= count as f64;
+                sum
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(timestamp: u64, cpu: f64, guest: Option<f64>) -> MetricsPoint {
+        MetricsPoint {
+            timestamp,
+            vmmem_memory_mb: 1000.0,
+            wsl_memory_used_mb: None,
+            wsl_cpu_percent: cpu,
+            guest_cpu_percent: guest,
+            available_system_memory_mb: 8000.0,
+            distros: vec![DistroMetricsPoint {
+                name: "Ubuntu".to_string(),
+                // Unknown in samples without the guest
+                disk_usage_mb: guest.map(|_| cpu * 10.0),
+                disk_size_mb: 2048.0,
+                is_running: guest.is_some(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_minutes_are_averaged_when_they_close() {
+        let mut ring = MetricsRing::default();
+        // 12:00:00 and 12:00:30, then a sample in the next minute
+        assert!(!ring.record(point(43200, 10.0, Some(40.0))));
+        assert!(!ring.record(point(43230, 30.0, None)));
+        assert!(ring.record(point(43260, 50.0, None)));
+
+        let minutes = ring.query(43260, 3600, MetricsResolution::Minute);
+        assert_eq!(minutes.len(), 2);
+        let first = &minutes[0];
+        assert_eq!(first.timestamp, 43200);
+        assert_eq!(first.wsl_cpu_percent, 20.0);
+        assert_eq!(first.guest_cpu_percent, Some(40.0));
+        // Only the first sample measured the guest
+        assert_eq!(first.distros[0].disk_usage_mb, Some(100.0));
+        assert!(first.distros[0].is_running);
+        // The minute in progress comes last
+        assert_eq!(minutes[1].timestamp, 43260);
+        assert_eq!(minutes[1].wsl_cpu_percent, 50.0);
+
+        let seconds = ring.query(43260, 45, MetricsResolution::Second);
+        let stamps: Vec<u64> = seconds.iter().map(|p| p.timestamp).collect();
+        assert_eq!(stamps, vec![43230, 43260]);
+    }
+
+    #[test]
+    fn test_tiers_drop_points_by_age() {
+        let mut ring = MetricsRing::default();
+        let start = 1_000_000 * 60;
+        for i in 0..(25 * 60) {
+            ring.record(point(start + i * 60, 1.0, None));
+        }
+        let now = start + (25 * 60 - 1) * 60;
+
+        assert_eq!(ring.seconds.len() as u64, SECOND_RETENTION_SECS / 60 + 1);
+        assert!(ring.minutes.len() as u64 <= MINUTE_RETENTION_SECS / 60 + 1);
+        assert!(ring
+            .query(now, MINUTE_RETENTION_SECS, MetricsResolution::Minute)
+            .iter()
+            .all(|p| p.timestamp + MINUTE_RETENTION_SECS >= now));
+    }
+
+    #[test]
+    fn test_same_second_keeps_the_later_sample() {
+        let mut ring = MetricsRing::default();
+        ring.record(point(100, 1.0, None));
+        ring.record(point(100, 2.0, None));
+
+        let seconds = ring.query(100, 10, MetricsResolution::Second);
+        assert_eq!(seconds.len(), 1);
+        assert_eq!(seconds[0].wsl_cpu_percent, 2.0);
+    }
+
+    #[test]
+    fn test_store_saves_and_loads() {
+        let path =
+            std::env::temp_dir().join(format!("wsl-tamer-metrics-{}.json", uuid::Uuid::new_v4()));
+        let store = MetricsHistoryStore::new(path.clone());
+        let now = unix_timestamp();
+        store.record(point(now - 5, 10.0, Some(5.0)));
+        store.record(point(now, 20.0, Some(5.0)));
+        store.save().unwrap();
+
+        let loaded = MetricsHistoryStore::load(path.clone());
+        let history = loaded.history(60, None).unwrap();
+        assert_eq!(history.resolution, MetricsResolution::Second);
+        assert_eq!(history.points.len(), 2);
+        assert_eq!(
+            loaded.history(3600, None).unwrap().resolution,
+            MetricsResolution::Minute
+        );
+        assert!(loaded.history(0, None).is_err());
+
+        loaded.discard_saved().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -3,8 +3,38 @@
 mod wsl_service;
 mod profile_manager;
 mod hardware_service;
+mod monitoring_service;
+mod job_manager;
+mod move_journal;
+mod snapshot_service;
+mod distro_registry;
+mod disk_metrics;
+mod disk_service;
+mod cpu_sampler;
+mod metrics_history;
+mod alert_engine;
+mod process_explorer;
+mod port_scanner;
+mod port_forward_service;
+mod config_validator;
+mod config_history;
 pub mod automation_engine;
 
 pub use wsl_service::*;
 pub use profile_manager::*;
 pub use hardware_service::*;
+pub use monitoring_service::*;
+pub use job_manager::*;
+pub use move_journal::*;
+pub use snapshot_service::*;
+pub use distro_registry::*;
+pub use disk_metrics::*;
+pub use disk_service::*;
+pub use cpu_sampler::*;
+pub use metrics_history::*;
+pub use alert_engine::*;
+pub use process_explorer::*;
+pub use port_scanner::*;
+pub use port_forward_service::*;
+pub use config_validator::*;
+pub use config_history::*;
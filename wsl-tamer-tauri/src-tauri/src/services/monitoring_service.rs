@@ -0,0 +1,364 @@
+//! Monitoring Service - Host and WSL resource metrics
+
+use crate::error::TamerResult;
+use crate::models::{
+    parse_size, DistributionState, DistroMetrics, SystemMetrics, WslConfig, WslDistribution,
+    WslMemoryBreakdown,
+};
+use crate::services::{CpuSample, CpuSampler, DiskMetricsCollector, WslService};
+use crate::utils::{run_wsl_command_to_writer, system_runner, CommandRunner, RunControl};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Sum of vmmem/vmmemWSL working sets in MB
+const VMMEM_MEMORY_SCRIPT: &str =
+    "(Get-Process -Name 'vmmem*' -ErrorAction SilentlyContinue | Measure-Object WorkingSet64 -Sum).Sum / 1MB";
+
+/// Time limit for reading /proc/meminfo in a distribution
+const MEMINFO_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct MonitoringService {
+    runner: Arc<dyn CommandRunner>,
+    disks: DiskMetricsCollector,
+    cpu: CpuSampler,
+}
+
+impl Default for MonitoringService {
+    fn default() -> Self {
+        Self::new(system_runner())
+    }
+}
+
+impl MonitoringService {
+    pub fn new(runner: Arc<dyn CommandRunner>) -> Self {
+        Self {
+            disks: DiskMetricsCollector::new(runner.clone()),
+            cpu: CpuSampler::new(runner.clone()),
+            runner,
+        }
+    }
+
+    /// Cached disk measurements
+    pub fn disks(&self) -> &DiskMetricsCollector {
+        &self.disks
+    }
+
+    /// Get real-time system metrics using Windows APIs + WSL query
+    ///
+    /// The guest's memory breakdown and CPU usage are only read from `guest`,
+    /// a distribution that is already running. Without one only host-side
+    /// numbers are taken, which neither boot a distribution nor keep the VM
+    /// from going idle.
+    pub fn get_system_metrics(&self, guest: Option<&str>) -> SystemMetrics {
+        // Get system memory using Windows API (instant)
+        let (total_mem, avail_mem) = host_memory_mb();
+
+        // Get vmmem process memory (host-side view of WSL memory commitment)
+        let vmmem_memory = self.get_vmmem_memory_mb();
+
+        // Get memory limit from .wslconfig (file read is fast)
+        let memory_limit = get_wsl_memory_limit().unwrap_or(total_mem / 2.0);
+
+        // Get detailed memory breakdown from inside WSL (if running)
+        let guest = guest.filter(|_| vmmem_memory > 0.0);
+        let wsl_memory = guest.and_then(|distro| {
+            self.get_wsl_memory_breakdown(distro)
+                .map_err(|e| log::debug!("Could not read /proc/meminfo: {}", e))
+                .ok()
+        });
+
+        // CPU usage since the previous call (both sides need the VM up)
+        let cpu = if vmmem_memory > 0.0 {
+            self.cpu.sample(guest)
+        } else {
+            self.cpu.reset();
+            CpuSample::default()
+        };
+
+        SystemMetrics {
+            vmmem_memory_mb: vmmem_memory,
+            wsl_memory_limit_mb: memory_limit,
+            wsl_memory,
+            wsl_cpu_percent: cpu.host_percent,
+            guest_cpu_percent: cpu.guest_percent,
+            total_system_memory_mb: total_mem,
+            available_system_memory_mb: avail_mem,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+
+    /// Get per-distribution disk metrics
+    ///
+    /// Disk sizes are cached per distribution (see [`DiskMetricsCollector`]),
+    /// so this is cheap to poll. Without `measure_guest` nothing runs inside
+    /// a distribution and guest usage is the last one measured.
+    pub fn get_distro_metrics(
+        &self,
+        distros: Vec<WslDistribution>,
+        measure_guest: bool,
+    ) -> Vec<DistroMetrics> {
+        distros
+            .into_iter()
+            .map(|distro| {
+                let disk = if measure_guest {
+                    self.disks.collect(&distro)
+                } else {
+                    self.disks.host_only(&distro)
+                };
+                let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+                DistroMetrics {
+                    disk_usage_mb: disk.used_bytes.map(mb),
+                    disk_size_mb: disk.allocated_bytes.map(mb).unwrap_or(0.0),
+                    virtual_size_mb: disk.virtual_bytes.map(mb).unwrap_or(0.0),
+                    reclaimable_mb: disk.reclaimable_bytes().map(mb),
+                    vhd_path: disk.vhd_path.map(|p| p.to_string_lossy().into_owned()),
+                    is_running: distro.state == DistributionState::Running,
+                    name: distro.name,
+                }
+            })
+            .collect()
+    }
+
+    /// Get vmmem process memory in MB
+    fn get_vmmem_memory_mb(&self) -> f64 {
+        // Use PowerShell - more reliable since it handles the memory value directly
+        // Checks for both vmmem and vmmemWSL process names
+        let output = self.runner.output(
+            "powershell",
+            &["-NoProfile", "-Command", VMMEM_MEMORY_SCRIPT],
+        );
+
+        if let Ok(output) = output {
+            let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if let Ok(mb) = stdout.parse::<f64>() {
+                if mb > 0.0 {
+                    return mb;
+                }
+            }
+        }
+
+        0.0
+    }
+
+    /// Query /proc/meminfo inside a running distribution for detailed memory breakdown
+    fn get_wsl_memory_breakdown(&self, distro: &str) -> TamerResult<WslMemoryBreakdown> {
+        // Guest output is UTF-8, unlike wsl.exe's own messages
+        let mut stdout = Vec::new();
+        run_wsl_command_to_writer(
+            self.runner.as_ref(),
+            &["-d", distro, "--", "cat", "/proc/meminfo"],
+            &mut stdout,
+            &RunControl::new(Some(MEMINFO_TIMEOUT), Default::default()),
+        )?;
+        parse_meminfo(&String::from_utf8_lossy(&stdout))
+    }
+}
+
+/// Total and available host memory in MB
+#[cfg(windows)]
+pub(crate) fn host_memory_mb() -> (f64, f64) {
+    use windows::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+    unsafe {
+        let mut mem_info = MEMORYSTATUSEX::default();
+        mem_info.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
+        if GlobalMemoryStatusEx(&mut mem_info).is_ok() {
+            let total_mb = mem_info.ullTotalPhys as f64 / (1024.0 * 1024.0);
+            let avail_mb = mem_info.ullAvailPhys as f64 / (1024.0 * 1024.0);
+            (total_mb, avail_mb)
+        } else {
+            (0.0, 0.0)
+        }
+    }
+}
+
+/// Total and available host memory in MB (unavailable off Windows)
+#[cfg(not(windows))]
+pub(crate) fn host_memory_mb() -> (f64, f64) {
+    (0.0, 0.0)
+}
+
+/// Parse /proc/meminfo output into WslMemoryBreakdown
+fn parse_meminfo(content: &str) -> TamerResult<WslMemoryBreakdown> {
+    let mut breakdown = WslMemoryBreakdown::default();
+
+    for line in content.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 2 {
+            continue;
+        }
+
+        let key = parts[0].trim_end_matches(':');
+        let value_kb: f64 = parts[1].parse().unwrap_or(0.0);
+        let value_mb = value_kb / 1024.0;
+
+        match key {
+            "MemTotal" => breakdown.total_mb = value_mb,
+            "MemFree" => breakdown.free_mb = value_mb,
+            "MemAvailable" => breakdown.available_mb = value_mb,
+            "Buffers" => breakdown.buffers_mb = value_mb,
+            "Cached" => breakdown.cached_mb = value_mb,
+            "SwapTotal" => breakdown.swap_total_mb = value_mb,
+            "SwapFree" => breakdown.swap_used_mb = breakdown.swap_total_mb - value_mb,
+            _ => {}
+        }
+    }
+
+    // Calculate used memory: total - free - buffers - cached
+    breakdown.used_mb =
+        breakdown.total_mb - breakdown.free_mb - breakdown.buffers_mb - breakdown.cached_mb;
+    if breakdown.used_mb < 0.0 {
+        breakdown.used_mb = breakdown.total_mb - breakdown.available_mb;
+    }
+
+    Ok(breakdown)
+}
+
+/// Get memory limit from .wslconfig
+fn get_wsl_memory_limit() -> Option<f64> {
+    let content = std::fs::read_to_string(WslService::get_wslconfig_path()).ok()?;
+    parse_memory_limit(&content)
+}
+
+/// Memory limit in MB from .wslconfig content, if one is set
+fn parse_memory_limit(content: &str) -> Option<f64> {
+    let config = WslConfig::from_ini(content).ok()?;
+    let bytes = parse_size(config.memory.as_deref()?)?;
+    Some(bytes as f64 / (1024.0 * 1024.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{CommandOutput, ScriptedRunner};
+
+    // --- parse_memory_limit tests ---
+
+    #[test]
+    fn test_parse_memory_gb() {
+        assert_eq!(parse_memory_limit("[wsl2]\nmemory=8GB"), Some(8192.0));
+        assert_eq!(parse_memory_limit("[wsl2]\nmemory = 4gb"), Some(4096.0));
+    }
+
+    #[test]
+    fn test_parse_memory_mb() {
+        assert_eq!(parse_memory_limit("[wsl2]\nmemory=512MB"), Some(512.0));
+        assert_eq!(parse_memory_limit("[wsl2]\nmemory=1024mb"), Some(1024.0));
+    }
+
+    #[test]
+    fn test_parse_memory_short_suffix() {
+        assert_eq!(parse_memory_limit("[wsl2]\nmemory=4G"), Some(4096.0));
+        assert_eq!(parse_memory_limit("[wsl2]\nmemory=256M"), Some(256.0));
+    }
+
+    #[test]
+    fn test_parse_memory_bytes() {
+        // 1 GB in bytes
+        assert_eq!(
+            parse_memory_limit("[wsl2]\nmemory=1073741824"),
+            Some(1024.0)
+        );
+    }
+
+    #[test]
+    fn test_parse_memory_invalid() {
+        assert_eq!(parse_memory_limit("[wsl2]\nmemory=notanumber"), None);
+        assert_eq!(parse_memory_limit("[wsl2]\nmemory="), None);
+        assert_eq!(parse_memory_limit(""), None);
+    }
+
+    #[test]
+    fn test_parse_memory_only_from_wsl2_section() {
+        let content = "[experimental]\nmemory=2GB\n\n[wsl2]\nprocessors=4\n";
+        assert_eq!(parse_memory_limit(content), None);
+        let content = "[experimental]\nmemory=2GB\n\n[wsl2]\nmemory=6GB\n";
+        assert_eq!(parse_memory_limit(content), Some(6144.0));
+    }
+
+    // --- parse_meminfo tests ---
+
+    #[test]
+    fn test_parse_meminfo_typical() {
+        let input = "\
+MemTotal:        8048596 kB
+MemFree:          524288 kB
+MemAvailable:    6291456 kB
+Buffers:          131072 kB
+Cached:          4194304 kB
+SwapTotal:       2097152 kB
+SwapFree:        1048576 kB";
+
+        let b = parse_meminfo(input).unwrap();
+        assert!((b.total_mb - 7860.0).abs() < 1.0);
+        assert!((b.free_mb - 512.0).abs() < 1.0);
+        assert!((b.cached_mb - 4096.0).abs() < 1.0);
+        assert!((b.buffers_mb - 128.0).abs() < 1.0);
+        assert!((b.swap_total_mb - 2048.0).abs() < 1.0);
+        assert!((b.swap_used_mb - 1024.0).abs() < 1.0);
+        assert!(b.used_mb > 0.0);
+    }
+
+    #[test]
+    fn test_parse_meminfo_empty() {
+        let b = parse_meminfo("").unwrap();
+        assert_eq!(b.total_mb, 0.0);
+        assert_eq!(b.free_mb, 0.0);
+    }
+
+    #[test]
+    fn test_parse_meminfo_partial() {
+        let input = "MemTotal: 4096 kB\nMemFree: 2048 kB";
+        let b = parse_meminfo(input).unwrap();
+        assert!((b.total_mb - 4.0).abs() < 0.01);
+        assert!((b.free_mb - 2.0).abs() < 0.01);
+    }
+
+    // --- service tests ---
+
+    #[test]
+    fn test_system_metrics_skip_meminfo_when_vm_stopped() {
+        let runner = Arc::new(ScriptedRunner::new().on(
+            "powershell",
+            &["-NoProfile", "-Command", VMMEM_MEMORY_SCRIPT],
+            CommandOutput::ok("0\r\n"),
+        ));
+        let service = MonitoringService::new(runner.clone());
+
+        let metrics = service.get_system_metrics(Some("Ubuntu"));
+        assert_eq!(metrics.vmmem_memory_mb, 0.0);
+        assert!(metrics.wsl_memory.is_none());
+        assert!(runner.calls().iter().all(|call| call.program != "wsl"));
+    }
+
+    #[test]
+    fn test_system_metrics_without_guest_stay_on_the_host() {
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on(
+                    "powershell",
+                    &["-NoProfile", "-Command", VMMEM_MEMORY_SCRIPT],
+                    CommandOutput::ok("2048\r\n"),
+                )
+                .on(
+                    "wsl",
+                    &["-d", "Ubuntu", "--", "cat", "/proc/meminfo"],
+                    CommandOutput::ok("MemTotal: 8192000 kB\nMemAvailable: 4096000 kB\n"),
+                ),
+        );
+        let service = MonitoringService::new(runner.clone());
+
+        let metrics = service.get_system_metrics(None);
+        assert_eq!(metrics.vmmem_memory_mb, 2048.0);
+        assert!(metrics.wsl_memory.is_none());
+        assert!(runner.calls().iter().all(|call| call.program != "wsl"));
+
+        // Reading the guest targets the running distribution, not the default one
+        let metrics = service.get_system_metrics(Some("Ubuntu"));
+        assert!(metrics.wsl_memory.is_some());
+        assert!(runner.was_called("wsl", &["-d", "Ubuntu", "--", "cat", "/proc/meminfo"]));
+    }
+}
@@ -0,0 +1,171 @@
+//! Move Journal - Persisted write-ahead log of distribution moves
+//!
+//! One JSON file per in-flight move. A journal that still exists on startup
+//! means the app died mid-move and the move must be resumed or rolled back.
+
+use crate::error::{TamerError, TamerResult};
+use crate::models::{MoveJournal, MoveStep};
+use crate::utils::{app_data_dir, unix_timestamp};
+use std::fs;
+use std::path::PathBuf;
+
+pub struct MoveJournalStore {
+    dir: PathBuf,
+}
+
+impl Default for MoveJournalStore {
+    fn default() -> Self {
+        Self::new(app_data_dir().join("moves"))
+    }
+}
+
+impl MoveJournalStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Start a journal for a new move
+    pub fn begin(
+        &self,
+        distro: &str,
+        new_location: &str,
+        original_location: Option<String>,
+        tar_path: String,
+    ) -> TamerResult<MoveJournal> {
+        let now = unix_timestamp();
+        let journal = MoveJournal {
+            id: uuid::Uuid::new_v4().to_string(),
+            distro: distro.to_string(),
+            new_location: new_location.to_string(),
+            original_location,
+            tar_path,
+            step: MoveStep::Exporting,
+            started_at: now,
+            updated_at: now,
+        };
+        self.save(&journal)?;
+        Ok(journal)
+    }
+
+    /// Record that `step` has completed
+    pub fn advance(&self, journal: &mut MoveJournal, step: MoveStep) -> TamerResult<()> {
+        journal.step = step;
+        journal.updated_at = unix_timestamp();
+        self.save(journal)
+    }
+
+    /// Persist a journal atomically (write to a temp file, then rename over)
+    pub fn save(&self, journal: &MoveJournal) -> TamerResult<()> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| TamerError::io("Failed to create move journal directory", e))?;
+
+        let json = serde_json::to_string_pretty(journal).map_err(|e| {
+            TamerError::Internal(format!("Failed to serialize move journal: {}", e))
+        })?;
+        let path = self.path(&journal.id);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, json).map_err(|e| TamerError::io("Failed to write move journal", e))?;
+        fs::rename(&tmp, &path).map_err(|e| TamerError::io("Failed to write move journal", e))
+    }
+
+    /// Delete a finished journal
+    pub fn remove(&self, id: &str) -> TamerResult<()> {
+        match fs::remove_file(self.path(id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(TamerError::io("Failed to remove move journal", e))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> TamerResult<MoveJournal> {
+        let content = fs::read_to_string(self.path(id)).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                TamerError::NotFound {
+                    what: format!("Move '{}'", id),
+                }
+            } else {
+                TamerError::io("Failed to read move journal", e)
+            }
+        })?;
+        serde_json::from_str(&content)
+            .map_err(|e| TamerError::Parse(format!("Corrupt move journal '{}': {}", id, e)))
+    }
+
+    /// All interrupted moves, oldest first (unreadable journals are skipped)
+    pub fn list(&self) -> Vec<MoveJournal> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut journals: Vec<MoveJournal> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                let content = fs::read_to_string(&path).ok()?;
+                match serde_json::from_str(&content) {
+                    Ok(journal) => Some(journal),
+                    Err(e) => {
+                        log::warn!("Skipping unreadable move journal {:?}: {}", path, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        journals.sort_by_key(|j| j.started_at);
+        journals
+    }
+
+    /// Pending move of a distribution, if any
+    pub fn find_by_distro(&self, distro: &str) -> Option<MoveJournal> {
+        self.list()
+            .into_iter()
+            .find(|j| j.distro.eq_ignore_ascii_case(distro))
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> MoveJournalStore {
+        MoveJournalStore::new(
+            std::env::temp_dir().join(format!("wsl-tamer-moves-{}", uuid::Uuid::new_v4())),
+        )
+    }
+
+    #[test]
+    fn test_journal_roundtrip_and_advance() {
+        let store = temp_store();
+        let mut journal = store
+            .begin(
+                "Ubuntu",
+                "D:\\WSL\\Ubuntu",
+                Some("C:\\WSL\\Ubuntu".to_string()),
+                "C:\\Temp\\Ubuntu_move.tar".to_string(),
+            )
+            .unwrap();
+
+        store.advance(&mut journal, MoveStep::Exported).unwrap();
+        let loaded = store.get(&journal.id).unwrap();
+        assert_eq!(loaded.step, MoveStep::Exported);
+        assert_eq!(store.find_by_distro("ubuntu").unwrap().id, journal.id);
+
+        store.remove(&journal.id).unwrap();
+        assert!(store.list().is_empty());
+        assert_eq!(store.get(&journal.id).unwrap_err().code(), "NOT_FOUND");
+        let _ = fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn test_missing_directory_lists_nothing() {
+        let store = temp_store();
+        assert!(store.list().is_empty());
+        assert!(store.remove("nope").is_ok());
+    }
+}
@@ -0,0 +1,644 @@
+//! Port Forward Service - Windows ports proxied into distributions
+//!
+//! Under NAT networking a distribution's address changes whenever WSL
+//! starts, so the `netsh interface portproxy` entries behind each rule are
+//! re-pointed at the current eth0 address. All changes of one sync go into a
+//! single script run with one elevation prompt; nothing is elevated when
+//! the proxies and firewall rules already match.
+
+use crate::error::{TamerError, TamerResult};
+use crate::models::{DistributionState, PortForwardRule, PortForwardStatus};
+use crate::services::WslService;
+use crate::utils::{
+    app_data_dir, format_command, run_elevated, run_wsl_command_to_writer, system_runner,
+    CommandRunner, RunControl,
+};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Limit for reading a distribution's address
+const ADDRESS_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An entry of `netsh interface portproxy show v4tov4`
+#[derive(Debug, Clone, PartialEq)]
+struct PortProxy {
+    listen_address: Ipv4Addr,
+    listen_port: u16,
+    connect_address: Ipv4Addr,
+    connect_port: u16,
+}
+
+pub struct PortForwardService {
+    runner: Arc<dyn CommandRunner>,
+    script_dir: PathBuf,
+    /// Addresses of the last sync (lowercased distribution name -> eth0),
+    /// to notice when WSL hands out new ones; also serializes syncs
+    addresses: Mutex<HashMap<String, Ipv4Addr>>,
+}
+
+impl Default for PortForwardService {
+    fn default() -> Self {
+        Self::new(system_runner(), app_data_dir().join("scripts"))
+    }
+}
+
+impl PortForwardService {
+    pub fn new(runner: Arc<dyn CommandRunner>, script_dir: PathBuf) -> Self {
+        Self {
+            runner,
+            script_dir,
+            addresses: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Point the proxies of `rules` at their distributions' current
+    /// addresses and remove those of disabled rules
+    ///
+    /// Rules of distributions that aren't running are left as they are;
+    /// they are picked up once the distribution starts.
+    pub fn sync(
+        &self,
+        wsl: &WslService,
+        rules: &[PortForwardRule],
+    ) -> TamerResult<Vec<PortForwardStatus>> {
+        let mut last = self.lock()?;
+        let addresses = self.resolve_running(wsl, rules)?;
+        self.sync_with(rules, &addresses)?;
+        *last = addresses.clone();
+        Ok(statuses(rules, &addresses, &self.current_proxies()?))
+    }
+
+    /// Sync only if a distribution's address changed since the last sync
+    ///
+    /// Called by the status polling thread; returns whether it synced.
+    pub fn reapply_if_changed(
+        &self,
+        wsl: &WslService,
+        rules: &[PortForwardRule],
+    ) -> TamerResult<bool> {
+        let mut last = self.lock()?;
+        let addresses = self.resolve_running(wsl, rules)?;
+        let changed = addresses
+            .iter()
+            .any(|(distro, address)| last.get(distro) != Some(address));
+        // Recorded first: a declined elevation prompt isn't repeated on
+        // every poll, only after the next address change
+        *last = addresses.clone();
+        if changed {
+            self.sync_with(rules, &addresses)?;
+        }
+        Ok(changed)
+    }
+
+    /// Where each rule's proxy currently points
+    pub fn status(
+        &self,
+        wsl: &WslService,
+        rules: &[PortForwardRule],
+    ) -> TamerResult<Vec<PortForwardStatus>> {
+        let addresses = self.resolve_running(wsl, rules)?;
+        Ok(statuses(rules, &addresses, &self.current_proxies()?))
+    }
+
+    /// Remove the proxy (and firewall rule) of a deleted rule
+    pub fn remove(&self, rule: &PortForwardRule) -> TamerResult<()> {
+        let _last = self.lock()?;
+        let disabled = PortForwardRule {
+            enabled: false,
+            ..rule.clone()
+        };
+        let commands = plan_commands(
+            &[disabled],
+            &HashMap::new(),
+            &self.current_proxies()?,
+            &self.current_firewall_rules(std::slice::from_ref(rule))?,
+        );
+        self.run_commands(&commands)
+    }
+
+    fn lock(&self) -> TamerResult<std::sync::MutexGuard<'_, HashMap<String, Ipv4Addr>>> {
+        self.addresses
+            .lock()
+            .map_err(|e| TamerError::Internal(e.to_string()))
+    }
+
+    fn sync_with(
+        &self,
+        rules: &[PortForwardRule],
+        addresses: &HashMap<String, Ipv4Addr>,
+    ) -> TamerResult<()> {
+        let commands = plan_commands(
+            rules,
+            addresses,
+            &self.current_proxies()?,
+            &self.current_firewall_rules(rules)?,
+        );
+        self.run_commands(&commands)
+    }
+
+    /// eth0 addresses of the running distributions enabled rules point at
+    ///
+    /// A distribution whose address can't be read is left out.
+    fn resolve_running(
+        &self,
+        wsl: &WslService,
+        rules: &[PortForwardRule],
+    ) -> TamerResult<HashMap<String, Ipv4Addr>> {
+        let running: Vec<String> = wsl
+            .get_distributions()?
+            .into_iter()
+            .filter(|d| d.state == DistributionState::Running)
+            .map(|d| d.name)
+            .collect();
+
+        let mut addresses = HashMap::new();
+        for rule in rules.iter().filter(|r| r.enabled) {
+            let key = rule.distro.to_lowercase();
+            if addresses.contains_key(&key) {
+                continue;
+            }
+            let Some(name) = running
+                .iter()
+                .find(|n| n.eq_ignore_ascii_case(&rule.distro))
+            else {
+                continue;
+            };
+            match self.resolve_address(name) {
+                Ok(address) => {
+                    addresses.insert(key, address);
+                }
+                Err(e) => log::warn!("Could not read the address of '{}': {}", name, e),
+            }
+        }
+        Ok(addresses)
+    }
+
+    /// Current eth0 address of a distribution
+    fn resolve_address(&self, name: &str) -> TamerResult<Ipv4Addr> {
+        // Guest output is UTF-8, unlike wsl.exe's own messages
+        let mut stdout = Vec::new();
+        run_wsl_command_to_writer(
+            self.runner.as_ref(),
+            &["-d", name, "--", "ip", "-4", "-o", "addr", "show", "eth0"],
+            &mut stdout,
+            &RunControl::new(Some(ADDRESS_TIMEOUT), Default::default()),
+        )?;
+        parse_eth0_address(&String::from_utf8_lossy(&stdout))
+    }
+
+    /// Port proxies Windows currently has (reading them needs no elevation)
+    fn current_proxies(&self) -> TamerResult<Vec<PortProxy>> {
+        let args = ["interface", "portproxy", "show", "v4tov4"];
+        let output =
+            self.runner
+                .output("netsh", &args)
+                .map_err(|e| TamerError::CommandUnavailable {
+                    program: "netsh".to_string(),
+                    message: e.to_string(),
+                })?;
+        if !output.success() {
+            return Err(TamerError::CommandFailed {
+                command: format_command("netsh", &args),
+                exit_code: output.exit_code,
+                // netsh reports errors on stdout
+                stderr: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+            });
+        }
+        Ok(parse_portproxy_show(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+
+    /// Names of the firewall rules of `rules` that Windows currently has
+    /// (reading them needs no elevation)
+    fn current_firewall_rules(&self, rules: &[PortForwardRule]) -> TamerResult<HashSet<String>> {
+        let mut present = HashSet::new();
+        for rule in rules {
+            let name = format!("name={}", rule.firewall_rule_name());
+            let output = self
+                .runner
+                .output("netsh", &["advfirewall", "firewall", "show", "rule", &name])
+                .map_err(|e| TamerError::CommandUnavailable {
+                    program: "netsh".to_string(),
+                    message: e.to_string(),
+                })?;
+            // netsh fails with "No rules match the specified criteria" otherwise
+            if output.success() {
+                present.insert(rule.firewall_rule_name());
+            }
+        }
+        Ok(present)
+    }
+
+    /// Run netsh `commands` as one elevated PowerShell script
+    fn run_commands(&self, commands: &[String]) -> TamerResult<()> {
+        if commands.is_empty() {
+            return Ok(());
+        }
+        let path = self.script_dir.join("port-forwards.ps1");
+        fs::create_dir_all(&self.script_dir)
+            .map_err(|e| TamerError::io("Failed to create script folder", e))?;
+        fs::write(&path, commands.join("\r\n"))
+            .map_err(|e| TamerError::io("Failed to write port forwarding script", e))?;
+
+        let path_arg = path.to_string_lossy();
+        let result = run_elevated(
+            self.runner.as_ref(),
+            "powershell",
+            &[
+                "-NoProfile",
+                "-ExecutionPolicy",
+                "Bypass",
+                "-File",
+                &path_arg,
+            ],
+        );
+        let _ = fs::remove_file(&path);
+        result
+    }
+}
+
+/// netsh commands that bring `current` proxies and the `firewall` rules
+/// present in line with `rules`
+///
+/// Proxies are matched to rules by listen address and port; proxies no rule
+/// listens on are never touched. Firewall rules are reconciled on their own,
+/// so switching `open_firewall` applies even when the proxy is up to date.
+fn plan_commands(
+    rules: &[PortForwardRule],
+    addresses: &HashMap<String, Ipv4Addr>,
+    current: &[PortProxy],
+    firewall: &HashSet<String>,
+) -> Vec<String> {
+    let mut commands = Vec::new();
+    for rule in rules {
+        let existing = current
+            .iter()
+            .find(|p| p.listen_address == rule.listen_address && p.listen_port == rule.listen_port);
+        let listen = format!(
+            "listenaddress={} listenport={}",
+            rule.listen_address, rule.listen_port
+        );
+        let delete_firewall = format!(
+            "netsh advfirewall firewall delete rule name={}",
+            rule.firewall_rule_name()
+        );
+        let add_firewall = format!(
+            "netsh advfirewall firewall add rule name={} dir=in action=allow protocol=TCP localport={}",
+            rule.firewall_rule_name(),
+            rule.listen_port
+        );
+        let firewall_open = firewall.contains(&rule.firewall_rule_name());
+
+        if !rule.enabled {
+            if existing.is_some() {
+                commands.push(format!(
+                    "netsh interface portproxy delete v4tov4 {}",
+                    listen
+                ));
+            }
+            if firewall_open {
+                commands.push(delete_firewall);
+            }
+            continue;
+        }
+
+        let Some(&address) = addresses.get(&rule.distro.to_lowercase()) else {
+            // The proxy waits for the distribution; a firewall rule no
+            // longer wanted can go right away
+            if firewall_open && !rule.open_firewall {
+                commands.push(delete_firewall);
+            }
+            continue;
+        };
+        let proxy_current = existing
+            .is_some_and(|p| p.connect_address == address && p.connect_port == rule.target_port);
+        if !proxy_current {
+            let verb = if existing.is_some() { "set" } else { "add" };
+            commands.push(format!(
+                "netsh interface portproxy {} v4tov4 {} connectaddress={} connectport={}",
+                verb, listen, address, rule.target_port
+            ));
+        }
+        match (rule.open_firewall, firewall_open) {
+            (true, false) => commands.push(add_firewall),
+            // Replaced along with the proxy rather than added again, so it
+            // never piles up
+            (true, true) if !proxy_current => {
+                commands.push(delete_firewall);
+                commands.push(add_firewall);
+            }
+            (false, true) => commands.push(delete_firewall),
+            _ => {}
+        }
+    }
+    commands
+}
+
+fn statuses(
+    rules: &[PortForwardRule],
+    addresses: &HashMap<String, Ipv4Addr>,
+    current: &[PortProxy],
+) -> Vec<PortForwardStatus> {
+    rules
+        .iter()
+        .map(|rule| {
+            let proxy = current.iter().find(|p| {
+                p.listen_address == rule.listen_address && p.listen_port == rule.listen_port
+            });
+            let address = addresses.get(&rule.distro.to_lowercase());
+            PortForwardStatus {
+                rule_id: rule.id.clone(),
+                connect_address: proxy.map(|p| p.connect_address),
+                active: rule.enabled
+                    && proxy.is_some_and(|p| {
+                        Some(&p.connect_address) == address && p.connect_port == rule.target_port
+                    }),
+            }
+        })
+        .collect()
+}
+
+/// Address from `ip -4 -o addr show eth0`
+fn parse_eth0_address(output: &str) -> TamerResult<Ipv4Addr> {
+    output
+        .split_whitespace()
+        .skip_while(|word| *word != "inet")
+        .nth(1)
+        .and_then(|cidr| cidr.split('/').next())
+        .and_then(|address| address.parse().ok())
+        .ok_or_else(|| TamerError::Parse(format!("No IPv4 address on eth0: {}", output.trim())))
+}
+
+/// Entries of `netsh interface portproxy show v4tov4`
+///
+/// ```text
+/// Listen on ipv4:             Connect to ipv4:
+///
+/// Address         Port        Address         Port
+/// --------------- ----------  --------------- ----------
+/// 0.0.0.0         8080        172.29.1.5      8080
+/// ```
+///
+/// Headers are localized, so rows are recognized by their shape alone.
+/// Entries connecting to host names are skipped.
+fn parse_portproxy_show(output: &str) -> Vec<PortProxy> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [listen_address, listen_port, connect_address, connect_port] = fields[..] else {
+                return None;
+            };
+            Some(PortProxy {
+                listen_address: match listen_address {
+                    "*" => Ipv4Addr::UNSPECIFIED,
+                    address => address.parse().ok()?,
+                },
+                listen_port: listen_port.parse().ok()?,
+                connect_address: connect_address.parse().ok()?,
+                connect_port: connect_port.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{CommandOutput, ScriptedRunner};
+
+    const PORTPROXY_SHOW: &str = "\r
+Listen on ipv4:             Connect to ipv4:\r
+\r
+Address         Port        Address         Port\r
+--------------- ----------  --------------- ----------\r
+0.0.0.0         8080        172.29.1.5      8080\r
+127.0.0.1       2222        172.29.1.5      22\r
+*               9000        builder.local   9000\r
+\r
+";
+
+    const DISTRO_LIST: &str = "  NAME      STATE           VERSION\r\n* Ubuntu    Running         2\r\n  Debian    Stopped         2\r\n";
+
+    const ADDR_ARGS: [&str; 9] = [
+        "-d", "Ubuntu", "--", "ip", "-4", "-o", "addr", "show", "eth0",
+    ];
+
+    fn rule(id: &str, listen_port: u16, distro: &str, target_port: u16) -> PortForwardRule {
+        PortForwardRule {
+            id: id.to_string(),
+            enabled: true,
+            listen_address: Ipv4Addr::UNSPECIFIED,
+            listen_port,
+            distro: distro.to_string(),
+            target_port,
+            open_firewall: false,
+        }
+    }
+
+    fn addresses(address: &str) -> HashMap<String, Ipv4Addr> {
+        HashMap::from([("ubuntu".to_string(), address.parse().unwrap())])
+    }
+
+    fn ip_output(address: &str) -> CommandOutput {
+        CommandOutput::ok(format!(
+            "2: eth0    inet {}/20 brd 172.29.15.255 scope global eth0\\       valid_lft forever preferred_lft forever\n",
+            address
+        ))
+    }
+
+    #[test]
+    fn test_parse_outputs() {
+        let proxies = parse_portproxy_show(PORTPROXY_SHOW);
+        assert_eq!(proxies.len(), 2);
+        assert_eq!(
+            proxies[1],
+            PortProxy {
+                listen_address: Ipv4Addr::LOCALHOST,
+                listen_port: 2222,
+                connect_address: "172.29.1.5".parse().unwrap(),
+                connect_port: 22,
+            }
+        );
+
+        assert_eq!(
+            parse_eth0_address(
+                "2: eth0    inet 172.29.1.5/20 brd 172.29.15.255 scope global eth0\\       valid_lft forever"
+            )
+            .unwrap(),
+            Ipv4Addr::new(172, 29, 1, 5)
+        );
+        assert!(parse_eth0_address("").is_err());
+    }
+
+    #[test]
+    fn test_plan_commands() {
+        let current = parse_portproxy_show(PORTPROXY_SHOW);
+        let mut web = rule("web", 8080, "Ubuntu", 8080);
+        let mut ssh = rule("ssh", 2222, "ubuntu", 22);
+        ssh.listen_address = Ipv4Addr::LOCALHOST;
+        let mut db = rule("db", 5432, "Ubuntu", 5432);
+        db.open_firewall = true;
+        let other = rule("other", 3000, "Debian", 3000);
+
+        // Up to date, new, and for a distribution that isn't running
+        let commands = plan_commands(
+            &[web.clone(), ssh.clone(), db.clone(), other],
+            &addresses("172.29.1.5"),
+            &current,
+            &HashSet::new(),
+        );
+        assert_eq!(
+            commands,
+            vec![
+                "netsh interface portproxy add v4tov4 listenaddress=0.0.0.0 listenport=5432 connectaddress=172.29.1.5 connectport=5432",
+                "netsh advfirewall firewall add rule name=WSL-Tamer-db dir=in action=allow protocol=TCP localport=5432",
+            ]
+        );
+
+        // New address; a disabled rule loses its proxy
+        web.enabled = false;
+        let commands = plan_commands(
+            &[web, ssh],
+            &addresses("172.29.7.9"),
+            &current,
+            &HashSet::new(),
+        );
+        assert_eq!(
+            commands,
+            vec![
+                "netsh interface portproxy delete v4tov4 listenaddress=0.0.0.0 listenport=8080",
+                "netsh interface portproxy set v4tov4 listenaddress=127.0.0.1 listenport=2222 connectaddress=172.29.7.9 connectport=22",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_firewall_with_matching_proxy() {
+        let current = parse_portproxy_show(PORTPROXY_SHOW);
+        let mut web = rule("web", 8080, "Ubuntu", 8080);
+
+        // Switched on: only the firewall rule is added
+        web.open_firewall = true;
+        let commands = plan_commands(
+            std::slice::from_ref(&web),
+            &addresses("172.29.1.5"),
+            &current,
+            &HashSet::new(),
+        );
+        assert_eq!(
+            commands,
+            vec![
+                "netsh advfirewall firewall add rule name=WSL-Tamer-web dir=in action=allow protocol=TCP localport=8080"
+            ]
+        );
+
+        // Already open: nothing to do
+        let open = HashSet::from(["WSL-Tamer-web".to_string()]);
+        let commands = plan_commands(
+            std::slice::from_ref(&web),
+            &addresses("172.29.1.5"),
+            &current,
+            &open,
+        );
+        assert!(commands.is_empty());
+
+        // Switched off: only the firewall rule is deleted
+        web.open_firewall = false;
+        let commands = plan_commands(&[web], &addresses("172.29.1.5"), &current, &open);
+        assert_eq!(
+            commands,
+            vec!["netsh advfirewall firewall delete rule name=WSL-Tamer-web"]
+        );
+    }
+
+    #[test]
+    fn test_reapply_only_when_address_changes() {
+        let dir = std::env::temp_dir().join(format!("wsl-tamer-ports-{}", uuid::Uuid::new_v4()));
+        let elevate = format!(
+            "try {{ $p = Start-Process 'powershell' -ArgumentList '\"-NoProfile\" \"-ExecutionPolicy\" \"Bypass\" \"-File\" \"{}\"' -Verb RunAs -Wait -PassThru -ErrorAction Stop }} catch {{ exit 1223 }}; exit $p.ExitCode",
+            dir.join("port-forwards.ps1").to_string_lossy()
+        );
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on(
+                    "wsl",
+                    &["--list", "--verbose"],
+                    CommandOutput::ok_utf16(DISTRO_LIST),
+                )
+                .on("wsl", &ADDR_ARGS, ip_output("172.29.1.5"))
+                .on("wsl", &ADDR_ARGS, ip_output("172.29.1.5"))
+                .on("wsl", &ADDR_ARGS, ip_output("172.29.7.9"))
+                .on(
+                    "netsh",
+                    &["interface", "portproxy", "show", "v4tov4"],
+                    CommandOutput::ok(PORTPROXY_SHOW),
+                )
+                .on(
+                    "netsh",
+                    &[
+                        "advfirewall",
+                        "firewall",
+                        "show",
+                        "rule",
+                        "name=WSL-Tamer-web",
+                    ],
+                    CommandOutput::failed(1, "No rules match the specified criteria.\r\n"),
+                )
+                .on(
+                    "powershell",
+                    &["-NoProfile", "-Command", &elevate],
+                    CommandOutput::ok(""),
+                ),
+        );
+        let service = PortForwardService::new(runner.clone(), dir.clone());
+        let wsl = WslService::new(runner.clone());
+        let rules = [rule("web", 8080, "Ubuntu", 8080)];
+        let elevations = || {
+            runner
+                .calls()
+                .iter()
+                .filter(|c| c.program == "powershell")
+                .count()
+        };
+
+        // First sight of the address, but the proxy already matches
+        assert!(service.reapply_if_changed(&wsl, &rules).unwrap());
+        assert_eq!(elevations(), 0);
+        assert!(!service.reapply_if_changed(&wsl, &rules).unwrap());
+
+        // WSL restarted with a new address: one elevated script
+        assert!(service.reapply_if_changed(&wsl, &rules).unwrap());
+        assert_eq!(elevations(), 1);
+        assert!(!dir.join("port-forwards.ps1").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_status() {
+        let current = parse_portproxy_show(PORTPROXY_SHOW);
+        let rules = [
+            rule("web", 8080, "Ubuntu", 8080),
+            rule("db", 5432, "Ubuntu", 5432),
+        ];
+
+        let status = statuses(&rules, &addresses("172.29.1.5"), &current);
+        assert!(status[0].active);
+        assert_eq!(
+            status[0].connect_address,
+            Some(Ipv4Addr::new(172, 29, 1, 5))
+        );
+        assert!(!status[1].active);
+        assert_eq!(status[1].connect_address, None);
+
+        // Stale address after a restart
+        let status = statuses(&rules, &addresses("172.29.7.9"), &current);
+        assert!(!status[0].active);
+    }
+}
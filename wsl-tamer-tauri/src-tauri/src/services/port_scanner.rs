@@ -0,0 +1,488 @@
+//! Port Scanner - Listening sockets across running distributions
+//!
+//! Each running distribution dumps `/proc/net/{tcp,tcp6,udp,udp6}`, its
+//! processes' socket descriptors and their names; parsing, the inode to PID
+//! mapping and the reachability verdict happen here in Rust.
+
+use crate::error::TamerResult;
+use crate::models::{
+    DistributionState, ListeningPort, NetworkingMode, SocketProtocol, WindowsReachability,
+    WslConfig,
+};
+use crate::services::WslService;
+use crate::utils::{run_wsl_command_with_input, system_runner, CommandRunner, RunControl};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Dumps the socket tables, socket descriptors and process names as
+/// `## <section>` blocks
+///
+/// Fed through stdin so nothing needs quoting on the wsl.exe command line.
+const SOCKET_DUMP_SCRIPT: &str = r###"for f in tcp tcp6 udp udp6; do
+  echo "## $f"
+  cat "/proc/net/$f" 2>/dev/null
+done
+echo "## fd"
+ls -l /proc/[0-9]*/fd 2>/dev/null
+echo "## comm"
+for p in /proc/[0-9]*; do
+  read -r c < "$p/comm" 2>/dev/null && echo "${p#/proc/} $c"
+done
+exit 0
+"###;
+
+/// Time limit for dumping the sockets of one distribution
+const DUMP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// `TCP_LISTEN` in /proc/net/tcp
+const TCP_LISTEN: &str = "0A";
+
+/// `TCP_CLOSE`: the state of a bound, unconnected UDP socket
+const UDP_UNCONNECTED: &str = "07";
+
+pub struct PortScanner {
+    runner: Arc<dyn CommandRunner>,
+}
+
+impl Default for PortScanner {
+    fn default() -> Self {
+        Self::new(system_runner())
+    }
+}
+
+impl PortScanner {
+    pub fn new(runner: Arc<dyn CommandRunner>) -> Self {
+        Self { runner }
+    }
+
+    /// Listening ports of all running distributions, ordered by port
+    ///
+    /// A distribution that can't be read is skipped rather than failing the
+    /// whole list.
+    pub fn list(&self, wsl: &WslService, config: &WslConfig) -> TamerResult<Vec<ListeningPort>> {
+        let mut dumps = Vec::new();
+        for distro in wsl.get_distributions()? {
+            if distro.state != DistributionState::Running {
+                continue;
+            }
+            match self.dump(&distro.name) {
+                Ok(dump) => dumps.push((distro.name, distro.version == "1", dump)),
+                Err(e) => log::warn!("Could not list sockets of '{}': {}", distro.name, e),
+            }
+        }
+        Ok(merge_listening_ports(dumps, config))
+    }
+
+    fn dump(&self, name: &str) -> TamerResult<SocketDump> {
+        let stdout = run_wsl_command_with_input(
+            self.runner.as_ref(),
+            &["-d", name, "-u", "root", "--", "sh", "-s"],
+            SOCKET_DUMP_SCRIPT.as_bytes(),
+            &RunControl::new(Some(DUMP_TIMEOUT), Default::default()),
+        )?;
+        Ok(parse_socket_dump(&String::from_utf8_lossy(&stdout)))
+    }
+}
+
+/// A listening socket from one of the /proc/net tables
+#[derive(Debug, Clone, PartialEq)]
+struct RawSocket {
+    protocol: SocketProtocol,
+    address: IpAddr,
+    port: u16,
+    inode: u64,
+}
+
+/// What one distribution reported
+#[derive(Debug, Default)]
+struct SocketDump {
+    sockets: Vec<RawSocket>,
+    /// Socket inode -> PID holding it
+    owners: HashMap<u64, u32>,
+    /// PID -> executable name
+    names: HashMap<u32, String>,
+}
+
+/// Parse the output of [`SOCKET_DUMP_SCRIPT`]
+fn parse_socket_dump(dump: &str) -> SocketDump {
+    let mut result = SocketDump::default();
+    let mut section = "";
+    let mut fd_pid = None;
+
+    for line in dump.lines() {
+        if let Some(name) = line.strip_prefix("## ") {
+            section = name.trim();
+            continue;
+        }
+        match section {
+            "tcp" | "tcp6" | "udp" | "udp6" => {
+                if let Some(socket) = parse_socket_line(section, line) {
+                    result.sockets.push(socket);
+                }
+            }
+            "fd" => {
+                // `ls -l` lists each `/proc/<pid>/fd:` directory, then its links
+                if let Some(dir) = line.strip_suffix("/fd:") {
+                    fd_pid = dir.strip_prefix("/proc/").and_then(|p| p.parse().ok());
+                } else if let (Some(pid), Some(inode)) = (fd_pid, socket_inode(line)) {
+                    result.owners.entry(inode).or_insert(pid);
+                }
+            }
+            "comm" => {
+                if let Some((pid, name)) = line.split_once(' ') {
+                    if let Ok(pid) = pid.parse() {
+                        result.names.insert(pid, name.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Inode of a `... -> socket:[12345]` descriptor line
+fn socket_inode(line: &str) -> Option<u64> {
+    let start = line.find("-> socket:[")? + "-> socket:[".len();
+    let rest = &line[start..];
+    rest[..rest.find(']')?].parse().ok()
+}
+
+/// Parse a /proc/net/{tcp,tcp6,udp,udp6} row, keeping listening sockets only
+///
+/// ```text
+///   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+///    0: 0100007F:0CEA 00000000:0000 0A 00000000:00000000 00:00000000 00000000   999        0 26150 ...
+/// ```
+fn parse_socket_line(table: &str, line: &str) -> Option<RawSocket> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 10 || !fields[0].ends_with(':') {
+        return None;
+    }
+    let (local, remote, state) = (fields[1], fields[2], fields[3]);
+    let protocol = if table.starts_with("tcp") {
+        if state != TCP_LISTEN {
+            return None;
+        }
+        SocketProtocol::Tcp
+    } else {
+        // Connected UDP sockets have a peer; only bound ones receive from anyone
+        let (_, remote_port) = remote.split_once(':')?;
+        if state != UDP_UNCONNECTED || u16::from_str_radix(remote_port, 16).ok()? != 0 {
+            return None;
+        }
+        SocketProtocol::Udp
+    };
+    let (address, port) = local.split_once(':')?;
+    Some(RawSocket {
+        protocol,
+        address: parse_hex_address(address)?,
+        port: u16::from_str_radix(port, 16).ok()?,
+        inode: fields[9].parse().ok()?,
+    })
+}
+
+/// Decode an address from /proc/net, printed as 32-bit words in host
+/// (little-endian) byte order
+fn parse_hex_address(hex: &str) -> Option<IpAddr> {
+    let word = |i: usize| -> Option<[u8; 4]> {
+        let value = u32::from_str_radix(hex.get(i * 8..i * 8 + 8)?, 16).ok()?;
+        Some(value.swap_bytes().to_be_bytes())
+    };
+    match hex.len() {
+        8 => Some(IpAddr::V4(Ipv4Addr::from(word(0)?))),
+        32 => {
+            let mut octets = [0u8; 16];
+            for i in 0..4 {
+                octets[i * 4..i * 4 + 4].copy_from_slice(&word(i)?);
+            }
+            Some(IpAddr::V6(Ipv6Addr::from(octets)))
+        }
+        _ => None,
+    }
+}
+
+/// How Windows reaches a socket bound to `address`
+///
+/// Follows WSL's rules: mirrored networking (and WSL 1, which uses the
+/// Windows stack) expose everything on localhost; NAT relays TCP ports bound
+/// to loopback or all interfaces to Windows' localhost when
+/// `localhostForwarding` is on, and otherwise only the VM's address works.
+pub fn windows_reachability(
+    protocol: SocketProtocol,
+    address: IpAddr,
+    wsl1: bool,
+    config: &WslConfig,
+) -> WindowsReachability {
+    if wsl1 {
+        return WindowsReachability::Localhost;
+    }
+    let loopback = match address {
+        IpAddr::V4(v4) => v4.is_loopback(),
+        IpAddr::V6(v6) => {
+            v6.is_loopback() || v6.to_ipv4_mapped().is_some_and(|v4| v4.is_loopback())
+        }
+    };
+    match config.networking_mode.clone().unwrap_or_default() {
+        NetworkingMode::Mirrored => WindowsReachability::Localhost,
+        NetworkingMode::Nat
+            if protocol == SocketProtocol::Tcp
+                && config.localhost_forwarding.unwrap_or(true)
+                && (loopback || address.is_unspecified()) =>
+        {
+            WindowsReachability::Localhost
+        }
+        _ if loopback => WindowsReachability::Unreachable,
+        _ => WindowsReachability::VmAddress,
+    }
+}
+
+/// Combine the dumps of several distributions into one list
+///
+/// WSL 2 distributions share the VM's network namespace, so every one of
+/// them lists the same sockets; each socket is reported once, under the
+/// distribution where a process holds it.
+fn merge_listening_ports(
+    dumps: Vec<(String, bool, SocketDump)>,
+    config: &WslConfig,
+) -> Vec<ListeningPort> {
+    let mut ports: Vec<ListeningPort> = Vec::new();
+    // (protocol, inode, WSL 1 distribution) -> index into `ports`; WSL 1
+    // distributions use the host's stack, so theirs are never shared
+    let mut seen: HashMap<(SocketProtocol, u64, Option<String>), usize> = HashMap::new();
+
+    for (distro, wsl1, dump) in dumps {
+        for socket in dump.sockets {
+            let pid = dump.owners.get(&socket.inode).copied();
+            let port = ListeningPort {
+                distro: distro.clone(),
+                protocol: socket.protocol,
+                address: socket.address,
+                port: socket.port,
+                pid,
+                process: pid.and_then(|pid| dump.names.get(&pid).cloned()),
+                reachability: windows_reachability(socket.protocol, socket.address, wsl1, config),
+            };
+            let key = (socket.protocol, socket.inode, wsl1.then(|| distro.clone()));
+            match seen.get(&key) {
+                Some(&i) => {
+                    if ports[i].pid.is_none() && pid.is_some() {
+                        ports[i] = port;
+                    }
+                }
+                None => {
+                    seen.insert(key, ports.len());
+                    ports.push(port);
+                }
+            }
+        }
+    }
+
+    ports.sort_by(|a, b| {
+        (a.port, a.protocol, &a.distro, a.address).cmp(&(b.port, b.protocol, &b.distro, b.address))
+    });
+    ports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{CommandOutput, ScriptedRunner};
+
+    /// Captured from Ubuntu 24.04 on WSL 2 (trimmed): sshd on all
+    /// interfaces, a dev server on loopback, node on IPv6, a DNS stub and a
+    /// connected UDP socket
+    const UBUNTU_DUMP: &str = "\
+## tcp
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 21230 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 31337 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:1F90 0100007F:C350 01 00000000:00000000 00:00000000 00000000  1000        0 31400 1 0000000000000000 20 4 30 10 -1
+## tcp6
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:0BB8 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 40001 1 0000000000000000 100 0 0 10 0
+## udp
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when ref pointer drops
+  120: 3500007F:0035 00000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 18000 2 0000000000000000 0
+  200: 0F1A11AC:A1B2 08080808:0035 01 00000000:00000000 00:00000000 00000000  1000        0 18100 2 0000000000000000 0
+## udp6
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when ref pointer drops
+## fd
+/proc/1/fd:
+total 0
+lrwx------ 1 root root 64 Oct 17 09:00 0 -> /dev/null
+/proc/233/fd:
+total 0
+lrwx------ 1 root root 64 Oct 17 09:00 3 -> socket:[21230]
+/proc/512/fd:
+total 0
+lrwx------ 1 alice alice 64 Oct 17 09:05 19 -> socket:[31337]
+lrwx------ 1 alice alice 64 Oct 17 09:05 20 -> socket:[40001]
+/proc/98/fd:
+lrwx------ 1 systemd-resolve systemd-resolve 64 Oct 17 09:00 12 -> socket:[18000]
+## comm
+1 init(Ubuntu)
+98 systemd-resolve
+233 sshd
+512 node
+";
+
+    /// The same VM seen from a second distribution: the sockets are there,
+    /// the processes holding them are not
+    const DEBIAN_DUMP: &str = "\
+## tcp
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 21230 1 0000000000000000 100 0 0 10 0
+   1: 00000000:1538 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 52000 1 0000000000000000 100 0 0 10 0
+## fd
+/proc/40/fd:
+lrwx------ 1 postgres postgres 64 Oct 17 09:10 7 -> socket:[52000]
+## comm
+40 postgres
+";
+
+    #[test]
+    fn test_parse_hex_address() {
+        assert_eq!(
+            parse_hex_address("0100007F"),
+            Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        );
+        assert_eq!(
+            parse_hex_address("0F1A11AC"),
+            Some("172.17.26.15".parse().unwrap())
+        );
+        assert_eq!(
+            parse_hex_address("00000000000000000000000001000000"),
+            Some(IpAddr::V6(Ipv6Addr::LOCALHOST))
+        );
+        assert_eq!(
+            parse_hex_address("B80D0120000000000000000001000000"),
+            Some("2001:db8::1".parse().unwrap())
+        );
+        assert_eq!(parse_hex_address("0100"), None);
+    }
+
+    #[test]
+    fn test_parse_socket_dump() {
+        let dump = parse_socket_dump(UBUNTU_DUMP);
+        let sockets: Vec<(SocketProtocol, u16, u64)> = dump
+            .sockets
+            .iter()
+            .map(|s| (s.protocol, s.port, s.inode))
+            .collect();
+        // The established TCP connection and the connected UDP socket are left out
+        assert_eq!(
+            sockets,
+            vec![
+                (SocketProtocol::Tcp, 22, 21230),
+                (SocketProtocol::Tcp, 8080, 31337),
+                (SocketProtocol::Tcp, 3000, 40001),
+                (SocketProtocol::Udp, 53, 18000),
+            ]
+        );
+        assert_eq!(
+            dump.sockets[3].address,
+            "127.0.0.53".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(dump.owners.get(&31337), Some(&512));
+        assert_eq!(dump.owners.get(&18000), Some(&98));
+        assert_eq!(dump.names.get(&1).map(String::as_str), Some("init(Ubuntu)"));
+    }
+
+    #[test]
+    fn test_windows_reachability() {
+        let loopback: IpAddr = "127.0.0.1".parse().unwrap();
+        let any: IpAddr = "0.0.0.0".parse().unwrap();
+        let vm: IpAddr = "172.17.26.15".parse().unwrap();
+        let nat = WslConfig::default();
+        let no_forwarding = WslConfig {
+            localhost_forwarding: Some(false),
+            ..Default::default()
+        };
+        let mirrored = WslConfig {
+            networking_mode: Some(NetworkingMode::Mirrored),
+            ..Default::default()
+        };
+
+        use SocketProtocol::{Tcp, Udp};
+        use WindowsReachability::*;
+        assert_eq!(windows_reachability(Tcp, loopback, false, &nat), Localhost);
+        assert_eq!(windows_reachability(Tcp, any, false, &nat), Localhost);
+        assert_eq!(windows_reachability(Tcp, vm, false, &nat), VmAddress);
+        assert_eq!(windows_reachability(Udp, any, false, &nat), VmAddress);
+        assert_eq!(
+            windows_reachability(Udp, loopback, false, &nat),
+            Unreachable
+        );
+        assert_eq!(
+            windows_reachability(Tcp, loopback, false, &no_forwarding),
+            Unreachable
+        );
+        assert_eq!(
+            windows_reachability(Tcp, any, false, &no_forwarding),
+            VmAddress
+        );
+        assert_eq!(
+            windows_reachability(Udp, loopback, false, &mirrored),
+            Localhost
+        );
+        assert_eq!(
+            windows_reachability(Udp, loopback, true, &no_forwarding),
+            Localhost
+        );
+    }
+
+    #[test]
+    fn test_merge_attributes_shared_sockets_to_owner() {
+        let ports = merge_listening_ports(
+            vec![
+                ("Debian".to_string(), false, parse_socket_dump(DEBIAN_DUMP)),
+                ("Ubuntu".to_string(), false, parse_socket_dump(UBUNTU_DUMP)),
+            ],
+            &WslConfig::default(),
+        );
+        let summary: Vec<(u16, &str, Option<&str>)> = ports
+            .iter()
+            .map(|p| (p.port, p.distro.as_str(), p.process.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (22, "Ubuntu", Some("sshd")),
+                (53, "Ubuntu", Some("systemd-resolve")),
+                (3000, "Ubuntu", Some("node")),
+                (5432, "Debian", Some("postgres")),
+                (8080, "Ubuntu", Some("node")),
+            ]
+        );
+        assert_eq!(ports[1].reachability, WindowsReachability::Unreachable);
+        assert_eq!(ports[2].reachability, WindowsReachability::Localhost);
+    }
+
+    #[test]
+    fn test_list_skips_stopped_distros() {
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on(
+                    "wsl",
+                    &["--list", "--verbose"],
+                    CommandOutput::ok_utf16(
+                        "  NAME      STATE           VERSION\r\n* Ubuntu    Running         2\r\n  Arch      Stopped         2\r\n",
+                    ),
+                )
+                .on(
+                    "wsl",
+                    &["-d", "Ubuntu", "-u", "root", "--", "sh", "-s"],
+                    CommandOutput::ok(UBUNTU_DUMP),
+                ),
+        );
+        let scanner = PortScanner::new(runner.clone());
+        let wsl = WslService::new(runner.clone());
+
+        let ports = scanner.list(&wsl, &WslConfig::default()).unwrap();
+        assert_eq!(ports.len(), 4);
+        assert!(!runner.was_called("wsl", &["-d", "Arch", "-u", "root", "--", "sh", "-s"]));
+    }
+}
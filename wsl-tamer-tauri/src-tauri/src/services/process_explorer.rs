@@ -0,0 +1,482 @@
+//! Process Explorer - Processes inside a running distribution
+//!
+//! A short shell script dumps the relevant parts of `/proc` (plus
+//! `/etc/passwd` for user names) as tagged lines; everything else, from
+//! splitting `stat` to building the process tree, happens here in Rust so it
+//! can be tested on captured output.
+
+use crate::error::{TamerError, TamerResult};
+use crate::models::{DistributionState, DistroProcess, ProcessNode, ProcessSignal};
+use crate::services::WslService;
+use crate::utils::{
+    run_wsl_command_to_writer, run_wsl_command_with_input, system_runner, CommandRunner, RunControl,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Dumps /proc as `<tag> <value>` lines, one `pid` line opening each process
+///
+/// Fed through stdin so nothing needs quoting on the wsl.exe command line.
+const PROC_DUMP_SCRIPT: &str = r#"cd /proc || exit 1
+echo "self $$"
+echo "uptime $(cat uptime)"
+while read -r key value; do [ "$key" = btime ] && echo "btime $value"; done < stat
+while IFS= read -r line; do echo "passwd $line"; done < /etc/passwd
+for p in [0-9]*; do
+  read -r stat < "$p/stat" 2>/dev/null || continue
+  echo "pid $p"
+  echo "stat $stat"
+  while read -r key uid rest; do [ "$key" = Uid: ] && echo "uid $uid" && break; done < "$p/status" 2>/dev/null
+  echo "cmdline $(tr '\0' ' ' < "$p/cmdline" 2>/dev/null)"
+  read -r cgroup < "$p/cgroup" 2>/dev/null && echo "cgroup $cgroup"
+done
+"#;
+
+/// Time limit for dumping /proc or signalling a process
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// `USER_HZ`: units of the times in /proc/<pid>/stat
+const CLOCK_TICKS: f64 = 100.0;
+
+/// Page size `rss` is counted in
+const PAGE_SIZE: u64 = 4096;
+
+pub struct ProcessExplorer {
+    runner: Arc<dyn CommandRunner>,
+}
+
+impl Default for ProcessExplorer {
+    fn default() -> Self {
+        Self::new(system_runner())
+    }
+}
+
+impl ProcessExplorer {
+    pub fn new(runner: Arc<dyn CommandRunner>) -> Self {
+        Self { runner }
+    }
+
+    /// Process tree of a running distribution
+    ///
+    /// Stopped distributions are not started just to be inspected.
+    pub fn list(&self, wsl: &WslService, name: &str) -> TamerResult<Vec<ProcessNode>> {
+        ensure_running(wsl, name)?;
+        let stdout = run_wsl_command_with_input(
+            self.runner.as_ref(),
+            &["-d", name, "-u", "root", "--", "sh", "-s"],
+            PROC_DUMP_SCRIPT.as_bytes(),
+            &RunControl::new(Some(COMMAND_TIMEOUT), Default::default()),
+        )?;
+        let processes = parse_proc_dump(&String::from_utf8_lossy(&stdout))?;
+        Ok(build_process_tree(processes))
+    }
+
+    /// Send `signal` to a process of a running distribution
+    pub fn kill(
+        &self,
+        wsl: &WslService,
+        name: &str,
+        pid: u32,
+        signal: ProcessSignal,
+    ) -> TamerResult<()> {
+        if pid <= 1 {
+            return Err(TamerError::InvalidInput(
+                "The distribution's init process cannot be signalled".to_string(),
+            ));
+        }
+        ensure_running(wsl, name)?;
+        let pid = pid.to_string();
+        let args = [
+            "-d",
+            name,
+            "-u",
+            "root",
+            "--",
+            "kill",
+            "-s",
+            signal.as_str(),
+            &pid,
+        ];
+        run_wsl_command_to_writer(
+            self.runner.as_ref(),
+            &args,
+            &mut std::io::sink(),
+            &RunControl::new(Some(COMMAND_TIMEOUT), Default::default()),
+        )
+    }
+}
+
+fn ensure_running(wsl: &WslService, name: &str) -> TamerResult<()> {
+    let distro = wsl
+        .get_distributions()?
+        .into_iter()
+        .find(|d| d.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| TamerError::DistroNotFound {
+            name: name.to_string(),
+        })?;
+    if distro.state != DistributionState::Running {
+        return Err(TamerError::InvalidOperation(format!(
+            "{} is not running",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Fields of /proc/<pid>/stat this module uses
+#[derive(Debug, Clone, PartialEq)]
+struct ProcStat {
+    comm: String,
+    state: String,
+    ppid: u32,
+    /// utime + stime, in clock ticks
+    cpu_ticks: u64,
+    threads: u32,
+    /// Clock ticks after boot
+    start_ticks: u64,
+    rss_pages: u64,
+}
+
+/// Parse a /proc/<pid>/stat line
+///
+/// `comm` is parenthesized and may itself contain spaces and parentheses,
+/// so the fields after it are found from the last `)`.
+fn parse_stat(line: &str) -> TamerResult<ProcStat> {
+    let invalid = || TamerError::Parse(format!("Invalid /proc stat line: {}", line));
+    let open = line.find('(').ok_or_else(invalid)?;
+    let close = line.rfind(')').ok_or_else(invalid)?;
+    if close < open {
+        return Err(invalid());
+    }
+    // Fields 3 onwards: state, ppid, ..., utime (14), stime (15), ...
+    let rest: Vec<&str> = line[close + 1..].split_whitespace().collect();
+    let field = |n: usize| -> TamerResult<u64> {
+        rest.get(n - 3)
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(invalid)
+    };
+    Ok(ProcStat {
+        comm: line[open + 1..close].to_string(),
+        state: rest.first().ok_or_else(invalid)?.to_string(),
+        ppid: field(4)? as u32,
+        cpu_ticks: field(14)? + field(15)?,
+        threads: field(20)? as u32,
+        start_ticks: field(22)?,
+        rss_pages: field(24)?,
+    })
+}
+
+/// Raw values of one process in the dump
+#[derive(Default)]
+struct ProcEntry {
+    pid: u32,
+    stat: Option<String>,
+    uid: Option<u32>,
+    cmdline: String,
+    cgroup: Option<String>,
+}
+
+/// Turn the output of [`PROC_DUMP_SCRIPT`] into processes
+///
+/// The script's own shell and the helpers it forks are left out. Processes
+/// that exited while being read (incomplete entries) are skipped.
+fn parse_proc_dump(dump: &str) -> TamerResult<Vec<DistroProcess>> {
+    let mut self_pid = None;
+    let mut uptime = None;
+    let mut boot_time = None;
+    let mut users = HashMap::new();
+    let mut entries: Vec<ProcEntry> = Vec::new();
+
+    for line in dump.lines() {
+        let (tag, value) = line.split_once(' ').unwrap_or((line, ""));
+        match tag {
+            "self" => self_pid = value.trim().parse::<u32>().ok(),
+            "uptime" => {
+                uptime = value
+                    .split_whitespace()
+                    .next()
+                    .and_then(|v| v.parse::<f64>().ok())
+            }
+            "btime" => boot_time = value.trim().parse::<u64>().ok(),
+            "passwd" => {
+                let fields: Vec<&str> = value.split(':').collect();
+                if let (Some(name), Some(Ok(uid))) =
+                    (fields.first(), fields.get(2).map(|u| u.parse::<u32>()))
+                {
+                    users.entry(uid).or_insert_with(|| name.to_string());
+                }
+            }
+            "pid" => {
+                if let Ok(pid) = value.trim().parse() {
+                    entries.push(ProcEntry {
+                        pid,
+                        ..Default::default()
+                    });
+                }
+            }
+            "stat" | "uid" | "cmdline" | "cgroup" => {
+                let Some(entry) = entries.last_mut() else {
+                    continue;
+                };
+                match tag {
+                    "stat" => entry.stat = Some(value.to_string()),
+                    "uid" => entry.uid = value.trim().parse().ok(),
+                    "cmdline" => entry.cmdline = value.trim_end().to_string(),
+                    _ => entry.cgroup = Some(value.trim().to_string()),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let uptime =
+        uptime.ok_or_else(|| TamerError::Parse("Process list has no uptime".to_string()))?;
+    let boot_time =
+        boot_time.ok_or_else(|| TamerError::Parse("Process list has no boot time".to_string()))?;
+
+    let mut processes = Vec::new();
+    for entry in entries {
+        let Some(stat) = entry.stat.as_deref() else {
+            continue;
+        };
+        let stat = parse_stat(stat)?;
+        if Some(entry.pid) == self_pid || Some(stat.ppid) == self_pid {
+            continue;
+        }
+
+        let started = stat.start_ticks as f64 / CLOCK_TICKS;
+        let cpu = stat.cpu_ticks as f64 / CLOCK_TICKS;
+        let elapsed = uptime - started;
+        let cpu_percent = if elapsed > 0.0 {
+            (cpu / elapsed * 100.0 * 10.0).round() / 10.0
+        } else {
+            0.0
+        };
+        let user = match entry.uid {
+            Some(uid) => users.get(&uid).cloned().unwrap_or_else(|| uid.to_string()),
+            None => String::new(),
+        };
+        let command = if entry.cmdline.is_empty() {
+            format!("[{}]", stat.comm)
+        } else {
+            entry.cmdline
+        };
+
+        processes.push(DistroProcess {
+            pid: entry.pid,
+            ppid: stat.ppid,
+            user,
+            name: stat.comm,
+            command,
+            state: stat.state,
+            cpu_percent,
+            rss_bytes: stat.rss_pages * PAGE_SIZE,
+            threads: stat.threads,
+            start_time: boot_time + started as u64,
+            cgroup: entry.cgroup.map(|c| cgroup_path(&c)),
+        });
+    }
+    Ok(processes)
+}
+
+/// Path of a `hierarchy:controllers:path` line from /proc/<pid>/cgroup
+fn cgroup_path(line: &str) -> String {
+    line.splitn(3, ':').nth(2).unwrap_or(line).to_string()
+}
+
+/// Arrange processes under their parents, ordered by PID
+///
+/// Processes whose parent isn't in the list (the distribution's init, or
+/// processes of other sessions) become roots.
+pub fn build_process_tree(mut processes: Vec<DistroProcess>) -> Vec<ProcessNode> {
+    processes.sort_by_key(|p| p.pid);
+    let pids: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+
+    let mut children: HashMap<u32, Vec<DistroProcess>> = HashMap::new();
+    let mut roots = Vec::new();
+    for process in processes {
+        if process.ppid != process.pid && pids.contains(&process.ppid) {
+            children.entry(process.ppid).or_default().push(process);
+        } else {
+            roots.push(process);
+        }
+    }
+
+    fn attach(
+        process: DistroProcess,
+        children: &mut HashMap<u32, Vec<DistroProcess>>,
+    ) -> ProcessNode {
+        let own = children.remove(&process.pid).unwrap_or_default();
+        ProcessNode {
+            children: own.into_iter().map(|c| attach(c, children)).collect(),
+            process,
+        }
+    }
+    roots
+        .into_iter()
+        .map(|process| attach(process, &mut children))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{CommandOutput, ScriptedRunner};
+
+    /// Captured from Ubuntu 24.04 on WSL 2 (trimmed), with the dump's own
+    /// shell as PID 412 and its `tr` as PID 415
+    const UBUNTU_DUMP: &str = "\
+self 412
+uptime 3605.42 28412.10
+btime 1760680000
+passwd root:x:0:0:root:/root:/bin/bash
+passwd daemon:x:1:1:daemon:/usr/sbin:/usr/sbin/nologin
+passwd alice:x:1000:1000:Alice,,,:/home/alice:/bin/bash
+pid 1
+stat 1 (init(Ubuntu)) S 0 0 0 0 -1 4194560 1034 0 12 0 5 18 0 0 20 0 2 0 2 2478080 312 18446744073709551615 1 1 0 0 0 0 0 0 65536 0 0 0 17 3 0 0 0 0 0 0 0 0 0 0 0 0 0
+uid 0
+cmdline /init
+cgroup 0::/init.scope
+pid 4
+stat 4 (init) S 1 0 0 0 -1 4194624 91 0 0 0 0 2 0 0 20 0 1 0 4 2486272 210 18446744073709551615 1 1 0 0 0 0 0 0 65536 0 0 0 17 0 0 0 0 0 0 0 0 0 0 0 0 0 0
+uid 0
+cmdline plan9 --control-socket 7 --log-level 4
+cgroup 0::/init.scope
+pid 205
+stat 205 (bash) S 204 205 205 34816 210 4194560 3210 0 4 0 36 12 0 0 20 0 1 0 120000 9252864 1280 18446744073709551615 1 1 0 0 0 0 65536 3670020 1266777851 0 0 0 17 1 0 0 0 0 0 0 0 0 0 0 0 0 0
+uid 1000
+cmdline -bash
+cgroup 0::/user.slice/user-1000.slice/session-c1.scope
+pid 210
+stat 210 (tmux: server) S 205 210 210 0 -1 4194368 980 0 0 0 720 180 0 0 20 0 1 0 180000 10485760 2048 18446744073709551615 1 1 0 0 0 0 0 0 134237699 0 0 0 17 2 0 0 0 0 0 0 0 0 0 0 0 0 0
+uid 1000
+cmdline tmux new -s work
+pid 204
+stat 204 (SessionLeader) S 1 204 204 0 -1 4194560 12 0 0 0 0 0 0 0 20 0 1 0 119990 2486272 120 18446744073709551615 1 1 0 0 0 0 0 0 65536 0 0 0 17 1 0 0 0 0 0 0 0 0 0 0 0 0 0
+uid 0
+cmdline
+cgroup 0::/init.scope
+pid 333
+stat 333 (node) R 210 333 210 34817 333 4194304 52000 0 3 0 9000 1000 0 0 20 0 11 0 300000 1231028224 65536 18446744073709551615 1 1 0 0 0 0 0 4096 17408 0 0 0 17 0 0 0 0 0 0 0 0 0 0 0 0 0 0
+uid 1234
+cmdline node server.js
+cgroup 0::/user.slice/user-1000.slice/session-c1.scope
+pid 399
+pid 412
+stat 412 (sh) S 411 412 412 0 -1 4194304 150 0 0 0 0 0 0 0 20 0 1 0 360500 2580480 230 18446744073709551615 1 1 0 0 0 0 0 0 65536 0 0 0 17 1 0 0 0 0 0 0 0 0 0 0 0 0 0
+uid 0
+cmdline sh -s
+pid 415
+stat 415 (tr) R 412 412 412 0 -1 4194304 90 0 0 0 0 0 0 0 20 0 1 0 360540 2580480 180 18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 1 0 0 0 0 0 0 0 0 0 0 0 0 0
+uid 0
+cmdline tr \\0
+";
+
+    #[test]
+    fn test_parse_stat_with_parentheses_in_comm() {
+        let stat = parse_stat(
+            "1 (init(Ubuntu)) S 0 0 0 0 -1 4194560 1034 0 12 0 5 18 0 0 20 0 2 0 2 2478080 312 18446744073709551615",
+        )
+        .unwrap();
+        assert_eq!(stat.comm, "init(Ubuntu)");
+        assert_eq!(stat.state, "S");
+        assert_eq!(stat.ppid, 0);
+        assert_eq!(stat.cpu_ticks, 23);
+        assert_eq!(stat.threads, 2);
+        assert_eq!(stat.start_ticks, 2);
+        assert_eq!(stat.rss_pages, 312);
+
+        assert!(parse_stat("1 (init) S 0").is_err());
+        assert!(parse_stat("garbage").is_err());
+    }
+
+    #[test]
+    fn test_parse_proc_dump() {
+        let processes = parse_proc_dump(UBUNTU_DUMP).unwrap();
+        let pids: Vec<u32> = processes.iter().map(|p| p.pid).collect();
+        // 399 vanished mid-read; 412 and 415 belong to the dump itself
+        assert_eq!(pids, vec![1, 4, 205, 210, 204, 333]);
+
+        let tmux = &processes[3];
+        assert_eq!(tmux.name, "tmux: server");
+        assert_eq!(tmux.user, "alice");
+        assert_eq!(tmux.command, "tmux new -s work");
+        assert_eq!(tmux.rss_bytes, 2048 * 4096);
+        assert_eq!(tmux.start_time, 1760680000 + 1800);
+        // 9 s of CPU over the 1805.42 s since it started
+        assert_eq!(tmux.cpu_percent, 0.5);
+        assert_eq!(tmux.cgroup, None);
+
+        let leader = &processes[4];
+        assert_eq!(leader.command, "[SessionLeader]");
+        assert_eq!(leader.cgroup.as_deref(), Some("/init.scope"));
+
+        let node = &processes[5];
+        assert_eq!(node.user, "1234");
+        assert_eq!(node.state, "R");
+        assert_eq!(node.threads, 11);
+        assert_eq!(
+            node.cgroup.as_deref(),
+            Some("/user.slice/user-1000.slice/session-c1.scope")
+        );
+
+        assert!(parse_proc_dump("pid 1\n").is_err());
+    }
+
+    #[test]
+    fn test_build_process_tree() {
+        let tree = build_process_tree(parse_proc_dump(UBUNTU_DUMP).unwrap());
+        assert_eq!(tree.len(), 1);
+        let init = &tree[0];
+        assert_eq!(init.process.pid, 1);
+        let children: Vec<u32> = init.children.iter().map(|c| c.process.pid).collect();
+        assert_eq!(children, vec![4, 204]);
+
+        let bash = &init.children[1].children[0];
+        assert_eq!(bash.process.pid, 205);
+        let tmux = &bash.children[0];
+        assert_eq!(tmux.children[0].process.name, "node");
+    }
+
+    #[test]
+    fn test_kill_refuses_init() {
+        let runner = Arc::new(ScriptedRunner::new());
+        let explorer = ProcessExplorer::new(runner.clone());
+        let wsl = WslService::new(runner.clone());
+
+        assert!(matches!(
+            explorer.kill(&wsl, "Ubuntu", 1, ProcessSignal::Kill),
+            Err(TamerError::InvalidInput(_))
+        ));
+        assert!(runner.calls().is_empty());
+    }
+
+    #[test]
+    fn test_kill_sends_signal_to_running_distro() {
+        let list = CommandOutput::ok_utf16(
+            "  NAME      STATE           VERSION\r\n* Ubuntu    Running         2\r\n",
+        );
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on("wsl", &["--list", "--verbose"], list)
+                .on(
+                    "wsl",
+                    &[
+                        "-d", "Ubuntu", "-u", "root", "--", "kill", "-s", "TERM", "333",
+                    ],
+                    CommandOutput::ok(""),
+                ),
+        );
+        let explorer = ProcessExplorer::new(runner.clone());
+        let wsl = WslService::new(runner.clone());
+
+        explorer
+            .kill(&wsl, "Ubuntu", 333, ProcessSignal::Term)
+            .unwrap();
+        assert!(runner.was_called(
+            "wsl",
+            &["-d", "Ubuntu", "-u", "root", "--", "kill", "-s", "TERM", "333"]
+        ));
+    }
+}
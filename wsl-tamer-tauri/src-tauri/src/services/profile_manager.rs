@@ -1,11 +1,19 @@
 //! Profile Manager - Profile and automation rule management
 
-use crate::models::{WslProfile, AutomationRule, AppConfig};
+use crate::error::{TamerError, TamerResult};
+use crate::models::{WslProfile, AutomationRule, AppConfig, OperationTimeouts, SnapshotPolicy, CompressionSettings, MetricsHistorySettings, PortForwardRule};
+use crate::utils::unix_timestamp;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 
 /// In-memory profile storage with persistence support
 pub struct ProfileManager {
     config: RwLock<AppConfig>,
+    /// File the config is saved to after every change (`None` = memory only)
+    config_file: Option<PathBuf>,
+    /// Where an unreadable config file was moved on startup
+    corrupt_backup: Option<PathBuf>,
 }
 
 impl Default for ProfileManager {
@@ -17,11 +25,89 @@ impl Default for ProfileManager {
 /// Error message for poisoned lock
 const LOCK_ERROR: &str = "Configuration lock poisoned - internal error";
 
+fn lock_error<T>(_: T) -> TamerError {
+    TamerError::Internal(LOCK_ERROR.to_string())
+}
+
 impl ProfileManager {
     pub fn new() -> Self {
         Self {
             config: RwLock::new(AppConfig::default()),
+            config_file: None,
+            corrupt_backup: None,
+        }
+    }
+
+    /// Load config from `path` (if it exists) and save every change back to it
+    ///
+    /// A file that can't be read or parsed is moved aside to
+    /// `config.json.corrupt-<timestamp>` rather than overwritten; see
+    /// [`Self::corrupt_backup`]. If it can't be moved either, changes are
+    /// kept in memory only.
+    pub fn with_config_file(path: PathBuf) -> Self {
+        let loaded = |config| Self {
+            config: RwLock::new(config),
+            config_file: Some(path.clone()),
+            corrupt_backup: None,
+        };
+        let problem = match fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str(&content) {
+                Ok(config) => return loaded(config),
+                Err(e) => e.to_string(),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return loaded(AppConfig::default())
+            }
+            Err(e) => e.to_string(),
+        };
+
+        let mut backup = path.clone().into_os_string();
+        backup.push(format!(".corrupt-{}", unix_timestamp()));
+        let backup = PathBuf::from(backup);
+        if let Err(e) = fs::rename(&path, &backup) {
+            log::error!(
+                "Unreadable config {:?} ({}) could not be moved aside ({}); changes won't be saved",
+                path, problem, e
+            );
+            return Self::new();
+        }
+        log::error!("Unreadable config {:?} ({}) moved to {:?}", path, problem, backup);
+        Self {
+            corrupt_backup: Some(backup),
+            ..loaded(AppConfig::default())
+        }
+    }
+
+    /// Where an unreadable config file was moved on startup, if it was
+    pub fn corrupt_backup(&self) -> Option<&Path> {
+        self.corrupt_backup.as_deref()
+    }
+
+    /// Apply `change` to a copy of the config, save the copy, and only then
+    /// make it live, so a failed save leaves the config as it was
+    fn update<R>(&self, change: impl FnOnce(&mut AppConfig) -> TamerResult<R>) -> TamerResult<R> {
+        let mut config = self.config.write().map_err(lock_error)?;
+        let mut updated = config.clone();
+        let result = change(&mut updated)?;
+        self.persist(&updated)?;
+        *config = updated;
+        Ok(result)
+    }
+
+    /// Write the config to the config file atomically (temp file, then rename)
+    fn persist(&self, config: &AppConfig) -> TamerResult<()> {
+        let Some(path) = &self.config_file else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| TamerError::io("Failed to create config directory", e))?;
         }
+        let json = serde_json::to_string_pretty(config)
+            .map_err(|e| TamerError::Internal(format!("Failed to serialize config: {}", e)))?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, json).map_err(|e| TamerError::io("Failed to save config", e))?;
+        fs::rename(&tmp, path).map_err(|e| TamerError::io("Failed to save config", e))
     }
 
     /// Initialize with default profiles
@@ -74,10 +160,56 @@ impl ProfileManager {
     }
 
     /// Load config from persistent storage
-    pub fn load_config(&self, stored_config: AppConfig) -> Result<(), String> {
-        let mut config = self.config.write().map_err(|_| LOCK_ERROR)?;
-        *config = stored_config;
-        Ok(())
+    pub fn load_config(&self, stored_config: AppConfig) -> TamerResult<()> {
+        self.update(|config| {
+            *config = stored_config;
+            Ok(())
+        })
+    }
+
+    /// Update time limits for long-running WSL operations
+    pub fn set_operation_timeouts(&self, timeouts: OperationTimeouts) -> TamerResult<()> {
+        self.update(|config| {
+            config.operation_timeouts = timeouts;
+            Ok(())
+        })
+    }
+
+    /// Update the default export compression settings
+    pub fn set_compression_settings(&self, settings: CompressionSettings) -> TamerResult<()> {
+        self.update(|config| {
+            config.export_compression = settings;
+            Ok(())
+        })
+    }
+
+    /// Update how metrics history is sampled and kept
+    pub fn set_metrics_history_settings(&self, settings: MetricsHistorySettings) -> TamerResult<()> {
+        self.update(|config| {
+            config.metrics_history = settings;
+            Ok(())
+        })
+    }
+
+    /// Add or replace a port forwarding rule
+    pub fn save_port_forward(&self, rule: PortForwardRule) -> TamerResult<()> {
+        self.update(|config| {
+            if let Some(existing) = config.port_forwards.iter_mut().find(|r| r.id == rule.id) {
+                *existing = rule;
+            } else {
+                config.port_forwards.push(rule);
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Delete a port forwarding rule
+    pub fn delete_port_forward(&self, id: &str) -> TamerResult<()> {
+        self.update(|config| {
+            config.port_forwards.retain(|r| r.id != id);
+            Ok(())
+        })
     }
 
     /// Get current config for persistence
@@ -105,54 +237,59 @@ impl ProfileManager {
     }
 
     /// Add or update a profile
-    pub fn save_profile(&self, profile: WslProfile) -> Result<(), String> {
-        let mut config = self.config.write().map_err(|_| LOCK_ERROR)?;
-        
-        if let Some(existing) = config.profiles.iter_mut().find(|p| p.id == profile.id) {
-            *existing = profile;
-        } else {
-            config.profiles.push(profile);
-        }
-
-        Ok(())
+    pub fn save_profile(&self, profile: WslProfile) -> TamerResult<()> {
+        self.update(|config| {
+            if let Some(existing) = config.profiles.iter_mut().find(|p| p.id == profile.id) {
+                *existing = profile;
+            } else {
+                config.profiles.push(profile);
+            }
+
+            Ok(())
+        })
     }
 
     /// Delete a profile
-    pub fn delete_profile(&self, id: &str) -> Result<(), String> {
-        let mut config = self.config.write().map_err(|_| LOCK_ERROR)?;
-        
-        // Don't delete if it's the last profile
-        if config.profiles.len() <= 1 {
-            return Err("Cannot delete the last profile".to_string());
-        }
-
-        config.profiles.retain(|p| p.id != id);
-
-        // Update default if needed
-        if config.default_profile_id.as_ref() == Some(&id.to_string()) {
-            config.default_profile_id = config.profiles.first().map(|p| p.id.clone());
-        }
-
-        Ok(())
+    pub fn delete_profile(&self, id: &str) -> TamerResult<()> {
+        self.update(|config| {
+            // Don't delete if it's the last profile
+            if config.profiles.len() <= 1 {
+                return Err(TamerError::InvalidOperation(
+                    "Cannot delete the last profile".to_string(),
+                ));
+            }
+
+            config.profiles.retain(|p| p.id != id);
+
+            // Update default if needed
+            if config.default_profile_id.as_ref() == Some(&id.to_string()) {
+                config.default_profile_id = config.profiles.first().map(|p| p.id.clone());
+            }
+
+            Ok(())
+        })
     }
 
     /// Set the default profile
-    pub fn set_default_profile(&self, id: &str) -> Result<(), String> {
-        let mut config = self.config.write().map_err(|_| LOCK_ERROR)?;
-        
-        if !config.profiles.iter().any(|p| p.id == id) {
-            return Err("Profile not found".to_string());
-        }
-
-        config.default_profile_id = Some(id.to_string());
-        Ok(())
+    pub fn set_default_profile(&self, id: &str) -> TamerResult<()> {
+        self.update(|config| {
+            if !config.profiles.iter().any(|p| p.id == id) {
+                return Err(TamerError::NotFound {
+                    what: format!("Profile '{}'", id),
+                });
+            }
+
+            config.default_profile_id = Some(id.to_string());
+            Ok(())
+        })
     }
 
     /// Set the current (active) profile
-    pub fn set_current_profile(&self, id: &str) -> Result<(), String> {
-        let mut config = self.config.write().map_err(|_| LOCK_ERROR)?;
-        config.current_profile_id = Some(id.to_string());
-        Ok(())
+    pub fn set_current_profile(&self, id: &str) -> TamerResult<()> {
+        self.update(|config| {
+            config.current_profile_id = Some(id.to_string());
+            Ok(())
+        })
     }
 
     /// Get the current profile
@@ -176,35 +313,88 @@ impl ProfileManager {
     }
 
     /// Save an automation rule
-    pub fn save_rule(&self, rule: AutomationRule) -> Result<(), String> {
-        let mut config = self.config.write().map_err(|_| LOCK_ERROR)?;
-        
-        if let Some(existing) = config.rules.iter_mut().find(|r| r.id == rule.id) {
-            *existing = rule;
-        } else {
-            config.rules.push(rule);
-        }
-
-        Ok(())
+    pub fn save_rule(&self, rule: AutomationRule) -> TamerResult<()> {
+        self.update(|config| {
+            if let Some(existing) = config.rules.iter_mut().find(|r| r.id == rule.id) {
+                *existing = rule;
+            } else {
+                config.rules.push(rule);
+            }
+
+            Ok(())
+        })
     }
 
     /// Delete an automation rule
-    pub fn delete_rule(&self, id: &str) -> Result<(), String> {
-        let mut config = self.config.write().map_err(|_| LOCK_ERROR)?;
-        config.rules.retain(|r| r.id != id);
-        Ok(())
+    pub fn delete_rule(&self, id: &str) -> TamerResult<()> {
+        self.update(|config| {
+            config.rules.retain(|r| r.id != id);
+            Ok(())
+        })
     }
 
     /// Toggle rule enabled state
-    pub fn toggle_rule(&self, id: &str) -> Result<bool, String> {
-        let mut config = self.config.write().map_err(|_| LOCK_ERROR)?;
-        
-        if let Some(rule) = config.rules.iter_mut().find(|r| r.id == id) {
+    pub fn toggle_rule(&self, id: &str) -> TamerResult<bool> {
+        self.update(|config| {
+            let Some(rule) = config.rules.iter_mut().find(|r| r.id == id) else {
+                return Err(TamerError::NotFound {
+                    what: format!("Rule '{}'", id),
+                });
+            };
             rule.is_enabled = !rule.is_enabled;
-            Ok(rule.is_enabled)
-        } else {
-            Err("Rule not found".to_string())
-        }
+            let enabled = rule.is_enabled;
+            Ok(enabled)
+        })
+    }
+
+    // === Snapshot Policies ===
+
+    /// Get all snapshot policies
+    pub fn get_snapshot_policies(&self) -> Vec<SnapshotPolicy> {
+        self.config.read()
+            .map(|c| c.snapshot_policies.clone())
+            .unwrap_or_default()
+    }
+
+    /// Add or replace the policy of a distribution
+    ///
+    /// The scheduler's `last_run` is kept when an existing policy is edited.
+    pub fn save_snapshot_policy(&self, mut policy: SnapshotPolicy) -> TamerResult<()> {
+        self.update(|config| {
+            if let Some(existing) = config.snapshot_policies.iter_mut()
+                .find(|p| p.distro.eq_ignore_ascii_case(&policy.distro))
+            {
+                policy.last_run = policy.last_run.or(existing.last_run);
+                *existing = policy;
+            } else {
+                config.snapshot_policies.push(policy);
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Delete the policy of a distribution
+    pub fn delete_snapshot_policy(&self, distro: &str) -> TamerResult<()> {
+        self.update(|config| {
+            config.snapshot_policies.retain(|p| !p.distro.eq_ignore_ascii_case(distro));
+            Ok(())
+        })
+    }
+
+    /// Record when the scheduler last snapshotted a distribution
+    pub fn record_snapshot_run(&self, distro: &str, timestamp: u64) -> TamerResult<()> {
+        self.update(|config| {
+            let Some(policy) = config.snapshot_policies.iter_mut()
+                .find(|p| p.distro.eq_ignore_ascii_case(distro))
+            else {
+                return Err(TamerError::NotFound {
+                    what: format!("Snapshot policy for '{}'", distro),
+                });
+            };
+            policy.last_run = Some(timestamp);
+            Ok(())
+        })
     }
 }
 
@@ -231,4 +421,67 @@ mod tests {
         let eco = manager.get_profile("eco").unwrap();
         assert_eq!(eco.memory, "3GB");
     }
+
+    #[test]
+    fn test_snapshot_policies_survive_restart() {
+        let path = std::env::temp_dir()
+            .join(format!("wsl-tamer-config-{}", uuid::Uuid::new_v4()))
+            .join("config.json");
+        let policy = SnapshotPolicy {
+            distro: "Ubuntu".to_string(),
+            enabled: true,
+            schedule: "0 3 * * *".to_string(),
+            format: Default::default(),
+            retention: Default::default(),
+            last_run: None,
+        };
+
+        let manager = ProfileManager::with_config_file(path.clone());
+        manager.save_snapshot_policy(policy.clone()).unwrap();
+        manager.record_snapshot_run("ubuntu", 1_700_000_000).unwrap();
+        // Editing the policy keeps the scheduler's last run
+        manager.save_snapshot_policy(SnapshotPolicy { schedule: "@weekly".to_string(), ..policy }).unwrap();
+
+        let reloaded = ProfileManager::with_config_file(path.clone());
+        let policies = reloaded.get_snapshot_policies();
+        assert_eq!(policies.len(), 1);
+        assert_eq!(policies[0].schedule, "@weekly");
+        assert_eq!(policies[0].last_run, Some(1_700_000_000));
+
+        reloaded.delete_snapshot_policy("Ubuntu").unwrap();
+        assert!(ProfileManager::with_config_file(path.clone()).get_snapshot_policies().is_empty());
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_corrupt_config_is_moved_aside() {
+        let dir = std::env::temp_dir().join(format!("wsl-tamer-config-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        fs::write(&path, "{ \"profiles\": [ truncated").unwrap();
+
+        let manager = ProfileManager::with_config_file(path.clone());
+        let backup = manager.corrupt_backup().unwrap().to_path_buf();
+        assert!(backup.file_name().unwrap().to_string_lossy().starts_with("config.json.corrupt-"));
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "{ \"profiles\": [ truncated");
+        assert!(!path.exists());
+
+        // Saving no longer touches the user's old file
+        manager.set_current_profile("eco").unwrap();
+        assert!(path.exists());
+        assert!(backup.exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_failed_save_keeps_previous_config() {
+        let dir = std::env::temp_dir().join(format!("wsl-tamer-config-{}", uuid::Uuid::new_v4()));
+        let manager = ProfileManager::with_config_file(dir.join("config.json"));
+        // The config directory can't be created where a file is in the way
+        fs::write(&dir, "").unwrap();
+
+        assert!(manager.set_current_profile("eco").is_err());
+        assert_eq!(manager.get_config().current_profile_id, None);
+        let _ = fs::remove_file(dir);
+    }
 }
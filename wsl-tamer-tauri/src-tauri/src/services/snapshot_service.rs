@@ -0,0 +1,723 @@
+//! Snapshot Service - Named point-in-time copies of distributions
+//!
+//! Snapshots live in a managed library, one folder per snapshot holding the
+//! exported data file and a `manifest.json` describing it:
+//!
+//! ```text
+//! <library>/<id>/manifest.json
+//! <library>/<id>/rootfs.tar | ext4.vhdx
+//! ```
+
+use crate::error::{TamerError, TamerResult};
+use crate::models::{
+    ExportOptions, JobPhase, RetentionPolicy, Snapshot, SnapshotFormat, SnapshotPolicy,
+};
+use crate::services::{JobHandle, WslService};
+use crate::utils::{app_data_dir, sha256_file, unix_timestamp, verify_export};
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Optional settings for a new snapshot
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotOptions {
+    pub format: SnapshotFormat,
+    pub notes: Option<String>,
+    /// Taken by the scheduler (subject to retention)
+    pub automatic: bool,
+}
+
+pub struct SnapshotService {
+    library: PathBuf,
+}
+
+impl Default for SnapshotService {
+    fn default() -> Self {
+        Self::new(app_data_dir().join("snapshots"))
+    }
+}
+
+impl SnapshotService {
+    pub fn new(library: PathBuf) -> Self {
+        Self { library }
+    }
+
+    /// Folder holding all snapshots
+    pub fn library(&self) -> &Path {
+        &self.library
+    }
+
+    /// Export `distro` into a new snapshot and record its manifest
+    ///
+    /// A partially written snapshot is removed if any step fails.
+    pub fn create(
+        &self,
+        wsl: &WslService,
+        distro: &str,
+        name: &str,
+        options: SnapshotOptions,
+        job: &JobHandle,
+    ) -> TamerResult<Snapshot> {
+        let SnapshotOptions {
+            format,
+            notes,
+            automatic,
+        } = options;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(TamerError::InvalidInput(
+                "Snapshot name cannot be empty".to_string(),
+            ));
+        }
+
+        let wsl_version = wsl
+            .get_distributions()?
+            .into_iter()
+            .find(|d| d.name.eq_ignore_ascii_case(distro))
+            .map(|d| d.version)
+            .ok_or_else(|| TamerError::DistroNotFound {
+                name: distro.to_string(),
+            })?;
+        if format == SnapshotFormat::Vhd && wsl_version != "2" {
+            return Err(TamerError::InvalidInput(
+                "VHD snapshots require a WSL 2 distribution".to_string(),
+            ));
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let dir = self.dir(&id);
+        fs::create_dir_all(&dir)
+            .map_err(|e| TamerError::io("Failed to create snapshot folder", e))?;
+
+        let data = dir.join(format.data_file_name());
+        let result = self
+            .write_snapshot(wsl, distro, format, &data, job)
+            .and_then(|(size_bytes, sha256)| {
+                let snapshot = Snapshot {
+                    id,
+                    name: name.to_string(),
+                    distro: distro.to_string(),
+                    created_at: unix_timestamp(),
+                    format,
+                    file_name: format.data_file_name().to_string(),
+                    size_bytes,
+                    sha256,
+                    wsl_version,
+                    notes: notes.filter(|n| !n.trim().is_empty()),
+                    automatic,
+                };
+                self.save_manifest(&snapshot)?;
+                Ok(snapshot)
+            });
+
+        if result.is_err() {
+            let _ = fs::remove_dir_all(&dir);
+        }
+        result
+    }
+
+    /// Export into `data` and return its size and checksum
+    fn write_snapshot(
+        &self,
+        wsl: &WslService,
+        distro: &str,
+        format: SnapshotFormat,
+        data: &Path,
+        job: &JobHandle,
+    ) -> TamerResult<(u64, String)> {
+        let data_path = data.to_string_lossy();
+        if format == SnapshotFormat::Tar {
+            // Tar exports are hashed while they are written
+            let manifest =
+                wsl.export_distribution(distro, &data_path, &ExportOptions::default(), job)?;
+            return Ok((manifest.size_bytes, manifest.sha256));
+        }
+        wsl.export_distribution_vhd(distro, &data_path, job)?;
+
+        job.set_phase(JobPhase::Checksumming);
+        let size = fs::metadata(data)
+            .map_err(|e| TamerError::io("Snapshot export is missing", e))?
+            .len();
+        if size == 0 {
+            return Err(TamerError::InvalidOperation(
+                "Snapshot export is empty".to_string(),
+            ));
+        }
+        let sha256 = sha256_file(data, job.cancel_token())?;
+        Ok((size, sha256))
+    }
+
+    /// All snapshots, newest first, optionally limited to one distribution
+    ///
+    /// Folders without a readable manifest are skipped.
+    pub fn list(&self, distro: Option<&str>) -> Vec<Snapshot> {
+        let Ok(entries) = fs::read_dir(&self.library) else {
+            return Vec::new();
+        };
+
+        let mut snapshots: Vec<Snapshot> = entries
+            .flatten()
+            .map(|entry| entry.path().join(MANIFEST_FILE))
+            .filter(|manifest| manifest.is_file())
+            .filter_map(|manifest| match read_manifest(&manifest) {
+                Ok(snapshot) => Some(snapshot),
+                Err(e) => {
+                    log::warn!("Skipping unreadable snapshot {:?}: {}", manifest, e);
+                    None
+                }
+            })
+            .filter(|s| distro.is_none_or(|d| s.distro.eq_ignore_ascii_case(d)))
+            .collect();
+        snapshots.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+        snapshots
+    }
+
+    pub fn get(&self, id: &str) -> TamerResult<Snapshot> {
+        // Ids come from the frontend and name folders in the library
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(TamerError::InvalidInput(format!(
+                "Invalid snapshot id '{}'",
+                id
+            )));
+        }
+        let manifest = self.dir(id).join(MANIFEST_FILE);
+        if !manifest.is_file() {
+            return Err(TamerError::NotFound {
+                what: format!("Snapshot '{}'", id),
+            });
+        }
+        read_manifest(&manifest)
+    }
+
+    /// Delete a snapshot and its data
+    pub fn delete(&self, id: &str) -> TamerResult<()> {
+        self.get(id)?;
+        fs::remove_dir_all(self.dir(id)).map_err(|e| TamerError::io("Failed to delete snapshot", e))
+    }
+
+    /// Recompute the data file checksum and compare it with the manifest
+    pub fn verify(&self, id: &str, job: &JobHandle) -> TamerResult<Snapshot> {
+        let snapshot = self.get(id)?;
+        job.set_phase(JobPhase::Checksumming);
+        let data = self.data_path(&snapshot);
+        let actual = match snapshot.format {
+            // Also checks the tar is a well-formed root filesystem
+            SnapshotFormat::Tar => verify_export(&data, job.cancel_token())?.sha256,
+            SnapshotFormat::Vhd => sha256_file(&data, job.cancel_token())?,
+        };
+        if !actual.eq_ignore_ascii_case(&snapshot.sha256) {
+            return Err(TamerError::InvalidOperation(format!(
+                "Snapshot '{}' is corrupt: checksum mismatch",
+                snapshot.name
+            )));
+        }
+        Ok(snapshot)
+    }
+
+    /// Replace the source distribution with the snapshot, at its current location
+    ///
+    /// The snapshot is verified before anything is touched. Once the
+    /// distribution has been unregistered the import can no longer be cancelled.
+    pub fn restore_in_place(&self, wsl: &WslService, id: &str, job: &JobHandle) -> TamerResult<()> {
+        let snapshot = self.verify(id, job)?;
+        let location = wsl.distro_location(&snapshot.distro)?.ok_or_else(|| {
+            TamerError::InvalidOperation(format!(
+                "Distribution '{}' is not registered; restore the snapshot as a new distribution instead",
+                snapshot.distro
+            ))
+        })?;
+
+        if job.is_cancelled() {
+            return Err(TamerError::Cancelled);
+        }
+        job.set_phase(JobPhase::Unregistering);
+        wsl.unregister_distribution(&snapshot.distro)?;
+
+        let job = job.uncancellable();
+        self.import(wsl, &snapshot, &snapshot.distro, &location, &job)
+            .and_then(|_| wsl.verify_registered(&snapshot.distro, &job))
+            .map_err(|e| {
+                e.context(format!(
+                    "Restore failed after '{}' was unregistered; snapshot '{}' is intact and can be restored again",
+                    snapshot.distro, snapshot.name
+                ))
+            })
+    }
+
+    /// Register the snapshot as a new distribution
+    pub fn restore_as_new(
+        &self,
+        wsl: &WslService,
+        id: &str,
+        new_name: &str,
+        location: &str,
+        job: &JobHandle,
+    ) -> TamerResult<()> {
+        if wsl.is_registered(new_name)? {
+            return Err(TamerError::InvalidInput(format!(
+                "A distribution named '{}' already exists",
+                new_name
+            )));
+        }
+        let snapshot = self.verify(id, job)?;
+        self.import(wsl, &snapshot, new_name, location, job)?;
+        wsl.verify_registered(new_name, job)
+    }
+
+    /// Take a scheduled snapshot for `policy`, then prune by its retention
+    ///
+    /// A failed prune is logged rather than failing the snapshot.
+    pub fn run_policy(
+        &self,
+        wsl: &WslService,
+        policy: &SnapshotPolicy,
+        job: &JobHandle,
+    ) -> TamerResult<Snapshot> {
+        let name = format!("Scheduled {}", Local::now().format("%Y-%m-%d %H:%M"));
+        let options = SnapshotOptions {
+            format: policy.format,
+            notes: None,
+            automatic: true,
+        };
+        let snapshot = self.create(wsl, &policy.distro, &name, options, job)?;
+
+        job.set_phase(JobPhase::CleaningUp);
+        if let Err(e) = self.apply_retention(&policy.distro, &policy.retention, &Local::now()) {
+            log::warn!("Failed to prune snapshots of '{}': {}", policy.distro, e);
+        }
+        Ok(snapshot)
+    }
+
+    /// Delete the automatic snapshots of `distro` that `retention` no longer keeps
+    ///
+    /// Returns the deleted snapshots. Manual snapshots are never pruned.
+    pub fn apply_retention<Tz: TimeZone>(
+        &self,
+        distro: &str,
+        retention: &RetentionPolicy,
+        now: &DateTime<Tz>,
+    ) -> TamerResult<Vec<Snapshot>> {
+        let automatic: Vec<Snapshot> = self
+            .list(Some(distro))
+            .into_iter()
+            .filter(|s| s.automatic)
+            .collect();
+
+        let expired = expired_snapshots(&automatic, retention, now);
+        let mut removed = Vec::new();
+        for snapshot in automatic.into_iter().filter(|s| expired.contains(&s.id)) {
+            self.delete(&snapshot.id)?;
+            removed.push(snapshot);
+        }
+        Ok(removed)
+    }
+
+    fn import(
+        &self,
+        wsl: &WslService,
+        snapshot: &Snapshot,
+        name: &str,
+        location: &str,
+        job: &JobHandle,
+    ) -> TamerResult<()> {
+        let data = self.data_path(snapshot);
+        let data = data.to_string_lossy();
+        match snapshot.format {
+            // Already checked by `verify`
+            SnapshotFormat::Tar => wsl.import_distribution_unchecked(name, location, &data, job),
+            SnapshotFormat::Vhd => wsl.import_distribution_vhd(name, location, &data, job),
+        }
+    }
+
+    fn save_manifest(&self, snapshot: &Snapshot) -> TamerResult<()> {
+        let json = serde_json::to_string_pretty(snapshot).map_err(|e| {
+            TamerError::Internal(format!("Failed to serialize snapshot manifest: {}", e))
+        })?;
+        fs::write(self.dir(&snapshot.id).join(MANIFEST_FILE), json)
+            .map_err(|e| TamerError::io("Failed to write snapshot manifest", e))
+    }
+
+    fn dir(&self, id: &str) -> PathBuf {
+        self.library.join(id)
+    }
+
+    fn data_path(&self, snapshot: &Snapshot) -> PathBuf {
+        self.dir(&snapshot.id).join(&snapshot.file_name)
+    }
+}
+
+/// IDs of the snapshots `retention` does not keep
+pub fn expired_snapshots<Tz: TimeZone>(
+    snapshots: &[Snapshot],
+    retention: &RetentionPolicy,
+    now: &DateTime<Tz>,
+) -> Vec<String> {
+    let mut sorted: Vec<&Snapshot> = snapshots.iter().collect();
+    sorted.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+
+    let tz = now.timezone();
+    let today = now.date_naive();
+    let day_of = |s: &Snapshot| {
+        tz.timestamp_opt(s.created_at as i64, 0)
+            .single()
+            .map(|t| t.date_naive())
+            .unwrap_or(today)
+    };
+    let week_start =
+        |d: NaiveDate| d - chrono::Days::new(d.weekday().num_days_from_monday() as u64);
+
+    let has_count_rule = retention.keep_last.is_some()
+        || retention.keep_daily.is_some()
+        || retention.keep_weekly.is_some();
+    let mut keep = vec![!has_count_rule; sorted.len()];
+
+    if let Some(n) = retention.keep_last {
+        keep.iter_mut().take(n as usize).for_each(|k| *k = true);
+    }
+    if let Some(days) = retention.keep_daily {
+        let mut seen = HashSet::new();
+        for (i, snapshot) in sorted.iter().enumerate() {
+            let day = day_of(snapshot);
+            if (today - day).num_days() < days as i64 && seen.insert(day) {
+                keep[i] = true;
+            }
+        }
+    }
+    if let Some(weeks) = retention.keep_weekly {
+        let this_week = week_start(today);
+        let mut seen = HashSet::new();
+        for (i, snapshot) in sorted.iter().enumerate() {
+            let week = week_start(day_of(snapshot));
+            if (this_week - week).num_days() / 7 < weeks as i64 && seen.insert(week) {
+                keep[i] = true;
+            }
+        }
+    }
+    if let Some(max_bytes) = retention.max_total_bytes {
+        let mut total = 0u64;
+        let mut newest = true;
+        for (i, snapshot) in sorted.iter().enumerate() {
+            if !keep[i] {
+                continue;
+            }
+            total = total.saturating_add(snapshot.size_bytes);
+            if total > max_bytes && !newest {
+                keep[i] = false;
+            }
+            newest = false;
+        }
+    }
+
+    sorted
+        .iter()
+        .zip(keep)
+        .filter(|(_, keep)| !keep)
+        .map(|(s, _)| s.id.clone())
+        .collect()
+}
+
+fn read_manifest(path: &Path) -> TamerResult<Snapshot> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| TamerError::io("Failed to read snapshot manifest", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| TamerError::Parse(format!("Corrupt snapshot manifest {:?}: {}", path, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{sample_rootfs_tar, CancellationToken, CommandOutput, ScriptedRunner};
+    use std::io::Write;
+    use std::sync::Arc;
+
+    fn temp_service() -> SnapshotService {
+        SnapshotService::new(
+            std::env::temp_dir().join(format!("wsl-tamer-snapshots-{}", uuid::Uuid::new_v4())),
+        )
+    }
+
+    /// Write a snapshot straight into the library, as `create` would
+    fn fake_snapshot(
+        service: &SnapshotService,
+        distro: &str,
+        format: SnapshotFormat,
+        created_at: u64,
+    ) -> Snapshot {
+        let id = uuid::Uuid::new_v4().to_string();
+        fs::create_dir_all(service.dir(&id)).unwrap();
+        let data = service.dir(&id).join(format.data_file_name());
+        let content = match format {
+            SnapshotFormat::Tar => sample_rootfs_tar(),
+            SnapshotFormat::Vhd => b"vhdx".to_vec(),
+        };
+        fs::write(&data, &content).unwrap();
+        let snapshot = Snapshot {
+            id,
+            name: format!("{} @ {}", distro, created_at),
+            distro: distro.to_string(),
+            created_at,
+            format,
+            file_name: format.data_file_name().to_string(),
+            size_bytes: content.len() as u64,
+            sha256: sha256_file(&data, &CancellationToken::new()).unwrap(),
+            wsl_version: "2".to_string(),
+            notes: None,
+            automatic: true,
+        };
+        service.save_manifest(&snapshot).unwrap();
+        snapshot
+    }
+
+    #[test]
+    fn test_list_filters_and_sorts_newest_first() {
+        let service = temp_service();
+        let old = fake_snapshot(&service, "Ubuntu", SnapshotFormat::Tar, 100);
+        let new = fake_snapshot(&service, "Ubuntu", SnapshotFormat::Tar, 200);
+        fake_snapshot(&service, "Debian", SnapshotFormat::Tar, 300);
+
+        let ids: Vec<String> = service
+            .list(Some("ubuntu"))
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(ids, vec![new.id.clone(), old.id.clone()]);
+        assert_eq!(service.list(None).len(), 3);
+
+        service.delete(&old.id).unwrap();
+        assert_eq!(service.get(&old.id).unwrap_err().code(), "NOT_FOUND");
+        assert_eq!(service.list(Some("Ubuntu")).len(), 1);
+        let _ = fs::remove_dir_all(service.library());
+    }
+
+    #[test]
+    fn test_ids_outside_the_library_are_rejected() {
+        let service = temp_service();
+        let snapshot = fake_snapshot(&service, "Ubuntu", SnapshotFormat::Tar, 100);
+
+        for id in ["..", "../x", &format!("{}/..", snapshot.id), ""] {
+            assert_eq!(service.get(id).unwrap_err().code(), "INVALID_INPUT");
+            assert_eq!(service.delete(id).unwrap_err().code(), "INVALID_INPUT");
+        }
+        assert!(service.library().is_dir());
+        assert_eq!(service.list(None).len(), 1);
+        let _ = fs::remove_dir_all(service.library());
+    }
+
+    #[test]
+    fn test_cancelled_snapshot_leaves_nothing_behind() {
+        let service = temp_service();
+        let runner = Arc::new(ScriptedRunner::new().on(
+            "wsl",
+            &["--list", "--verbose"],
+            CommandOutput::ok_utf16(
+                "  NAME      STATE           VERSION\r\n* Ubuntu    Stopped         2\r\n",
+            ),
+        ));
+        let wsl = WslService::new(runner);
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let err = service
+            .create(
+                &wsl,
+                "Ubuntu",
+                "before upgrade",
+                SnapshotOptions::default(),
+                &JobHandle::detached(cancel),
+            )
+            .unwrap_err();
+        assert_eq!(err.code(), "CANCELLED");
+        assert!(service.list(None).is_empty());
+        assert_eq!(fs::read_dir(service.library()).unwrap().count(), 0);
+        let _ = fs::remove_dir_all(service.library());
+    }
+
+    #[test]
+    fn test_restore_as_new_imports_vhd() {
+        let service = temp_service();
+        let snapshot = fake_snapshot(&service, "Ubuntu", SnapshotFormat::Vhd, 100);
+        let data = service.data_path(&snapshot).to_string_lossy().to_string();
+        let import = [
+            "--import",
+            "Ubuntu-restored",
+            "D:\\WSL\\Restored",
+            &data,
+            "--vhd",
+        ];
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on(
+                    "wsl",
+                    &["--list", "--quiet"],
+                    CommandOutput::ok_utf16("Ubuntu\r\n"),
+                )
+                .on(
+                    "wsl",
+                    &["--list", "--quiet"],
+                    CommandOutput::ok_utf16("Ubuntu\r\nUbuntu-restored\r\n"),
+                )
+                .on("wsl", &import, CommandOutput::ok("")),
+        );
+        let wsl = WslService::new(runner.clone());
+
+        service
+            .restore_as_new(
+                &wsl,
+                &snapshot.id,
+                "Ubuntu-restored",
+                "D:\\WSL\\Restored",
+                &JobHandle::default(),
+            )
+            .unwrap();
+        assert!(runner.was_called("wsl", &import));
+        let _ = fs::remove_dir_all(service.library());
+    }
+
+    #[test]
+    fn test_corrupt_snapshot_is_not_restored() {
+        let service = temp_service();
+        let snapshot = fake_snapshot(&service, "Ubuntu", SnapshotFormat::Tar, 100);
+        // Still a valid tar, but no longer the one that was hashed
+        fs::OpenOptions::new()
+            .append(true)
+            .open(service.data_path(&snapshot))
+            .unwrap()
+            .write_all(&[0u8; 512])
+            .unwrap();
+        let runner = Arc::new(ScriptedRunner::new());
+        let wsl = WslService::new(runner.clone());
+
+        let err = service
+            .restore_in_place(&wsl, &snapshot.id, &JobHandle::default())
+            .unwrap_err();
+        assert_eq!(err.code(), "INVALID_OPERATION");
+        assert!(runner.calls().is_empty());
+        let _ = fs::remove_dir_all(service.library());
+    }
+
+    /// Automatic snapshot `days` days and `hours` hours before `now`
+    fn aged(now: &DateTime<chrono::Utc>, id: &str, days: i64, hours: i64, size: u64) -> Snapshot {
+        let created = *now - chrono::TimeDelta::days(days) - chrono::TimeDelta::hours(hours);
+        Snapshot {
+            id: id.to_string(),
+            name: id.to_string(),
+            distro: "Ubuntu".to_string(),
+            created_at: created.timestamp() as u64,
+            format: SnapshotFormat::Tar,
+            file_name: "rootfs.tar".to_string(),
+            size_bytes: size,
+            sha256: String::new(),
+            wsl_version: "2".to_string(),
+            notes: None,
+            automatic: true,
+        }
+    }
+
+    #[test]
+    fn test_retention_keep_last_daily_and_weekly() {
+        // Saturday noon
+        let now = DateTime::parse_from_rfc3339("2026-10-17T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let snapshots = vec![
+            aged(&now, "today-late", 0, 1, 1),
+            aged(&now, "today-early", 0, 6, 1),
+            aged(&now, "yesterday", 1, 0, 1),
+            aged(&now, "three-days", 3, 0, 1),
+            aged(&now, "last-week", 7, 0, 1),
+            aged(&now, "two-weeks", 14, 0, 1),
+            aged(&now, "two-months", 60, 0, 1),
+        ];
+
+        let keep_last = RetentionPolicy {
+            keep_last: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            expired_snapshots(&snapshots, &keep_last, &now),
+            vec![
+                "yesterday",
+                "three-days",
+                "last-week",
+                "two-weeks",
+                "two-months"
+            ]
+        );
+
+        // Newest per day for 2 days, newest per week for 3 weeks
+        let calendar = RetentionPolicy {
+            keep_daily: Some(2),
+            keep_weekly: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            expired_snapshots(&snapshots, &calendar, &now),
+            vec!["today-early", "three-days", "two-months"]
+        );
+
+        // No rules at all keeps everything
+        assert!(expired_snapshots(&snapshots, &RetentionPolicy::default(), &now).is_empty());
+    }
+
+    #[test]
+    fn test_retention_size_cap_drops_oldest_but_keeps_newest() {
+        let now = DateTime::parse_from_rfc3339("2026-10-17T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let snapshots = vec![
+            aged(&now, "new", 0, 1, 40),
+            aged(&now, "mid", 1, 0, 40),
+            aged(&now, "old", 2, 0, 40),
+        ];
+
+        let cap = RetentionPolicy {
+            max_total_bytes: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(expired_snapshots(&snapshots, &cap, &now), vec!["old"]);
+
+        let tiny = RetentionPolicy {
+            max_total_bytes: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(
+            expired_snapshots(&snapshots, &tiny, &now),
+            vec!["mid", "old"]
+        );
+    }
+
+    #[test]
+    fn test_apply_retention_spares_manual_snapshots() {
+        let service = temp_service();
+        let now = chrono::Utc::now();
+        let newest = fake_snapshot(
+            &service,
+            "Ubuntu",
+            SnapshotFormat::Tar,
+            now.timestamp() as u64,
+        );
+        let older = fake_snapshot(&service, "Ubuntu", SnapshotFormat::Tar, 100);
+        let mut manual = fake_snapshot(&service, "Ubuntu", SnapshotFormat::Tar, 50);
+        manual.automatic = false;
+        service.save_manifest(&manual).unwrap();
+
+        let retention = RetentionPolicy {
+            keep_last: Some(1),
+            ..Default::default()
+        };
+        let removed = service.apply_retention("Ubuntu", &retention, &now).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].id, older.id);
+
+        let left: Vec<String> = service
+            .list(Some("Ubuntu"))
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(left, vec![newest.id, manual.id]);
+        let _ = fs::remove_dir_all(service.library());
+    }
+}
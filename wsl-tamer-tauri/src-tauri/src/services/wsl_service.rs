@@ -1,32 +1,129 @@
 //! WSL Service - Core WSL operations
 
+use crate::error::{TamerError, TamerResult};
 use crate::models::{
-    DistributionState, OnlineDistribution, WslDistribution, WslProfile, WslStatus,
+    Compression, CompressionSettings, DistributionState, DistroRegistration, ExportManifest,
+    ExportOptions, ExportVerification, JobPhase, MoveJournal, MoveStep, OnlineDistribution,
+    OperationTimeouts, WslDistribution, WslProfile, WslStatus,
 };
+use crate::models::{ConfigOrigin, ConfigTarget, ConfigVersion, DiffLine};
+use crate::services::{ConfigHistory, DistroRegistry, JobHandle, MoveJournalStore};
 use crate::utils::{
-    clean_distro_name, is_process_running, run_powershell_command, run_wsl_command,
+    clean_distro_name, decode_utf16le, diff_lines, export_compression, format_command,
+    is_process_running, remove_export, run_powershell_command, run_wsl_command,
+    run_wsl_command_controlled, run_wsl_command_from_reader, run_wsl_command_to_writer,
+    system_runner, unix_timestamp, verify_export, write_export_manifest, CancellationToken,
+    CommandRunner, Compressor, Decompressor, HashingWriter, IniDocument, RunControl,
 };
-use std::path::PathBuf;
-use std::sync::{LazyLock, Mutex};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, Instant};
 
 /// Cache TTL for distribution list (2 seconds)
 const DISTRO_CACHE_TTL_MS: u64 = 2000;
 
-/// Cached distribution list for performance
-static DISTRO_CACHE: LazyLock<Mutex<Option<(Instant, Vec<WslDistribution>)>>> =
-    LazyLock::new(|| Mutex::new(None));
-
-/// Guard to prevent concurrent cache refreshes (thundering herd)
-static CACHE_REFRESHING: LazyLock<Mutex<bool>> = LazyLock::new(|| Mutex::new(false));
+/// Prints a marker line and wsl.conf if there is one, nothing otherwise
+const READ_WSL_CONF: &str = "if [ -e /etc/wsl.conf ]; then echo exists; cat /etc/wsl.conf; fi";
+
+pub struct WslService {
+    runner: Arc<dyn CommandRunner>,
+    /// Cached distribution list for performance
+    distro_cache: Mutex<Option<(Instant, Vec<WslDistribution>)>>,
+    /// Guard to prevent concurrent cache refreshes (thundering herd)
+    cache_refreshing: Mutex<bool>,
+    /// Time limits for export/import/install
+    timeouts: RwLock<OperationTimeouts>,
+    /// Default levels for compressed exports
+    compression: RwLock<CompressionSettings>,
+    /// Write-ahead journal for moves
+    move_journal: MoveJournalStore,
+    /// Ids of journaled moves running in this process
+    active_moves: Mutex<HashSet<String>>,
+    /// Distribution metadata from the Lxss registry key
+    registry: DistroRegistry,
+    /// Earlier versions of .wslconfig and wsl.conf files
+    config_history: ConfigHistory,
+    wslconfig_path: PathBuf,
+}
 
-pub struct WslService;
+impl Default for WslService {
+    fn default() -> Self {
+        Self::new(system_runner())
+    }
+}
 
 impl WslService {
+    pub fn new(runner: Arc<dyn CommandRunner>) -> Self {
+        Self {
+            runner,
+            distro_cache: Mutex::new(None),
+            cache_refreshing: Mutex::new(false),
+            timeouts: RwLock::new(OperationTimeouts::default()),
+            compression: RwLock::new(CompressionSettings::default()),
+            move_journal: MoveJournalStore::default(),
+            active_moves: Mutex::new(HashSet::new()),
+            registry: DistroRegistry::default(),
+            config_history: ConfigHistory::default(),
+            wslconfig_path: Self::get_wslconfig_path(),
+        }
+    }
+
+    /// Use a different move journal location
+    pub fn with_move_journal(mut self, store: MoveJournalStore) -> Self {
+        self.move_journal = store;
+        self
+    }
+
+    /// Keep configuration history in a different location
+    pub fn with_config_history(mut self, history: ConfigHistory) -> Self {
+        self.config_history = history;
+        self
+    }
+
+    /// Manage a different .wslconfig file
+    pub fn with_wslconfig_path(mut self, path: PathBuf) -> Self {
+        self.wslconfig_path = path;
+        self
+    }
+
+    /// Read distribution metadata from a different registry
+    pub fn with_distro_registry(mut self, registry: DistroRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Replace the time limits used for long-running operations
+    pub fn set_operation_timeouts(&self, timeouts: OperationTimeouts) {
+        if let Ok(mut current) = self.timeouts.write() {
+            *current = timeouts;
+        }
+    }
+
+    fn operation_timeouts(&self) -> OperationTimeouts {
+        self.timeouts.read().map(|t| t.clone()).unwrap_or_default()
+    }
+
+    /// Replace the default compression levels used for exports
+    pub fn set_compression_settings(&self, settings: CompressionSettings) {
+        if let Ok(mut current) = self.compression.write() {
+            *current = settings;
+        }
+    }
+
+    fn compression_settings(&self) -> CompressionSettings {
+        self.compression
+            .read()
+            .map(|c| c.clone())
+            .unwrap_or_default()
+    }
+
     /// Get list of installed WSL distributions (with caching)
-    pub fn get_distributions() -> Result<Vec<WslDistribution>, String> {
+    pub fn get_distributions(&self) -> TamerResult<Vec<WslDistribution>> {
         // Check cache first
-        if let Ok(cache) = DISTRO_CACHE.lock() {
+        if let Ok(cache) = self.distro_cache.lock() {
             if let Some((cached_at, ref distros)) = *cache {
                 if cached_at.elapsed() < Duration::from_millis(DISTRO_CACHE_TTL_MS) {
                     return Ok(distros.clone());
@@ -36,33 +133,39 @@ impl WslService {
 
         // Prevent thundering herd: if another thread is already refreshing, return stale cache
         {
-            let mut refreshing = CACHE_REFRESHING.lock().map_err(|e| e.to_string())?;
+            let mut refreshing = self
+                .cache_refreshing
+                .lock()
+                .map_err(|e| TamerError::Internal(e.to_string()))?;
             if *refreshing {
                 // Another thread is refreshing — return stale cache if available
-                if let Ok(cache) = DISTRO_CACHE.lock() {
+                if let Ok(cache) = self.distro_cache.lock() {
                     if let Some((_, ref distros)) = *cache {
                         return Ok(distros.clone());
                     }
                 }
                 // No stale cache at all — wait and retry
-                return Err("Distribution list is being refreshed, please retry".to_string());
+                return Err(TamerError::Busy(
+                    "Distribution list is being refreshed, please retry".to_string(),
+                ));
             }
             *refreshing = true;
         }
 
         // We hold the refreshing flag — do the expensive work
-        let result = run_wsl_command(&["--list", "--verbose"]);
+        let result = run_wsl_command(self.runner.as_ref(), &["--list", "--verbose"]);
 
         // Always clear the refreshing flag, even on error
-        if let Ok(mut refreshing) = CACHE_REFRESHING.lock() {
+        if let Ok(mut refreshing) = self.cache_refreshing.lock() {
             *refreshing = false;
         }
 
         let output = result?;
-        let distros = Self::parse_distributions(&output)?;
+        let mut distros = Self::parse_distributions(&output)?;
+        self.attach_registrations(&mut distros);
 
         // Update cache
-        if let Ok(mut cache) = DISTRO_CACHE.lock() {
+        if let Ok(mut cache) = self.distro_cache.lock() {
             *cache = Some((Instant::now(), distros.clone()));
         }
 
@@ -70,12 +173,13 @@ impl WslService {
     }
 
     /// Force refresh the distribution list (bypasses cache)
-    pub fn refresh_distributions() -> Result<Vec<WslDistribution>, String> {
-        let output = run_wsl_command(&["--list", "--verbose"])?;
-        let distros = Self::parse_distributions(&output)?;
+    pub fn refresh_distributions(&self) -> TamerResult<Vec<WslDistribution>> {
+        let output = run_wsl_command(self.runner.as_ref(), &["--list", "--verbose"])?;
+        let mut distros = Self::parse_distributions(&output)?;
+        self.attach_registrations(&mut distros);
 
         // Update cache
-        if let Ok(mut cache) = DISTRO_CACHE.lock() {
+        if let Ok(mut cache) = self.distro_cache.lock() {
             *cache = Some((Instant::now(), distros.clone()));
         }
 
@@ -83,14 +187,33 @@ impl WslService {
     }
 
     /// Invalidate the distribution cache (call after modifying distros)
-    pub fn invalidate_distro_cache() {
-        if let Ok(mut cache) = DISTRO_CACHE.lock() {
+    pub fn invalidate_distro_cache(&self) {
+        if let Ok(mut cache) = self.distro_cache.lock() {
             *cache = None;
         }
     }
 
+    /// Add Lxss registry metadata to each distribution
+    ///
+    /// The list is still useful without it, so a registry failure is only logged.
+    fn attach_registrations(&self, distros: &mut [WslDistribution]) {
+        let registrations = match self.registry.list() {
+            Ok(registrations) => registrations,
+            Err(e) => {
+                log::warn!("Could not read distribution registry metadata: {}", e);
+                return;
+            }
+        };
+        for distro in distros {
+            distro.registration = registrations
+                .iter()
+                .find(|r| r.name.eq_ignore_ascii_case(&distro.name))
+                .cloned();
+        }
+    }
+
     /// Parse `wsl --list --verbose` output
-    fn parse_distributions(output: &str) -> Result<Vec<WslDistribution>, String> {
+    fn parse_distributions(output: &str) -> TamerResult<Vec<WslDistribution>> {
         let mut distributions = Vec::new();
         let lines: Vec<&str> = output.lines().collect();
 
@@ -135,6 +258,7 @@ impl WslService {
                     state,
                     version,
                     is_default,
+                    registration: None,
                 });
             }
         }
@@ -143,14 +267,14 @@ impl WslService {
     }
 
     /// Check if WSL is currently running
-    pub fn is_wsl_running() -> bool {
+    pub fn is_wsl_running(&self) -> bool {
         // Check for the WSL 2 VM process
         if is_process_running("vmmemWSL") || is_process_running("vmmem") {
             return true;
         }
 
         // Fall back to checking running distributions
-        match run_wsl_command(&["--list", "--running"]) {
+        match run_wsl_command(self.runner.as_ref(), &["--list", "--running"]) {
             Ok(output) => {
                 let lines: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
                 lines.len() > 1 // More than just header
@@ -160,193 +284,623 @@ impl WslService {
     }
 
     /// Get WSL status
-    pub fn get_status() -> WslStatus {
+    pub fn get_status(&self) -> WslStatus {
         WslStatus {
-            is_installed: Self::is_wsl_installed(),
-            is_running: Self::is_wsl_running(),
-            default_version: Self::get_default_version(),
-            kernel_version: Self::get_kernel_version(),
+            is_installed: self.is_wsl_installed(),
+            is_running: self.is_wsl_running(),
+            default_version: self.get_default_version(),
+            kernel_version: self.get_kernel_version(),
         }
     }
 
     /// Check if WSL is installed
-    fn is_wsl_installed() -> bool {
-        run_wsl_command(&["--status"]).is_ok()
+    fn is_wsl_installed(&self) -> bool {
+        run_wsl_command(self.runner.as_ref(), &["--status"]).is_ok()
     }
 
     /// Get default WSL version
-    fn get_default_version() -> Option<String> {
-        run_wsl_command(&["--status"]).ok().and_then(|output| {
-            output
-                .lines()
-                .find(|l| l.to_lowercase().contains("default version"))
-                .and_then(|l| l.split(':').last())
-                .map(|v| v.trim().to_string())
-        })
+    fn get_default_version(&self) -> Option<String> {
+        run_wsl_command(self.runner.as_ref(), &["--status"])
+            .ok()
+            .and_then(|output| {
+                output
+                    .lines()
+                    .find(|l| l.to_lowercase().contains("default version"))
+                    .and_then(|l| l.split(':').next_back())
+                    .map(|v| v.trim().to_string())
+            })
     }
 
     /// Get WSL kernel version
-    fn get_kernel_version() -> Option<String> {
-        run_wsl_command(&["--status"]).ok().and_then(|output| {
-            output
-                .lines()
-                .find(|l| l.to_lowercase().contains("kernel version"))
-                .and_then(|l| l.split(':').last())
-                .map(|v| v.trim().to_string())
-        })
+    fn get_kernel_version(&self) -> Option<String> {
+        run_wsl_command(self.runner.as_ref(), &["--status"])
+            .ok()
+            .and_then(|output| {
+                output
+                    .lines()
+                    .find(|l| l.to_lowercase().contains("kernel version"))
+                    .and_then(|l| l.split(':').next_back())
+                    .map(|v| v.trim().to_string())
+            })
     }
 
     /// Start a distribution in a new terminal window
-    pub fn start_distribution(name: &str) -> Result<(), String> {
-        std::process::Command::new("wt")
-            .args(["-p", name])
-            .spawn()
-            .map_err(|e| format!("Failed to start terminal: {}", e))?;
+    pub fn start_distribution(&self, name: &str) -> TamerResult<()> {
+        self.runner
+            .spawn("wt", &["-p", name])
+            .map_err(|e| TamerError::CommandUnavailable {
+                program: "wt".to_string(),
+                message: e.to_string(),
+            })?;
         Ok(())
     }
 
     /// Start a distribution in background
-    pub fn start_distribution_background(name: &str) -> Result<(), String> {
-        run_wsl_command(&["-d", name, "--", "echo", "started"])?;
+    pub fn start_distribution_background(&self, name: &str) -> TamerResult<()> {
+        run_wsl_command(self.runner.as_ref(), &["-d", name, "--", "echo", "started"])?;
         Ok(())
     }
 
     /// Stop a specific distribution
-    pub fn stop_distribution(name: &str) -> Result<(), String> {
-        run_wsl_command(&["--terminate", name])?;
+    pub fn stop_distribution(&self, name: &str) -> TamerResult<()> {
+        run_wsl_command(self.runner.as_ref(), &["--terminate", name])?;
         Ok(())
     }
 
     /// Stop all running WSL instances
-    pub fn shutdown_all() -> Result<(), String> {
-        run_wsl_command(&["--shutdown"])?;
+    pub fn shutdown_all(&self) -> TamerResult<()> {
+        run_wsl_command(self.runner.as_ref(), &["--shutdown"])?;
         Ok(())
     }
 
     /// Set the default distribution
-    pub fn set_default(name: &str) -> Result<(), String> {
-        run_wsl_command(&["--set-default", name])?;
+    pub fn set_default(&self, name: &str) -> TamerResult<()> {
+        run_wsl_command(self.runner.as_ref(), &["--set-default", name])?;
         Ok(())
     }
 
     /// Reclaim memory by dropping caches
-    pub fn reclaim_memory() -> Result<(), String> {
+    pub fn reclaim_memory(&self) -> TamerResult<()> {
         // Try to drop caches on all running distributions
-        let _ = run_wsl_command(&[
-            "-u",
-            "root",
-            "--",
-            "sh",
-            "-c",
-            "echo 1 > /proc/sys/vm/drop_caches || true",
-        ]);
+        let _ = run_wsl_command(
+            self.runner.as_ref(),
+            &[
+                "-u",
+                "root",
+                "--",
+                "sh",
+                "-c",
+                "echo 1 > /proc/sys/vm/drop_caches || true",
+            ],
+        );
         Ok(())
     }
 
     /// Kill all WSL processes  
-    pub fn kill_all() -> Result<(), String> {
+    pub fn kill_all(&self) -> TamerResult<()> {
         run_powershell_command(
+            self.runner.as_ref(),
             "Get-Process vmmemWSL -ErrorAction SilentlyContinue | Stop-Process -Force",
         )?;
-        Self::shutdown_all()
+        self.shutdown_all()
+    }
+
+    /// Export a distribution to a tar file, optionally compressed
+    ///
+    /// The tar is streamed from wsl.exe through the compressor (chosen by
+    /// `options`, else by the file extension) and hashed while it is written;
+    /// the checksum is recorded in a sidecar manifest for `verify_export`. A
+    /// partial export is deleted if the export fails.
+    pub fn export_distribution(
+        &self,
+        name: &str,
+        path: &str,
+        options: &ExportOptions,
+        job: &JobHandle,
+    ) -> TamerResult<ExportManifest> {
+        let export = Path::new(path);
+        let compression = options
+            .compression
+            .unwrap_or_else(|| Compression::from_path(export));
+        let level = options
+            .level
+            .unwrap_or_else(|| self.compression_settings().level(compression));
+        compression.check_level(level)?;
+
+        job.set_phase(JobPhase::Exporting);
+        job.track_file(export);
+        let result = self.stream_export(name, export, compression, level, job);
+        if result.is_err() {
+            remove_export(export);
+        }
+        result
+    }
+
+    fn stream_export(
+        &self,
+        name: &str,
+        export: &Path,
+        compression: Compression,
+        level: i32,
+        job: &JobHandle,
+    ) -> TamerResult<ExportManifest> {
+        let write_error =
+            |e: std::io::Error| TamerError::io(format!("Failed to write {}", export.display()), e);
+        let file = File::create(export)
+            .map_err(|e| TamerError::io(format!("Failed to create {}", export.display()), e))?;
+        let mut writer =
+            Compressor::new(HashingWriter::new(BufWriter::new(file)), compression, level)
+                .map_err(write_error)?;
+        let control = RunControl::new(
+            self.operation_timeouts().export(),
+            job.cancel_token().clone(),
+        );
+        run_wsl_command_to_writer(
+            self.runner.as_ref(),
+            &["--export", name, "-"],
+            &mut writer,
+            &control,
+        )?;
+        let (hashed, uncompressed_bytes) = writer.finish().map_err(write_error)?;
+        let (sha256, size_bytes) = hashed.finish().map_err(write_error)?;
+
+        let manifest = ExportManifest {
+            distro: name.to_string(),
+            file_name: export
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            size_bytes,
+            sha256,
+            created_at: unix_timestamp(),
+            compression,
+            uncompressed_bytes,
+        };
+        write_export_manifest(export, &manifest)?;
+        log::info!(
+            "Exported '{}' to {} ({} bytes, {:.1}x {:?})",
+            name,
+            export.display(),
+            size_bytes,
+            manifest.compression_ratio(),
+            compression
+        );
+        Ok(manifest)
+    }
+
+    /// Export a WSL2 distribution's virtual disk to a .vhdx file
+    pub fn export_distribution_vhd(
+        &self,
+        name: &str,
+        path: &str,
+        job: &JobHandle,
+    ) -> TamerResult<()> {
+        self.run_export(&["--export", name, path, "--vhd"], path, job)
     }
 
-    /// Export a distribution to a tar file
-    pub fn export_distribution(name: &str, path: &str) -> Result<(), String> {
-        run_wsl_command(&["--export", name, path])?;
+    fn run_export(&self, args: &[&str], path: &str, job: &JobHandle) -> TamerResult<()> {
+        job.set_phase(JobPhase::Exporting);
+        job.track_file(Path::new(path));
+        let control = RunControl::new(
+            self.operation_timeouts().export(),
+            job.cancel_token().clone(),
+        );
+        run_wsl_command_controlled(self.runner.as_ref(), args, &control)?;
         Ok(())
     }
 
-    /// Import a distribution from a tar file
-    pub fn import_distribution(name: &str, location: &str, tar_path: &str) -> Result<(), String> {
-        run_wsl_command(&["--import", name, location, tar_path])?;
+    /// Import a distribution from a tar file, once `verify_export` accepts it
+    ///
+    /// Compressed tars are decompressed on the fly and piped to wsl.exe.
+    pub fn import_distribution(
+        &self,
+        name: &str,
+        location: &str,
+        tar_path: &str,
+        job: &JobHandle,
+    ) -> TamerResult<()> {
+        self.verify_export(tar_path, job)?;
+        self.import_distribution_unchecked(name, location, tar_path, job)
+    }
+
+    /// Import a tar the caller has already checked with `verify_export`
+    pub fn import_distribution_unchecked(
+        &self,
+        name: &str,
+        location: &str,
+        tar_path: &str,
+        job: &JobHandle,
+    ) -> TamerResult<()> {
+        match export_compression(Path::new(tar_path)) {
+            Compression::None => {
+                self.run_import(&["--import", name, location, tar_path], None, job)
+            }
+            compression => {
+                let file = File::open(tar_path)
+                    .map_err(|e| TamerError::io(format!("Failed to open {}", tar_path), e))?;
+                let mut tar = Decompressor::new(BufReader::new(file), compression)
+                    .map_err(|e| TamerError::io(format!("Failed to read {}", tar_path), e))?;
+                // wsl.exe reads the tar from stdin when the file name is "-"
+                self.run_import(&["--import", name, location, "-"], Some(&mut tar), job)
+            }
+        }
+    }
+
+    /// Check an exported tar before importing it
+    ///
+    /// Validates the sidecar checksum (when there is one) and that the archive
+    /// is well-formed and contains a root filesystem.
+    pub fn verify_export(
+        &self,
+        tar_path: &str,
+        job: &JobHandle,
+    ) -> TamerResult<ExportVerification> {
+        job.set_phase(JobPhase::Checksumming);
+        verify_export(Path::new(tar_path), job.cancel_token())
+    }
+
+    /// Import a distribution from an exported .vhdx file
+    pub fn import_distribution_vhd(
+        &self,
+        name: &str,
+        location: &str,
+        vhd_path: &str,
+        job: &JobHandle,
+    ) -> TamerResult<()> {
+        self.run_import(&["--import", name, location, vhd_path, "--vhd"], None, job)
+    }
+
+    /// Run `wsl --import`, feeding stdin from `source` if given
+    fn run_import(
+        &self,
+        args: &[&str],
+        source: Option<&mut (dyn Read + Send)>,
+        job: &JobHandle,
+    ) -> TamerResult<()> {
+        job.set_phase(JobPhase::Importing);
+        let control = RunControl::new(
+            self.operation_timeouts().import(),
+            job.cancel_token().clone(),
+        );
+        match source {
+            Some(source) => {
+                run_wsl_command_from_reader(self.runner.as_ref(), args, source, &control)?
+            }
+            None => run_wsl_command_controlled(self.runner.as_ref(), args, &control)?,
+        };
+        self.invalidate_distro_cache();
+        Ok(())
+    }
+
+    /// Confirm that an imported distribution is actually registered
+    pub fn verify_registered(&self, name: &str, job: &JobHandle) -> TamerResult<()> {
+        job.set_phase(JobPhase::Verifying);
+        if !self.is_registered(name)? {
+            return Err(TamerError::DistroNotFound {
+                name: name.to_string(),
+            }
+            .context("Import reported success but the distribution is not registered"));
+        }
         Ok(())
     }
 
     /// Clone a distribution
-    pub fn clone_distribution(source: &str, new_name: &str, location: &str) -> Result<(), String> {
+    pub fn clone_distribution(
+        &self,
+        source: &str,
+        new_name: &str,
+        location: &str,
+        job: &JobHandle,
+    ) -> TamerResult<()> {
         // Create temp file for export
         let temp_dir = std::env::temp_dir();
         let temp_file = temp_dir.join(format!("{}_clone_{}.tar", source, uuid::Uuid::new_v4()));
         let temp_path = temp_file.to_string_lossy().to_string();
 
-        // Export source
-        Self::export_distribution(source, &temp_path)?;
-
-        // Import as new distro
-        let result = Self::import_distribution(new_name, location, &temp_path);
+        // Export source, then import as new distro
+        let result = self
+            .export_distribution(source, &temp_path, &ExportOptions::default(), job)
+            .and_then(|_| self.import_distribution(new_name, location, &temp_path, job))
+            .and_then(|_| self.verify_registered(new_name, job));
 
         // Clean up temp file
-        let _ = std::fs::remove_file(&temp_file);
+        job.set_phase(JobPhase::CleaningUp);
+        remove_export(&temp_file);
 
         result
     }
 
-    /// Move a distribution to a new location
+    /// Whether a distribution with this name is registered (uncached)
     ///
-    /// Safety: imports at new location FIRST, verifies success, then unregisters old.
-    /// This prevents data loss if the import fails.
-    pub fn move_distribution(name: &str, new_location: &str) -> Result<(), String> {
-        // Create temp file for export
-        let temp_dir = std::env::temp_dir();
-        let temp_file = temp_dir.join(format!("{}_move_{}.tar", name, uuid::Uuid::new_v4()));
-        let temp_path = temp_file.to_string_lossy().to_string();
+    /// Unlike `refresh_distributions`, an empty distro list is not an error.
+    pub fn is_registered(&self, name: &str) -> TamerResult<bool> {
+        let args = ["--list", "--quiet"];
+        let output = self.runner.output("wsl", &args).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                TamerError::WslNotInstalled
+            } else {
+                TamerError::CommandUnavailable {
+                    program: "wsl".to_string(),
+                    message: e.to_string(),
+                }
+            }
+        })?;
 
-        // Use a temporary name so both distros can coexist during the move
-        let temp_name = format!("{}-wsl-tamer-moving", name);
+        let stdout = decode_utf16le(&output.stdout);
+        if !output.success() {
+            let stderr = decode_utf16le(&output.stderr);
+            let combined = format!("{}{}", stdout, stderr);
+            if combined.contains("WSL_E_DEFAULT_DISTRO_NOT_FOUND")
+                || combined.contains("has no installed distributions")
+            {
+                self.invalidate_distro_cache();
+                return Ok(false);
+            }
+            return Err(TamerError::CommandFailed {
+                command: format_command("wsl", &args),
+                exit_code: output.exit_code,
+                stderr: stderr.trim().to_string(),
+            });
+        }
 
-        // Export
-        Self::export_distribution(name, &temp_path)?;
+        self.invalidate_distro_cache();
+        Ok(stdout
+            .lines()
+            .map(clean_distro_name)
+            .any(|d| d.eq_ignore_ascii_case(name)))
+    }
 
-        // Import at new location under temp name (original still exists for safety)
-        let import_result = Self::import_distribution(&temp_name, new_location, &temp_path);
+    /// Folder holding a distribution's virtual disk, from the Lxss registry key
+    pub fn distro_location(&self, name: &str) -> TamerResult<Option<String>> {
+        Ok(self
+            .registry
+            .find(name)?
+            .map(|r| r.base_path)
+            .filter(|path| !path.is_empty()))
+    }
 
-        // Clean up temp file regardless of import result
-        let _ = std::fs::remove_file(&temp_file);
+    /// Lxss registry metadata of a distribution, if it is registered
+    pub fn distro_registration(&self, name: &str) -> TamerResult<Option<DistroRegistration>> {
+        self.registry.find(name)
+    }
 
-        // If import failed, leave original intact and report error
-        import_result.map_err(|e| {
-            format!(
-                "Failed to import at new location (original preserved): {}",
-                e
-            )
-        })?;
+    /// Move a distribution to a new location
+    ///
+    /// Every step is recorded in a write-ahead journal before moving on, so an
+    /// interrupted move can be finished with `resume_move` or undone with
+    /// `rollback_move`. The original is only unregistered once its export is
+    /// complete, and is re-imported from that export if the new import fails.
+    pub fn move_distribution(
+        &self,
+        name: &str,
+        new_location: &str,
+        job: &JobHandle,
+    ) -> TamerResult<()> {
+        let mut active = self.lock_active_moves()?;
+        if let Some(pending) = self.move_journal.find_by_distro(name) {
+            return Err(TamerError::InvalidOperation(
+                if active.contains(&pending.id) {
+                    format!("'{}' is already being moved", pending.distro)
+                } else {
+                    format!(
+                    "An interrupted move of '{}' is pending (id {}); resume or roll it back first",
+                    pending.distro, pending.id
+                )
+                },
+            ));
+        }
 
-        // Import succeeded — safe to unregister original
-        Self::unregister_distribution(name)?;
+        let original_location = self.distro_location(name).unwrap_or_else(|e| {
+            log::warn!("Could not determine location of '{}': {}", name, e);
+            None
+        });
+        let temp_file =
+            std::env::temp_dir().join(format!("{}_move_{}.tar", name, uuid::Uuid::new_v4()));
+        // Claimed while still holding the lock, so the new journal is never
+        // listed as interrupted
+        let journal = self.move_journal.begin(
+            name,
+            new_location,
+            original_location,
+            temp_file.to_string_lossy().to_string(),
+        )?;
+        active.insert(journal.id.clone());
+        drop(active);
+        let _active = ActiveMove {
+            moves: &self.active_moves,
+            id: journal.id.clone(),
+        };
 
-        // Re-register under original name by exporting temp → importing as original
-        let temp_file2 = temp_dir.join(format!("{}_rename_{}.tar", name, uuid::Uuid::new_v4()));
-        let temp_path2 = temp_file2.to_string_lossy().to_string();
+        self.drive_move(journal, job)
+    }
 
-        let rename_result = Self::export_distribution(&temp_name, &temp_path2).and_then(|_| {
-            Self::unregister_distribution(&temp_name)?;
-            Self::import_distribution(name, new_location, &temp_path2)
-        });
+    /// Moves interrupted by a crash or shutdown
+    ///
+    /// The journals of moves still running in this process are left out.
+    pub fn pending_moves(&self) -> Vec<MoveJournal> {
+        let Ok(active) = self.lock_active_moves() else {
+            return Vec::new();
+        };
+        self.move_journal
+            .list()
+            .into_iter()
+            .filter(|journal| !active.contains(&journal.id))
+            .collect()
+    }
+
+    /// Interrupted move by ID
+    pub fn pending_move(&self, id: &str) -> TamerResult<MoveJournal> {
+        let journal = self.move_journal.get(id)?;
+        if self.lock_active_moves()?.contains(id) {
+            return Err(move_in_progress(&journal));
+        }
+        Ok(journal)
+    }
 
-        let _ = std::fs::remove_file(&temp_file2);
+    /// Continue an interrupted move from its last completed step
+    pub fn resume_move(&self, id: &str, job: &JobHandle) -> TamerResult<()> {
+        let journal = self.move_journal.get(id)?;
+        let _active = self.claim_move(&journal)?;
+        self.drive_move(journal, job)
+    }
 
-        rename_result.map_err(|e| {
-            format!(
-                "Move succeeded but rename failed (distro available as '{}'): {}",
-                temp_name, e
-            )
+    /// Undo an interrupted move, leaving the distribution at its original location
+    pub fn rollback_move(&self, id: &str, job: &JobHandle) -> TamerResult<()> {
+        let journal = self.move_journal.get(id)?;
+        let _active = self.claim_move(&journal)?;
+        let tar = PathBuf::from(&journal.tar_path);
+
+        let original_intact = matches!(journal.step, MoveStep::Exporting | MoveStep::Exported)
+            && self.is_registered(&journal.distro)?;
+        if !original_intact {
+            let location = journal.original_location.as_deref().ok_or_else(|| {
+                TamerError::InvalidOperation(
+                    "Original location is unknown; resume the move instead".to_string(),
+                )
+            })?;
+            if !tar.is_file() {
+                return Err(TamerError::NotFound {
+                    what: format!("Move export '{}'", journal.tar_path),
+                });
+            }
+            // Drop the copy at the new location, then restore from the export
+            if self.is_registered(&journal.distro)? {
+                self.unregister_distribution(&journal.distro)?;
+            }
+            self.import_distribution(&journal.distro, location, &journal.tar_path, job)?;
+            self.verify_registered(&journal.distro, job)?;
+        }
+
+        job.set_phase(JobPhase::CleaningUp);
+        remove_export(&tar);
+        self.move_journal.remove(&journal.id)
+    }
+
+    /// Run a move from its journaled step to completion
+    /// Mark a journaled move as running until the guard is dropped
+    ///
+    /// Fails if it already runs, so two jobs never work on one move.
+    fn claim_move(&self, journal: &MoveJournal) -> TamerResult<ActiveMove<'_>> {
+        if !self.lock_active_moves()?.insert(journal.id.clone()) {
+            return Err(move_in_progress(journal));
+        }
+        Ok(ActiveMove {
+            moves: &self.active_moves,
+            id: journal.id.clone(),
         })
     }
 
+    fn lock_active_moves(&self) -> TamerResult<std::sync::MutexGuard<'_, HashSet<String>>> {
+        self.active_moves
+            .lock()
+            .map_err(|e| TamerError::Internal(e.to_string()))
+    }
+
+    fn drive_move(&self, mut journal: MoveJournal, job: &JobHandle) -> TamerResult<()> {
+        let tar = PathBuf::from(&journal.tar_path);
+
+        loop {
+            match journal.step {
+                MoveStep::Exporting => {
+                    let exported = self
+                        .export_distribution(
+                            &journal.distro,
+                            &journal.tar_path,
+                            &ExportOptions::default(),
+                            job,
+                        )
+                        .and_then(|_| self.verify_export(&journal.tar_path, job));
+                    if let Err(e) = exported {
+                        // Nothing has changed yet — drop the partial export and the journal
+                        remove_export(&tar);
+                        let _ = self.move_journal.remove(&journal.id);
+                        return Err(e);
+                    }
+                    self.move_journal
+                        .advance(&mut journal, MoveStep::Exported)?;
+                }
+                MoveStep::Exported => {
+                    // Last point where cancelling leaves the original untouched
+                    if job.is_cancelled() {
+                        remove_export(&tar);
+                        let _ = self.move_journal.remove(&journal.id);
+                        return Err(TamerError::Cancelled);
+                    }
+                    // The export is about to become the only copy
+                    self.verify_export(&journal.tar_path, job)?;
+                    if self.is_registered(&journal.distro)? {
+                        self.unregister_distribution(&journal.distro)?;
+                    }
+                    self.move_journal
+                        .advance(&mut journal, MoveStep::Unregistered)?;
+                }
+                MoveStep::Unregistered => {
+                    if !self.is_registered(&journal.distro)? {
+                        if let Err(e) = self.import_distribution_unchecked(
+                            &journal.distro,
+                            &journal.new_location,
+                            &journal.tar_path,
+                            job,
+                        ) {
+                            return Err(self.restore_original(&journal, e));
+                        }
+                    }
+                    self.verify_registered(&journal.distro, job)?;
+                    self.move_journal
+                        .advance(&mut journal, MoveStep::Imported)?;
+                }
+                MoveStep::Imported => {
+                    job.set_phase(JobPhase::CleaningUp);
+                    remove_export(&tar);
+                    return self.move_journal.remove(&journal.id);
+                }
+            }
+        }
+    }
+
+    /// Re-import the original after the import at the new location failed
+    ///
+    /// Not cancellable: this is the path that keeps the user's data registered.
+    fn restore_original(&self, journal: &MoveJournal, err: TamerError) -> TamerError {
+        let Some(location) = journal.original_location.as_deref() else {
+            return err.context(format!(
+                "Import at new location failed; the export is kept at {} so the move can be resumed",
+                journal.tar_path
+            ));
+        };
+
+        let restore = JobHandle::detached(CancellationToken::new());
+        match self.import_distribution_unchecked(
+            &journal.distro,
+            location,
+            &journal.tar_path,
+            &restore,
+        ) {
+            Ok(()) => {
+                remove_export(Path::new(&journal.tar_path));
+                let _ = self.move_journal.remove(&journal.id);
+                err.context("Import at new location failed; the original was restored")
+            }
+            Err(restore_err) => err.context(format!(
+                "Import at new location failed and restoring the original failed ({}); the export is kept at {}",
+                restore_err, journal.tar_path
+            )),
+        }
+    }
+
     /// Unregister (delete) a distribution
-    pub fn unregister_distribution(name: &str) -> Result<(), String> {
-        run_wsl_command(&["--unregister", name])?;
+    pub fn unregister_distribution(&self, name: &str) -> TamerResult<()> {
+        run_wsl_command(self.runner.as_ref(), &["--unregister", name])?;
         Ok(())
     }
 
     /// Get list of online distributions available for install
-    pub fn get_online_distributions() -> Result<Vec<OnlineDistribution>, String> {
-        let output = run_wsl_command(&["--list", "--online"])?;
+    pub fn get_online_distributions(&self) -> TamerResult<Vec<OnlineDistribution>> {
+        let output = run_wsl_command(self.runner.as_ref(), &["--list", "--online"])?;
         Self::parse_online_distributions(&output)
     }
 
-    fn parse_online_distributions(output: &str) -> Result<Vec<OnlineDistribution>, String> {
+    fn parse_online_distributions(output: &str) -> TamerResult<Vec<OnlineDistribution>> {
         let mut distros = Vec::new();
 
         for line in output.lines().skip(3) {
@@ -374,18 +928,26 @@ impl WslService {
     }
 
     /// Install an online distribution
-    pub fn install_distribution(name: &str) -> Result<(), String> {
-        run_wsl_command(&["--install", "-d", name])?;
+    pub fn install_distribution(&self, name: &str, job: &JobHandle) -> TamerResult<()> {
+        job.set_phase(JobPhase::Installing);
+        let control = RunControl::new(
+            self.operation_timeouts().install(),
+            job.cancel_token().clone(),
+        );
+        run_wsl_command_controlled(self.runner.as_ref(), &["--install", "-d", name], &control)?;
+        self.invalidate_distro_cache();
         Ok(())
     }
 
     /// Open file explorer to WSL path
-    pub fn open_explorer(name: &str) -> Result<(), String> {
+    pub fn open_explorer(&self, name: &str) -> TamerResult<()> {
         let wsl_path = format!("\\\\wsl$\\{}", name);
-        std::process::Command::new("explorer")
-            .arg(&wsl_path)
-            .spawn()
-            .map_err(|e| format!("Failed to open explorer: {}", e))?;
+        self.runner.spawn("explorer", &[&wsl_path]).map_err(|e| {
+            TamerError::CommandUnavailable {
+                program: "explorer".to_string(),
+                message: e.to_string(),
+            }
+        })?;
         Ok(())
     }
 
@@ -397,76 +959,248 @@ impl WslService {
     }
 
     /// Read .wslconfig file
-    pub fn read_wslconfig() -> Result<String, String> {
-        let path = Self::get_wslconfig_path();
-        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read .wslconfig: {}", e))
+    pub fn read_wslconfig(&self) -> TamerResult<String> {
+        std::fs::read_to_string(&self.wslconfig_path)
+            .map_err(|e| TamerError::io("Failed to read .wslconfig", e))
+    }
+
+    /// Write .wslconfig file, keeping the replaced content in the history
+    pub fn write_wslconfig(&self, content: &str, origin: ConfigOrigin) -> TamerResult<()> {
+        let prior = self.read_wslconfig_file()?;
+        self.config_history.record(
+            ConfigTarget::Wslconfig,
+            prior.as_deref(),
+            Some(content),
+            origin,
+        )?;
+        std::fs::write(&self.wslconfig_path, content)
+            .map_err(|e| TamerError::io("Failed to write .wslconfig", e))
+    }
+
+    /// Delete .wslconfig, keeping its content in the history
+    fn remove_wslconfig(&self, origin: ConfigOrigin) -> TamerResult<()> {
+        let Some(prior) = self.read_wslconfig_file()? else {
+            return Ok(());
+        };
+        self.config_history
+            .record(ConfigTarget::Wslconfig, Some(&prior), None, origin)?;
+        std::fs::remove_file(&self.wslconfig_path)
+            .map_err(|e| TamerError::io("Failed to delete .wslconfig", e))
+    }
+
+    /// Content of .wslconfig, `None` if there is none
+    fn read_wslconfig_file(&self) -> TamerResult<Option<String>> {
+        match std::fs::read_to_string(&self.wslconfig_path) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(TamerError::io("Failed to read .wslconfig", e)),
+        }
+    }
+
+    /// Parse .wslconfig for editing (an empty document if there is none)
+    pub fn read_wslconfig_document(&self) -> TamerResult<IniDocument> {
+        match std::fs::read_to_string(&self.wslconfig_path) {
+            Ok(content) => Ok(IniDocument::parse(&content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(IniDocument::default()),
+            Err(e) => Err(TamerError::io("Failed to read .wslconfig", e)),
+        }
+    }
+
+    /// Apply a profile to .wslconfig, keeping the file's other settings and comments
+    pub fn apply_profile(&self, profile: &WslProfile, origin: ConfigOrigin) -> TamerResult<()> {
+        let mut doc = self.read_wslconfig_document()?;
+        profile.apply_to(&mut doc);
+        self.write_wslconfig(&doc.to_string(), origin)
+    }
+
+    /// Stored versions of configuration files, newest first
+    pub fn config_history(&self, target: Option<&ConfigTarget>) -> Vec<ConfigVersion> {
+        self.config_history.list(target)
+    }
+
+    /// Line diff between two stored versions
+    ///
+    /// Without `to_id` the version is compared with the file as it is now.
+    pub fn diff_config_versions(
+        &self,
+        from_id: &str,
+        to_id: Option<&str>,
+    ) -> TamerResult<Vec<DiffLine>> {
+        let from = self.config_history.get(from_id)?;
+        let to = match to_id {
+            Some(id) => self.config_history.get(id)?.content,
+            None => self.read_config(&from.version.target)?,
+        };
+        Ok(diff_lines(&from.content, &to))
     }
 
-    /// Write .wslconfig file
-    pub fn write_wslconfig(content: &str) -> Result<(), String> {
-        let path = Self::get_wslconfig_path();
-        std::fs::write(&path, content).map_err(|e| format!("Failed to write .wslconfig: {}", e))
+    /// Restore a stored version of a configuration file
+    ///
+    /// The content being replaced is stored first, so a rollback can itself
+    /// be rolled back. Rolling back to before the file existed deletes it.
+    pub fn rollback_config(&self, version_id: &str) -> TamerResult<()> {
+        let stored = self.config_history.get(version_id)?;
+        let origin = ConfigOrigin::Rollback {
+            version_id: version_id.to_string(),
+        };
+        match (&stored.version.target, stored.version.existed) {
+            (ConfigTarget::Wslconfig, true) => self.write_wslconfig(&stored.content, origin),
+            (ConfigTarget::Wslconfig, false) => self.remove_wslconfig(origin),
+            (ConfigTarget::DistroConfig { distro }, true) => {
+                self.write_distro_config(distro, &stored.content, origin)
+            }
+            (ConfigTarget::DistroConfig { distro }, false) => {
+                self.remove_distro_config(distro, origin)
+            }
+        }
     }
 
-    /// Apply a profile to .wslconfig
-    pub fn apply_profile(profile: &WslProfile) -> Result<(), String> {
-        let config = profile.to_wslconfig();
-        Self::write_wslconfig(&config)
+    /// Current content of a configuration file (empty if there is none)
+    fn read_config(&self, target: &ConfigTarget) -> TamerResult<String> {
+        match target {
+            ConfigTarget::Wslconfig => Ok(self.read_wslconfig_document()?.to_string()),
+            ConfigTarget::DistroConfig { distro } => Ok(self
+                .read_distro_config_document(distro)?
+                .map(|doc| doc.to_string())
+                .unwrap_or_default()),
+        }
     }
 
     /// Read wsl.conf from a distribution
-    pub fn read_distro_config(name: &str) -> Result<String, String> {
-        run_wsl_command(&["-d", name, "-u", "root", "--", "cat", "/etc/wsl.conf"])
+    pub fn read_distro_config(&self, name: &str) -> TamerResult<String> {
+        run_wsl_command(
+            self.runner.as_ref(),
+            &["-d", name, "-u", "root", "--", "cat", "/etc/wsl.conf"],
+        )
+    }
+
+    /// Read wsl.conf from a distribution as an editable document
+    ///
+    /// The file is read as UTF-8; `None` if the distribution has none, so an
+    /// empty file can be told apart from a missing one.
+    pub fn read_distro_config_document(&self, name: &str) -> TamerResult<Option<IniDocument>> {
+        let args = ["-d", name, "-u", "root", "--", "sh", "-c", READ_WSL_CONF];
+        let output =
+            self.runner
+                .output("wsl", &args)
+                .map_err(|e| TamerError::CommandUnavailable {
+                    program: "wsl".to_string(),
+                    message: e.to_string(),
+                })?;
+
+        if !output.success() {
+            return Err(TamerError::CommandFailed {
+                command: format_command("wsl", &args),
+                exit_code: output.exit_code,
+                stderr: decode_utf16le(&output.stderr).trim().to_string(),
+            });
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout.strip_prefix("exists\n").map(IniDocument::parse))
     }
 
-    /// Write wsl.conf to a distribution
+    /// Write wsl.conf to a distribution, keeping the replaced content in the history
     /// Uses stdin piping to avoid shell injection — no user content in command args
-    pub fn write_distro_config(name: &str, content: &str) -> Result<(), String> {
-        use std::io::Write;
-        use std::os::windows::process::CommandExt;
-        use std::process::{Command, Stdio};
-
-        let mut child = Command::new("wsl")
-            .args([
-                "-d",
-                name,
-                "-u",
-                "root",
-                "--",
-                "sh",
-                "-c",
-                "cat > /etc/wsl.conf",
-            ])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .creation_flags(0x08000000) // CREATE_NO_WINDOW
-            .spawn()
-            .map_err(|e| format!("Failed to spawn wsl: {}", e))?;
-
-        if let Some(mut stdin) = child.stdin.take() {
-            stdin
-                .write_all(content.as_bytes())
-                .map_err(|e| format!("Failed to write config content: {}", e))?;
-            // stdin is dropped here, closing the pipe and signaling EOF to cat
-        }
-
-        let output = child
-            .wait_with_output()
-            .map_err(|e| format!("Failed to wait for wsl: {}", e))?;
-
-        if !output.status.success() {
+    pub fn write_distro_config(
+        &self,
+        name: &str,
+        content: &str,
+        origin: ConfigOrigin,
+    ) -> TamerResult<()> {
+        let prior = self
+            .read_distro_config_document(name)?
+            .map(|doc| doc.to_string());
+        self.config_history.record(
+            ConfigTarget::DistroConfig {
+                distro: name.to_string(),
+            },
+            prior.as_deref(),
+            Some(content),
+            origin,
+        )?;
+
+        let args = [
+            "-d",
+            name,
+            "-u",
+            "root",
+            "--",
+            "sh",
+            "-c",
+            "cat > /etc/wsl.conf",
+        ];
+        let output = self
+            .runner
+            .output_with_stdin("wsl", &args, content.as_bytes())
+            .map_err(|e| TamerError::CommandUnavailable {
+                program: "wsl".to_string(),
+                message: e.to_string(),
+            })?;
+
+        if !output.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("Failed to write wsl.conf: {}", stderr.trim()));
+            return Err(TamerError::CommandFailed {
+                command: format_command("wsl", &args),
+                exit_code: output.exit_code,
+                stderr: stderr.trim().to_string(),
+            });
         }
 
         Ok(())
     }
+
+    /// Delete a distribution's wsl.conf, keeping its content in the history
+    fn remove_distro_config(&self, name: &str, origin: ConfigOrigin) -> TamerResult<()> {
+        let Some(prior) = self.read_distro_config_document(name)? else {
+            return Ok(());
+        };
+        self.config_history.record(
+            ConfigTarget::DistroConfig {
+                distro: name.to_string(),
+            },
+            Some(&prior.to_string()),
+            None,
+            origin,
+        )?;
+        run_wsl_command(
+            self.runner.as_ref(),
+            &["-d", name, "-u", "root", "--", "rm", "-f", "/etc/wsl.conf"],
+        )
+        .map(|_| ())
+    }
+}
+
+/// A journaled move running in this process; released when dropped
+struct ActiveMove<'a> {
+    moves: &'a Mutex<HashSet<String>>,
+    id: String,
+}
+
+impl Drop for ActiveMove<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut moves) = self.moves.lock() {
+            moves.remove(&self.id);
+        }
+    }
+}
+
+fn move_in_progress(journal: &MoveJournal) -> TamerError {
+    TamerError::InvalidOperation(format!(
+        "The move of '{}' is still in progress",
+        journal.distro
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::DiffKind;
+    use crate::services::sample_lxss_registry;
+    use crate::utils::{
+        encode_utf16le, export_manifest_path, read_export_manifest, sample_rootfs_tar,
+        CancellationToken, CommandOutput, ScriptedRunner,
+    };
 
     #[test]
     fn test_parse_distributions() {
@@ -481,4 +1215,712 @@ mod tests {
         assert!(!distros[1].is_default);
         assert_eq!(distros[1].state, DistributionState::Stopped);
     }
+
+    #[test]
+    fn test_get_distributions_uses_runner_and_caches() {
+        let runner = Arc::new(ScriptedRunner::new().on(
+            "wsl",
+            &["--list", "--verbose"],
+            CommandOutput::ok_utf16(
+                "  NAME      STATE           VERSION\r\n* Ubuntu    Running         2\r\n",
+            ),
+        ));
+        let service = WslService::new(runner.clone());
+
+        let distros = service.get_distributions().unwrap();
+        assert_eq!(distros.len(), 1);
+        assert_eq!(distros[0].name, "Ubuntu");
+
+        // Second call is served from the cache
+        service.get_distributions().unwrap();
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn test_distributions_carry_registry_metadata() {
+        let runner = Arc::new(ScriptedRunner::new().on(
+            "wsl",
+            &["--list", "--verbose"],
+            CommandOutput::ok_utf16(
+                "  NAME      STATE           VERSION\r\n* Ubuntu    Running         2\r\n  Arch      Stopped         2\r\n",
+            ),
+        ));
+        let service = WslService::new(runner.clone())
+            .with_distro_registry(DistroRegistry::new(Arc::new(sample_lxss_registry())));
+
+        let distros = service.get_distributions().unwrap();
+        let ubuntu = distros[0].registration.as_ref().unwrap();
+        assert_eq!(ubuntu.default_uid, Some(1000));
+        assert!(ubuntu.vhd_path().unwrap().ends_with("ext4.vhdx"));
+        // Listed by wsl.exe but missing from the registry
+        assert!(distros[1].registration.is_none());
+
+        assert_eq!(
+            service.distro_location("debian").unwrap().as_deref(),
+            Some("D:\\WSL\\Debian")
+        );
+        assert_eq!(service.distro_location("Arch").unwrap(), None);
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn test_command_failure_surfaces_stderr() {
+        let runner = Arc::new(ScriptedRunner::new().on(
+            "wsl",
+            &["--terminate", "Ubuntu"],
+            CommandOutput::failed(
+                1,
+                encode_utf16le("There is no distribution with the supplied name."),
+            ),
+        ));
+        let service = WslService::new(runner);
+
+        let err = service.stop_distribution("Ubuntu").unwrap_err();
+        assert!(matches!(err, TamerError::DistroNotFound { ref name } if name == "Ubuntu"));
+    }
+
+    #[test]
+    fn test_read_distro_config_document_as_utf8() {
+        let args = [
+            "-d",
+            "Ubuntu",
+            "-u",
+            "root",
+            "--",
+            "sh",
+            "-c",
+            READ_WSL_CONF,
+        ];
+        let runner = ScriptedRunner::new()
+            .on(
+                "wsl",
+                &args,
+                CommandOutput::ok("exists\n[user]\ndefault=jürgen\n"),
+            )
+            .on("wsl", &args, CommandOutput::ok("exists\n"))
+            .on("wsl", &args, CommandOutput::ok(""));
+        let service = WslService::new(Arc::new(runner));
+
+        let doc = service.read_distro_config_document("Ubuntu").unwrap();
+        assert_eq!(doc.unwrap().get("user", "default"), Some("jürgen"));
+        // An empty /etc/wsl.conf
+        let doc = service.read_distro_config_document("Ubuntu").unwrap();
+        assert_eq!(doc.unwrap().to_string(), "");
+        // No /etc/wsl.conf
+        assert!(service
+            .read_distro_config_document("Ubuntu")
+            .unwrap()
+            .is_none());
+    }
+
+    /// Empty configuration history in a scratch directory
+    fn scratch_history() -> (PathBuf, ConfigHistory) {
+        let dir = std::env::temp_dir().join(format!("wsl-tamer-test-{}", uuid::Uuid::new_v4()));
+        let history = ConfigHistory::new(dir.join("config-history"));
+        (dir, history)
+    }
+
+    #[test]
+    fn test_write_distro_config_pipes_content_via_stdin() {
+        let read_args = [
+            "-d",
+            "Ubuntu",
+            "-u",
+            "root",
+            "--",
+            "sh",
+            "-c",
+            READ_WSL_CONF,
+        ];
+        let args = [
+            "-d",
+            "Ubuntu",
+            "-u",
+            "root",
+            "--",
+            "sh",
+            "-c",
+            "cat > /etc/wsl.conf",
+        ];
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on(
+                    "wsl",
+                    &read_args,
+                    CommandOutput::ok("exists\n[boot]\nsystemd=false\n"),
+                )
+                .on("wsl", &args, CommandOutput::ok("")),
+        );
+        let (_dir, history) = scratch_history();
+        let service = WslService::new(runner.clone()).with_config_history(history);
+
+        service
+            .write_distro_config("Ubuntu", "[boot]\nsystemd=true\n", ConfigOrigin::Manual)
+            .unwrap();
+
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(
+            calls[1].stdin.as_deref(),
+            Some(&b"[boot]\nsystemd=true\n"[..])
+        );
+
+        // The replaced file went to the history first
+        let versions = service.config_history(None);
+        assert_eq!(versions.len(), 1);
+        assert_eq!(
+            versions[0].target,
+            ConfigTarget::DistroConfig {
+                distro: "Ubuntu".into()
+            }
+        );
+        assert_eq!(versions[0].size_bytes, 21);
+        assert!(versions[0].existed);
+    }
+
+    #[test]
+    fn test_write_distro_config_records_whether_file_existed() {
+        let read_args = [
+            "-d",
+            "Ubuntu",
+            "-u",
+            "root",
+            "--",
+            "sh",
+            "-c",
+            READ_WSL_CONF,
+        ];
+        let write_args = [
+            "-d",
+            "Ubuntu",
+            "-u",
+            "root",
+            "--",
+            "sh",
+            "-c",
+            "cat > /etc/wsl.conf",
+        ];
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on("wsl", &read_args, CommandOutput::ok("exists\n"))
+                .on("wsl", &read_args, CommandOutput::ok(""))
+                .on("wsl", &write_args, CommandOutput::ok("")),
+        );
+        let (_dir, history) = scratch_history();
+        let service = WslService::new(runner).with_config_history(history);
+
+        // An empty file existed; then one that didn't
+        service
+            .write_distro_config("Ubuntu", "[boot]\nsystemd=true\n", ConfigOrigin::Manual)
+            .unwrap();
+        service
+            .write_distro_config("Ubuntu", "[boot]\nsystemd=true\n", ConfigOrigin::Manual)
+            .unwrap();
+
+        let existed: Vec<_> = service
+            .config_history(None)
+            .iter()
+            .map(|v| v.existed)
+            .collect();
+        assert_eq!(existed, vec![false, true]);
+    }
+
+    #[test]
+    fn test_wslconfig_writes_are_versioned_and_roll_back() {
+        let (dir, history) = scratch_history();
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".wslconfig");
+        let service = WslService::new(Arc::new(ScriptedRunner::new()))
+            .with_config_history(history)
+            .with_wslconfig_path(path.clone());
+
+        service
+            .write_wslconfig("[wsl2]\nmemory=8GB\n", ConfigOrigin::Manual)
+            .unwrap();
+        let rule = ConfigOrigin::AutomationRule {
+            rule_id: "r1".into(),
+            rule_name: "Low memory".into(),
+        };
+        service
+            .write_wslconfig("[wsl2]\nmemory=1GB\n", rule.clone())
+            .unwrap();
+
+        let versions = service.config_history(Some(&ConfigTarget::Wslconfig));
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].replaced_by, rule);
+        assert!(versions[0].existed);
+        assert!(!versions[1].existed);
+
+        let diff: Vec<_> = service
+            .diff_config_versions(&versions[0].id, None)
+            .unwrap()
+            .into_iter()
+            .map(|l| (l.kind, l.text))
+            .collect();
+        assert_eq!(
+            diff,
+            vec![
+                (DiffKind::Unchanged, "[wsl2]".to_string()),
+                (DiffKind::Removed, "memory=8GB".to_string()),
+                (DiffKind::Added, "memory=1GB".to_string()),
+            ]
+        );
+
+        service.rollback_config(&versions[0].id).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "[wsl2]\nmemory=8GB\n"
+        );
+        let latest = &service.config_history(None)[0];
+        assert_eq!(
+            latest.replaced_by,
+            ConfigOrigin::Rollback {
+                version_id: versions[0].id.clone()
+            }
+        );
+        assert_eq!(
+            service
+                .diff_config_versions(&latest.id, Some(&versions[0].id))
+                .unwrap()
+                .len(),
+            3
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_rollback_to_before_file_existed_deletes_it() {
+        let (dir, history) = scratch_history();
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".wslconfig");
+        let read_args = [
+            "-d",
+            "Ubuntu",
+            "-u",
+            "root",
+            "--",
+            "sh",
+            "-c",
+            READ_WSL_CONF,
+        ];
+        let rm_args = [
+            "-d",
+            "Ubuntu",
+            "-u",
+            "root",
+            "--",
+            "rm",
+            "-f",
+            "/etc/wsl.conf",
+        ];
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on("wsl", &read_args, CommandOutput::ok("exists\n[boot]\n"))
+                .on("wsl", &rm_args, CommandOutput::ok("")),
+        );
+        let service = WslService::new(runner.clone())
+            .with_config_history(history)
+            .with_wslconfig_path(path.clone());
+
+        service
+            .write_wslconfig("[wsl2]\nmemory=8GB\n", ConfigOrigin::Manual)
+            .unwrap();
+        let created = service.config_history(None)[0].clone();
+        assert!(!created.existed);
+
+        service.rollback_config(&created.id).unwrap();
+        assert!(!path.exists());
+        // The deleted content is kept, so the rollback can be undone
+        let latest = &service.config_history(Some(&ConfigTarget::Wslconfig))[0];
+        assert!(latest.existed);
+        assert_eq!(
+            service.config_history.get(&latest.id).unwrap().content,
+            "[wsl2]\nmemory=8GB\n"
+        );
+        service.rollback_config(&latest.id).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "[wsl2]\nmemory=8GB\n"
+        );
+
+        // wsl.conf is deleted inside the distribution
+        let ubuntu = ConfigTarget::DistroConfig {
+            distro: "Ubuntu".into(),
+        };
+        let missing = service
+            .config_history
+            .record(ubuntu.clone(), None, Some("[boot]\n"), ConfigOrigin::Manual)
+            .unwrap()
+            .unwrap();
+        service.rollback_config(&missing.id).unwrap();
+        assert!(runner.was_called("wsl", &rm_args));
+        assert!(service.config_history(Some(&ubuntu))[0].existed);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_clone_stops_when_cancelled() {
+        let runner = Arc::new(ScriptedRunner::new());
+        let service = WslService::new(runner.clone());
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let err = service
+            .clone_distribution(
+                "Ubuntu",
+                "Ubuntu-copy",
+                "D:\\WSL",
+                &JobHandle::detached(cancel),
+            )
+            .unwrap_err();
+        assert!(matches!(err, TamerError::Cancelled));
+        assert!(runner.calls().is_empty());
+    }
+
+    #[test]
+    fn test_install_failure_is_not_cancellation() {
+        let runner = Arc::new(ScriptedRunner::new().on(
+            "wsl",
+            &["--install", "-d", "Nope"],
+            CommandOutput::failed(1, encode_utf16le("Invalid distribution name: 'Nope'.")),
+        ));
+        let service = WslService::new(runner);
+
+        let err = service
+            .install_distribution("Nope", &JobHandle::default())
+            .unwrap_err();
+        assert_eq!(err.code(), "COMMAND_FAILED");
+    }
+
+    #[test]
+    fn test_import_verification_requires_registration() {
+        let runner = Arc::new(ScriptedRunner::new().on(
+            "wsl",
+            &["--list", "--quiet"],
+            CommandOutput::ok_utf16("Ubuntu\r\n"),
+        ));
+        let service = WslService::new(runner);
+        let job = JobHandle::default();
+
+        assert!(service.verify_registered("ubuntu", &job).is_ok());
+        let err = service.verify_registered("Ubuntu-copy", &job).unwrap_err();
+        match err {
+            TamerError::OperationFailed { source, .. } => {
+                assert_eq!(source.code(), "DISTRO_NOT_FOUND")
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_export_streams_tar_and_writes_manifest() {
+        let tar = sample_rootfs_tar();
+        let runner = Arc::new(ScriptedRunner::new().on(
+            "wsl",
+            &["--export", "Ubuntu", "-"],
+            CommandOutput::ok(tar.clone()),
+        ));
+        let service = WslService::new(runner);
+        let (dir, _) = move_fixture();
+        let path = dir.join("Ubuntu.tar");
+        let path_str = path.to_string_lossy().to_string();
+        let job = JobHandle::default();
+
+        let manifest = service
+            .export_distribution("Ubuntu", &path_str, &ExportOptions::default(), &job)
+            .unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), tar);
+        assert_eq!(manifest.size_bytes, tar.len() as u64);
+        assert_eq!(manifest.file_name, "Ubuntu.tar");
+        assert_eq!(read_export_manifest(&path).unwrap(), Some(manifest.clone()));
+
+        let report = service.verify_export(&path_str, &job).unwrap();
+        assert!(report.checksum_verified);
+        assert_eq!(report.sha256, manifest.sha256);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_compressed_export_round_trips_through_import_pipe() {
+        let tar = sample_rootfs_tar();
+        let import = ["--import", "Ubuntu-copy", "D:\\WSL\\Copy", "-"];
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on(
+                    "wsl",
+                    &["--export", "Ubuntu", "-"],
+                    CommandOutput::ok(tar.clone()),
+                )
+                .on("wsl", &import, CommandOutput::ok("")),
+        );
+        let service = WslService::new(runner.clone());
+        let (dir, _) = move_fixture();
+        let path = dir.join("Ubuntu.tar.zst").to_string_lossy().to_string();
+        let job = JobHandle::default();
+
+        let manifest = service
+            .export_distribution("Ubuntu", &path, &ExportOptions::default(), &job)
+            .unwrap();
+        assert_eq!(manifest.compression, Compression::Zstd);
+        assert_eq!(manifest.uncompressed_bytes, tar.len() as u64);
+        assert!(manifest.compression_ratio() > 1.0);
+
+        service
+            .import_distribution("Ubuntu-copy", "D:\\WSL\\Copy", &path, &job)
+            .unwrap();
+        let call = runner.calls().pop().unwrap();
+        assert_eq!(call.args, import);
+        assert_eq!(call.stdin, Some(tar));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_export_rejects_out_of_range_level() {
+        let runner = Arc::new(ScriptedRunner::new());
+        let service = WslService::new(runner.clone());
+        let options = ExportOptions {
+            compression: Some(Compression::Gzip),
+            level: Some(12),
+        };
+
+        let err = service
+            .export_distribution("Ubuntu", "D:\\Ubuntu.tar", &options, &JobHandle::default())
+            .unwrap_err();
+        assert_eq!(err.code(), "INVALID_INPUT");
+        assert!(runner.calls().is_empty());
+    }
+
+    #[test]
+    fn test_failed_export_leaves_nothing_behind() {
+        let runner = Arc::new(ScriptedRunner::new().on(
+            "wsl",
+            &["--export", "Nope", "-"],
+            CommandOutput::failed(
+                1,
+                encode_utf16le("There is no distribution with the supplied name."),
+            ),
+        ));
+        let service = WslService::new(runner);
+        let (dir, _) = move_fixture();
+        let path = dir.join("Nope.tar");
+
+        let err = service
+            .export_distribution(
+                "Nope",
+                &path.to_string_lossy(),
+                &ExportOptions::default(),
+                &JobHandle::default(),
+            )
+            .unwrap_err();
+        assert_eq!(err.code(), "DISTRO_NOT_FOUND");
+        assert!(!path.exists() && !export_manifest_path(&path).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_import_refuses_malformed_tar() {
+        let runner = Arc::new(ScriptedRunner::new());
+        let service = WslService::new(runner.clone());
+        let (dir, tar) = move_fixture();
+        std::fs::write(&tar, b"not really a tar").unwrap();
+
+        let err = service
+            .import_distribution("Ubuntu", "D:\\WSL\\Ubuntu", &tar, &JobHandle::default())
+            .unwrap_err();
+        assert_eq!(err.code(), "PARSE_ERROR");
+        assert!(runner.calls().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// Scratch directory holding a fake move export
+    fn move_fixture() -> (PathBuf, String) {
+        let dir = std::env::temp_dir().join(format!("wsl-tamer-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let tar = dir.join("Ubuntu_move.tar");
+        std::fs::write(&tar, sample_rootfs_tar()).unwrap();
+        let tar = tar.to_string_lossy().to_string();
+        (dir, tar)
+    }
+
+    /// Service whose journal holds a move of Ubuntu interrupted after `step`
+    fn interrupted_move(
+        runner: Arc<ScriptedRunner>,
+        dir: &Path,
+        tar: &str,
+        step: MoveStep,
+        original_location: Option<&str>,
+    ) -> (WslService, MoveJournal) {
+        let store = MoveJournalStore::new(dir.join("moves"));
+        let mut journal = store
+            .begin(
+                "Ubuntu",
+                "D:\\WSL\\Ubuntu",
+                original_location.map(String::from),
+                tar.to_string(),
+            )
+            .unwrap();
+        store.advance(&mut journal, step).unwrap();
+
+        let service =
+            WslService::new(runner).with_move_journal(MoveJournalStore::new(dir.join("moves")));
+        (service, journal)
+    }
+
+    #[test]
+    fn test_resume_after_unregister_imports_under_final_name() {
+        let (dir, tar) = move_fixture();
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on(
+                    "wsl",
+                    &["--list", "--quiet"],
+                    CommandOutput::ok_utf16("Debian\r\n"),
+                )
+                .on(
+                    "wsl",
+                    &["--list", "--quiet"],
+                    CommandOutput::ok_utf16("Debian\r\nUbuntu\r\n"),
+                )
+                .on(
+                    "wsl",
+                    &["--import", "Ubuntu", "D:\\WSL\\Ubuntu", &tar],
+                    CommandOutput::ok(""),
+                ),
+        );
+        let (service, journal) =
+            interrupted_move(runner.clone(), &dir, &tar, MoveStep::Unregistered, None);
+
+        service
+            .resume_move(&journal.id, &JobHandle::default())
+            .unwrap();
+
+        assert!(runner.was_called("wsl", &["--import", "Ubuntu", "D:\\WSL\\Ubuntu", &tar]));
+        // No temporary "-wsl-tamer-moving" distro and no second export
+        assert!(!runner
+            .calls()
+            .iter()
+            .any(|c| c.args.first().map(String::as_str) == Some("--export")));
+        assert!(!Path::new(&tar).exists());
+        assert!(service.pending_moves().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_failed_import_restores_original() {
+        let (dir, tar) = move_fixture();
+        let runner = Arc::new(
+            ScriptedRunner::new()
+                .on(
+                    "wsl",
+                    &["--list", "--quiet"],
+                    CommandOutput::ok_utf16("Ubuntu\r\n"),
+                )
+                .on(
+                    "wsl",
+                    &["--list", "--quiet"],
+                    CommandOutput {
+                        exit_code: Some(-1),
+                        stdout: encode_utf16le(
+                            "Windows Subsystem for Linux has no installed distributions.\r\n",
+                        ),
+                        stderr: Vec::new(),
+                    },
+                )
+                .on("wsl", &["--unregister", "Ubuntu"], CommandOutput::ok(""))
+                .on(
+                    "wsl",
+                    &["--import", "Ubuntu", "D:\\WSL\\Ubuntu", &tar],
+                    CommandOutput::failed(
+                        1,
+                        encode_utf16le("There is not enough space on the disk."),
+                    ),
+                )
+                .on(
+                    "wsl",
+                    &["--import", "Ubuntu", "C:\\WSL\\Ubuntu", &tar],
+                    CommandOutput::ok(""),
+                ),
+        );
+        let (service, journal) = interrupted_move(
+            runner.clone(),
+            &dir,
+            &tar,
+            MoveStep::Exported,
+            Some("C:\\WSL\\Ubuntu"),
+        );
+
+        let err = service
+            .resume_move(&journal.id, &JobHandle::default())
+            .unwrap_err();
+
+        assert!(err.to_string().contains("the original was restored"));
+        assert!(runner.was_called("wsl", &["--import", "Ubuntu", "C:\\WSL\\Ubuntu", &tar]));
+        assert!(service.pending_moves().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rollback_before_unregister_keeps_original() {
+        let (dir, tar) = move_fixture();
+        let runner = Arc::new(ScriptedRunner::new().on(
+            "wsl",
+            &["--list", "--quiet"],
+            CommandOutput::ok_utf16("Ubuntu\r\n"),
+        ));
+        let (service, journal) =
+            interrupted_move(runner.clone(), &dir, &tar, MoveStep::Exported, None);
+
+        service
+            .rollback_move(&journal.id, &JobHandle::default())
+            .unwrap();
+
+        assert!(!runner.was_called("wsl", &["--unregister", "Ubuntu"]));
+        assert!(!Path::new(&tar).exists());
+        assert!(service.pending_moves().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_move_refused_while_previous_move_pending() {
+        let (dir, tar) = move_fixture();
+        let runner = Arc::new(ScriptedRunner::new());
+        let (service, _) = interrupted_move(runner.clone(), &dir, &tar, MoveStep::Exported, None);
+
+        let err = service
+            .move_distribution("Ubuntu", "E:\\WSL", &JobHandle::default())
+            .unwrap_err();
+        assert_eq!(err.code(), "INVALID_OPERATION");
+        assert!(runner.calls().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_running_move_is_not_pending() {
+        let (dir, tar) = move_fixture();
+        let runner = Arc::new(ScriptedRunner::new());
+        let (service, journal) =
+            interrupted_move(runner.clone(), &dir, &tar, MoveStep::Unregistered, None);
+
+        // The journal belongs to a job that is still importing
+        let running = service.claim_move(&journal).unwrap();
+        assert!(service.pending_moves().is_empty());
+        assert_eq!(
+            service.pending_move(&journal.id).unwrap_err().code(),
+            "INVALID_OPERATION"
+        );
+        for result in [
+            service.resume_move(&journal.id, &JobHandle::default()),
+            service.rollback_move(&journal.id, &JobHandle::default()),
+        ] {
+            assert_eq!(result.unwrap_err().code(), "INVALID_OPERATION");
+        }
+        assert!(runner.calls().is_empty());
+
+        // Once the job is gone, the journal is an interrupted move
+        drop(running);
+        assert_eq!(service.pending_moves().len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
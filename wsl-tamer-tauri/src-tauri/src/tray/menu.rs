@@ -4,12 +4,11 @@ use tauri::{
     AppHandle, Manager,
     menu::{Menu, MenuItem, Submenu, PredefinedMenuItem},
 };
-use crate::services::WslService;
-use crate::commands::get_profile_manager;
+use crate::commands::{activate_profile, get_profile_manager, get_wsl_service};
 
 /// Build the tray context menu
 pub fn build_tray_menu(app: &AppHandle) -> Result<Menu<tauri::Wry>, tauri::Error> {
-    let is_running = WslService::is_wsl_running();
+    let is_running = get_wsl_service().is_wsl_running();
     
     // Status indicator
     let status_text = if is_running { "● Running" } else { "○ Stopped" };
@@ -35,8 +34,8 @@ pub fn build_tray_menu(app: &AppHandle) -> Result<Menu<tauri::Wry>, tauri::Error
             let prefix = if current_id.as_ref() == Some(&profile.id) { "✓ " } else { "  " };
             let item = MenuItem::with_id(
                 app, 
-                &format!("profile_{}", profile.id),
-                &format!("{}{}", prefix, profile.name),
+                format!("profile_{}", profile.id),
+                format!("{}{}", prefix, profile.name),
                 true,
                 None::<&str>
             )?;
@@ -78,24 +77,24 @@ pub fn handle_tray_menu_event(app: &AppHandle, id: &str) {
     match id {
         "launch_wsl" => {
             // Launch default distro in terminal
-            if let Ok(distros) = WslService::get_distributions() {
+            if let Ok(distros) = get_wsl_service().get_distributions() {
                 if let Some(default) = distros.iter().find(|d| d.is_default) {
-                    let _ = WslService::start_distribution(&default.name);
+                    let _ = get_wsl_service().start_distribution(&default.name);
                 }
             }
         }
         "start_background" => {
-            if let Ok(distros) = WslService::get_distributions() {
+            if let Ok(distros) = get_wsl_service().get_distributions() {
                 if let Some(default) = distros.iter().find(|d| d.is_default) {
-                    let _ = WslService::start_distribution_background(&default.name);
+                    let _ = get_wsl_service().start_distribution_background(&default.name);
                 }
             }
         }
         "shutdown" => {
-            let _ = WslService::shutdown_all();
+            let _ = get_wsl_service().shutdown_all();
         }
         "reclaim" => {
-            let _ = WslService::reclaim_memory();
+            let _ = get_wsl_service().reclaim_memory();
         }
         "settings" => {
             // Show settings window
@@ -109,9 +108,7 @@ pub fn handle_tray_menu_event(app: &AppHandle, id: &str) {
         }
         id if id.starts_with("profile_") => {
             let profile_id = id.trim_start_matches("profile_");
-            if let Some(profile) = get_profile_manager().get_profile(profile_id) {
-                let _ = WslService::apply_profile(&profile);
-                let _ = get_profile_manager().set_current_profile(profile_id);
+            if activate_profile(profile_id, None).is_ok() {
                 // Rebuild menu to update checkmarks
                 if let Some(tray) = app.tray_by_id("main") {
                     if let Ok(menu) = build_tray_menu(app) {
@@ -0,0 +1,60 @@
+//! Cooperative cancellation and time limits for long-running commands
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Shared flag used to ask a running operation to stop
+///
+/// Clones share the same flag, so the UI side can keep one copy and hand
+/// another to the worker thread.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Limits applied to a single process run
+#[derive(Debug, Clone, Default)]
+pub struct RunControl {
+    /// Kill the process if it runs longer than this (`None` = no limit)
+    pub timeout: Option<Duration>,
+    /// Kill the process when this token is cancelled
+    pub cancel: CancellationToken,
+}
+
+impl RunControl {
+    pub fn new(timeout: Option<Duration>, cancel: CancellationToken) -> Self {
+        Self { timeout, cancel }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_clones_share_state() {
+        let token = CancellationToken::new();
+        let other = token.clone();
+        assert!(!other.is_cancelled());
+
+        token.cancel();
+        assert!(other.is_cancelled());
+    }
+}
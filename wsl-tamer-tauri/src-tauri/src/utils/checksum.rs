@@ -0,0 +1,139 @@
+//! File checksums
+
+use crate::error::{TamerError, TamerResult};
+use crate::utils::CancellationToken;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// Hex-encoded SHA-256 of a file, streamed in 1 MiB chunks
+///
+/// Checks `cancel` between chunks so hashing a multi-gigabyte export can be
+/// interrupted.
+pub fn sha256_file(path: &Path, cancel: &CancellationToken) -> TamerResult<String> {
+    let mut file = File::open(path)
+        .map_err(|e| TamerError::io(format!("Failed to open {}", path.display()), e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1024 * 1024];
+
+    loop {
+        if cancel.is_cancelled() {
+            return Err(TamerError::Cancelled);
+        }
+        let read = file
+            .read(&mut buf)
+            .map_err(|e| TamerError::io(format!("Failed to read {}", path.display()), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Writer that hashes everything passing through it
+pub struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+    bytes: u64,
+}
+
+impl<W: Write> HashingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+            bytes: 0,
+        }
+    }
+
+    /// Flush and return the hex-encoded SHA-256 and byte count
+    pub fn finish(mut self) -> io::Result<(String, u64)> {
+        self.inner.flush()?;
+        Ok((format!("{:x}", self.hasher.finalize()), self.bytes))
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reader that hashes everything read through it
+pub struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+    bytes: u64,
+}
+
+impl<R: Read> HashingReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+            bytes: 0,
+        }
+    }
+
+    /// Hex-encoded SHA-256 and byte count of everything read so far
+    pub fn finish(self) -> (String, u64) {
+        (format!("{:x}", self.hasher.finalize()), self.bytes)
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        self.bytes += read as u64;
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_file() {
+        let path = std::env::temp_dir().join(format!("wsl-tamer-sha-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"abc").unwrap();
+        let hash = sha256_file(&path, &CancellationToken::new()).unwrap();
+        assert_eq!(
+            hash,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        assert_eq!(sha256_file(&path, &cancel).unwrap_err().code(), "CANCELLED");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_hashing_writer_and_reader_agree() {
+        let mut writer = HashingWriter::new(Vec::new());
+        writer.write_all(b"ab").unwrap();
+        writer.write_all(b"c").unwrap();
+        let (written, bytes) = writer.finish().unwrap();
+        assert_eq!(bytes, 3);
+
+        let mut reader = HashingReader::new(&b"abc"[..]);
+        io::copy(&mut reader, &mut io::sink()).unwrap();
+        assert_eq!(reader.finish(), (written.clone(), 3));
+        assert_eq!(
+            written,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Streaming compression for exported tarballs
+
+use crate::models::Compression;
+use flate2::bufread::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use std::io::{self, BufRead, Read, Write};
+use xz2::bufread::XzDecoder;
+use xz2::write::XzEncoder;
+
+enum Encoder<W: Write> {
+    Plain(W),
+    Gzip(GzEncoder<W>),
+    Zstd(zstd::Encoder<'static, W>),
+    Xz(XzEncoder<W>),
+}
+
+/// Writer that compresses everything written to it into `W`
+///
+/// `finish` must be called to write the end of the compressed stream.
+pub struct Compressor<W: Write> {
+    encoder: Encoder<W>,
+    bytes_in: u64,
+}
+
+impl<W: Write> Compressor<W> {
+    /// `level` must be within `compression.level_range()`
+    pub fn new(inner: W, compression: Compression, level: i32) -> io::Result<Self> {
+        let encoder = match compression {
+            Compression::None => Encoder::Plain(inner),
+            Compression::Gzip => Encoder::Gzip(GzEncoder::new(
+                inner,
+                flate2::Compression::new(level as u32),
+            )),
+            Compression::Zstd => Encoder::Zstd(zstd::Encoder::new(inner, level)?),
+            Compression::Xz => Encoder::Xz(XzEncoder::new(inner, level as u32)),
+        };
+        Ok(Self {
+            encoder,
+            bytes_in: 0,
+        })
+    }
+
+    /// Complete the compressed stream and return the inner writer and the
+    /// number of uncompressed bytes written
+    pub fn finish(self) -> io::Result<(W, u64)> {
+        let inner = match self.encoder {
+            Encoder::Plain(inner) => inner,
+            Encoder::Gzip(encoder) => encoder.finish()?,
+            Encoder::Zstd(encoder) => encoder.finish()?,
+            Encoder::Xz(encoder) => encoder.finish()?,
+        };
+        Ok((inner, self.bytes_in))
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match &mut self.encoder {
+            Encoder::Plain(inner) => inner,
+            Encoder::Gzip(encoder) => encoder,
+            Encoder::Zstd(encoder) => encoder,
+            Encoder::Xz(encoder) => encoder,
+        }
+    }
+}
+
+impl<W: Write> Write for Compressor<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.writer().write(buf)?;
+        self.bytes_in += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer().flush()
+    }
+}
+
+enum Decoder<R: BufRead> {
+    Plain(R),
+    Gzip(MultiGzDecoder<R>),
+    Zstd(zstd::Decoder<'static, R>),
+    Xz(XzDecoder<R>),
+}
+
+/// Reader that decompresses `R`
+///
+/// Corrupt input surfaces as an `InvalidData` (or `InvalidInput`) read error.
+pub struct Decompressor<R: BufRead> {
+    decoder: Decoder<R>,
+    bytes_out: u64,
+}
+
+impl<R: BufRead> Decompressor<R> {
+    pub fn new(inner: R, compression: Compression) -> io::Result<Self> {
+        let decoder = match compression {
+            Compression::None => Decoder::Plain(inner),
+            Compression::Gzip => Decoder::Gzip(MultiGzDecoder::new(inner)),
+            Compression::Zstd => Decoder::Zstd(zstd::Decoder::with_buffer(inner)?),
+            Compression::Xz => Decoder::Xz(XzDecoder::new_multi_decoder(inner)),
+        };
+        Ok(Self {
+            decoder,
+            bytes_out: 0,
+        })
+    }
+
+    /// Number of decompressed bytes read so far
+    pub fn bytes_out(&self) -> u64 {
+        self.bytes_out
+    }
+}
+
+impl<R: BufRead> Read for Decompressor<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = match &mut self.decoder {
+            Decoder::Plain(inner) => inner.read(buf)?,
+            Decoder::Gzip(decoder) => decoder.read(buf)?,
+            Decoder::Zstd(decoder) => decoder.read(buf)?,
+            Decoder::Xz(decoder) => decoder.read(buf)?,
+        };
+        self.bytes_out += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_every_format() {
+        let data = b"root:x:0:0:root:/root:/bin/bash\n".repeat(512);
+        for compression in [
+            Compression::None,
+            Compression::Gzip,
+            Compression::Zstd,
+            Compression::Xz,
+        ] {
+            let level = *compression.level_range().end();
+            let mut compressor = Compressor::new(Vec::new(), compression, level).unwrap();
+            compressor.write_all(&data).unwrap();
+            let (compressed, bytes_in) = compressor.finish().unwrap();
+            assert_eq!(bytes_in, data.len() as u64);
+            if compression != Compression::None {
+                assert!(compressed.len() < data.len() / 4, "{:?}", compression);
+            }
+
+            let mut decompressor = Decompressor::new(&compressed[..], compression).unwrap();
+            let mut out = Vec::new();
+            decompressor.read_to_end(&mut out).unwrap();
+            assert_eq!(out, data);
+            assert_eq!(decompressor.bytes_out(), data.len() as u64);
+        }
+    }
+
+    #[test]
+    fn test_corrupt_stream_is_a_read_error() {
+        let mut decompressor =
+            Decompressor::new(&b"definitely not zstd"[..], Compression::Zstd).unwrap();
+        assert!(decompressor.read_to_end(&mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+//! Line diffs of small text files
+
+use crate::models::{DiffKind, DiffLine};
+
+/// Line-by-line diff of `old` to `new` (longest common subsequence)
+///
+/// Meant for configuration files: time and memory grow with the product of
+/// the line counts.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j]: length of the LCS of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let line = |kind, text: &str| DiffLine {
+        kind,
+        text: text.to_string(),
+    };
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(line(DiffKind::Unchanged, old[i]));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            lines.push(line(DiffKind::Removed, old[i]));
+            i += 1;
+        } else {
+            lines.push(line(DiffKind::Added, new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|t| line(DiffKind::Removed, t)));
+    lines.extend(new[j..].iter().map(|t| line(DiffKind::Added, t)));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(lines: &[DiffLine]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|l| {
+                let sign = match l.kind {
+                    DiffKind::Unchanged => ' ',
+                    DiffKind::Added => '+',
+                    DiffKind::Removed => '-',
+                };
+                format!("{}{}", sign, l.text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let old = "[wsl2]\nmemory=8GB\nswap=0\n";
+        let new = "[wsl2]\nmemory=12GB\nswap=0\nprocessors=4\n";
+        assert_eq!(
+            render(&diff_lines(old, new)),
+            vec![
+                " [wsl2]",
+                "-memory=8GB",
+                "+memory=12GB",
+                " swap=0",
+                "+processors=4"
+            ]
+        );
+
+        assert!(diff_lines("", "").is_empty());
+        assert_eq!(render(&diff_lines("", "a\r\nb")), vec!["+a", "+b"]);
+        assert_eq!(render(&diff_lines("a", "")), vec!["-a"]);
+    }
+}
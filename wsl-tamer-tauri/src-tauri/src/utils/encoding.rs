@@ -28,7 +28,25 @@ pub fn decode_utf16le(bytes: &[u8]) -> String {
         .collect();
 
     let decoded = String::from_utf16_lossy(&utf16);
-    decoded.replace('\0', "").replace('\r', "")
+    decoded.replace(['\0', '\r'], "")
+}
+
+/// Decode output that is either wsl.exe's own (UTF-16LE) or that of a
+/// program run inside a distribution (UTF-8)
+///
+/// UTF-8 text never contains NUL bytes, while UTF-16LE text of any ASCII
+/// message does.
+pub fn decode_wsl_output(bytes: &[u8]) -> String {
+    if bytes.contains(&0) {
+        decode_utf16le(bytes)
+    } else {
+        String::from_utf8_lossy(bytes).replace('\r', "")
+    }
+}
+
+/// Encode text as UTF-16LE bytes (inverse of `decode_utf16le`, no BOM)
+pub fn encode_utf16le(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(|u| u.to_le_bytes()).collect()
 }
 
 /// Clean a distribution name by removing non-printable characters
@@ -53,6 +71,25 @@ mod tests {
         assert_eq!(decode_utf16le(&bytes), "Ubuntu");
     }
 
+    #[test]
+    fn test_decode_wsl_output() {
+        assert_eq!(
+            decode_wsl_output(&encode_utf16le("Übersicht\r\n")),
+            "Übersicht\n"
+        );
+        assert_eq!(
+            decode_wsl_output("kill: (42) - Keine Berechtigung\r\n".as_bytes()),
+            "kill: (42) - Keine Berechtigung\n"
+        );
+        assert_eq!(decode_wsl_output(b""), "");
+    }
+
+    #[test]
+    fn test_encode_utf16le_roundtrip() {
+        let text = "  NAME      STATE\n* Ubuntu    Running";
+        assert_eq!(decode_utf16le(&encode_utf16le(text)), text);
+    }
+
     #[test]
     fn test_clean_distro_name() {
         assert_eq!(clean_distro_name("Ubuntu\0\0"), "Ubuntu");
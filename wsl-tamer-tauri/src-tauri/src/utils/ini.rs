@@ -0,0 +1,343 @@
+//! Lossless INI documents for `.wslconfig` and `wsl.conf`
+//!
+//! The file is kept as its original lines. Reading never changes it and
+//! editing rewrites only the lines of the keys being set or removed, so
+//! comments, ordering, unknown keys and sections, and line endings survive a
+//! save byte for byte.
+
+use std::fmt;
+
+/// A line of the document, without its line ending
+#[derive(Debug, Clone, PartialEq)]
+struct IniLine {
+    text: String,
+    /// `"\n"`, `"\r\n"`, or empty for a last line without one
+    ending: String,
+}
+
+/// What a line holds
+#[derive(Debug, PartialEq)]
+enum LineKind<'a> {
+    Section(&'a str),
+    Entry {
+        key: &'a str,
+        value: &'a str,
+    },
+    /// Blank line, comment, or text that isn't INI
+    Other,
+}
+
+impl IniLine {
+    fn kind(&self) -> LineKind<'_> {
+        let line = self.text.trim_start_matches('\u{feff}').trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            return LineKind::Other;
+        }
+        if let Some(rest) = line.strip_prefix('[') {
+            if let Some(end) = rest.find(']') {
+                return LineKind::Section(rest[..end].trim());
+            }
+        }
+        match line.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => LineKind::Entry {
+                key: key.trim(),
+                value: value.trim(),
+            },
+            _ => LineKind::Other,
+        }
+    }
+}
+
+/// An INI file that can be edited without losing anything else in it
+///
+/// Section and key names match case-insensitively, like WSL's own parser.
+/// When a key occurs more than once, the last occurrence wins.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IniDocument {
+    lines: Vec<IniLine>,
+}
+
+impl IniDocument {
+    pub fn parse(content: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut rest = content;
+        while !rest.is_empty() {
+            let (line, ending, next) = match rest.find('\n') {
+                Some(i) if rest[..i].ends_with('\r') => (&rest[..i - 1], "\r\n", &rest[i + 1..]),
+                Some(i) => (&rest[..i], "\n", &rest[i + 1..]),
+                None => (rest, "", ""),
+            };
+            lines.push(IniLine {
+                text: line.to_string(),
+                ending: ending.to_string(),
+            });
+            rest = next;
+        }
+        Self { lines }
+    }
+
+    /// Value of `key` in `section`
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.find(section, key).map(|i| match self.lines[i].kind() {
+            LineKind::Entry { value, .. } => value,
+            _ => unreachable!("find only returns entries"),
+        })
+    }
+
+    /// Names of the sections, in file order (repeated sections once)
+    pub fn sections(&self) -> Vec<&str> {
+        let mut sections: Vec<&str> = Vec::new();
+        for line in &self.lines {
+            if let LineKind::Section(name) = line.kind() {
+                if !sections.iter().any(|s| s.eq_ignore_ascii_case(name)) {
+                    sections.push(name);
+                }
+            }
+        }
+        sections
+    }
+
+    /// `(key, value)` pairs of a section, in file order
+    pub fn entries(&self, section: &str) -> Vec<(&str, &str)> {
+        let mut entries = Vec::new();
+        for (current, line) in self.walk() {
+            if let (Some(current), LineKind::Entry { key, value }) = (current, line.kind()) {
+                if current.eq_ignore_ascii_case(section) {
+                    entries.push((key, value));
+                }
+            }
+        }
+        entries
+    }
+
+    /// Set `key` in `section`, adding the key (and section) if missing
+    ///
+    /// An existing line keeps everything up to its `=` and the spacing after
+    /// it; a value identical to the current one leaves the line untouched.
+    /// Values differing only in case are still written, since not every
+    /// setting treats them alike.
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        if let Some(i) = self.find(section, key) {
+            let line = &mut self.lines[i];
+            if matches!(line.kind(), LineKind::Entry { value: old, .. } if old == value) {
+                return;
+            }
+            let eq = line.text.find('=').expect("entries contain '='");
+            let after = &line.text[eq + 1..];
+            let prefix_len = eq + 1 + after.len() - after.trim_start().len();
+            line.text = format!("{}{}", &line.text[..prefix_len], value);
+            return;
+        }
+
+        let entry = format!("{}={}", key, value);
+        match self.section_end(section) {
+            Some(at) => self.insert(at, entry),
+            None => {
+                if self.lines.last().is_some_and(|l| !l.text.trim().is_empty()) {
+                    self.insert(self.lines.len(), String::new());
+                }
+                self.insert(self.lines.len(), format!("[{}]", section));
+                self.insert(self.lines.len(), entry);
+            }
+        }
+    }
+
+    /// Remove every occurrence of `key` from `section`
+    pub fn remove(&mut self, section: &str, key: &str) {
+        while let Some(i) = self.find(section, key) {
+            let removed = self.lines.remove(i);
+            if i == self.lines.len() {
+                if let Some(last) = self.lines.last_mut() {
+                    last.ending = removed.ending;
+                }
+            }
+        }
+    }
+
+    /// Set `key` to `value`, or remove it for `None`
+    pub fn set_opt(&mut self, section: &str, key: &str, value: Option<impl ToString>) {
+        match value {
+            Some(value) => self.set(section, key, &value.to_string()),
+            None => self.remove(section, key),
+        }
+    }
+
+    /// Lines with the section each one is in (`None` before the first header)
+    fn walk(&self) -> impl Iterator<Item = (Option<&str>, &IniLine)> {
+        let mut current = None;
+        self.lines.iter().map(move |line| {
+            if let LineKind::Section(name) = line.kind() {
+                current = Some(name);
+            }
+            (current, line)
+        })
+    }
+
+    /// Index of the last occurrence of `key` in `section`
+    fn find(&self, section: &str, key: &str) -> Option<usize> {
+        self.walk()
+            .enumerate()
+            .filter(|(_, (current, line))| {
+                current.is_some_and(|c| c.eq_ignore_ascii_case(section))
+                    && matches!(line.kind(), LineKind::Entry { key: k, .. } if k.eq_ignore_ascii_case(key))
+            })
+            .map(|(i, _)| i)
+            .last()
+    }
+
+    /// Where a new key of `section` goes: after the last non-blank line of
+    /// its last occurrence, so blank lines before the next section stay put
+    fn section_end(&self, section: &str) -> Option<usize> {
+        let mut end = None;
+        let mut in_section = false;
+        for (i, line) in self.lines.iter().enumerate() {
+            match line.kind() {
+                LineKind::Section(name) => {
+                    in_section = name.eq_ignore_ascii_case(section);
+                    if in_section {
+                        end = Some(i + 1);
+                    }
+                }
+                _ if in_section && !line.text.trim().is_empty() => end = Some(i + 1),
+                _ => {}
+            }
+        }
+        end
+    }
+
+    /// Insert a line, using the document's line ending
+    fn insert(&mut self, at: usize, text: String) {
+        let newline = self
+            .lines
+            .iter()
+            .map(|l| l.ending.as_str())
+            .find(|e| !e.is_empty())
+            .unwrap_or("\n")
+            .to_string();
+        let ending = if at == self.lines.len() {
+            // Appending: the previous last line needs an ending now, and the
+            // new one takes over whether the file ended with a newline
+            match self.lines.last_mut() {
+                Some(last) => std::mem::replace(&mut last.ending, newline),
+                None => newline,
+            }
+        } else {
+            newline
+        };
+        self.lines.insert(at, IniLine { text, ending });
+    }
+}
+
+impl fmt::Display for IniDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            f.write_str(&line.text)?;
+            f.write_str(&line.ending)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WSLCONFIG: &str = "\
+# Settings apply across all Linux distros running on WSL 2\r
+[wsl2]\r
+\r
+# Limits VM memory\r
+memory = 8GB\r
+processors=4\r
+kernelModules=C:\\\\modules.vhdx\r
+vmIdleTimeout=60000\r
+\r
+[experimental]\r
+autoMemoryReclaim=gradual\r
+hostAddressLoopback=true\r
+\r
+[custom]\r
+anything goes here\r
+";
+
+    #[test]
+    fn test_untouched_document_round_trips() {
+        for content in [WSLCONFIG, "", "[wsl2]\nmemory=4GB", "\u{feff}[wsl2]\n\n\n"] {
+            assert_eq!(IniDocument::parse(content).to_string(), content);
+        }
+    }
+
+    #[test]
+    fn test_get_and_entries() {
+        let doc = IniDocument::parse(WSLCONFIG);
+        assert_eq!(doc.get("wsl2", "memory"), Some("8GB"));
+        assert_eq!(doc.get("WSL2", "Processors"), Some("4"));
+        assert_eq!(
+            doc.get("experimental", "autoMemoryReclaim"),
+            Some("gradual")
+        );
+        assert_eq!(doc.get("wsl2", "autoMemoryReclaim"), None);
+        assert_eq!(doc.sections(), vec!["wsl2", "experimental", "custom"]);
+        assert_eq!(doc.entries("experimental").len(), 2);
+
+        let doc = IniDocument::parse("[wsl2]\nswap=1GB\n[wsl2]\nswap=2GB\n");
+        assert_eq!(doc.get("wsl2", "swap"), Some("2GB"));
+    }
+
+    #[test]
+    fn test_set_edits_only_the_touched_line() {
+        let mut doc = IniDocument::parse(WSLCONFIG);
+        doc.set("wsl2", "memory", "16GB");
+        doc.set("wsl2", "processors", "4");
+        assert_eq!(
+            doc.to_string(),
+            WSLCONFIG.replace("memory = 8GB", "memory = 16GB")
+        );
+
+        // A change of case alone is still a change
+        doc.set("wsl2", "memory", "16gb");
+        assert_eq!(
+            doc.to_string(),
+            WSLCONFIG.replace("memory = 8GB", "memory = 16gb")
+        );
+    }
+
+    #[test]
+    fn test_set_adds_keys_and_sections() {
+        let mut doc = IniDocument::parse(WSLCONFIG);
+        doc.set("wsl2", "swap", "0");
+        doc.set("experimental", "sparseVhd", "true");
+        doc.set("network", "generateHosts", "false");
+        let expected = WSLCONFIG
+            .replace(
+                "vmIdleTimeout=60000\r\n",
+                "vmIdleTimeout=60000\r\nswap=0\r\n",
+            )
+            .replace(
+                "hostAddressLoopback=true\r\n",
+                "hostAddressLoopback=true\r\nsparseVhd=true\r\n",
+            )
+            + "\r\n[network]\r\ngenerateHosts=false\r\n";
+        assert_eq!(doc.to_string(), expected);
+
+        let mut doc = IniDocument::default();
+        doc.set("wsl2", "memory", "4GB");
+        assert_eq!(doc.to_string(), "[wsl2]\nmemory=4GB\n");
+
+        // No trailing newline stays that way
+        let mut doc = IniDocument::parse("[wsl2]\nmemory=4GB");
+        doc.set("wsl2", "swap", "0");
+        assert_eq!(doc.to_string(), "[wsl2]\nmemory=4GB\nswap=0");
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut doc = IniDocument::parse("[wsl2]\nswap=1GB\nmemory=4GB\n[wsl2]\nswap=2GB");
+        doc.remove("wsl2", "swap");
+        assert_eq!(doc.to_string(), "[wsl2]\nmemory=4GB\n[wsl2]");
+
+        doc.set_opt("wsl2", "memory", None::<String>);
+        doc.set_opt("wsl2", "processors", Some(2));
+        assert_eq!(doc.to_string(), "[wsl2]\n[wsl2]\nprocessors=2");
+    }
+}
@@ -3,9 +3,31 @@
 mod process;
 mod encoding;
 mod sanitize;
+mod runner;
+mod cancel;
+mod time;
+mod paths;
+mod checksum;
+mod compression;
+mod tarball;
+mod registry;
+mod format;
+mod ini;
+mod diff;
 pub mod rate_limit;
 
 pub use process::*;
 pub use encoding::*;
 pub use sanitize::*;
+pub use runner::*;
+pub use cancel::*;
+pub use time::*;
+pub use paths::*;
+pub use checksum::*;
+pub use compression::*;
+pub use tarball::*;
+pub use registry::*;
+pub use format::*;
+pub use ini::*;
+pub use diff::*;
 
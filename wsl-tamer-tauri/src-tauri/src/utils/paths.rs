@@ -0,0 +1,14 @@
+//! Well-known application paths
+
+use std::path::PathBuf;
+
+/// Tauri bundle identifier, used as the app's data folder name
+const APP_IDENTIFIER: &str = "com.ryanhaver.wsl-tamer";
+
+/// Per-user data directory for state that must survive restarts
+/// (`%LOCALAPPDATA%\com.ryanhaver.wsl-tamer` on Windows)
+pub fn app_data_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(APP_IDENTIFIER)
+}
@@ -1,66 +1,259 @@
 //! Process execution utilities
 
-use crate::utils::decode_utf16le;
-use std::process::{Command, Stdio};
+use crate::error::{TamerError, TamerResult};
+use crate::utils::{decode_utf16le, decode_wsl_output, CommandOutput, CommandRunner, RunControl};
+use std::io::{self, ErrorKind, Read, Write};
+use std::time::Duration;
 
-/// Result type for process operations
-pub type ProcessResult<T> = Result<T, String>;
+/// wsl.exe flags whose value is a distribution name
+const DISTRO_FLAGS: &[&str] = &[
+    "-d",
+    "--distribution",
+    "-t",
+    "--terminate",
+    "-s",
+    "--set-default",
+    "--unregister",
+    "--export",
+];
 
 /// Run a WSL command and return the output
-pub fn run_wsl_command(args: &[&str]) -> ProcessResult<String> {
-    let output = Command::new("wsl")
-        .args(args)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .creation_flags(0x08000000) // CREATE_NO_WINDOW on Windows
-        .output()
-        .map_err(|e| format!("Failed to execute wsl command: {}", e))?;
+pub fn run_wsl_command(runner: &dyn CommandRunner, args: &[&str]) -> TamerResult<String> {
+    wsl_result(args, runner.output("wsl", args), None)
+}
+
+/// Run a WSL command under a timeout and cancellation token
+pub fn run_wsl_command_controlled(
+    runner: &dyn CommandRunner,
+    args: &[&str],
+    control: &RunControl,
+) -> TamerResult<String> {
+    wsl_result(
+        args,
+        runner.output_controlled("wsl", args, control),
+        control.timeout,
+    )
+}
+
+/// Run a WSL command under a timeout and cancellation token, streaming its
+/// stdout into `sink` (e.g. `wsl --export <name> -`)
+///
+/// A failure to write to `sink` is reported as an I/O error rather than as a
+/// wsl.exe failure.
+pub fn run_wsl_command_to_writer(
+    runner: &dyn CommandRunner,
+    args: &[&str],
+    sink: &mut (dyn Write + Send),
+    control: &RunControl,
+) -> TamerResult<()> {
+    let mut sink = ErrorTrap::new(sink);
+    let result = runner.output_to_writer("wsl", args, &mut sink, control);
+    if let Some(e) = sink.error {
+        return Err(TamerError::io("Failed to write wsl.exe output", e));
+    }
+    wsl_result(args, result, control.timeout).map(|_| ())
+}
+
+/// Run a WSL command under a timeout and cancellation token, streaming
+/// `source` into its stdin (e.g. `wsl --import <name> <dir> -`)
+///
+/// A failure to read `source` is reported as an I/O error rather than as a
+/// wsl.exe failure.
+pub fn run_wsl_command_from_reader(
+    runner: &dyn CommandRunner,
+    args: &[&str],
+    source: &mut (dyn Read + Send),
+    control: &RunControl,
+) -> TamerResult<String> {
+    let mut source = ErrorTrap::new(source);
+    let result = runner.output_from_reader("wsl", args, &mut source, control);
+    if let Some(e) = source.error {
+        return Err(TamerError::io("Failed to read wsl.exe input", e));
+    }
+    wsl_result(args, result, control.timeout)
+}
+
+/// Run a program inside a distribution under a timeout and cancellation
+/// token, feeding `input` to its stdin, and return its stdout as written
+///
+/// Meant for `wsl -d <name> -- sh -s` with a script on stdin; unlike
+/// wsl.exe's own messages, the output is the program's (usually UTF-8).
+pub fn run_wsl_command_with_input(
+    runner: &dyn CommandRunner,
+    args: &[&str],
+    input: &[u8],
+    control: &RunControl,
+) -> TamerResult<Vec<u8>> {
+    let result = runner.output_from_reader("wsl", args, &mut &input[..], control);
+    wsl_output(args, result, control.timeout).map(|output| output.stdout)
+}
+
+/// Reader or writer that remembers the first error of the one it wraps
+struct ErrorTrap<'a, T: ?Sized> {
+    inner: &'a mut T,
+    error: Option<io::Error>,
+}
+
+impl<'a, T: ?Sized> ErrorTrap<'a, T> {
+    fn new(inner: &'a mut T) -> Self {
+        Self { inner, error: None }
+    }
+
+    fn trap<R>(&mut self, result: io::Result<R>) -> io::Result<R> {
+        result.map_err(|e| {
+            let copy = io::Error::new(e.kind(), e.to_string());
+            self.error.get_or_insert(e);
+            copy
+        })
+    }
+}
 
-    if !output.status.success() {
-        let stderr = decode_utf16le(&output.stderr);
-        return Err(format!("WSL command failed: {}", stderr.trim()));
+impl<T: Write + ?Sized> Write for ErrorTrap<'_, T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let result = self.inner.write(buf);
+        self.trap(result)
     }
 
-    Ok(decode_utf16le(&output.stdout))
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.inner.flush();
+        self.trap(result)
+    }
+}
+
+impl<T: Read + ?Sized> Read for ErrorTrap<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let result = self.inner.read(buf);
+        self.trap(result)
+    }
+}
+
+/// Classify the outcome of a wsl.exe invocation
+fn wsl_result(
+    args: &[&str],
+    output: io::Result<CommandOutput>,
+    timeout: Option<Duration>,
+) -> TamerResult<String> {
+    wsl_output(args, output, timeout).map(|output| decode_utf16le(&output.stdout))
+}
+
+/// Classify the outcome of a wsl.exe invocation, keeping stdout undecoded
+fn wsl_output(
+    args: &[&str],
+    output: io::Result<CommandOutput>,
+    timeout: Option<Duration>,
+) -> TamerResult<CommandOutput> {
+    let output = output.map_err(|e| match e.kind() {
+        ErrorKind::NotFound => TamerError::WslNotInstalled,
+        ErrorKind::TimedOut => TamerError::TimedOut {
+            command: format_command("wsl", args),
+            timeout_secs: timeout.map(|t| t.as_secs()).unwrap_or_default(),
+        },
+        ErrorKind::Interrupted => TamerError::Cancelled,
+        _ => TamerError::CommandUnavailable {
+            program: "wsl".to_string(),
+            message: e.to_string(),
+        },
+    })?;
+
+    if !output.success() {
+        let stderr = decode_wsl_output(&output.stderr).trim().to_string();
+        if is_distro_not_found(&stderr) {
+            if let Some(name) = target_distro(args) {
+                return Err(TamerError::DistroNotFound {
+                    name: name.to_string(),
+                });
+            }
+        }
+        return Err(TamerError::CommandFailed {
+            command: format_command("wsl", args),
+            exit_code: output.exit_code,
+            stderr,
+        });
+    }
+
+    Ok(output)
 }
 
 /// Run a PowerShell command and return the output
-pub fn run_powershell_command(script: &str) -> ProcessResult<String> {
-    let output = Command::new("powershell")
-        .args(["-NoProfile", "-Command", script])
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .creation_flags(0x08000000)
-        .output()
-        .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
-
-    if !output.status.success() {
+pub fn run_powershell_command(runner: &dyn CommandRunner, script: &str) -> TamerResult<String> {
+    let output = runner
+        .output("powershell", &["-NoProfile", "-Command", script])
+        .map_err(|e| TamerError::CommandUnavailable {
+            program: "powershell".to_string(),
+            message: e.to_string(),
+        })?;
+
+    if !output.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("PowerShell command failed: {}", stderr.trim()));
+        return Err(TamerError::CommandFailed {
+            command: "powershell".to_string(),
+            exit_code: output.exit_code,
+            stderr: stderr.trim().to_string(),
+        });
     }
 
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
+/// Exit code the elevation script uses when the UAC prompt is dismissed
+/// (Windows' `ERROR_CANCELLED`)
+const ELEVATION_DECLINED: i32 = 1223;
+
 /// Run a command with elevated privileges (UAC prompt)
-pub fn run_elevated(program: &str, args: &[&str]) -> ProcessResult<()> {
-    let args_str = args.join("\" \"");
+///
+/// The elevated program's exit code is passed back, so a failure is reported
+/// as `CommandFailed` and a dismissed prompt as `ElevationCancelled`.
+pub fn run_elevated(runner: &dyn CommandRunner, program: &str, args: &[&str]) -> TamerResult<()> {
+    // Both go inside single-quoted PowerShell strings, where ' is written ''
+    let quote = |s: &str| s.replace('\'', "''");
+    let args_str = quote(&args.join("\" \""));
     let script = format!(
-        "Start-Process '{}' -ArgumentList '\"{}\"' -Verb RunAs -Wait",
-        program, args_str
+        "try {{ $p = Start-Process '{}' -ArgumentList '\"{}\"' -Verb RunAs -Wait -PassThru -ErrorAction Stop }} catch {{ exit {} }}; exit $p.ExitCode",
+        quote(program),
+        args_str,
+        ELEVATION_DECLINED
     );
 
-    let output = Command::new("powershell")
-        .args(["-NoProfile", "-Command", &script])
-        .creation_flags(0x08000000)
-        .status()
-        .map_err(|e| format!("Failed to run elevated command: {}", e))?;
+    let output = runner
+        .output("powershell", &["-NoProfile", "-Command", &script])
+        .map_err(|e| TamerError::CommandUnavailable {
+            program: "powershell".to_string(),
+            message: e.to_string(),
+        })?;
 
-    if !output.success() {
-        return Err("Elevated command failed or was cancelled".to_string());
+    match output.exit_code {
+        Some(0) => Ok(()),
+        Some(ELEVATION_DECLINED) => Err(TamerError::ElevationCancelled),
+        exit_code => Err(TamerError::CommandFailed {
+            command: format_command(program, args),
+            exit_code,
+            stderr: match String::from_utf8_lossy(&output.stderr).trim() {
+                "" => format!("exited with code {}", exit_code.unwrap_or(-1)),
+                stderr => stderr.to_string(),
+            },
+        }),
     }
+}
+
+/// Render a program and its arguments for error reporting
+pub fn format_command(program: &str, args: &[&str]) -> String {
+    std::iter::once(program)
+        .chain(args.iter().copied())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-    Ok(())
+/// Whether wsl.exe stderr reports an unknown distribution
+fn is_distro_not_found(stderr: &str) -> bool {
+    stderr.contains("WSL_E_DISTRO_NOT_FOUND")
+        || stderr.contains("There is no distribution with the supplied name")
+}
+
+/// Distribution name targeted by a wsl.exe invocation, if any
+fn target_distro<'a>(args: &[&'a str]) -> Option<&'a str> {
+    args.windows(2)
+        .find(|pair| DISTRO_FLAGS.contains(&pair[0]))
+        .map(|pair| pair[1])
 }
 
 /// Check if a process is running by name
@@ -103,31 +296,182 @@ pub fn is_process_running(name: &str) -> bool {
             let _ = CloseHandle(snapshot);
         }
     }
+    #[cfg(not(windows))]
+    let _ = name;
     false
 }
 
-/// Windows-specific creation flags
-#[cfg(windows)]
-trait CommandExt {
-    fn creation_flags(&mut self, flags: u32) -> &mut Self;
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{encode_utf16le, ScriptedRunner};
 
-#[cfg(windows)]
-impl CommandExt for Command {
-    fn creation_flags(&mut self, flags: u32) -> &mut Self {
-        use std::os::windows::process::CommandExt as WinCommandExt;
-        WinCommandExt::creation_flags(self, flags)
+    #[test]
+    fn test_missing_wsl_is_not_installed() {
+        let runner = ScriptedRunner::new();
+        let err = run_wsl_command(&runner, &["--status"]).unwrap_err();
+        assert_eq!(err.code(), "WSL_NOT_INSTALLED");
     }
-}
 
-#[cfg(not(windows))]
-trait CommandExt {
-    fn creation_flags(&mut self, _flags: u32) -> &mut Self;
-}
+    #[test]
+    fn test_unknown_distro_is_classified() {
+        let runner = ScriptedRunner::new().on(
+            "wsl",
+            &["--terminate", "Arch"],
+            CommandOutput::failed(
+                -1,
+                encode_utf16le(
+                    "There is no distribution with the supplied name.\r\nError code: Wsl/Service/WSL_E_DISTRO_NOT_FOUND\r\n",
+                ),
+            ),
+        );
+        let err = run_wsl_command(&runner, &["--terminate", "Arch"]).unwrap_err();
+        assert!(matches!(err, TamerError::DistroNotFound { ref name } if name == "Arch"));
+    }
+
+    #[test]
+    fn test_command_failure_captures_exit_code_and_stderr() {
+        let runner = ScriptedRunner::new().on(
+            "wsl",
+            &["--shutdown"],
+            CommandOutput::failed(5, encode_utf16le("Access is denied.\r\n")),
+        );
+        match run_wsl_command(&runner, &["--shutdown"]).unwrap_err() {
+            TamerError::CommandFailed {
+                command,
+                exit_code,
+                stderr,
+            } => {
+                assert_eq!(command, "wsl --shutdown");
+                assert_eq!(exit_code, Some(5));
+                assert_eq!(stderr, "Access is denied.");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_command_with_input_returns_guest_output() {
+        let args = ["-d", "Ubuntu", "--", "sh", "-s"];
+        let runner = ScriptedRunner::new()
+            .on("wsl", &args, CommandOutput::ok("pid 1\n"))
+            .on(
+                "wsl",
+                &["-d", "Arch", "--", "sh", "-s"],
+                CommandOutput::failed(
+                    -1,
+                    encode_utf16le("Error code: Wsl/Service/WSL_E_DISTRO_NOT_FOUND\r\n"),
+                ),
+            );
+        let control = RunControl::new(Some(Duration::from_secs(30)), Default::default());
+
+        let stdout = run_wsl_command_with_input(&runner, &args, b"echo", &control).unwrap();
+        assert_eq!(stdout, b"pid 1\n");
+        assert_eq!(runner.calls()[0].stdin.as_deref(), Some(&b"echo"[..]));
+
+        let err =
+            run_wsl_command_with_input(&runner, &["-d", "Arch", "--", "sh", "-s"], b"", &control)
+                .unwrap_err();
+        assert!(matches!(err, TamerError::DistroNotFound { ref name } if name == "Arch"));
+    }
+
+    #[test]
+    fn test_guest_stderr_is_utf8() {
+        let runner = ScriptedRunner::new().on(
+            "wsl",
+            &["-d", "Ubuntu", "--", "kill", "42"],
+            CommandOutput::failed(1, "kill: (42) - No such process\n".as_bytes().to_vec()),
+        );
+        match run_wsl_command(&runner, &["-d", "Ubuntu", "--", "kill", "42"]).unwrap_err() {
+            TamerError::CommandFailed { stderr, .. } => {
+                assert_eq!(stderr, "kill: (42) - No such process")
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cancelled_run_is_reported() {
+        let runner = ScriptedRunner::new();
+        let control = RunControl::default();
+        control.cancel.cancel();
+        let err = run_wsl_command_controlled(&runner, &["--export", "Ubuntu", "out.tar"], &control)
+            .unwrap_err();
+        assert!(matches!(err, TamerError::Cancelled));
+        assert!(runner.calls().is_empty());
+    }
+
+    #[test]
+    fn test_timeout_reports_limit() {
+        let err = wsl_result(
+            &["--install", "-d", "Ubuntu"],
+            Err(io::Error::new(ErrorKind::TimedOut, "Timed out")),
+            Some(Duration::from_secs(1800)),
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), "TIMED_OUT");
+        assert_eq!(
+            err.to_string(),
+            "wsl --install -d Ubuntu timed out after 1800s"
+        );
+    }
+
+    #[test]
+    fn test_failed_elevation_is_cancelled() {
+        let runner = ScriptedRunner::new().on(
+            "powershell",
+            &[
+                "-NoProfile",
+                "-Command",
+                "try { $p = Start-Process 'usbipd' -ArgumentList '\"bind\"' -Verb RunAs -Wait -PassThru -ErrorAction Stop } catch { exit 1223 }; exit $p.ExitCode",
+            ],
+            CommandOutput::failed(1223, ""),
+        );
+        let err = run_elevated(&runner, "usbipd", &["bind"]).unwrap_err();
+        assert_eq!(err.code(), "ELEVATION_CANCELLED");
+    }
+
+    #[test]
+    fn test_failed_elevated_program_reports_exit_code() {
+        let runner = ScriptedRunner::new().on(
+            "powershell",
+            &[
+                "-NoProfile",
+                "-Command",
+                "try { $p = Start-Process 'diskpart' -ArgumentList '\"/s\" \"compact.txt\"' -Verb RunAs -Wait -PassThru -ErrorAction Stop } catch { exit 1223 }; exit $p.ExitCode",
+            ],
+            CommandOutput::failed(5, ""),
+        );
+        match run_elevated(&runner, "diskpart", &["/s", "compact.txt"]).unwrap_err() {
+            TamerError::CommandFailed {
+                command,
+                exit_code,
+                stderr,
+            } => {
+                assert_eq!(command, "diskpart /s compact.txt");
+                assert_eq!(exit_code, Some(5));
+                assert_eq!(stderr, "exited with code 5");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
 
-#[cfg(not(windows))]
-impl CommandExt for Command {
-    fn creation_flags(&mut self, _flags: u32) -> &mut Self {
-        self
+    #[test]
+    fn test_elevated_arguments_escape_quotes() {
+        let runner = ScriptedRunner::new().on(
+            "powershell",
+            &[
+                "-NoProfile",
+                "-Command",
+                "try { $p = Start-Process 'diskpart' -ArgumentList '\"/s\" \"C:\\Users\\o''brien\\compact.txt\"' -Verb RunAs -Wait -PassThru -ErrorAction Stop } catch { exit 1223 }; exit $p.ExitCode",
+            ],
+            CommandOutput::ok(""),
+        );
+        run_elevated(
+            &runner,
+            "diskpart",
+            &["/s", r"C:\Users\o'brien\compact.txt"],
+        )
+        .unwrap();
     }
 }
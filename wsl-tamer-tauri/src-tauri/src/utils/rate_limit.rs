@@ -1,3 +1,4 @@
+use crate::error::{TamerError, TamerResult};
 use std::collections::HashMap;
 use std::fmt;
 use std::sync::{LazyLock, Mutex};
@@ -28,8 +29,10 @@ static RATE_LIMITS: LazyLock<Mutex<HashMap<RateLimitKey, Instant>>> =
 /// Check rate limit and update timestamp if allowed
 ///
 /// Returns Ok(()) if the call is allowed, Err if rate limited
-pub fn rate_limit(key: RateLimitKey, min_interval_ms: u64) -> Result<(), String> {
-    let mut limits = RATE_LIMITS.lock().map_err(|_| "Rate limiter lock error")?;
+pub fn rate_limit(key: RateLimitKey, min_interval_ms: u64) -> TamerResult<()> {
+    let mut limits = RATE_LIMITS
+        .lock()
+        .map_err(|_| TamerError::Internal("Rate limiter lock error".to_string()))?;
 
     let now = Instant::now();
     let min_interval = Duration::from_millis(min_interval_ms);
@@ -37,7 +40,10 @@ pub fn rate_limit(key: RateLimitKey, min_interval_ms: u64) -> Result<(), String>
     if let Some(last) = limits.get(&key) {
         if now.duration_since(*last) < min_interval {
             let remaining = min_interval - now.duration_since(*last);
-            return Err(format!("Rate limited - wait {}ms", remaining.as_millis()));
+            return Err(TamerError::Busy(format!(
+                "Rate limited - wait {}ms",
+                remaining.as_millis()
+            )));
         }
     }
 
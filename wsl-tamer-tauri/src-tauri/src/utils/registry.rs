@@ -0,0 +1,229 @@
+//! Read-only access to the current user's registry hive
+//!
+//! Services read the registry through [`RegistryReader`] so they can be
+//! tested on any platform with a [`MemoryRegistry`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::sync::{Arc, RwLock};
+
+/// A registry value, reduced to the types the app reads
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistryValue {
+    /// `REG_SZ` or `REG_EXPAND_SZ` (unexpanded)
+    String(String),
+    Dword(u32),
+    MultiString(Vec<String>),
+    /// Any other type (binary, QWORD, ...)
+    Other,
+}
+
+impl RegistryValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_u32(&self) -> Option<u32> {
+        match self {
+            Self::Dword(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_strings(&self) -> Option<&[String]> {
+        match self {
+            Self::MultiString(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// Reads keys below `HKEY_CURRENT_USER`
+///
+/// Paths use backslashes, e.g. `Software\Microsoft\Windows\CurrentVersion\Lxss`.
+/// A missing key fails with `ErrorKind::NotFound`.
+pub trait RegistryReader: Send + Sync {
+    /// Names of the direct subkeys of `path`
+    fn subkeys(&self, path: &str) -> io::Result<Vec<String>>;
+
+    /// All values of `path`, by name
+    fn values(&self, path: &str) -> io::Result<HashMap<String, RegistryValue>>;
+}
+
+/// Registry reader for the running platform
+///
+/// Outside Windows there is no registry, so this is an empty [`MemoryRegistry`].
+pub fn system_registry() -> Arc<dyn RegistryReader> {
+    #[cfg(windows)]
+    {
+        Arc::new(WindowsRegistry)
+    }
+    #[cfg(not(windows))]
+    {
+        Arc::new(MemoryRegistry::new())
+    }
+}
+
+/// The real registry, via `winreg`
+#[cfg(windows)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WindowsRegistry;
+
+#[cfg(windows)]
+impl WindowsRegistry {
+    fn open(path: &str) -> io::Result<winreg::RegKey> {
+        winreg::RegKey::predef(winreg::enums::HKEY_CURRENT_USER).open_subkey(path)
+    }
+}
+
+#[cfg(windows)]
+impl RegistryReader for WindowsRegistry {
+    fn subkeys(&self, path: &str) -> io::Result<Vec<String>> {
+        Self::open(path)?.enum_keys().collect()
+    }
+
+    fn values(&self, path: &str) -> io::Result<HashMap<String, RegistryValue>> {
+        use winreg::enums::RegType;
+        use winreg::types::FromRegValue;
+
+        let mut values = HashMap::new();
+        for entry in Self::open(path)?.enum_values() {
+            let (name, raw) = entry?;
+            let value = match raw.vtype {
+                RegType::REG_SZ | RegType::REG_EXPAND_SZ => {
+                    RegistryValue::String(String::from_reg_value(&raw)?)
+                }
+                RegType::REG_DWORD => RegistryValue::Dword(u32::from_reg_value(&raw)?),
+                RegType::REG_MULTI_SZ => {
+                    RegistryValue::MultiString(Vec::<String>::from_reg_value(&raw)?)
+                }
+                _ => RegistryValue::Other,
+            };
+            values.insert(name, value);
+        }
+        Ok(values)
+    }
+}
+
+/// Keys by lower-cased path, with the path as first written and the values
+type KeyMap = BTreeMap<String, (String, HashMap<String, RegistryValue>)>;
+
+/// In-memory registry for tests and non-Windows builds
+///
+/// Key paths are matched case-insensitively, like the real registry.
+#[derive(Debug, Default)]
+pub struct MemoryRegistry {
+    keys: RwLock<KeyMap>,
+}
+
+impl MemoryRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a value, creating the key (and its parents) if needed
+    pub fn with_value(self, path: &str, name: &str, value: RegistryValue) -> Self {
+        self.set_value(path, name, value);
+        self
+    }
+
+    pub fn set_value(&self, path: &str, name: &str, value: RegistryValue) {
+        if let Ok(mut keys) = self.keys.write() {
+            let mut parent = String::new();
+            for part in path.split('\\') {
+                if !parent.is_empty() {
+                    parent.push('\\');
+                }
+                parent.push_str(part);
+                keys.entry(parent.to_lowercase())
+                    .or_insert_with(|| (parent.clone(), HashMap::new()));
+            }
+            if let Some((_, values)) = keys.get_mut(&path.to_lowercase()) {
+                values.insert(name.to_string(), value);
+            }
+        }
+    }
+
+    /// Delete a key and everything below it
+    pub fn remove_key(&self, path: &str) {
+        if let Ok(mut keys) = self.keys.write() {
+            let path = path.to_lowercase();
+            let prefix = format!("{}\\", path);
+            keys.retain(|key, _| *key != path && !key.starts_with(&prefix));
+        }
+    }
+
+    fn not_found(path: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Registry key not found: {}", path),
+        )
+    }
+}
+
+impl RegistryReader for MemoryRegistry {
+    fn subkeys(&self, path: &str) -> io::Result<Vec<String>> {
+        let keys = self
+            .keys
+            .read()
+            .map_err(|_| io::Error::other("Registry lock poisoned"))?;
+        let lower = path.to_lowercase();
+        if !keys.contains_key(&lower) {
+            return Err(Self::not_found(path));
+        }
+        let prefix = format!("{}\\", lower);
+        Ok(keys
+            .iter()
+            .filter_map(|(key, (original, _))| {
+                let rest = key.strip_prefix(&prefix)?;
+                let name = original.rsplit('\\').next().unwrap_or(original);
+                (!rest.contains('\\')).then(|| name.to_string())
+            })
+            .collect())
+    }
+
+    fn values(&self, path: &str) -> io::Result<HashMap<String, RegistryValue>> {
+        let keys = self
+            .keys
+            .read()
+            .map_err(|_| io::Error::other("Registry lock poisoned"))?;
+        keys.get(&path.to_lowercase())
+            .map(|(_, values)| values.clone())
+            .ok_or_else(|| Self::not_found(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_registry_keys_and_values() {
+        let registry = MemoryRegistry::new()
+            .with_value(
+                "Software\\Lxss\\{a}",
+                "DistributionName",
+                RegistryValue::String("Ubuntu".to_string()),
+            )
+            .with_value("Software\\Lxss\\{B}", "Version", RegistryValue::Dword(2));
+
+        let mut subkeys = registry.subkeys("software\\LXSS").unwrap();
+        subkeys.sort();
+        assert_eq!(subkeys, vec!["{B}", "{a}"]);
+        assert_eq!(registry.subkeys("Software").unwrap(), vec!["Lxss"]);
+        assert_eq!(
+            registry.values("Software\\Lxss\\{b}").unwrap()["Version"].as_u32(),
+            Some(2)
+        );
+
+        registry.remove_key("Software\\Lxss\\{a}");
+        assert_eq!(registry.subkeys("Software\\Lxss").unwrap(), vec!["{B}"]);
+        assert_eq!(
+            registry.values("Software\\Missing").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+}
@@ -0,0 +1,589 @@
+//! Command runner abstraction
+//!
+//! Services never call `std::process::Command` directly; they go through a
+//! [`CommandRunner`] so the whole service layer can be exercised on any OS
+//! with [`ScriptedRunner`] replaying captured output.
+
+use crate::utils::RunControl;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Windows CREATE_NO_WINDOW process creation flag
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// How often a controlled run checks for exit, cancellation and timeout
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Captured output of a finished process
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandOutput {
+    /// Exit code (`None` if the process was terminated by a signal)
+    pub exit_code: Option<i32>,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+impl CommandOutput {
+    /// Successful exit with the given raw stdout
+    pub fn ok(stdout: impl Into<Vec<u8>>) -> Self {
+        Self {
+            exit_code: Some(0),
+            stdout: stdout.into(),
+            stderr: Vec::new(),
+        }
+    }
+
+    /// Successful exit with stdout encoded as UTF-16LE, like wsl.exe's own output
+    pub fn ok_utf16(stdout: &str) -> Self {
+        Self::ok(crate::utils::encode_utf16le(stdout))
+    }
+
+    /// Failed exit with the given code and raw stderr
+    pub fn failed(exit_code: i32, stderr: impl Into<Vec<u8>>) -> Self {
+        Self {
+            exit_code: Some(exit_code),
+            stdout: Vec::new(),
+            stderr: stderr.into(),
+        }
+    }
+
+    /// Whether the process exited with code 0
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Executes external programs on behalf of the services
+pub trait CommandRunner: Send + Sync {
+    /// Run a program to completion, capturing stdout and stderr
+    fn output(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput>;
+
+    /// Run a program to completion, writing `input` to its stdin first
+    fn output_with_stdin(
+        &self,
+        program: &str,
+        args: &[&str],
+        input: &[u8],
+    ) -> io::Result<CommandOutput>;
+
+    /// Start a program without waiting for it (terminal windows, Explorer)
+    fn spawn(&self, program: &str, args: &[&str]) -> io::Result<()>;
+
+    /// Run a program to completion under a timeout and cancellation token
+    ///
+    /// Fails with `ErrorKind::TimedOut` when the timeout elapses and with
+    /// `ErrorKind::Interrupted` when the token is cancelled; in both cases
+    /// the process tree has been killed before returning.
+    fn output_controlled(
+        &self,
+        program: &str,
+        args: &[&str],
+        control: &RunControl,
+    ) -> io::Result<CommandOutput> {
+        if control.cancel.is_cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "Cancelled"));
+        }
+        self.output(program, args)
+    }
+
+    /// Like `output_controlled`, but streams stdout into `sink` as it is
+    /// produced instead of buffering it (the returned `stdout` is empty)
+    fn output_to_writer(
+        &self,
+        program: &str,
+        args: &[&str],
+        sink: &mut (dyn Write + Send),
+        control: &RunControl,
+    ) -> io::Result<CommandOutput> {
+        let mut output = self.output_controlled(program, args, control)?;
+        sink.write_all(&output.stdout)?;
+        output.stdout.clear();
+        Ok(output)
+    }
+
+    /// Like `output_controlled`, but streams `source` into stdin while the
+    /// program runs (e.g. `wsl --import <name> <dir> -`)
+    ///
+    /// Fails if reading `source` fails, even when the program succeeded.
+    fn output_from_reader(
+        &self,
+        program: &str,
+        args: &[&str],
+        source: &mut (dyn Read + Send),
+        control: &RunControl,
+    ) -> io::Result<CommandOutput> {
+        if control.cancel.is_cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "Cancelled"));
+        }
+        let mut input = Vec::new();
+        source.read_to_end(&mut input)?;
+        self.output_with_stdin(program, args, &input)
+    }
+}
+
+/// Runner backed by `std::process::Command`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemRunner;
+
+impl SystemRunner {
+    fn command(program: &str, args: &[&str]) -> Command {
+        let mut cmd = Command::new(program);
+        cmd.args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .creation_flags(CREATE_NO_WINDOW);
+        cmd
+    }
+
+    /// Run under `control`, either capturing stdout or copying it into `sink`,
+    /// and feeding stdin from `source` if given
+    fn run_controlled(
+        program: &str,
+        args: &[&str],
+        sink: Option<&mut (dyn Write + Send)>,
+        source: Option<&mut (dyn Read + Send)>,
+        control: &RunControl,
+    ) -> io::Result<CommandOutput> {
+        if control.cancel.is_cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "Cancelled"));
+        }
+
+        let stdin = if source.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        };
+        let mut child = Self::command(program, args).stdin(stdin).spawn()?;
+        let stderr = child.stderr.take().map(drain_pipe);
+        let started = Instant::now();
+
+        // Scoped so the copy threads can borrow `sink` and `source`; they end
+        // once the pipes close, which also happens when the process tree is killed
+        thread::scope(|scope| {
+            // Dropping the pipe at the end of the copy signals EOF
+            let fed = match (child.stdin.take(), source) {
+                (Some(mut pipe), Some(source)) => {
+                    Some(scope.spawn(move || io::copy(source, &mut pipe)))
+                }
+                _ => None,
+            };
+
+            let (stdout, copied) = match (child.stdout.take(), sink) {
+                (Some(mut pipe), Some(sink)) => {
+                    (None, Some(scope.spawn(move || io::copy(&mut pipe, sink))))
+                }
+                // Drain pipes on separate threads so a chatty process can't block on a full pipe
+                (pipe, _) => (pipe.map(drain_pipe), None),
+            };
+
+            let status = loop {
+                match child.try_wait() {
+                    Ok(Some(status)) => break status,
+                    Ok(None) => {}
+                    // Don't leave the process running when it can't be waited on
+                    Err(e) => {
+                        kill_tree(&mut child);
+                        return Err(e);
+                    }
+                }
+                if control.cancel.is_cancelled() {
+                    kill_tree(&mut child);
+                    return Err(io::Error::new(io::ErrorKind::Interrupted, "Cancelled"));
+                }
+                if let Some(timeout) = control.timeout {
+                    if started.elapsed() >= timeout {
+                        kill_tree(&mut child);
+                        return Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            format!("Timed out after {}s", timeout.as_secs()),
+                        ));
+                    }
+                }
+                thread::sleep(POLL_INTERVAL);
+            };
+
+            if let Some(copied) = copied {
+                copied
+                    .join()
+                    .map_err(|_| io::Error::other("Output copy thread panicked"))??;
+            }
+            if let Some(fed) = fed {
+                match fed
+                    .join()
+                    .map_err(|_| io::Error::other("Input copy thread panicked"))?
+                {
+                    // The program stopped reading; its exit status tells why
+                    Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+                    Err(e) => return Err(e),
+                    Ok(_) => {}
+                }
+            }
+
+            Ok(CommandOutput {
+                exit_code: status.code(),
+                stdout: join_pipe(stdout),
+                stderr: join_pipe(stderr),
+            })
+        })
+    }
+}
+
+impl CommandRunner for SystemRunner {
+    fn output(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+        let output = Self::command(program, args).output()?;
+        Ok(CommandOutput {
+            exit_code: output.status.code(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        })
+    }
+
+    fn output_with_stdin(
+        &self,
+        program: &str,
+        args: &[&str],
+        input: &[u8],
+    ) -> io::Result<CommandOutput> {
+        let mut child = Self::command(program, args).stdin(Stdio::piped()).spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(input)?;
+            // stdin is dropped here, closing the pipe and signaling EOF
+        }
+
+        let output = child.wait_with_output()?;
+        Ok(CommandOutput {
+            exit_code: output.status.code(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        })
+    }
+
+    fn spawn(&self, program: &str, args: &[&str]) -> io::Result<()> {
+        Command::new(program).args(args).spawn()?;
+        Ok(())
+    }
+
+    fn output_controlled(
+        &self,
+        program: &str,
+        args: &[&str],
+        control: &RunControl,
+    ) -> io::Result<CommandOutput> {
+        Self::run_controlled(program, args, None, None, control)
+    }
+
+    fn output_to_writer(
+        &self,
+        program: &str,
+        args: &[&str],
+        sink: &mut (dyn Write + Send),
+        control: &RunControl,
+    ) -> io::Result<CommandOutput> {
+        Self::run_controlled(program, args, Some(sink), None, control)
+    }
+
+    fn output_from_reader(
+        &self,
+        program: &str,
+        args: &[&str],
+        source: &mut (dyn Read + Send),
+        control: &RunControl,
+    ) -> io::Result<CommandOutput> {
+        Self::run_controlled(program, args, None, Some(source), control)
+    }
+}
+
+/// Read a child pipe to the end on a background thread
+fn drain_pipe(mut pipe: impl Read + Send + 'static) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
+
+fn join_pipe(handle: Option<JoinHandle<Vec<u8>>>) -> Vec<u8> {
+    handle.and_then(|h| h.join().ok()).unwrap_or_default()
+}
+
+/// Kill a child and everything it started
+///
+/// wsl.exe hands work to helper processes, so killing only the direct child
+/// would leave an export running. Pipe reader threads are left detached; they
+/// finish once the last process holding the pipe exits.
+fn kill_tree(child: &mut Child) {
+    #[cfg(windows)]
+    {
+        let pid = child.id().to_string();
+        let _ = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .creation_flags(CREATE_NO_WINDOW)
+            .status();
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Shared runner used by the production service instances
+static SYSTEM_RUNNER: LazyLock<Arc<dyn CommandRunner>> = LazyLock::new(|| Arc::new(SystemRunner));
+
+/// Get the shared system runner
+pub fn system_runner() -> Arc<dyn CommandRunner> {
+    SYSTEM_RUNNER.clone()
+}
+
+/// Scripted responses keyed by program and argument list
+type ResponseMap = HashMap<(String, Vec<String>), VecDeque<CommandOutput>>;
+
+/// A single invocation recorded by [`ScriptedRunner`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedCall {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Bytes written to stdin, if any
+    pub stdin: Option<Vec<u8>>,
+}
+
+/// Fake runner that replays scripted output and records every call
+///
+/// Responses are keyed by program and exact argument list and returned in
+/// FIFO order; the last response for a key is repeated once the queue is
+/// down to one entry. Unscripted calls fail with `NotFound`, the same error
+/// a missing executable produces.
+#[derive(Debug, Default)]
+pub struct ScriptedRunner {
+    responses: Mutex<ResponseMap>,
+    calls: Mutex<Vec<RecordedCall>>,
+}
+
+impl ScriptedRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a response for `program args...`
+    pub fn on(self, program: &str, args: &[&str], output: CommandOutput) -> Self {
+        if let Ok(mut responses) = self.responses.lock() {
+            responses
+                .entry(Self::key(program, args))
+                .or_default()
+                .push_back(output);
+        }
+        self
+    }
+
+    /// All calls made so far, in order
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        self.calls.lock().map(|c| c.clone()).unwrap_or_default()
+    }
+
+    /// Whether `program args...` was invoked at least once
+    pub fn was_called(&self, program: &str, args: &[&str]) -> bool {
+        let (program, args) = Self::key(program, args);
+        self.calls()
+            .iter()
+            .any(|c| c.program == program && c.args == args)
+    }
+
+    fn key(program: &str, args: &[&str]) -> (String, Vec<String>) {
+        (
+            program.to_string(),
+            args.iter().map(|a| a.to_string()).collect(),
+        )
+    }
+
+    fn replay(
+        &self,
+        program: &str,
+        args: &[&str],
+        stdin: Option<&[u8]>,
+    ) -> io::Result<CommandOutput> {
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.push(RecordedCall {
+                program: program.to_string(),
+                args: args.iter().map(|a| a.to_string()).collect(),
+                stdin: stdin.map(|s| s.to_vec()),
+            });
+        }
+
+        let mut responses = self
+            .responses
+            .lock()
+            .map_err(|_| io::Error::other("Scripted runner lock poisoned"))?;
+        let queue = responses.get_mut(&Self::key(program, args));
+        match queue {
+            Some(q) if q.len() > 1 => Ok(q.pop_front().unwrap_or_default()),
+            Some(q) if q.len() == 1 => Ok(q[0].clone()),
+            _ => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No scripted response for: {} {}", program, args.join(" ")),
+            )),
+        }
+    }
+}
+
+impl CommandRunner for ScriptedRunner {
+    fn output(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+        self.replay(program, args, None)
+    }
+
+    fn output_with_stdin(
+        &self,
+        program: &str,
+        args: &[&str],
+        input: &[u8],
+    ) -> io::Result<CommandOutput> {
+        self.replay(program, args, Some(input))
+    }
+
+    fn spawn(&self, program: &str, args: &[&str]) -> io::Result<()> {
+        self.replay(program, args, None).map(|_| ())
+    }
+}
+
+/// Windows-specific creation flags
+#[cfg(windows)]
+trait CommandExt {
+    fn creation_flags(&mut self, flags: u32) -> &mut Self;
+}
+
+#[cfg(windows)]
+impl CommandExt for Command {
+    fn creation_flags(&mut self, flags: u32) -> &mut Self {
+        use std::os::windows::process::CommandExt as WinCommandExt;
+        WinCommandExt::creation_flags(self, flags)
+    }
+}
+
+#[cfg(not(windows))]
+trait CommandExt {
+    fn creation_flags(&mut self, _flags: u32) -> &mut Self;
+}
+
+#[cfg(not(windows))]
+impl CommandExt for Command {
+    fn creation_flags(&mut self, _flags: u32) -> &mut Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripted_runner_replays_in_order() {
+        let runner = ScriptedRunner::new()
+            .on("wsl", &["--status"], CommandOutput::ok("first"))
+            .on("wsl", &["--status"], CommandOutput::ok("second"));
+
+        assert_eq!(
+            runner.output("wsl", &["--status"]).unwrap().stdout,
+            b"first"
+        );
+        assert_eq!(
+            runner.output("wsl", &["--status"]).unwrap().stdout,
+            b"second"
+        );
+        // Last response repeats
+        assert_eq!(
+            runner.output("wsl", &["--status"]).unwrap().stdout,
+            b"second"
+        );
+    }
+
+    #[test]
+    fn test_scripted_runner_unscripted_call_is_not_found() {
+        let runner = ScriptedRunner::new();
+        let err = runner.output("usbipd", &["list"]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_scripted_runner_records_calls_and_stdin() {
+        let runner = ScriptedRunner::new().on("sh", &["-c", "cat"], CommandOutput::ok(""));
+        runner
+            .output_with_stdin("sh", &["-c", "cat"], b"hello")
+            .unwrap();
+
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].program, "sh");
+        assert_eq!(calls[0].stdin.as_deref(), Some(&b"hello"[..]));
+        assert!(runner.was_called("sh", &["-c", "cat"]));
+    }
+
+    #[test]
+    fn test_scripted_runner_honours_cancelled_token() {
+        let runner = ScriptedRunner::new().on("wsl", &["--shutdown"], CommandOutput::ok(""));
+        let control = RunControl::default();
+        assert!(runner
+            .output_controlled("wsl", &["--shutdown"], &control)
+            .is_ok());
+
+        control.cancel.cancel();
+        let err = runner
+            .output_controlled("wsl", &["--shutdown"], &control)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Interrupted);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_system_runner_kills_on_timeout() {
+        let control = RunControl::new(Some(Duration::from_millis(200)), Default::default());
+        let started = Instant::now();
+        let err = SystemRunner
+            .output_controlled("sleep", &["5"], &control)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_system_runner_captures_output_under_control() {
+        let output = SystemRunner
+            .output_controlled("echo", &["hello"], &RunControl::default())
+            .unwrap();
+        assert!(output.success());
+        assert_eq!(output.stdout, b"hello\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_system_runner_streams_stdout_to_writer() {
+        let mut sink = Vec::new();
+        let output = SystemRunner
+            .output_to_writer("echo", &["hello"], &mut sink, &RunControl::default())
+            .unwrap();
+        assert!(output.success());
+        assert!(output.stdout.is_empty());
+        assert_eq!(sink, b"hello\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_system_runner_feeds_stdin_from_reader() {
+        let mut source = &b"piped input"[..];
+        let output = SystemRunner
+            .output_from_reader("cat", &[], &mut source, &RunControl::default())
+            .unwrap();
+        assert!(output.success());
+        assert_eq!(output.stdout, b"piped input");
+    }
+
+    #[test]
+    fn test_command_output_success() {
+        assert!(CommandOutput::ok("").success());
+        assert!(!CommandOutput::failed(1, "boom").success());
+        assert!(!CommandOutput::default().success());
+    }
+}
@@ -3,25 +3,33 @@
 //! All user inputs should be validated through these functions
 //! before being passed to shell commands or external processes.
 
+use crate::error::{TamerError, TamerResult};
+
 /// Validate and sanitize WSL distribution names
 ///
 /// Distro names should only contain alphanumeric, dash, underscore, and dot
-pub fn validate_distro_name(name: &str) -> Result<&str, String> {
+pub fn validate_distro_name(name: &str) -> TamerResult<&str> {
     if name.is_empty() {
-        return Err("Distribution name cannot be empty".into());
+        return Err(TamerError::InvalidInput(
+            "Distribution name cannot be empty".into(),
+        ));
     }
     if name.len() > 100 {
-        return Err("Distribution name too long (max 100 chars)".into());
+        return Err(TamerError::InvalidInput(
+            "Distribution name too long (max 100 chars)".into(),
+        ));
     }
     if !name
         .chars()
         .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.')
     {
-        return Err("Distribution name contains invalid characters".into());
+        return Err(TamerError::InvalidInput(
+            "Distribution name contains invalid characters".into(),
+        ));
     }
     // Check for path traversal attempts
     if name.contains("..") {
-        return Err("Invalid distribution name".into());
+        return Err(TamerError::InvalidInput("Invalid distribution name".into()));
     }
     Ok(name)
 }
@@ -29,18 +37,22 @@ pub fn validate_distro_name(name: &str) -> Result<&str, String> {
 /// Validate Linux filesystem paths
 ///
 /// Rejects characters that could be used for shell injection
-pub fn validate_linux_path(path: &str) -> Result<&str, String> {
+pub fn validate_linux_path(path: &str) -> TamerResult<&str> {
     if path.is_empty() {
-        return Err("Path cannot be empty".into());
+        return Err(TamerError::InvalidInput("Path cannot be empty".into()));
     }
     // Reject shell metacharacters
     const DANGEROUS_CHARS: &[char] = &['\'', '"', '`', '$', ';', '&', '|', '\n', '\r', '\0'];
     if path.contains(DANGEROUS_CHARS) {
-        return Err("Path contains invalid characters".into());
+        return Err(TamerError::InvalidInput(
+            "Path contains invalid characters".into(),
+        ));
     }
     // Linux paths should be absolute
     if !path.starts_with('/') {
-        return Err("Linux path must be absolute (start with /)".into());
+        return Err(TamerError::InvalidInput(
+            "Linux path must be absolute (start with /)".into(),
+        ));
     }
     Ok(path)
 }
@@ -48,16 +60,18 @@ pub fn validate_linux_path(path: &str) -> Result<&str, String> {
 /// Validate Windows filesystem paths
 ///
 /// Rejects characters that could be used for shell injection
-pub fn validate_windows_path(path: &str) -> Result<&str, String> {
+pub fn validate_windows_path(path: &str) -> TamerResult<&str> {
     if path.is_empty() {
-        return Err("Path cannot be empty".into());
+        return Err(TamerError::InvalidInput("Path cannot be empty".into()));
     }
     // Reject shell metacharacters and Windows-specific dangers
     const DANGEROUS_CHARS: &[char] = &[
         '\'', '"', '`', '$', ';', '&', '|', '\n', '\r', '\0', '<', '>',
     ];
     if path.contains(DANGEROUS_CHARS) {
-        return Err("Path contains invalid characters".into());
+        return Err(TamerError::InvalidInput(
+            "Path contains invalid characters".into(),
+        ));
     }
     Ok(path)
 }
@@ -65,30 +79,34 @@ pub fn validate_windows_path(path: &str) -> Result<&str, String> {
 /// Validate USB bus IDs
 ///
 /// Format should be like "1-2" or "1-2.3"
-pub fn validate_bus_id(id: &str) -> Result<&str, String> {
+pub fn validate_bus_id(id: &str) -> TamerResult<&str> {
     if id.is_empty() {
-        return Err("Bus ID cannot be empty".into());
+        return Err(TamerError::InvalidInput("Bus ID cannot be empty".into()));
     }
     if !id
         .chars()
         .all(|c| c.is_ascii_digit() || c == '-' || c == '.')
     {
-        return Err("Invalid bus ID format".into());
+        return Err(TamerError::InvalidInput("Invalid bus ID format".into()));
     }
     Ok(id)
 }
 
 /// Validate device paths (like \\.\PhysicalDrive0)
-pub fn validate_device_path(path: &str) -> Result<&str, String> {
+pub fn validate_device_path(path: &str) -> TamerResult<&str> {
     if path.is_empty() {
-        return Err("Device path cannot be empty".into());
+        return Err(TamerError::InvalidInput(
+            "Device path cannot be empty".into(),
+        ));
     }
     // Only allow alphanumeric, backslash, dot, and colon
     if !path
         .chars()
         .all(|c| c.is_alphanumeric() || c == '\\' || c == '.' || c == ':')
     {
-        return Err("Invalid device path format".into());
+        return Err(TamerError::InvalidInput(
+            "Invalid device path format".into(),
+        ));
     }
     Ok(path)
 }
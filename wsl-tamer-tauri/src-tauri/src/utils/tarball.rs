@@ -0,0 +1,267 @@
+//! Exported tarball integrity: sidecar manifests and structural checks
+
+use crate::error::{TamerError, TamerResult};
+use crate::models::{Compression, ExportManifest, ExportVerification};
+use crate::utils::{CancellationToken, Decompressor, HashingReader};
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Sidecar manifest of an export (`<export>.manifest.json`)
+pub fn export_manifest_path(export: &Path) -> PathBuf {
+    let mut name = export.as_os_str().to_os_string();
+    name.push(".manifest.json");
+    PathBuf::from(name)
+}
+
+pub fn write_export_manifest(export: &Path, manifest: &ExportManifest) -> TamerResult<()> {
+    let json = serde_json::to_string_pretty(manifest)
+        .map_err(|e| TamerError::Internal(format!("Failed to serialize export manifest: {}", e)))?;
+    fs::write(export_manifest_path(export), json)
+        .map_err(|e| TamerError::io("Failed to write export manifest", e))
+}
+
+/// Manifest of an export, if it has one
+pub fn read_export_manifest(export: &Path) -> TamerResult<Option<ExportManifest>> {
+    let content = match fs::read_to_string(export_manifest_path(export)) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(TamerError::io("Failed to read export manifest", e)),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| TamerError::Parse(format!("Corrupt export manifest: {}", e)))
+}
+
+/// Compression of an export: as recorded in its manifest, else by extension
+pub fn export_compression(export: &Path) -> Compression {
+    match read_export_manifest(export) {
+        Ok(Some(manifest)) => manifest.compression,
+        _ => Compression::from_path(export),
+    }
+}
+
+/// Delete an export and its manifest (best effort)
+pub fn remove_export(export: &Path) {
+    let _ = fs::remove_file(export);
+    let _ = fs::remove_file(export_manifest_path(export));
+}
+
+/// Check that an export is safe to import
+///
+/// Reads the file once, hashing it while decompressing it and walking the tar
+/// headers. Fails if the archive is malformed, if it does not look like a
+/// root filesystem (`/etc/os-release` and `/bin`), or if a sidecar manifest
+/// exists and its checksum does not match.
+pub fn verify_export(export: &Path, cancel: &CancellationToken) -> TamerResult<ExportVerification> {
+    let display = export.display();
+    let file = File::open(export)
+        .map_err(|e| TamerError::io(format!("Failed to open export {}", display), e))?;
+    let compression = export_compression(export);
+    let mut reader = HashingReader::new(BufReader::new(file));
+    let malformed = |e: io::Error| {
+        TamerError::Parse(format!(
+            "Export {} is not a valid tar archive: {}",
+            display, e
+        ))
+    };
+
+    let mut entries = 0u64;
+    let mut has_os_release = false;
+    let mut has_bin = false;
+    let uncompressed_bytes;
+    {
+        let mut decompressor =
+            Decompressor::new(BufReader::new(&mut reader), compression).map_err(malformed)?;
+        let mut archive = tar::Archive::new(&mut decompressor);
+        for entry in archive.entries().map_err(malformed)? {
+            if cancel.is_cancelled() {
+                return Err(TamerError::Cancelled);
+            }
+            let entry = entry.map_err(malformed)?;
+            let path = entry.path().map_err(malformed)?;
+            let path = path.to_string_lossy();
+            let path = path.trim_start_matches("./").trim_start_matches('/');
+
+            has_os_release |= path == "etc/os-release";
+            has_bin |= path.trim_end_matches('/') == "bin" || path.starts_with("bin/");
+            entries += 1;
+        }
+        // Read to the end of the stream, which also checks the codec's own
+        // integrity trailer
+        io::copy(&mut archive.into_inner(), &mut io::sink()).map_err(malformed)?;
+        uncompressed_bytes = decompressor.bytes_out();
+    }
+    // Hash the end-of-archive padding too, so the checksum covers the whole file
+    io::copy(&mut reader, &mut io::sink())
+        .map_err(|e| TamerError::io(format!("Failed to read export {}", display), e))?;
+    let (sha256, size_bytes) = reader.finish();
+
+    if entries == 0 {
+        return Err(TamerError::Parse(format!(
+            "Export {} is not a valid tar archive: no entries",
+            display
+        )));
+    }
+    let missing: Vec<&str> = [("/etc/os-release", has_os_release), ("/bin", has_bin)]
+        .into_iter()
+        .filter(|(_, found)| !found)
+        .map(|(path, _)| path)
+        .collect();
+    if !missing.is_empty() {
+        return Err(TamerError::InvalidOperation(format!(
+            "Export {} does not contain a root filesystem (missing {})",
+            display,
+            missing.join(", ")
+        )));
+    }
+
+    let checksum_verified = match read_export_manifest(export)? {
+        Some(manifest) if !manifest.sha256.eq_ignore_ascii_case(&sha256) => {
+            return Err(TamerError::InvalidOperation(format!(
+                "Export {} is corrupt: checksum does not match its manifest",
+                display
+            )));
+        }
+        Some(_) => true,
+        None => false,
+    };
+
+    Ok(ExportVerification {
+        size_bytes,
+        sha256,
+        checksum_verified,
+        entries,
+        compression,
+        uncompressed_bytes,
+    })
+}
+
+/// Minimal root filesystem tarball for tests
+#[cfg(test)]
+pub fn sample_rootfs_tar() -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut dir = tar::Header::new_gnu();
+    dir.set_entry_type(tar::EntryType::Directory);
+    dir.set_mode(0o755);
+    dir.set_size(0);
+    builder
+        .append_data(&mut dir, "./bin/", io::empty())
+        .unwrap();
+
+    let os_release = b"ID=ubuntu\nVERSION_ID=\"24.04\"\n";
+    let mut file = tar::Header::new_gnu();
+    file.set_mode(0o644);
+    file.set_size(os_release.len() as u64);
+    builder
+        .append_data(&mut file, "./etc/os-release", &os_release[..])
+        .unwrap();
+    builder.into_inner().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{Compressor, HashingWriter};
+    use std::io::Write;
+
+    fn temp_export(content: &[u8]) -> PathBuf {
+        temp_export_named(content, "tar")
+    }
+
+    fn temp_export_named(content: &[u8], extension: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "wsl-tamer-export-{}.{}",
+            uuid::Uuid::new_v4(),
+            extension
+        ));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_verify_export_checks_structure_and_checksum() {
+        let tar = sample_rootfs_tar();
+        let path = temp_export(&tar);
+        let cancel = CancellationToken::new();
+
+        // No manifest: structure only
+        let report = verify_export(&path, &cancel).unwrap();
+        assert_eq!(report.entries, 2);
+        assert_eq!(report.size_bytes, tar.len() as u64);
+        assert!(!report.checksum_verified);
+
+        let mut writer = HashingWriter::new(Vec::new());
+        writer.write_all(&tar).unwrap();
+        let (sha256, size_bytes) = writer.finish().unwrap();
+        let mut manifest = ExportManifest {
+            distro: "Ubuntu".to_string(),
+            file_name: "export.tar".to_string(),
+            size_bytes,
+            sha256,
+            created_at: 0,
+            compression: Compression::None,
+            uncompressed_bytes: size_bytes,
+        };
+        write_export_manifest(&path, &manifest).unwrap();
+        assert!(verify_export(&path, &cancel).unwrap().checksum_verified);
+
+        manifest.sha256 = "0".repeat(64);
+        write_export_manifest(&path, &manifest).unwrap();
+        assert_eq!(
+            verify_export(&path, &cancel).unwrap_err().code(),
+            "INVALID_OPERATION"
+        );
+
+        remove_export(&path);
+        assert!(!path.exists() && !export_manifest_path(&path).exists());
+    }
+
+    #[test]
+    fn test_verify_compressed_export_by_extension() {
+        let tar = sample_rootfs_tar();
+        let mut compressor = Compressor::new(Vec::new(), Compression::Xz, 6).unwrap();
+        compressor.write_all(&tar).unwrap();
+        let (compressed, _) = compressor.finish().unwrap();
+
+        let path = temp_export_named(&compressed, "tar.xz");
+        let report = verify_export(&path, &CancellationToken::new()).unwrap();
+        assert_eq!(report.compression, Compression::Xz);
+        assert_eq!(report.entries, 2);
+        assert_eq!(report.size_bytes, compressed.len() as u64);
+        assert_eq!(report.uncompressed_bytes, tar.len() as u64);
+        remove_export(&path);
+
+        // A truncated stream is caught by the codec
+        let truncated = temp_export_named(&compressed[..compressed.len() - 8], "tar.xz");
+        assert_eq!(
+            verify_export(&truncated, &CancellationToken::new())
+                .unwrap_err()
+                .code(),
+            "PARSE_ERROR"
+        );
+        remove_export(&truncated);
+    }
+
+    #[test]
+    fn test_verify_export_rejects_garbage_and_non_rootfs() {
+        let cancel = CancellationToken::new();
+        let garbage = temp_export(b"not really a tar");
+        assert_eq!(
+            verify_export(&garbage, &cancel).unwrap_err().code(),
+            "PARSE_ERROR"
+        );
+        remove_export(&garbage);
+
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        builder
+            .append_data(&mut header, "notes.txt", &b"hello"[..])
+            .unwrap();
+        let not_rootfs = temp_export(&builder.into_inner().unwrap());
+        let err = verify_export(&not_rootfs, &cancel).unwrap_err();
+        assert!(err.to_string().contains("/etc/os-release, /bin"), "{}", err);
+        remove_export(&not_rootfs);
+    }
+}
@@ -0,0 +1,11 @@
+//! Time helpers
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Current Unix timestamp in seconds
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -0,0 +1,145 @@
+//! Block allocation table (MS-VHDX 2.5): where each payload block lives
+//!
+//! Payload block entries are interleaved with one sector bitmap entry after
+//! every `chunk_ratio` of them.
+
+use super::{invalid, read_at, u64_at, RegionEntry, VhdxMetadata, MIB};
+use crate::error::TamerResult;
+use std::io::{Read, Seek};
+
+/// Sectors described by one sector bitmap block
+const SECTORS_PER_BITMAP: u64 = 1 << 23;
+
+/// State of a payload block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadState {
+    /// Never written (reads as zeros, or from the parent)
+    NotPresent,
+    /// Contents are undefined
+    Undefined,
+    /// Reads as zeros
+    Zero,
+    /// Trimmed by the guest
+    Unmapped,
+    /// Stored in the file
+    FullyPresent,
+    /// Stored in the file, sectors missing from it come from the parent
+    PartiallyPresent,
+}
+
+impl PayloadState {
+    fn from_bits(bits: u64) -> Option<Self> {
+        match bits {
+            0 => Some(Self::NotPresent),
+            1 => Some(Self::Undefined),
+            2 => Some(Self::Zero),
+            3 => Some(Self::Unmapped),
+            6 => Some(Self::FullyPresent),
+            7 => Some(Self::PartiallyPresent),
+            _ => None,
+        }
+    }
+
+    /// Whether the block occupies space in the file
+    pub fn is_allocated(self) -> bool {
+        matches!(self, Self::FullyPresent | Self::PartiallyPresent)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PayloadBlock {
+    pub state: PayloadState,
+    /// Offset of the block in the file (0 unless allocated)
+    pub file_offset: u64,
+}
+
+/// Payload block entries of the BAT, in virtual disk order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bat {
+    pub block_size: u32,
+    pub chunk_ratio: u64,
+    pub blocks: Vec<PayloadBlock>,
+}
+
+impl Bat {
+    /// Read the payload entries for the disk described by `metadata`
+    ///
+    /// Fails if an allocated block lies (partly) beyond `file_size`.
+    pub fn read<R: Read + Seek>(
+        reader: &mut R,
+        region: &RegionEntry,
+        metadata: &VhdxMetadata,
+        file_size: u64,
+    ) -> TamerResult<Self> {
+        let block_size = metadata.block_size as u64;
+        let chunk_ratio = SECTORS_PER_BITMAP * metadata.logical_sector_size as u64 / block_size;
+        let data_blocks = metadata.virtual_disk_size.div_ceil(block_size);
+        let index = |block: u64| block + block / chunk_ratio;
+
+        let entries = if data_blocks == 0 {
+            0
+        } else {
+            index(data_blocks - 1) + 1
+        };
+        if entries * 8 > region.length as u64 {
+            return Err(invalid("BAT region is too small for the virtual disk size"));
+        }
+        let mut table = vec![0u8; (entries * 8) as usize];
+        read_at(reader, region.file_offset, &mut table)?;
+
+        let blocks = (0..data_blocks)
+            .map(|block| {
+                let entry = u64_at(&table, (index(block) * 8) as usize);
+                let state = PayloadState::from_bits(entry & 0b111).ok_or_else(|| {
+                    invalid(&format!(
+                        "BAT entry for block {} has an invalid state",
+                        block
+                    ))
+                })?;
+                let file_offset = (entry >> 20) * MIB;
+                if state.is_allocated()
+                    && (file_offset == 0 || file_offset + block_size > file_size)
+                {
+                    return Err(invalid(&format!("block {} lies outside the file", block)));
+                }
+                Ok(PayloadBlock { state, file_offset })
+            })
+            .collect::<TamerResult<Vec<_>>>()?;
+
+        Ok(Self {
+            block_size: metadata.block_size,
+            chunk_ratio,
+            blocks,
+        })
+    }
+
+    /// Number of payload blocks stored in the file
+    pub fn allocated_blocks(&self) -> u64 {
+        self.blocks
+            .iter()
+            .filter(|b| b.state.is_allocated())
+            .count() as u64
+    }
+
+    /// Share of allocated blocks (0.0–1.0) that don't directly follow the
+    /// previous allocated block in the file
+    ///
+    /// 0 means the disk's data is laid out in virtual order; compaction and
+    /// heavy rewriting drive it up.
+    pub fn fragmentation(&self) -> f64 {
+        let offsets: Vec<u64> = self
+            .blocks
+            .iter()
+            .filter(|b| b.state.is_allocated())
+            .map(|b| b.file_offset)
+            .collect();
+        if offsets.len() < 2 {
+            return 0.0;
+        }
+        let breaks = offsets
+            .windows(2)
+            .filter(|pair| pair[1] != pair[0] + self.block_size as u64)
+            .count();
+        breaks as f64 / (offsets.len() - 1) as f64
+    }
+}
@@ -0,0 +1,198 @@
+//! Synthetic VHDX images for tests
+
+use super::bat::PayloadState;
+use super::crc32c::checksum_excluding;
+use super::header::{HEADER_OFFSETS, HEADER_SIGNATURE, HEADER_SIZE, HEADER_VERSION};
+use super::metadata::{
+    FILE_PARAMETERS, LOGICAL_SECTOR_SIZE, METADATA_SIGNATURE, PHYSICAL_SECTOR_SIZE,
+    VIRTUAL_DISK_SIZE,
+};
+use super::region::{
+    BAT_REGION, METADATA_REGION, REGION_TABLE_OFFSETS, REGION_TABLE_SIGNATURE, REGION_TABLE_SIZE,
+};
+use super::{Guid, FILE_SIGNATURE, KIB, MIB};
+
+const LOG_OFFSET: usize = 512 * KIB as usize;
+const LOG_LENGTH: usize = 512 * KIB as usize;
+const METADATA_OFFSET: usize = MIB as usize;
+const BAT_OFFSET: usize = 2 * MIB as usize;
+const REGION_LENGTH: usize = MIB as usize;
+/// Payload blocks are placed from here on
+pub const PAYLOAD_OFFSET: u64 = 3 * MIB;
+/// Item data starts after the 64 KiB metadata table
+const ITEM_DATA_OFFSET: usize = 64 * KIB as usize;
+
+/// Builds a small dynamic VHDX: identifier, both headers, both region
+/// tables, metadata and a BAT with the requested blocks allocated
+///
+/// Headers carry sequence numbers 1 and 2, so the second one is current.
+pub struct VhdxBuilder {
+    virtual_size: u64,
+    block_size: u32,
+    logical_sector_size: u32,
+    physical_sector_size: u32,
+    pending_log: bool,
+    /// Payload block index, state and file offset
+    blocks: Vec<(u64, PayloadState, u64)>,
+}
+
+impl VhdxBuilder {
+    pub fn new(virtual_size: u64) -> Self {
+        Self {
+            virtual_size,
+            block_size: 32 * MIB as u32,
+            logical_sector_size: 512,
+            physical_sector_size: 4096,
+            pending_log: false,
+            blocks: Vec::new(),
+        }
+    }
+
+    pub fn block_size(mut self, block_size: u32) -> Self {
+        self.block_size = block_size;
+        self
+    }
+
+    /// Store `block` in the file, right after the last stored block
+    pub fn allocate(self, block: u64) -> Self {
+        let offset = self
+            .blocks
+            .iter()
+            .filter(|(_, state, _)| state.is_allocated())
+            .map(|(_, _, offset)| offset + self.block_size as u64)
+            .max()
+            .unwrap_or(PAYLOAD_OFFSET);
+        self.allocate_at(block, offset)
+    }
+
+    /// Store `block` in the file at `file_offset` (a multiple of 1 MiB)
+    pub fn allocate_at(self, block: u64, file_offset: u64) -> Self {
+        self.with_state(block, PayloadState::FullyPresent, file_offset)
+    }
+
+    /// Mark `block` with a state that takes no space in the file
+    pub fn unallocated(self, block: u64, state: PayloadState) -> Self {
+        self.with_state(block, state, 0)
+    }
+
+    /// Give the current header a log GUID, as if writes were pending
+    pub fn pending_log(mut self) -> Self {
+        self.pending_log = true;
+        self
+    }
+
+    fn with_state(mut self, block: u64, state: PayloadState, file_offset: u64) -> Self {
+        self.blocks.push((block, state, file_offset));
+        self
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        let end = self
+            .blocks
+            .iter()
+            .map(|(_, _, offset)| offset + self.block_size as u64)
+            .max()
+            .unwrap_or(0)
+            .max(PAYLOAD_OFFSET);
+        let mut image = vec![0u8; end as usize];
+        image[..8].copy_from_slice(FILE_SIGNATURE);
+
+        for (i, offset) in HEADER_OFFSETS.into_iter().enumerate() {
+            let at = offset as usize;
+            let header = &mut image[at..at + HEADER_SIZE];
+            header[..4].copy_from_slice(HEADER_SIGNATURE);
+            header[8..16].copy_from_slice(&(i as u64 + 1).to_le_bytes());
+            header[16..32].fill(0x11);
+            header[32..48].fill(0x22);
+            if self.pending_log && i == 1 {
+                header[48..64].fill(0x33);
+            }
+            header[66..68].copy_from_slice(&HEADER_VERSION.to_le_bytes());
+            header[68..72].copy_from_slice(&(LOG_LENGTH as u32).to_le_bytes());
+            header[72..80].copy_from_slice(&(LOG_OFFSET as u64).to_le_bytes());
+            seal(header);
+        }
+
+        for offset in REGION_TABLE_OFFSETS {
+            let at = offset as usize;
+            let table = &mut image[at..at + REGION_TABLE_SIZE];
+            table[..4].copy_from_slice(REGION_TABLE_SIGNATURE);
+            put_u32(table, 8, 2);
+            region_entry(table, 16, &BAT_REGION, BAT_OFFSET);
+            region_entry(table, 48, &METADATA_REGION, METADATA_OFFSET);
+            seal(table);
+        }
+
+        self.write_metadata(&mut image);
+
+        let chunk_ratio = (1u64 << 23) * self.logical_sector_size as u64 / self.block_size as u64;
+        for &(block, state, offset) in &self.blocks {
+            let index = block + block / chunk_ratio;
+            let entry = (offset / MIB) << 20 | state_bits(state);
+            let at = BAT_OFFSET + (index * 8) as usize;
+            image[at..at + 8].copy_from_slice(&entry.to_le_bytes());
+        }
+        image
+    }
+
+    fn write_metadata(&self, image: &mut [u8]) {
+        let table = METADATA_OFFSET;
+        image[table..table + 8].copy_from_slice(METADATA_SIGNATURE);
+        let items: [(Guid, Vec<u8>); 4] = [
+            (FILE_PARAMETERS, {
+                let mut data = self.block_size.to_le_bytes().to_vec();
+                data.extend_from_slice(&0u32.to_le_bytes());
+                data
+            }),
+            (VIRTUAL_DISK_SIZE, self.virtual_size.to_le_bytes().to_vec()),
+            (
+                LOGICAL_SECTOR_SIZE,
+                self.logical_sector_size.to_le_bytes().to_vec(),
+            ),
+            (
+                PHYSICAL_SECTOR_SIZE,
+                self.physical_sector_size.to_le_bytes().to_vec(),
+            ),
+        ];
+        image[table + 10..table + 12].copy_from_slice(&(items.len() as u16).to_le_bytes());
+
+        let mut data_offset = ITEM_DATA_OFFSET;
+        for (i, (id, data)) in items.iter().enumerate() {
+            let at = table + 32 + i * 32;
+            image[at..at + 16].copy_from_slice(id);
+            put_u32(image, at + 16, data_offset as u32);
+            put_u32(image, at + 20, data.len() as u32);
+            let start = table + data_offset;
+            image[start..start + data.len()].copy_from_slice(data);
+            data_offset += 8;
+        }
+    }
+}
+
+fn state_bits(state: PayloadState) -> u64 {
+    match state {
+        PayloadState::NotPresent => 0,
+        PayloadState::Undefined => 1,
+        PayloadState::Zero => 2,
+        PayloadState::Unmapped => 3,
+        PayloadState::FullyPresent => 6,
+        PayloadState::PartiallyPresent => 7,
+    }
+}
+
+/// Store the CRC-32C of a header or region table in its checksum field
+fn seal(structure: &mut [u8]) {
+    let checksum = checksum_excluding(structure, 4);
+    put_u32(structure, 4, checksum);
+}
+
+fn region_entry(table: &mut [u8], at: usize, id: &Guid, offset: usize) {
+    table[at..at + 16].copy_from_slice(id);
+    table[at + 16..at + 24].copy_from_slice(&(offset as u64).to_le_bytes());
+    put_u32(table, at + 24, REGION_LENGTH as u32);
+    put_u32(table, at + 28, 1);
+}
+
+fn put_u32(buf: &mut [u8], at: usize, value: u32) {
+    buf[at..at + 4].copy_from_slice(&value.to_le_bytes());
+}
@@ -0,0 +1,80 @@
+//! Headers (MS-VHDX 2.2): two copies, the current one has the highest
+//! sequence number
+
+use super::crc32c::checksum_excluding;
+use super::{guid_at, invalid, read_at, u16_at, u32_at, u64_at, Guid, KIB};
+use crate::error::TamerResult;
+use std::io::{Read, Seek};
+
+pub const HEADER_OFFSETS: [u64; 2] = [64 * KIB, 128 * KIB];
+pub const HEADER_SIZE: usize = 4 * KIB as usize;
+pub const HEADER_SIGNATURE: &[u8; 4] = b"head";
+/// The only version defined by the specification
+pub const HEADER_VERSION: u16 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VhdxHeader {
+    pub sequence_number: u64,
+    pub file_write_guid: Guid,
+    pub data_write_guid: Guid,
+    /// Non-zero when the log holds entries that must be replayed
+    pub log_guid: Guid,
+    pub version: u16,
+    pub log_length: u32,
+    pub log_offset: u64,
+}
+
+impl VhdxHeader {
+    /// Read both headers and return the current one
+    ///
+    /// A header with a bad checksum is ignored; the file is only invalid if
+    /// neither copy can be used.
+    pub fn read_current<R: Read + Seek>(reader: &mut R) -> TamerResult<Self> {
+        let mut current: Option<Self> = None;
+        let mut last_error = invalid("no header");
+        for offset in HEADER_OFFSETS {
+            let mut buf = vec![0u8; HEADER_SIZE];
+            read_at(reader, offset, &mut buf)?;
+            match Self::parse(&buf) {
+                Ok(header) => {
+                    if current
+                        .as_ref()
+                        .is_none_or(|c| header.sequence_number > c.sequence_number)
+                    {
+                        current = Some(header);
+                    }
+                }
+                Err(e) => last_error = e,
+            }
+        }
+        current.ok_or(last_error)
+    }
+
+    /// Parse one 4 KiB header
+    pub fn parse(buf: &[u8]) -> TamerResult<Self> {
+        if &buf[..4] != HEADER_SIGNATURE {
+            return Err(invalid("missing 'head' header signature"));
+        }
+        if u32_at(buf, 4) != checksum_excluding(&buf[..HEADER_SIZE], 4) {
+            return Err(invalid("header checksum mismatch"));
+        }
+        let version = u16_at(buf, 66);
+        if version != HEADER_VERSION {
+            return Err(invalid(&format!("unsupported version {}", version)));
+        }
+        Ok(Self {
+            sequence_number: u64_at(buf, 8),
+            file_write_guid: guid_at(buf, 16),
+            data_write_guid: guid_at(buf, 32),
+            log_guid: guid_at(buf, 48),
+            version,
+            log_length: u32_at(buf, 68),
+            log_offset: u64_at(buf, 72),
+        })
+    }
+
+    /// Whether the log must be replayed before the file is consistent
+    pub fn has_pending_log(&self) -> bool {
+        self.log_guid != Guid::default()
+    }
+}
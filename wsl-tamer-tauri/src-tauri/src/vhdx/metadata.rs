@@ -0,0 +1,120 @@
+//! Metadata region (MS-VHDX 2.6): disk and sector sizes
+
+use super::{guid, guid_at, invalid, read_at, u16_at, u32_at, u64_at, Guid, RegionEntry};
+use crate::error::TamerResult;
+use std::io::{Read, Seek};
+
+pub const METADATA_SIGNATURE: &[u8; 8] = b"metadata";
+const TABLE_SIZE: usize = 64 * 1024;
+const HEADER_SIZE: usize = 32;
+const ENTRY_SIZE: usize = 32;
+const MAX_ENTRIES: usize = 2047;
+
+pub const FILE_PARAMETERS: Guid = guid(
+    0xCAA16737,
+    0xFA36,
+    0x4D43,
+    [0xB3, 0xB6, 0x33, 0xF0, 0xAA, 0x44, 0xE7, 0x6B],
+);
+pub const VIRTUAL_DISK_SIZE: Guid = guid(
+    0x2FA54224,
+    0xCD1B,
+    0x4876,
+    [0xB2, 0x11, 0x5D, 0xBE, 0xD8, 0x3B, 0xF4, 0xB8],
+);
+pub const LOGICAL_SECTOR_SIZE: Guid = guid(
+    0x8141BF1D,
+    0xA96F,
+    0x4709,
+    [0xBA, 0x47, 0xF2, 0x33, 0xA8, 0xFA, 0xAB, 0x5F],
+);
+pub const PHYSICAL_SECTOR_SIZE: Guid = guid(
+    0xCDA348C7,
+    0x445D,
+    0x4471,
+    [0x9C, 0xC9, 0xE9, 0x88, 0x52, 0x51, 0xC5, 0x56],
+);
+
+/// Well-known metadata items
+#[derive(Debug, Clone, PartialEq)]
+pub struct VhdxMetadata {
+    pub virtual_disk_size: u64,
+    /// Payload block size (1 MiB–256 MiB, a power of two)
+    pub block_size: u32,
+    pub logical_sector_size: u32,
+    pub physical_sector_size: u32,
+    /// Blocks stay allocated even when trimmed (fixed disks)
+    pub leave_blocks_allocated: bool,
+    /// Differencing disk
+    pub has_parent: bool,
+}
+
+impl VhdxMetadata {
+    /// Read the metadata table and its items from `region`
+    pub fn read<R: Read + Seek>(reader: &mut R, region: &RegionEntry) -> TamerResult<Self> {
+        let mut table = vec![0u8; TABLE_SIZE.min(region.length as usize)];
+        if table.len() < HEADER_SIZE {
+            return Err(invalid("metadata region is too small"));
+        }
+        read_at(reader, region.file_offset, &mut table)?;
+        if &table[..8] != METADATA_SIGNATURE {
+            return Err(invalid("missing 'metadata' table signature"));
+        }
+        let count = u16_at(&table, 10) as usize;
+        if count > MAX_ENTRIES || HEADER_SIZE + count * ENTRY_SIZE > table.len() {
+            return Err(invalid("metadata table has too many entries"));
+        }
+
+        // Locate an item's data within the region
+        let item = |id: &Guid| -> Option<(u64, usize)> {
+            (0..count)
+                .map(|i| HEADER_SIZE + i * ENTRY_SIZE)
+                .find_map(|at| {
+                    (guid_at(&table, at) == *id).then(|| {
+                        let offset = u32_at(&table, at + 16) as u64;
+                        let length = u32_at(&table, at + 20) as usize;
+                        (region.file_offset + offset, length)
+                    })
+                })
+        };
+        let mut read_item = |id: &Guid, min_len: usize, name: &str| -> TamerResult<Vec<u8>> {
+            let (offset, length) =
+                item(id).ok_or_else(|| invalid(&format!("metadata has no {} item", name)))?;
+            if length < min_len {
+                return Err(invalid(&format!("{} item is too short", name)));
+            }
+            let mut data = vec![0u8; min_len];
+            read_at(reader, offset, &mut data)?;
+            Ok(data)
+        };
+
+        let parameters = read_item(&FILE_PARAMETERS, 8, "file parameters")?;
+        let virtual_disk_size = u64_at(&read_item(&VIRTUAL_DISK_SIZE, 8, "virtual disk size")?, 0);
+        let logical_sector_size = u32_at(
+            &read_item(&LOGICAL_SECTOR_SIZE, 4, "logical sector size")?,
+            0,
+        );
+        let physical_sector_size = u32_at(
+            &read_item(&PHYSICAL_SECTOR_SIZE, 4, "physical sector size")?,
+            0,
+        );
+
+        let block_size = u32_at(&parameters, 0);
+        let flags = u32_at(&parameters, 4);
+        if !block_size.is_power_of_two() || !(1 << 20..=256 << 20).contains(&block_size) {
+            return Err(invalid("block size is out of range"));
+        }
+        if ![512, 4096].contains(&logical_sector_size) {
+            return Err(invalid("logical sector size must be 512 or 4096"));
+        }
+
+        Ok(Self {
+            virtual_disk_size,
+            block_size,
+            logical_sector_size,
+            physical_sector_size,
+            leave_blocks_allocated: flags & 1 != 0,
+            has_parent: flags & 2 != 0,
+        })
+    }
+}
@@ -0,0 +1,280 @@
+//! VHDX - Pure-Rust reader for the virtual disks WSL2 distributions live on
+//!
+//! Parses the structures needed to size and sanity-check a disk (MS-VHDX
+//! sections 2.1–2.6): the file identifier, headers, region table, metadata
+//! region and block allocation table. The log is not replayed, and payload
+//! data is never read. All integers are little-endian.
+
+pub mod bat;
+mod crc32c;
+pub mod header;
+pub mod metadata;
+pub mod region;
+
+#[cfg(test)]
+pub mod fixture;
+
+pub use bat::{Bat, PayloadBlock, PayloadState};
+pub use header::VhdxHeader;
+pub use metadata::VhdxMetadata;
+pub use region::{RegionEntry, RegionTable};
+
+use crate::error::{TamerError, TamerResult};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Signature at the start of every VHDX file
+pub const FILE_SIGNATURE: &[u8; 8] = b"vhdxfile";
+
+pub const KIB: u64 = 1024;
+pub const MIB: u64 = 1024 * KIB;
+
+/// Sizes and layout of a VHDX file
+#[derive(Debug, Clone, PartialEq)]
+pub struct VhdxInfo {
+    /// Size of the file on the host (space actually allocated)
+    pub file_size: u64,
+    /// Maximum size of the disk as seen by the guest
+    pub virtual_size: u64,
+    pub block_size: u32,
+    pub logical_sector_size: u32,
+    pub physical_sector_size: u32,
+    /// Differencing disk
+    pub has_parent: bool,
+    /// Payload blocks needed to cover the virtual size
+    pub total_blocks: u64,
+    /// Payload blocks stored in the file
+    pub allocated_blocks: u64,
+    /// Share of allocated blocks out of virtual order (0.0–1.0)
+    pub fragmentation: f64,
+    /// The log holds writes that haven't been applied yet
+    pub has_pending_log: bool,
+}
+
+impl VhdxInfo {
+    /// Bytes of payload blocks stored in the file
+    pub fn allocated_size(&self) -> u64 {
+        self.allocated_blocks * self.block_size as u64
+    }
+
+    /// Bytes of the file not taken by payload blocks (headers, log,
+    /// metadata, BAT and free space left behind by trimmed blocks)
+    pub fn overhead_size(&self) -> u64 {
+        self.file_size.saturating_sub(self.allocated_size())
+    }
+}
+
+/// Read the sizes of the VHDX at `path`
+pub fn read_info(path: &Path) -> TamerResult<VhdxInfo> {
+    let file = File::open(path)
+        .map_err(|e| TamerError::io(format!("Failed to open {}", path.display()), e))?;
+    let file_size = file
+        .metadata()
+        .map_err(|e| TamerError::io(format!("Failed to read {}", path.display()), e))?
+        .len();
+    parse(&mut BufReader::new(file), file_size)
+        .map_err(|e| e.context(format!("Failed to read VHDX {}", path.display())))
+}
+
+/// Parse a VHDX image of `file_size` bytes from `reader`
+pub fn parse<R: Read + Seek>(reader: &mut R, file_size: u64) -> TamerResult<VhdxInfo> {
+    let mut signature = [0u8; 8];
+    read_at(reader, 0, &mut signature)?;
+    if &signature != FILE_SIGNATURE {
+        return Err(invalid("missing 'vhdxfile' signature"));
+    }
+
+    let header = VhdxHeader::read_current(reader)?;
+    let regions = RegionTable::read(reader)?;
+    let metadata_region = regions
+        .find(&region::METADATA_REGION)
+        .ok_or_else(|| invalid("region table has no metadata region"))?;
+    let bat_region = regions
+        .find(&region::BAT_REGION)
+        .ok_or_else(|| invalid("region table has no BAT region"))?;
+    let metadata = VhdxMetadata::read(reader, metadata_region)?;
+    let bat = Bat::read(reader, bat_region, &metadata, file_size)?;
+
+    Ok(VhdxInfo {
+        file_size,
+        virtual_size: metadata.virtual_disk_size,
+        block_size: metadata.block_size,
+        logical_sector_size: metadata.logical_sector_size,
+        physical_sector_size: metadata.physical_sector_size,
+        has_parent: metadata.has_parent,
+        total_blocks: bat.blocks.len() as u64,
+        allocated_blocks: bat.allocated_blocks(),
+        fragmentation: bat.fragmentation(),
+        has_pending_log: header.has_pending_log(),
+    })
+}
+
+/// GUID in its on-disk form (first three fields little-endian)
+pub type Guid = [u8; 16];
+
+/// Build the on-disk form of `{d1-d2-d3-d4}`
+pub const fn guid(d1: u32, d2: u16, d3: u16, d4: [u8; 8]) -> Guid {
+    let a = d1.to_le_bytes();
+    let b = d2.to_le_bytes();
+    let c = d3.to_le_bytes();
+    [
+        a[0], a[1], a[2], a[3], b[0], b[1], c[0], c[1], d4[0], d4[1], d4[2], d4[3], d4[4], d4[5],
+        d4[6], d4[7],
+    ]
+}
+
+fn invalid(message: &str) -> TamerError {
+    TamerError::Parse(format!("Invalid VHDX: {}", message))
+}
+
+fn read_at<R: Read + Seek>(reader: &mut R, offset: u64, buf: &mut [u8]) -> TamerResult<()> {
+    reader
+        .seek(SeekFrom::Start(offset))
+        .and_then(|_| reader.read_exact(buf))
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => invalid("file is truncated"),
+            _ => TamerError::io("Failed to read VHDX", e),
+        })
+}
+
+fn u16_at(buf: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([buf[offset], buf[offset + 1]])
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(buf[offset..offset + 4].try_into().unwrap_or_default())
+}
+
+fn u64_at(buf: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(buf[offset..offset + 8].try_into().unwrap_or_default())
+}
+
+fn guid_at(buf: &[u8], offset: usize) -> Guid {
+    buf[offset..offset + 16].try_into().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_reads_sizes_from_sample_file() {
+        let image = fixture::VhdxBuilder::new(256 * MIB).build();
+        let path = std::env::temp_dir().join(format!("wsl-tamer-{}.vhdx", uuid::Uuid::new_v4()));
+        std::fs::write(&path, &image).unwrap();
+
+        let info = read_info(&path).unwrap();
+        assert_eq!(info.file_size, image.len() as u64);
+        assert_eq!(info.virtual_size, 256 * MIB);
+        assert_eq!(info.block_size, 32 * MIB as u32);
+        assert_eq!(info.logical_sector_size, 512);
+        assert_eq!(info.physical_sector_size, 4096);
+        assert!(!info.has_parent);
+        assert_eq!(info.total_blocks, 8);
+        assert_eq!(info.allocated_blocks, 0);
+        assert!(!info.has_pending_log);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_allocation_and_fragmentation_from_bat() {
+        let image = fixture::VhdxBuilder::new(8 * MIB)
+            .block_size(MIB as u32)
+            .allocate(0)
+            .allocate(1)
+            .allocate(2)
+            .unallocated(3, PayloadState::Unmapped)
+            .allocate_at(5, fixture::PAYLOAD_OFFSET + 10 * MIB)
+            .build();
+        let info = parse(&mut Cursor::new(&image), image.len() as u64).unwrap();
+
+        assert_eq!(info.total_blocks, 8);
+        assert_eq!(info.allocated_blocks, 4);
+        assert_eq!(info.allocated_size(), 4 * MIB);
+        assert_eq!(info.overhead_size(), image.len() as u64 - 4 * MIB);
+        // 0-1-2 are contiguous, 5 is not
+        assert!((info.fragmentation - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_bat_skips_sector_bitmap_entries() {
+        // 32 MiB blocks with 512-byte sectors: a bitmap entry after every 128
+        let image = fixture::VhdxBuilder::new(8 * 1024 * MIB)
+            .unallocated(127, PayloadState::Zero)
+            .unallocated(130, PayloadState::Unmapped)
+            .build();
+        let mut reader = Cursor::new(&image);
+        let regions = RegionTable::read(&mut reader).unwrap();
+        let metadata =
+            VhdxMetadata::read(&mut reader, regions.find(&region::METADATA_REGION).unwrap())
+                .unwrap();
+        let bat = Bat::read(
+            &mut reader,
+            regions.find(&region::BAT_REGION).unwrap(),
+            &metadata,
+            image.len() as u64,
+        )
+        .unwrap();
+
+        assert_eq!(bat.chunk_ratio, 128);
+        assert_eq!(bat.blocks.len(), 256);
+        assert_eq!(bat.blocks[127].state, PayloadState::Zero);
+        assert_eq!(bat.blocks[128].state, PayloadState::NotPresent);
+        assert_eq!(bat.blocks[130].state, PayloadState::Unmapped);
+    }
+
+    #[test]
+    fn test_headers_fall_back_to_the_valid_copy() {
+        let mut image = fixture::VhdxBuilder::new(MIB).pending_log().build();
+        let current = VhdxHeader::read_current(&mut Cursor::new(&image)).unwrap();
+        assert_eq!(current.sequence_number, 2);
+        assert!(current.has_pending_log());
+
+        // Damage the current header and the first region table
+        image[header::HEADER_OFFSETS[1] as usize + 100] ^= 0xFF;
+        image[region::REGION_TABLE_OFFSETS[0] as usize + 20] ^= 0xFF;
+        let info = parse(&mut Cursor::new(&image), image.len() as u64).unwrap();
+        assert!(!info.has_pending_log);
+
+        image[header::HEADER_OFFSETS[0] as usize + 100] ^= 0xFF;
+        let err = parse(&mut Cursor::new(&image), image.len() as u64).unwrap_err();
+        assert!(err.to_string().contains("checksum"), "{}", err);
+    }
+
+    #[test]
+    fn test_rejects_blocks_outside_the_file() {
+        let image = fixture::VhdxBuilder::new(4 * MIB)
+            .block_size(MIB as u32)
+            .allocate(0)
+            .allocate(1)
+            .build();
+        let truncated = &image[..image.len() - 1024];
+        let err = parse(&mut Cursor::new(truncated), truncated.len() as u64).unwrap_err();
+        assert!(err.to_string().contains("block 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_rejects_non_vhdx_and_truncated_files() {
+        let err = parse(&mut Cursor::new(vec![0u8; 4096]), 4096).unwrap_err();
+        assert!(err.to_string().contains("signature"), "{}", err);
+
+        let image = fixture::VhdxBuilder::new(MIB).build();
+        let truncated = &image[..300 * KIB as usize];
+        let err = parse(&mut Cursor::new(truncated), truncated.len() as u64).unwrap_err();
+        assert_eq!(err.code(), "PARSE_ERROR");
+    }
+
+    #[test]
+    fn test_guid_on_disk_layout() {
+        // 2DC27766-F623-4200-9D64-115E9BFD4A08
+        assert_eq!(
+            region::BAT_REGION,
+            [
+                0x66, 0x77, 0xC2, 0x2D, 0x23, 0xF6, 0x00, 0x42, 0x9D, 0x64, 0x11, 0x5E, 0x9B, 0xFD,
+                0x4A, 0x08
+            ]
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Region table (MS-VHDX 2.3): where the BAT and metadata regions live
+
+use super::crc32c::checksum_excluding;
+use super::{guid, guid_at, invalid, read_at, u32_at, u64_at, Guid, KIB};
+use crate::error::TamerResult;
+use std::io::{Read, Seek};
+
+/// The two copies of the region table
+pub const REGION_TABLE_OFFSETS: [u64; 2] = [192 * KIB, 256 * KIB];
+pub const REGION_TABLE_SIZE: usize = 64 * KIB as usize;
+pub const REGION_TABLE_SIGNATURE: &[u8; 4] = b"regi";
+const HEADER_SIZE: usize = 16;
+const ENTRY_SIZE: usize = 32;
+const MAX_ENTRIES: usize = 2047;
+
+/// Block allocation table region
+pub const BAT_REGION: Guid = guid(
+    0x2DC27766,
+    0xF623,
+    0x4200,
+    [0x9D, 0x64, 0x11, 0x5E, 0x9B, 0xFD, 0x4A, 0x08],
+);
+/// Metadata region
+pub const METADATA_REGION: Guid = guid(
+    0x8B7CA206,
+    0x4790,
+    0x4B9A,
+    [0xB8, 0xFE, 0x57, 0x5F, 0x05, 0x0F, 0x88, 0x6E],
+);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionEntry {
+    pub guid: Guid,
+    pub file_offset: u64,
+    pub length: u32,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionTable {
+    pub entries: Vec<RegionEntry>,
+}
+
+impl RegionTable {
+    /// Read the first valid copy of the region table
+    pub fn read<R: Read + Seek>(reader: &mut R) -> TamerResult<Self> {
+        let mut last_error = invalid("no region table");
+        for offset in REGION_TABLE_OFFSETS {
+            let mut buf = vec![0u8; REGION_TABLE_SIZE];
+            read_at(reader, offset, &mut buf)?;
+            match Self::parse(&buf) {
+                Ok(table) => return Ok(table),
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Parse one 64 KiB copy of the table
+    pub fn parse(buf: &[u8]) -> TamerResult<Self> {
+        if &buf[..4] != REGION_TABLE_SIGNATURE {
+            return Err(invalid("missing 'regi' region table signature"));
+        }
+        if u32_at(buf, 4) != checksum_excluding(&buf[..REGION_TABLE_SIZE], 4) {
+            return Err(invalid("region table checksum mismatch"));
+        }
+        let count = u32_at(buf, 8) as usize;
+        if count > MAX_ENTRIES {
+            return Err(invalid("region table has too many entries"));
+        }
+
+        let entries = (0..count)
+            .map(|i| {
+                let at = HEADER_SIZE + i * ENTRY_SIZE;
+                RegionEntry {
+                    guid: guid_at(buf, at),
+                    file_offset: u64_at(buf, at + 16),
+                    length: u32_at(buf, at + 24),
+                    required: u32_at(buf, at + 28) & 1 != 0,
+                }
+            })
+            .collect::<Vec<RegionEntry>>();
+
+        // Regions we don't understand may only be skipped if optional
+        if let Some(unknown) = entries
+            .iter()
+            .find(|e| e.required && e.guid != BAT_REGION && e.guid != METADATA_REGION)
+        {
+            return Err(invalid(&format!(
+                "unsupported required region at offset {}",
+                unknown.file_offset
+            )));
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn find(&self, guid: &Guid) -> Option<&RegionEntry> {
+        self.entries.iter().find(|e| &e.guid == guid)
+    }
+}
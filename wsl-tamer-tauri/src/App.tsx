@@ -3,10 +3,10 @@
 import { useState, useEffect, useMemo, useCallback } from 'react';
 import { Sidebar } from './components/Sidebar';
 import { ErrorBoundary } from './components/ErrorBoundary';
-import { ToastProvider } from './contexts/ToastContext';
+import { ToastProvider, useToast } from './contexts/ToastContext';
 import { ConfirmProvider, useConfirm } from './contexts/ConfirmContext';
 import { TextInputProvider } from './contexts/TextInputContext';
-import { diskCache } from './services';
+import { diskCache, automationService } from './services';
 import { 
   GeneralPage, 
   DistributionsPage, 
@@ -28,6 +28,17 @@ function AppContent() {
   const [isDarkMode, setIsDarkMode] = useState(true);
   const [hasUnsavedChanges, setHasUnsavedChanges] = useState(false);
   const confirm = useConfirm();
+  const { showToast } = useToast();
+
+  // Surface resource alerts while the window is open
+  useEffect(() => {
+    const unlisten = automationService.onAlertFired(alert => {
+      showToast('warning', `${alert.ruleName}: ${alert.message}`, 8000);
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, [showToast]);
 
   // Apply dark mode class to document
   useEffect(() => {
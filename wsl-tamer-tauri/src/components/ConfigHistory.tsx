@@ -0,0 +1,224 @@
+// Config History - Earlier versions of .wslconfig and wsl.conf with diff and rollback
+
+import { useState, useEffect } from 'react';
+import { wslService } from '../services';
+import { useConfirm } from '../contexts/ConfirmContext';
+import { toErrorMessage } from '../utils/errorUtils';
+import { formatBytes } from '../utils/formatUtils';
+import type { ConfigOrigin, ConfigTarget, ConfigVersion, DiffLine } from '../types';
+
+interface ConfigHistoryProps {
+  // Called after a rollback so editors can reload the file
+  onRollback?: () => void;
+}
+
+function targetLabel(target: ConfigTarget): string {
+  return target.kind === 'wslconfig' ? '.wslconfig' : `${target.distro}: /etc/wsl.conf`;
+}
+
+function originLabel(origin: ConfigOrigin): string {
+  switch (origin.type) {
+    case 'manual':
+      return 'Edited manually';
+    case 'profile':
+      return `Profile "${origin.profileName}"`;
+    case 'automationRule':
+      return `Automation rule "${origin.ruleName}"`;
+    case 'rollback':
+      return 'Rollback';
+  }
+}
+
+function formatDate(timestamp: number): string {
+  // Version timestamps are Unix seconds
+  return new Date(timestamp * 1000).toLocaleString('en-US', {
+    year: 'numeric',
+    month: 'short',
+    day: 'numeric',
+    hour: '2-digit',
+    minute: '2-digit',
+    second: '2-digit'
+  });
+}
+
+const diffSigns: Record<DiffLine['kind'], string> = {
+  unchanged: ' ',
+  added: '+',
+  removed: '-',
+};
+
+export function ConfigHistory({ onRollback }: ConfigHistoryProps) {
+  const confirm = useConfirm();
+  const [versions, setVersions] = useState<ConfigVersion[]>([]);
+  const [selected, setSelected] = useState<ConfigVersion | null>(null);
+  // Version to compare with; null compares with the current file
+  const [compareTo, setCompareTo] = useState<string | null>(null);
+  const [diff, setDiff] = useState<DiffLine[]>([]);
+  const [loading, setLoading] = useState(true);
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const [success, setSuccess] = useState<string | null>(null);
+
+  useEffect(() => {
+    loadHistory();
+  }, []);
+
+  useEffect(() => {
+    if (selected) {
+      loadDiff(selected.id, compareTo);
+    }
+  }, [selected, compareTo]);
+
+  async function loadHistory() {
+    try {
+      setLoading(true);
+      setError(null);
+      setVersions(await wslService.getConfigHistory());
+    } catch (err: unknown) {
+      setError(`Failed to load history: ${toErrorMessage(err)}`);
+    } finally {
+      setLoading(false);
+    }
+  }
+
+  async function loadDiff(fromId: string, toId: string | null) {
+    try {
+      setError(null);
+      setDiff(await wslService.diffConfigVersions(fromId, toId ?? undefined));
+    } catch (err: unknown) {
+      setDiff([]);
+      setError(`Failed to compare versions: ${toErrorMessage(err)}`);
+    }
+  }
+
+  function selectVersion(version: ConfigVersion) {
+    setSelected(version);
+    setCompareTo(null);
+  }
+
+  async function handleRollback(version: ConfigVersion) {
+    const ok = await confirm({
+      title: 'Roll Back Configuration',
+      message: `Restore ${targetLabel(version.target)} to the version from before ${formatDate(version.replacedAt)}?\n\nThe current file is kept in the history.`
+    });
+    if (!ok) {
+      return;
+    }
+
+    try {
+      setBusy(true);
+      setError(null);
+      await wslService.rollbackConfig(version.id);
+      setSuccess(`${targetLabel(version.target)} rolled back. Restart WSL for changes to take effect.`);
+      setTimeout(() => setSuccess(null), 5000);
+      setSelected(null);
+      onRollback?.();
+      await loadHistory();
+    } catch (err: unknown) {
+      setError(`Rollback failed: ${toErrorMessage(err)}`);
+    } finally {
+      setBusy(false);
+    }
+  }
+
+  if (loading) {
+    return (
+      <div className="loading-container">
+        <div className="loading-spinner"></div>
+        <p>Loading history...</p>
+      </div>
+    );
+  }
+
+  // Other versions of the same file, for comparing against
+  const sameTarget = selected
+    ? versions.filter(v => v.id !== selected.id && targetLabel(v.target) === targetLabel(selected.target))
+    : [];
+
+  return (
+    <div className="config-history">
+      {error && (
+        <div className="alert alert-error">
+          <span className="alert-icon">⚠️</span>
+          <span>{error}</span>
+          <button className="alert-dismiss" onClick={() => setError(null)}>×</button>
+        </div>
+      )}
+
+      {success && (
+        <div className="alert alert-success">
+          <span className="alert-icon">✓</span>
+          <span>{success}</span>
+          <button className="alert-dismiss" onClick={() => setSuccess(null)}>×</button>
+        </div>
+      )}
+
+      {versions.length === 0 ? (
+        <p className="config-history-empty">
+          No earlier versions yet. Every change to .wslconfig or a wsl.conf keeps the version it replaces here.
+        </p>
+      ) : (
+        <div className="config-history-list">
+          {versions.map(version => (
+            <div
+              key={version.id}
+              className={`config-history-item ${selected?.id === version.id ? 'selected' : ''}`}
+              onClick={() => selectVersion(version)}
+            >
+              <div className="config-history-info">
+                <span className="config-history-target">{targetLabel(version.target)}</span>
+                <span className="config-history-meta">
+                  Replaced {formatDate(version.replacedAt)} · {originLabel(version.replacedBy)} ·{' '}
+                  {version.existed ? formatBytes(version.sizeBytes) : 'file did not exist'}
+                </span>
+              </div>
+              <button
+                className="btn btn-sm btn-secondary"
+                onClick={e => {
+                  e.stopPropagation();
+                  handleRollback(version);
+                }}
+                disabled={busy}
+                title="Restore this version"
+              >
+                Roll Back
+              </button>
+            </div>
+          ))}
+        </div>
+      )}
+
+      {selected && (
+        <div className="config-section">
+          <div className="config-history-compare">
+            <label htmlFor="compare-to">Compare with</label>
+            <select
+              id="compare-to"
+              className="form-select"
+              value={compareTo ?? ''}
+              onChange={e => setCompareTo(e.target.value || null)}
+            >
+              <option value="">Current file</option>
+              {sameTarget.map(v => (
+                <option key={v.id} value={v.id}>
+                  Version replaced {formatDate(v.replacedAt)}
+                </option>
+              ))}
+            </select>
+          </div>
+          {diff.every(line => line.kind === 'unchanged') ? (
+            <p className="config-history-empty">No differences.</p>
+          ) : (
+            <pre className="config-diff">
+              {diff.map((line, i) => (
+                <div key={i} className={`config-diff-line ${line.kind}`}>
+                  {diffSigns[line.kind]} {line.text}
+                </div>
+              ))}
+            </pre>
+          )}
+        </div>
+      )}
+    </div>
+  );
+}
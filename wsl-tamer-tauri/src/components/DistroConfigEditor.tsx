@@ -5,7 +5,7 @@ import { wslService } from '../services';
 import { InfoTooltip } from './Tooltip';
 import { useConfirm } from '../contexts/ConfirmContext';
 import { toErrorMessage } from '../utils/errorUtils';
-import type { WslDistribution, DistroConfig } from '../types';
+import type { WslDistribution, DistroConfig, ConfigDiagnostic } from '../types';
 
 interface DistroConfigEditorProps {
   onUnsavedChanges?: (hasChanges: boolean) => void;
@@ -20,7 +20,11 @@ const tooltips: Record<string, string> = {
   interop_enabled: 'Allow launching Windows executables from WSL',
   appendWindowsPath: 'Add Windows PATH to WSL PATH',
   automount_enabled: 'Automatically mount Windows drives under /mnt',
+  automount_root: 'Directory Windows drives are mounted under (default /mnt/)',
   automount_options: 'Mount options for Windows drives (e.g., metadata,uid=1000)',
+  mountFsTab: 'Mount the file systems listed in /etc/fstab at startup',
+  default_user: 'User to log in as when the distribution starts',
+  gpu_enabled: 'Give Linux applications access to the Windows GPU',
 };
 
 export function DistroConfigEditor({ onUnsavedChanges }: DistroConfigEditorProps) {
@@ -33,6 +37,7 @@ export function DistroConfigEditor({ onUnsavedChanges }: DistroConfigEditorProps
   const [error, setError] = useState<string | null>(null);
   const [success, setSuccess] = useState<string | null>(null);
   const [hasChanges, setHasChanges] = useState(false);
+  const [diagnostics, setDiagnostics] = useState<ConfigDiagnostic[]>([]);
 
   // Load distributions on mount
   useEffect(() => {
@@ -63,12 +68,12 @@ export function DistroConfigEditor({ onUnsavedChanges }: DistroConfigEditorProps
   async function loadDistroConfig(distroName: string) {
     try {
       setError(null);
-      const content = await wslService.readDistroConfig(distroName);
-      setConfig(parseDistroConfig(content));
+      setDiagnostics([]);
+      setConfig(await wslService.getDistroConfigTyped(distroName));
       setHasChanges(false);
     } catch (err: unknown) {
-      // Config might not exist, that's OK
       setConfig({});
+      setError(`Failed to load wsl.conf: ${toErrorMessage(err)}`);
     }
   }
 
@@ -76,8 +81,7 @@ export function DistroConfigEditor({ onUnsavedChanges }: DistroConfigEditorProps
     try {
       setSaving(true);
       setError(null);
-      const content = serializeDistroConfig(config);
-      await wslService.writeDistroConfig(selectedDistro, content);
+      setDiagnostics(await wslService.saveDistroConfigTyped(selectedDistro, config));
       setSuccess('Configuration saved! Restart the distribution for changes to take effect.');
       setHasChanges(false);
       setTimeout(() => setSuccess(null), 5000);
@@ -156,6 +160,20 @@ export function DistroConfigEditor({ onUnsavedChanges }: DistroConfigEditorProps
         </div>
       )}
 
+      {diagnostics.length > 0 && (
+        <div className="alert alert-warning">
+          <ul className="config-diagnostics">
+            {diagnostics.map((d, i) => (
+              <li key={i} className={`config-diagnostic ${d.severity}`}>
+                <code>{d.key}</code> {d.message}
+                {d.suggestion && <span className="config-diagnostic-fix"> {d.suggestion}.</span>}
+              </li>
+            ))}
+          </ul>
+          <button className="alert-dismiss" onClick={() => setDiagnostics([])}>×</button>
+        </div>
+      )}
+
       <div className="config-sections">
         {/* Boot Section */}
         <section className="config-section">
@@ -295,6 +313,34 @@ export function DistroConfigEditor({ onUnsavedChanges }: DistroConfigEditorProps
               </label>
             </div>
 
+            <div className="config-field">
+              <label>
+                Mount fstab
+                <InfoTooltip content={tooltips.mountFsTab} />
+              </label>
+              <label className="switch">
+                <input
+                  type="checkbox"
+                  checked={config.mountFsTab ?? true}
+                  onChange={e => updateConfig('mountFsTab', e.target.checked)}
+                />
+                <span className="slider"></span>
+              </label>
+            </div>
+
+            <div className="config-field">
+              <label>
+                Mount Root
+                <InfoTooltip content={tooltips.automount_root} />
+              </label>
+              <input
+                type="text"
+                value={config.automountRoot ?? ''}
+                onChange={e => updateConfig('automountRoot', e.target.value || undefined)}
+                placeholder="/mnt/"
+              />
+            </div>
+
             <div className="config-field full-width">
               <label>
                 Mount Options
@@ -309,6 +355,40 @@ export function DistroConfigEditor({ onUnsavedChanges }: DistroConfigEditorProps
             </div>
           </div>
         </section>
+
+        {/* User & GPU Section */}
+        <section className="config-section">
+          <h3>User &amp; GPU</h3>
+          <div className="config-grid">
+            <div className="config-field">
+              <label>
+                Default User
+                <InfoTooltip content={tooltips.default_user} />
+              </label>
+              <input
+                type="text"
+                value={config.defaultUser ?? ''}
+                onChange={e => updateConfig('defaultUser', e.target.value || undefined)}
+                placeholder="e.g., dev"
+              />
+            </div>
+
+            <div className="config-field">
+              <label>
+                Enable GPU
+                <InfoTooltip content={tooltips.gpu_enabled} />
+              </label>
+              <label className="switch">
+                <input
+                  type="checkbox"
+                  checked={config.gpuEnabled ?? true}
+                  onChange={e => updateConfig('gpuEnabled', e.target.checked)}
+                />
+                <span className="slider"></span>
+              </label>
+            </div>
+          </div>
+        </section>
       </div>
 
       {/* Actions */}
@@ -332,88 +412,4 @@ export function DistroConfigEditor({ onUnsavedChanges }: DistroConfigEditorProps
   );
 }
 
-// Parse wsl.conf content to DistroConfig object
-function parseDistroConfig(content: string): DistroConfig {
-  const config: DistroConfig = {};
-  const lines = content.split('\n');
-  let currentSection = '';
-
-  for (const line of lines) {
-    const trimmed = line.trim();
-    if (trimmed.startsWith('[') && trimmed.endsWith(']')) {
-      currentSection = trimmed.slice(1, -1).toLowerCase();
-      continue;
-    }
-
-    if (!trimmed || trimmed.startsWith('#')) continue;
-
-    const [key, value] = trimmed.split('=').map(s => s.trim());
-    if (!key || value === undefined) continue;
-
-    const boolVal = value.toLowerCase() === 'true';
-
-    switch (currentSection) {
-      case 'boot':
-        if (key === 'systemd') config.systemd = boolVal;
-        if (key === 'command') config.bootCommand = value;
-        break;
-      case 'network':
-        if (key === 'hostname') config.hostname = value;
-        if (key === 'generateHosts') config.generateHosts = boolVal;
-        if (key === 'generateResolvConf') config.generateResolvConf = boolVal;
-        break;
-      case 'interop':
-        if (key === 'enabled') config.interopEnabled = boolVal;
-        if (key === 'appendWindowsPath') config.appendWindowsPath = boolVal;
-        break;
-      case 'automount':
-        if (key === 'enabled') config.automountEnabled = boolVal;
-        if (key === 'options') config.automountOptions = value;
-        break;
-    }
-  }
-
-  return config;
-}
-
-// Serialize DistroConfig to wsl.conf format
-function serializeDistroConfig(config: DistroConfig): string {
-  const sections: string[] = [];
-
-  // Boot section
-  const bootLines: string[] = [];
-  if (config.systemd !== undefined) bootLines.push(`systemd=${config.systemd}`);
-  if (config.bootCommand) bootLines.push(`command=${config.bootCommand}`);
-  if (bootLines.length > 0) {
-    sections.push('[boot]', ...bootLines, '');
-  }
-
-  // Network section
-  const networkLines: string[] = [];
-  if (config.hostname) networkLines.push(`hostname=${config.hostname}`);
-  if (config.generateHosts !== undefined) networkLines.push(`generateHosts=${config.generateHosts}`);
-  if (config.generateResolvConf !== undefined) networkLines.push(`generateResolvConf=${config.generateResolvConf}`);
-  if (networkLines.length > 0) {
-    sections.push('[network]', ...networkLines, '');
-  }
-
-  // Interop section
-  const interopLines: string[] = [];
-  if (config.interopEnabled !== undefined) interopLines.push(`enabled=${config.interopEnabled}`);
-  if (config.appendWindowsPath !== undefined) interopLines.push(`appendWindowsPath=${config.appendWindowsPath}`);
-  if (interopLines.length > 0) {
-    sections.push('[interop]', ...interopLines, '');
-  }
-
-  // Automount section
-  const automountLines: string[] = [];
-  if (config.automountEnabled !== undefined) automountLines.push(`enabled=${config.automountEnabled}`);
-  if (config.automountOptions) automountLines.push(`options=${config.automountOptions}`);
-  if (automountLines.length > 0) {
-    sections.push('[automount]', ...automountLines, '');
-  }
-
-  return sections.join('\n');
-}
-
 export default DistroConfigEditor;
@@ -5,7 +5,7 @@ import { load } from '@tauri-apps/plugin-store';
 import { wslService } from '../services';
 import { formatBytes } from '../utils/formatUtils';
 import { toErrorMessage } from '../utils/errorUtils';
-import type { SystemMetrics, DistroMetrics } from '../types';
+import type { SystemMetrics, DistroMetrics, MetricsPoint, MetricsHistorySettings, ListeningPort, WindowsReachability } from '../types';
 
 const STORE_FILE = 'settings.json';
 const POLLING_KEY = 'monitoring-polling-enabled';
@@ -17,9 +17,29 @@ interface MonitoringDashboardProps {
 // Polling interval: 2 minutes
 const POLL_INTERVAL_MS = 2 * 60 * 1000;
 
+// Graphs cover the last hour, one point per minute
+const HISTORY_RANGE_SECS = 60 * 60;
+
+/** SVG polyline points for `values` scaled to a 100x30 box */
+function sparkline(values: number[], max: number): string {
+  if (values.length < 2 || max <= 0) return '';
+  return values
+    .map((v, i) => `${(i / (values.length - 1)) * 100},${30 - (Math.min(v, max) / max) * 30}`)
+    .join(' ');
+}
+
+const REACHABILITY_LABELS: Record<WindowsReachability, string> = {
+  localhost: 'localhost',
+  vmAddress: 'VM IP only',
+  unreachable: 'Not reachable',
+};
+
 export function MonitoringDashboard({ compact = false }: MonitoringDashboardProps) {
   const [metrics, setMetrics] = useState<SystemMetrics | null>(null);
   const [distroMetrics, setDistroMetrics] = useState<DistroMetrics[]>([]);
+  const [history, setHistory] = useState<MetricsPoint[]>([]);
+  const [historySettings, setHistorySettings] = useState<MetricsHistorySettings | null>(null);
+  const [ports, setPorts] = useState<ListeningPort[]>([]);
   const [isPolling, setIsPolling] = useState(true);
   const [error, setError] = useState<string | null>(null);
   const intervalRef = useRef<number | null>(null);
@@ -37,16 +57,29 @@ export function MonitoringDashboard({ compact = false }: MonitoringDashboardProp
     })();
   }, []);
 
+  // History is recorded by the backend; it is off unless turned on here
+  useEffect(() => {
+    if (compact) return;
+    wslService.getMetricsHistorySettings()
+      .then(setHistorySettings)
+      .catch(() => { /* toggle stays hidden */ });
+  }, [compact]);
+
 
 
   const fetchMetrics = useCallback(async () => {
     try {
-      const [sysMetrics, distMetrics] = await Promise.all([
+      const [sysMetrics, distMetrics, hist] = await Promise.all([
         wslService.getSystemMetrics(),
-        wslService.getDistroMetrics()
+        wslService.getDistroMetrics(),
+        wslService.getMetricsHistory(HISTORY_RANGE_SECS, 'minute')
       ]);
       setMetrics(sysMetrics);
       setDistroMetrics(distMetrics);
+      setHistory(hist.points);
+      if (!compact) {
+        setPorts(await wslService.getListeningPorts());
+      }
       setError(null);
     } catch (err: unknown) {
       const msg = toErrorMessage(err);
@@ -55,7 +88,7 @@ export function MonitoringDashboard({ compact = false }: MonitoringDashboardProp
         setError(msg);
       }
     }
-  }, []);
+  }, [compact]);
 
   useEffect(() => {
     // Initial fetch
@@ -86,11 +119,24 @@ export function MonitoringDashboard({ compact = false }: MonitoringDashboardProp
     }
   }
 
+  async function toggleHistory() {
+    if (!historySettings) return;
+    const updated = { ...historySettings, enabled: !historySettings.enabled };
+    try {
+      await wslService.setMetricsHistorySettings(updated);
+      setHistorySettings(updated);
+    } catch (err: unknown) {
+      setError(`Failed to update history settings: ${toErrorMessage(err)}`);
+    }
+  }
+
   // Calculate percentages for progress bars
   const memoryPercent = metrics
     ? Math.min(100, (metrics.vmmemMemoryMb / metrics.wslMemoryLimitMb) * 100)
     : 0;
 
+  const cpuPercent = metrics ? Math.min(100, metrics.wslCpuPercent) : 0;
+
   const systemMemoryPercent = metrics
     ? Math.min(100, ((metrics.totalSystemMemoryMb - metrics.availableSystemMemoryMb) / metrics.totalSystemMemoryMb) * 100)
     : 0;
@@ -118,13 +164,26 @@ export function MonitoringDashboard({ compact = false }: MonitoringDashboardProp
     <div className="monitoring-dashboard">
       <div className="monitoring-header">
         <h3>Resource Monitor</h3>
-        <button 
-          className={`btn btn-sm ${isPolling ? 'btn-success' : 'btn-secondary'}`}
-          onClick={togglePolling}
-          title={isPolling ? 'Pause auto-refresh (every 2 min)' : 'Resume auto-refresh'}
-        >
-          {isPolling ? '⏸ Pause' : '▶ Resume'}
-        </button>
+        <div className="monitoring-actions">
+          {historySettings && (
+            <button
+              className={`btn btn-sm ${historySettings.enabled ? 'btn-success' : 'btn-secondary'}`}
+              onClick={toggleHistory}
+              title={historySettings.enabled
+                ? `Stop recording history in the background (every ${historySettings.intervalSecs} s)`
+                : 'Record history in the background for the graphs'}
+            >
+              {historySettings.enabled ? '● Recording' : '○ Record history'}
+            </button>
+          )}
+          <button 
+            className={`btn btn-sm ${isPolling ? 'btn-success' : 'btn-secondary'}`}
+            onClick={togglePolling}
+            title={isPolling ? 'Pause auto-refresh (every 2 min)' : 'Resume auto-refresh'}
+          >
+            {isPolling ? '⏸ Pause' : '▶ Resume'}
+          </button>
+        </div>
       </div>
 
       {error && (
@@ -162,6 +221,38 @@ export function MonitoringDashboard({ compact = false }: MonitoringDashboardProp
           </div>
         </div>
 
+        {/* VM CPU */}
+        <div className="metric-card">
+          <div className="metric-icon">⚙️</div>
+          <div className="metric-content">
+            <div className="metric-header">
+              <span className="metric-title">WSL CPU</span>
+              <span className="metric-percent">{Math.round(cpuPercent)}%</span>
+            </div>
+            <div className="progress-bar">
+              <div 
+                className={`progress-fill ${cpuPercent > 80 ? 'warning' : ''} ${cpuPercent > 95 ? 'critical' : ''}`}
+                style={{ width: `${cpuPercent}%` }}
+              />
+            </div>
+            <div className="metric-details">
+              {metrics && metrics.vmmemMemoryMb > 0 ? (
+                <>
+                  <span>Host {metrics.wslCpuPercent.toFixed(1)}%</span>
+                  {metrics.guestCpuPercent !== null && (
+                    <>
+                      <span className="separator">·</span>
+                      <span>Guest {metrics.guestCpuPercent.toFixed(1)}%</span>
+                    </>
+                  )}
+                </>
+              ) : (
+                <span>Not running</span>
+              )}
+            </div>
+          </div>
+        </div>
+
         {/* System Memory */}
         <div className="metric-card">
           <div className="metric-icon">💻</div>
@@ -238,8 +329,27 @@ export function MonitoringDashboard({ compact = false }: MonitoringDashboardProp
         </div>
       )}
 
+      {/* Last hour (recorded in the background) */}
+      {history.length > 1 && metrics && (
+        <div className="metrics-history">
+          <h4>Last Hour</h4>
+          <div className="history-chart">
+            <span className="history-label">CPU</span>
+            <svg viewBox="0 0 100 30" preserveAspectRatio="none">
+              <polyline className="history-line cpu" points={sparkline(history.map(p => p.wslCpuPercent), 100)} />
+            </svg>
+          </div>
+          <div className="history-chart">
+            <span className="history-label">Memory</span>
+            <svg viewBox="0 0 100 30" preserveAspectRatio="none">
+              <polyline className="history-line memory" points={sparkline(history.map(p => p.vmmemMemoryMb), metrics.wslMemoryLimitMb)} />
+            </svg>
+          </div>
+        </div>
+      )}
+
       {/* Distribution Disk Usage */}
-      {distroMetrics.length > 0 && distroMetrics.some(d => d.diskUsageMb > 0) && (
+      {distroMetrics.length > 0 && distroMetrics.some(d => (d.diskUsageMb ?? 0) > 0 || d.diskSizeMb > 0) && (
         <div className="distro-metrics">
           <h4>Distribution Disk Usage</h4>
           <div className="distro-list">
@@ -249,7 +359,44 @@ export function MonitoringDashboard({ compact = false }: MonitoringDashboardProp
                   {distro.isRunning ? '🟢' : '⚪'}
                 </span>
                 <span className="distro-name">{distro.name}</span>
-                <span className="distro-size">{formatBytes(distro.diskUsageMb * 1024 * 1024)}</span>
+                <span
+                  className="distro-size"
+                  title={distro.vhdPath ?? undefined}
+                >
+                  {distro.diskUsageMb !== null
+                    ? `${formatBytes(distro.diskUsageMb * 1024 * 1024)} used`
+                    : 'Usage unknown'}
+                  {distro.diskSizeMb > 0 && ` / ${formatBytes(distro.diskSizeMb * 1024 * 1024)} on disk`}
+                  {distro.virtualSizeMb > 0 && ` (max ${formatBytes(distro.virtualSizeMb * 1024 * 1024)})`}
+                </span>
+                {distro.reclaimableMb !== null && distro.reclaimableMb >= 1024 && (
+                  <span className="distro-reclaimable">
+                    {formatBytes(distro.reclaimableMb * 1024 * 1024)} reclaimable
+                  </span>
+                )}
+              </div>
+            ))}
+          </div>
+        </div>
+      )}
+
+      {/* Listening Ports */}
+      {ports.length > 0 && (
+        <div className="distro-metrics">
+          <h4>Listening Ports</h4>
+          <div className="distro-list">
+            {ports.map(port => (
+              <div key={`${port.distro}-${port.protocol}-${port.address}-${port.port}`} className="distro-metric-row">
+                <span className="distro-size">
+                  {port.protocol.toUpperCase()} {port.address.includes(':') ? `[${port.address}]` : port.address}:{port.port}
+                </span>
+                <span className="distro-name">{port.distro}</span>
+                <span className="distro-size">
+                  {port.process ?? '?'}{port.pid !== null && ` (${port.pid})`}
+                </span>
+                <span className={`port-reachability ${port.reachability}`}>
+                  {REACHABILITY_LABELS[port.reachability]}
+                </span>
               </div>
             ))}
           </div>
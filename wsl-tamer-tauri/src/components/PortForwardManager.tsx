@@ -0,0 +1,189 @@
+// Port Forward Manager Component - Windows ports proxied into distributions
+
+import { useState, useEffect } from 'react';
+import { portForwardService } from '../services';
+import type { PortForwardRule, PortForwardStatus, WslDistribution } from '../types';
+import { toErrorMessage } from '../utils/errorUtils';
+import { useConfirm } from '../contexts/ConfirmContext';
+
+interface PortForwardManagerProps {
+  distributions: WslDistribution[];
+}
+
+export function PortForwardManager({ distributions }: PortForwardManagerProps) {
+  const confirm = useConfirm();
+  const [rules, setRules] = useState<PortForwardRule[]>([]);
+  const [statuses, setStatuses] = useState<PortForwardStatus[]>([]);
+  const [distro, setDistro] = useState('');
+  const [listenPort, setListenPort] = useState('');
+  const [targetPort, setTargetPort] = useState('');
+  const [openFirewall, setOpenFirewall] = useState(false);
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    loadRules();
+  }, []);
+
+  async function loadRules() {
+    try {
+      setRules(await portForwardService.getRules());
+      setStatuses(await portForwardService.getStatus());
+    } catch (err) {
+      console.error('Failed to load port forwards:', err);
+    }
+  }
+
+  async function run(action: () => Promise<PortForwardStatus[] | void>) {
+    setBusy(true);
+    setError(null);
+    try {
+      const result = await action();
+      if (result) setStatuses(result);
+      setRules(await portForwardService.getRules());
+    } catch (err) {
+      setError(toErrorMessage(err));
+    } finally {
+      setBusy(false);
+    }
+  }
+
+  function handleAdd() {
+    const listen = parseInt(listenPort, 10);
+    const target = parseInt(targetPort || listenPort, 10);
+    if (!distro || !(listen > 0 && listen < 65536) || !(target > 0 && target < 65536)) {
+      setError('Choose a distribution and ports between 1 and 65535');
+      return;
+    }
+    run(async () => {
+      const result = await portForwardService.saveRule({
+        id: crypto.randomUUID(),
+        enabled: true,
+        listenAddress: '0.0.0.0',
+        listenPort: listen,
+        distro,
+        targetPort: target,
+        openFirewall,
+      });
+      setListenPort('');
+      setTargetPort('');
+      return result;
+    });
+  }
+
+  async function handleDelete(rule: PortForwardRule) {
+    const ok = await confirm({
+      title: 'Remove Port Forward',
+      message: `Stop forwarding port ${rule.listenPort} to ${rule.distro}?`,
+      confirmText: 'Remove',
+      danger: true,
+    });
+    if (ok) {
+      run(async () => {
+        await portForwardService.deleteRule(rule.id);
+        return portForwardService.getStatus();
+      });
+    }
+  }
+
+  function statusText(rule: PortForwardRule): string {
+    const status = statuses.find(s => s.ruleId === rule.id);
+    if (!rule.enabled) return 'Paused';
+    if (status?.active) return `→ ${status.connectAddress}:${rule.targetPort}`;
+    if (status?.connectAddress) return `Stale (${status.connectAddress})`;
+    return 'Not applied';
+  }
+
+  return (
+    <div className="snapshot-manager">
+      <div className="snapshot-header">
+        <h3>Port Forwarding</h3>
+        <button className="btn btn-sm btn-secondary" onClick={() => run(portForwardService.apply)} disabled={busy || rules.length === 0}>
+          🔄 Re-apply
+        </button>
+      </div>
+
+      {error && (
+        <div className="alert alert-error">
+          <span>❌ {error}</span>
+          <button className="alert-close" onClick={() => setError(null)} aria-label="Dismiss error">×</button>
+        </div>
+      )}
+
+      <div className="snapshot-section">
+        <h4>➕ Forward a Windows Port</h4>
+        <p>Makes a port inside a distribution reachable from the network under NAT networking. Applying asks for administrator rights.</p>
+        <div className="export-controls">
+          <select value={distro} onChange={(e) => setDistro(e.target.value)} className="form-select" disabled={busy}>
+            <option value="">Select distribution...</option>
+            {distributions.map(d => (
+              <option key={d.name} value={d.name}>{d.name}</option>
+            ))}
+          </select>
+          <input
+            type="number"
+            className="form-input"
+            placeholder="Windows port"
+            value={listenPort}
+            onChange={(e) => setListenPort(e.target.value)}
+            disabled={busy}
+          />
+          <input
+            type="number"
+            className="form-input"
+            placeholder="Distro port (same)"
+            value={targetPort}
+            onChange={(e) => setTargetPort(e.target.value)}
+            disabled={busy}
+          />
+          <button className="btn btn-primary" onClick={handleAdd} disabled={busy || !distro || !listenPort}>
+            Add
+          </button>
+        </div>
+        <label className="export-hint">
+          <input type="checkbox" checked={openFirewall} onChange={(e) => setOpenFirewall(e.target.checked)} />{' '}
+          Allow inbound connections in Windows Firewall
+        </label>
+      </div>
+
+      {rules.length > 0 && (
+        <div className="snapshot-section">
+          <h4>🔀 Forwarded Ports</h4>
+          <div className="snapshot-list">
+            {rules.map(rule => (
+              <div key={rule.id} className="snapshot-item">
+                <div className="snapshot-info">
+                  <span className="snapshot-name">
+                    {rule.listenAddress}:{rule.listenPort} → {rule.distro}:{rule.targetPort}
+                  </span>
+                  <span className="snapshot-date">
+                    {statusText(rule)}{rule.openFirewall ? ' · firewall open' : ''}
+                  </span>
+                </div>
+                <div className="snapshot-actions">
+                  <button
+                    className="btn btn-sm btn-secondary"
+                    onClick={() => run(() => portForwardService.saveRule({ ...rule, enabled: !rule.enabled }))}
+                    disabled={busy}
+                  >
+                    {rule.enabled ? 'Pause' : 'Resume'}
+                  </button>
+                  <button
+                    className="btn btn-sm btn-danger"
+                    onClick={() => handleDelete(rule)}
+                    disabled={busy}
+                    title="Remove port forward"
+                  >
+                    🗑️
+                  </button>
+                </div>
+              </div>
+            ))}
+          </div>
+        </div>
+      )}
+    </div>
+  );
+}
+
+export default PortForwardManager;
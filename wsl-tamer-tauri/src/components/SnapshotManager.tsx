@@ -1,24 +1,21 @@
 // Snapshot Manager Component - Distribution backup and restore
 
 import { useState, useEffect } from 'react';
-import { invoke } from '@tauri-apps/api/core';
 import { open, save } from '@tauri-apps/plugin-dialog';
-import { load } from '@tauri-apps/plugin-store';
-import type { WslDistribution } from '../types';
+import { wslService, jobService, snapshotService, profileService } from '../services';
+import type { Job, Snapshot, SnapshotPolicy, WslDistribution } from '../types';
 import { toErrorMessage } from '../utils/errorUtils';
+import { EXPORT_EXTENSIONS, exportExtension, formatExportSize } from '../utils/formatUtils';
 import { useConfirm } from '../contexts/ConfirmContext';
 import { useTextInput } from '../contexts/TextInputContext';
 
-const STORE_FILE = 'settings.json';
-const SNAPSHOTS_KEY = 'snapshot-history';
-
-interface Snapshot {
-  id: string;
-  distroName: string;
-  timestamp: number;
-  path: string;
-  sizeMb: number;
-}
+const PHASE_LABELS: Partial<Record<Job['phase'], string>> = {
+  exporting: 'Exporting',
+  importing: 'Importing',
+  checksumming: 'Verifying checksum',
+  unregistering: 'Unregistering',
+  verifying: 'Verifying',
+};
 
 interface SnapshotManagerProps {
   distributions: WslDistribution[];
@@ -29,37 +26,193 @@ export function SnapshotManager({ distributions, onRefresh }: SnapshotManagerPro
   const confirm = useConfirm();
   const textInput = useTextInput();
   const [snapshots, setSnapshots] = useState<Snapshot[]>([]);
+  const [policies, setPolicies] = useState<SnapshotPolicy[]>([]);
   const [selectedDistro, setSelectedDistro] = useState<string>('');
   const [isExporting, setIsExporting] = useState(false);
   const [isImporting, setIsImporting] = useState(false);
+  const [busySnapshot, setBusySnapshot] = useState<string | null>(null);
   const [progress, setProgress] = useState<string>('');
   const [error, setError] = useState<string | null>(null);
   const [success, setSuccess] = useState<string | null>(null);
 
   useEffect(() => {
     loadSnapshots();
+    loadPolicies();
   }, []);
 
   async function loadSnapshots() {
     try {
-      const store = await load(STORE_FILE);
-      const saved = await store.get<Snapshot[]>(SNAPSHOTS_KEY);
-      if (saved) {
-        setSnapshots(saved);
-      }
-    } catch {
-      // First run — no store yet
+      setSnapshots(await snapshotService.listSnapshots());
+    } catch (err) {
+      console.error('Failed to load snapshots:', err);
     }
   }
 
-  async function saveSnapshotsToStorage(newSnapshots: Snapshot[]) {
-    setSnapshots(newSnapshots);
+  async function loadPolicies() {
     try {
-      const store = await load(STORE_FILE);
-      await store.set(SNAPSHOTS_KEY, newSnapshots);
-      await store.save();
+      setPolicies(await snapshotService.getPolicies());
     } catch (err) {
-      console.error('Failed to persist snapshots:', err);
+      console.error('Failed to load snapshot schedules:', err);
+    }
+  }
+
+  async function handleSchedule(distro: string) {
+    const existing = policies.find(p => p.distro === distro);
+    const schedule = await textInput({
+      title: 'Schedule Snapshots',
+      message: `When to snapshot ${distro} (cron: minute hour day month weekday):`,
+      placeholder: 'e.g. 0 3 * * * for every night at 03:00',
+      defaultValue: existing?.schedule ?? '0 3 * * *',
+      confirmText: 'Continue'
+    });
+    if (!schedule) {
+      return;
+    }
+
+    const keepLast = await textInput({
+      title: 'Schedule Snapshots',
+      message: 'How many scheduled snapshots to keep:',
+      defaultValue: String(existing?.retention.keepLast ?? 7),
+      confirmText: 'Save Schedule'
+    });
+    if (!keepLast) {
+      return;
+    }
+
+    try {
+      await snapshotService.savePolicy({
+        distro,
+        enabled: true,
+        schedule,
+        format: existing?.format ?? 'tar',
+        retention: { ...existing?.retention, keepLast: Math.max(1, parseInt(keepLast, 10) || 1) },
+        lastRun: existing?.lastRun,
+      });
+      setSuccess(`Scheduled snapshots of ${distro}: ${schedule}`);
+      await loadPolicies();
+    } catch (err: unknown) {
+      setError(`Failed to save schedule: ${toErrorMessage(err)}`);
+    }
+  }
+
+  async function handleTogglePolicy(policy: SnapshotPolicy) {
+    try {
+      await snapshotService.savePolicy({ ...policy, enabled: !policy.enabled });
+      await loadPolicies();
+    } catch (err: unknown) {
+      setError(`Failed to update schedule: ${toErrorMessage(err)}`);
+    }
+  }
+
+  async function handleDeletePolicy(policy: SnapshotPolicy) {
+    const ok = await confirm({ title: 'Remove Schedule', message: `Stop taking scheduled snapshots of ${policy.distro}?\n\nExisting snapshots are kept.` });
+    if (!ok) {
+      return;
+    }
+
+    try {
+      await snapshotService.deletePolicy(policy.distro);
+      await loadPolicies();
+    } catch (err: unknown) {
+      setError(`Failed to remove schedule: ${toErrorMessage(err)}`);
+    }
+  }
+
+  /** Wait for a background job, mirroring its phase in the progress line */
+  async function runJob(job: Job, label: string): Promise<boolean> {
+    const result = await jobService.waitForJob(job.id, (update) => {
+      const phase = PHASE_LABELS[update.phase];
+      setProgress(phase ? `${label}: ${phase}...` : `${label}...`);
+    });
+    setProgress('');
+    return result.status === 'completed';
+  }
+
+  async function handleCreateSnapshot(distro: string) {
+    const name = await textInput({
+      title: 'Take Snapshot',
+      message: `Name the snapshot of ${distro}:`,
+      placeholder: 'e.g. Before upgrade',
+      confirmText: 'Take Snapshot'
+    });
+    if (!name) {
+      return;
+    }
+
+    try {
+      setIsExporting(true);
+      setError(null);
+      setProgress(`Taking snapshot of ${distro}...`);
+      const job = await snapshotService.createSnapshot(distro, name);
+      if (await runJob(job, `Snapshot of ${distro}`)) {
+        setSuccess(`Snapshot "${name}" of ${distro} created`);
+      }
+      await loadSnapshots();
+    } catch (err: unknown) {
+      setError(`Snapshot failed: ${toErrorMessage(err)}`);
+    } finally {
+      setIsExporting(false);
+      setProgress('');
+    }
+  }
+
+  async function handleRestoreSnapshot(snapshot: Snapshot) {
+    const ok = await confirm({
+      title: 'Restore Snapshot',
+      message: `Replace ${snapshot.distro} with snapshot "${snapshot.name}"?\n\nAll changes made since ${formatDate(snapshot.createdAt)} will be lost.`
+    });
+    if (!ok) {
+      return;
+    }
+
+    try {
+      setBusySnapshot(snapshot.id);
+      setError(null);
+      const job = await snapshotService.restoreSnapshot(snapshot.id);
+      if (await runJob(job, `Restoring ${snapshot.distro}`)) {
+        setSuccess(`${snapshot.distro} restored from "${snapshot.name}"`);
+      }
+      onRefresh?.();
+    } catch (err: unknown) {
+      setError(`Restore failed: ${toErrorMessage(err)}`);
+    } finally {
+      setBusySnapshot(null);
+      setProgress('');
+    }
+  }
+
+  async function handleRestoreAsNew(snapshot: Snapshot) {
+    const newName = await textInput({
+      title: 'Restore as New Distribution',
+      message: 'Enter a name for the restored distribution:',
+      placeholder: `e.g. ${snapshot.distro}-restored`,
+      confirmText: 'Continue'
+    });
+    if (!newName) {
+      return;
+    }
+
+    const location = await open({
+      title: 'Select Installation Directory',
+      directory: true
+    });
+    if (!location) {
+      return;
+    }
+
+    try {
+      setBusySnapshot(snapshot.id);
+      setError(null);
+      const job = await snapshotService.restoreSnapshotAsNew(snapshot.id, newName, location);
+      if (await runJob(job, `Restoring ${newName}`)) {
+        setSuccess(`Snapshot "${snapshot.name}" restored as ${newName}`);
+      }
+      onRefresh?.();
+    } catch (err: unknown) {
+      setError(`Restore failed: ${toErrorMessage(err)}`);
+    } finally {
+      setBusySnapshot(null);
+      setProgress('');
     }
   }
 
@@ -76,12 +229,13 @@ export function SnapshotManager({ distributions, onRefresh }: SnapshotManagerPro
       setProgress('Selecting export location...');
 
       // Open save dialog
+      const { defaultFormat } = await profileService.getCompressionSettings();
       const filePath = await save({
         title: `Export ${distro}`,
-        defaultPath: `${distro}-backup-${new Date().toISOString().split('T')[0]}.tar`,
+        defaultPath: `${distro}-backup-${new Date().toISOString().split('T')[0]}.${exportExtension(defaultFormat)}`,
         filters: [{
           name: 'WSL Export',
-          extensions: ['tar']
+          extensions: EXPORT_EXTENSIONS
         }]
       });
 
@@ -93,23 +247,12 @@ export function SnapshotManager({ distributions, onRefresh }: SnapshotManagerPro
 
       setProgress(`Exporting ${distro}... This may take several minutes.`);
 
-      await invoke('export_distribution', { 
-        name: distro, 
-        path: filePath 
-      });
-
-      // Create snapshot record
-      const snapshot: Snapshot = {
-        id: `${Date.now()}-${Math.random().toString(36).substr(2, 9)}`,
-        distroName: distro,
-        timestamp: Date.now(),
-        path: filePath,
-        sizeMb: 0 // Will be updated after export
-      };
-
-      saveSnapshotsToStorage([snapshot, ...snapshots]);
-      
-      setSuccess(`Successfully exported ${distro} to ${filePath}`);
+      const job = await wslService.exportDistribution(distro, filePath);
+      if (await runJob(job, `Exporting ${distro}`)) {
+        const manifest = await wslService.getExportManifest(filePath);
+        const size = manifest ? ` (${formatExportSize(manifest)})` : '';
+        setSuccess(`Successfully exported ${distro} to ${filePath}${size}`);
+      }
       setProgress('');
     } catch (err: unknown) {
       setError(`Export failed: ${toErrorMessage(err)}`);
@@ -129,7 +272,7 @@ export function SnapshotManager({ distributions, onRefresh }: SnapshotManagerPro
         title: 'Select WSL Backup',
         filters: [{
           name: 'WSL Export',
-          extensions: ['tar', 'tar.gz', 'vhdx']
+          extensions: [...EXPORT_EXTENSIONS, 'vhdx']
         }]
       });
 
@@ -165,13 +308,10 @@ export function SnapshotManager({ distributions, onRefresh }: SnapshotManagerPro
 
       setProgress(`Importing ${newName}... This may take several minutes.`);
 
-      await invoke('import_distribution', {
-        name: newName,
-        location: location,
-        tarPath: filePath
-      });
-
-      setSuccess(`Successfully imported ${newName}`);
+      const job = await wslService.importDistribution(newName, location, filePath);
+      if (await runJob(job, `Importing ${newName}`)) {
+        setSuccess(`Successfully imported ${newName}`);
+      }
       setProgress('');
       
       if (onRefresh) {
@@ -185,17 +325,22 @@ export function SnapshotManager({ distributions, onRefresh }: SnapshotManagerPro
   }
 
   async function handleDeleteSnapshot(snapshot: Snapshot) {
-    const ok = await confirm({ title: 'Remove Snapshot', message: `Remove snapshot record for ${snapshot.distroName}?\n\nNote: The backup file will NOT be deleted.` });
+    const ok = await confirm({ title: 'Delete Snapshot', message: `Delete snapshot "${snapshot.name}" of ${snapshot.distro}?\n\nThe snapshot data will be permanently removed.` });
     if (!ok) {
       return;
     }
 
-    const newSnapshots = snapshots.filter(s => s.id !== snapshot.id);
-    saveSnapshotsToStorage(newSnapshots);
+    try {
+      await snapshotService.deleteSnapshot(snapshot.id);
+      await loadSnapshots();
+    } catch (err: unknown) {
+      setError(`Delete failed: ${toErrorMessage(err)}`);
+    }
   }
 
   function formatDate(timestamp: number): string {
-    return new Date(timestamp).toLocaleDateString('en-US', {
+    // Snapshot timestamps are Unix seconds
+    return new Date(timestamp * 1000).toLocaleDateString('en-US', {
       year: 'numeric',
       month: 'short',
       day: 'numeric',
@@ -204,6 +349,12 @@ export function SnapshotManager({ distributions, onRefresh }: SnapshotManagerPro
     });
   }
 
+  function formatSize(bytes: number): string {
+    return bytes >= 1024 ** 3
+      ? `${(bytes / 1024 ** 3).toFixed(1)} GB`
+      : `${(bytes / 1024 ** 2).toFixed(0)} MB`;
+  }
+
   return (
     <div className="snapshot-manager">
       <div className="snapshot-header">
@@ -280,25 +431,82 @@ export function SnapshotManager({ distributions, onRefresh }: SnapshotManagerPro
         </button>
       </div>
 
-      {/* Snapshot History */}
+      {/* Snapshot Library */}
       {snapshots.length > 0 && (
         <div className="snapshot-section">
-          <h4>📋 Backup History</h4>
+          <h4>📋 Snapshots</h4>
           <div className="snapshot-list">
             {snapshots.map(snapshot => (
               <div key={snapshot.id} className="snapshot-item">
                 <div className="snapshot-info">
-                  <span className="snapshot-name">{snapshot.distroName}</span>
-                  <span className="snapshot-date">{formatDate(snapshot.timestamp)}</span>
-                  <span className="snapshot-path" title={snapshot.path}>
-                    {snapshot.path.split('\\').pop()}
+                  <span className="snapshot-name">
+                    {snapshot.automatic ? '⏰ ' : ''}{snapshot.name}
                   </span>
+                  <span className="snapshot-date">
+                    {snapshot.distro} · {formatDate(snapshot.createdAt)} · {formatSize(snapshot.sizeBytes)}
+                  </span>
+                  {snapshot.notes && (
+                    <span className="snapshot-path" title={snapshot.notes}>{snapshot.notes}</span>
+                  )}
                 </div>
                 <div className="snapshot-actions">
+                  <button
+                    className="btn btn-sm btn-secondary"
+                    onClick={() => handleRestoreSnapshot(snapshot)}
+                    disabled={busySnapshot !== null}
+                    title={`Replace ${snapshot.distro} with this snapshot`}
+                  >
+                    Restore
+                  </button>
+                  <button
+                    className="btn btn-sm btn-secondary"
+                    onClick={() => handleRestoreAsNew(snapshot)}
+                    disabled={busySnapshot !== null}
+                    title="Restore as a new distribution"
+                  >
+                    Restore as New
+                  </button>
                   <button 
                     className="btn btn-sm btn-danger"
                     onClick={() => handleDeleteSnapshot(snapshot)}
-                    title="Remove from history"
+                    disabled={busySnapshot === snapshot.id}
+                    title="Delete snapshot"
+                  >
+                    🗑️
+                  </button>
+                </div>
+              </div>
+            ))}
+          </div>
+        </div>
+      )}
+
+      {/* Scheduled Snapshots */}
+      {policies.length > 0 && (
+        <div className="snapshot-section">
+          <h4>⏰ Scheduled Snapshots</h4>
+          <div className="snapshot-list">
+            {policies.map(policy => (
+              <div key={policy.distro} className="snapshot-item">
+                <div className="snapshot-info">
+                  <span className="snapshot-name">{policy.distro}</span>
+                  <span className="snapshot-date">
+                    <code>{policy.schedule}</code>
+                    {policy.retention.keepLast ? ` · keep last ${policy.retention.keepLast}` : ''}
+                    {policy.lastRun ? ` · last run ${formatDate(policy.lastRun)}` : ''}
+                  </span>
+                </div>
+                <div className="snapshot-actions">
+                  <button
+                    className="btn btn-sm btn-secondary"
+                    onClick={() => handleTogglePolicy(policy)}
+                  >
+                    {policy.enabled ? 'Pause' : 'Resume'}
+                  </button>
+                  <button
+                    className="btn btn-sm btn-danger"
+                    onClick={() => handleDeletePolicy(policy)}
+                    title="Remove schedule"
                   >
                     🗑️
                   </button>
@@ -318,6 +526,19 @@ export function SnapshotManager({ distributions, onRefresh }: SnapshotManagerPro
               <span className="distro-name">
                 {d.state === 'Running' ? '🟢' : '⚪'} {d.name}
               </span>
+              <button
+                className="btn btn-sm btn-secondary"
+                onClick={() => handleCreateSnapshot(d.name)}
+                disabled={isExporting}
+              >
+                Take Snapshot
+              </button>
+              <button
+                className="btn btn-sm btn-secondary"
+                onClick={() => handleSchedule(d.name)}
+              >
+                Schedule
+              </button>
               <button
                 className="btn btn-sm btn-secondary"
                 onClick={() => handleExport(d.name)}
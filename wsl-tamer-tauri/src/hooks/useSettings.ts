@@ -4,6 +4,7 @@ import { useState, useEffect, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { load } from '@tauri-apps/plugin-store';
 import type { Theme } from '../types';
+import { toErrorMessage } from '../utils/errorUtils';
 
 const STORE_FILE = 'settings.json';
 
@@ -61,7 +62,7 @@ export function useSettings(): UseSettingsReturn {
         autoCheckUpdates: autoCheckUpdates ?? true,
       });
     } catch (err: unknown) {
-      setError(`Failed to load settings: ${err}`);
+      setError(`Failed to load settings: ${toErrorMessage(err)}`);
     } finally {
       setLoading(false);
     }
@@ -87,7 +88,7 @@ export function useSettings(): UseSettingsReturn {
       await invoke('set_theme', { theme });
       setSettings(prev => ({ ...prev, theme }));
     } catch (err: unknown) {
-      setError(`Failed to update theme: ${err}`);
+      setError(`Failed to update theme: ${toErrorMessage(err)}`);
       throw err;
     }
   }, []);
@@ -97,7 +98,7 @@ export function useSettings(): UseSettingsReturn {
       await invoke('set_start_with_windows', { enabled });
       setSettings(prev => ({ ...prev, startWithWindows: enabled }));
     } catch (err: unknown) {
-      setError(`Failed to update startup setting: ${err}`);
+      setError(`Failed to update startup setting: ${toErrorMessage(err)}`);
       throw err;
     }
   }, []);
@@ -1,9 +1,10 @@
 // Automation Page - Profile Automation Rules
 
 import { useState, useEffect } from 'react';
-import type { AutomationRule, TriggerType, WslProfile } from '../types';
+import type { AutomationRule, TriggerType, WslProfile, AlertCondition, AlertAction, AlertMetric } from '../types';
 import { toErrorMessage } from '../utils/errorUtils';
 import { automationService, profileService } from '../services';
+import { alertMetrics } from '../services/automationService';
 import { useConfirm } from '../contexts/ConfirmContext';
 
 // Generate unique ID using platform crypto
@@ -36,9 +37,39 @@ const triggerDescriptions: Record<TriggerType, { label: string; placeholder: str
     placeholder: 'Network name or SSID',
     icon: '📶',
     description: 'Activate when connected to a specific network'
+  },
+  Metric: {
+    label: 'Resource Alert',
+    placeholder: '',
+    icon: '📈',
+    description: 'Notify or act when WSL memory, CPU or disk crosses a threshold'
   }
 };
 
+const defaultAlert: AlertCondition = {
+  metric: 'vmmemPercentOfLimit',
+  comparison: 'above',
+  threshold: 90,
+  forSecs: 120,
+  hysteresis: 5,
+  cooldownSecs: 600,
+  distro: null
+};
+
+/** Short description of an alert action */
+function describeAction(action: AlertAction, profiles: WslProfile[]): string {
+  switch (action.type) {
+    case 'notify':
+      return 'Notify';
+    case 'reclaimMemory':
+      return 'Reclaim memory';
+    case 'applyProfile':
+      return `Apply ${profiles.find(p => p.id === action.profileId)?.name ?? 'profile'}`;
+    case 'stopDistro':
+      return `Stop ${action.name}`;
+  }
+}
+
 export default function AutomationPage() {
   const [rules, setRules] = useState<AutomationRule[]>([]);
   const [profiles, setProfiles] = useState<WslProfile[]>([]);
@@ -205,6 +236,7 @@ export default function AutomationPage() {
               key={rule.id}
               rule={rule}
               profile={profiles.find(p => p.id === rule.targetProfileId)}
+              profiles={profiles}
               onToggle={() => handleToggleRule(rule.id)}
               onEdit={() => handleEditRule(rule)}
               onDelete={() => handleDeleteRule(rule.id)}
@@ -221,6 +253,7 @@ export default function AutomationPage() {
           <li><strong>Process-based:</strong> Activate "Performance" when resource-heavy apps start</li>
           <li><strong>Power-based:</strong> Use "Battery Saver" when on battery power</li>
           <li><strong>Network-based:</strong> Apply "Work" profile when connected to office network</li>
+          <li><strong>Resource alerts:</strong> Get notified (or reclaim memory, stop a distro) when WSL memory stays above 90% of its limit</li>
         </ul>
       </div>
     </div>
@@ -231,13 +264,15 @@ export default function AutomationPage() {
 interface RuleCardProps {
   rule: AutomationRule;
   profile?: WslProfile;
+  profiles: WslProfile[];
   onToggle: () => void;
   onEdit: () => void;
   onDelete: () => void;
 }
 
-function RuleCard({ rule, profile, onToggle, onEdit, onDelete }: RuleCardProps) {
+function RuleCard({ rule, profile, profiles, onToggle, onEdit, onDelete }: RuleCardProps) {
   const trigger = triggerDescriptions[rule.triggerType];
+  const isAlert = rule.triggerType === 'Metric' && rule.alert;
   
   return (
     <div className={`rule-card ${rule.isEnabled ? 'enabled' : 'disabled'}`}>
@@ -247,7 +282,9 @@ function RuleCard({ rule, profile, onToggle, onEdit, onDelete }: RuleCardProps)
           <h3>{rule.name || 'Unnamed Rule'}</h3>
           <div className="rule-meta">
             <span className="trigger-type">{trigger.label}</span>
-            <span className="trigger-value">{rule.triggerValue}</span>
+            <span className="trigger-value">
+              {isAlert ? automationService.getAlertDescription(rule.alert!) : rule.triggerValue}
+            </span>
           </div>
         </div>
         <div className="rule-toggle">
@@ -261,8 +298,12 @@ function RuleCard({ rule, profile, onToggle, onEdit, onDelete }: RuleCardProps)
       </div>
       
       <div className="rule-target">
-        <span className="target-label">Applies:</span>
-        <span className="target-profile">{profile?.name || 'Unknown Profile'}</span>
+        <span className="target-label">{isAlert ? 'Does:' : 'Applies:'}</span>
+        <span className="target-profile">
+          {isAlert
+            ? (rule.actions ?? []).map(a => describeAction(a, profiles)).join(', ')
+            : profile?.name || 'Unknown Profile'}
+        </span>
       </div>
       
       <div className="rule-actions">
@@ -316,6 +357,31 @@ function RuleEditor({ rule, profiles, isNew, onSave, onCancel }: RuleEditorProps
   }
 
   const trigger = triggerDescriptions[formData.triggerType];
+  const alert = formData.alert ?? defaultAlert;
+  const actions = formData.actions ?? [];
+
+  function handleAlertChange<K extends keyof AlertCondition>(key: K, value: AlertCondition[K]) {
+    setFormData(prev => ({ ...prev, alert: { ...(prev.alert ?? defaultAlert), [key]: value } }));
+  }
+
+  function hasAction(type: AlertAction['type']): boolean {
+    return actions.some(a => a.type === type);
+  }
+
+  function setAction(action: AlertAction, enabled: boolean) {
+    const others = actions.filter(a => a.type !== action.type);
+    handleChange('actions', enabled ? [...others, action] : others);
+  }
+
+  function handleTriggerTypeChange(type: TriggerType) {
+    handleChange('triggerType', type);
+    if (type === 'Metric' && !formData.alert) {
+      setFormData(prev => ({ ...prev, alert: defaultAlert, actions: prev.actions?.length ? prev.actions : [{ type: 'notify' }] }));
+    }
+  }
+
+  const stopAction = actions.find(a => a.type === 'stopDistro');
+  const profileAction = actions.find(a => a.type === 'applyProfile');
 
   return (
     <div className="modal-overlay" onClick={onCancel}>
@@ -341,7 +407,7 @@ function RuleEditor({ rule, profiles, isNew, onSave, onCancel }: RuleEditorProps
             <select
               id="triggerType"
               value={formData.triggerType}
-              onChange={e => handleChange('triggerType', e.target.value as TriggerType)}
+              onChange={e => handleTriggerTypeChange(e.target.value as TriggerType)}
             >
               {(Object.keys(triggerDescriptions) as TriggerType[]).map(type => (
                 <option key={type} value={type}>
@@ -352,49 +418,194 @@ function RuleEditor({ rule, profiles, isNew, onSave, onCancel }: RuleEditorProps
             <p className="form-hint">{trigger.description}</p>
           </div>
 
-          <div className="form-group">
-            <label htmlFor="triggerValue">Trigger Value</label>
-            {formData.triggerType === 'PowerState' ? (
+          {formData.triggerType === 'Metric' ? (
+            <>
+              <div className="form-group">
+                <label htmlFor="alertMetric">Metric</label>
+                <select
+                  id="alertMetric"
+                  value={alert.metric}
+                  onChange={e => handleAlertChange('metric', e.target.value as AlertMetric)}
+                >
+                  {(Object.keys(alertMetrics) as AlertMetric[]).map(metric => (
+                    <option key={metric} value={metric}>{alertMetrics[metric].label}</option>
+                  ))}
+                </select>
+              </div>
+
+              <div className="form-row">
+                <div className="form-group">
+                  <label htmlFor="alertComparison">When</label>
+                  <select
+                    id="alertComparison"
+                    value={alert.comparison}
+                    onChange={e => handleAlertChange('comparison', e.target.value as AlertCondition['comparison'])}
+                  >
+                    <option value="above">Above</option>
+                    <option value="below">Below</option>
+                  </select>
+                </div>
+                <div className="form-group">
+                  <label htmlFor="alertThreshold">Threshold ({alertMetrics[alert.metric].unit})</label>
+                  <input
+                    id="alertThreshold"
+                    type="number"
+                    value={alert.threshold}
+                    onChange={e => handleAlertChange('threshold', parseFloat(e.target.value))}
+                    className={errors.threshold ? 'error' : ''}
+                  />
+                  {errors.threshold && <span className="form-error">{errors.threshold}</span>}
+                </div>
+              </div>
+
+              <div className="form-row">
+                <div className="form-group">
+                  <label htmlFor="alertFor">For (seconds)</label>
+                  <input
+                    id="alertFor"
+                    type="number"
+                    min={0}
+                    value={alert.forSecs}
+                    onChange={e => handleAlertChange('forSecs', Math.max(0, parseInt(e.target.value) || 0))}
+                  />
+                </div>
+                <div className="form-group">
+                  <label htmlFor="alertHysteresis">Hysteresis</label>
+                  <input
+                    id="alertHysteresis"
+                    type="number"
+                    min={0}
+                    value={alert.hysteresis}
+                    onChange={e => handleAlertChange('hysteresis', Math.max(0, parseFloat(e.target.value) || 0))}
+                  />
+                </div>
+                <div className="form-group">
+                  <label htmlFor="alertCooldown">Cooldown (seconds)</label>
+                  <input
+                    id="alertCooldown"
+                    type="number"
+                    min={0}
+                    value={alert.cooldownSecs}
+                    onChange={e => handleAlertChange('cooldownSecs', Math.max(0, parseInt(e.target.value) || 0))}
+                  />
+                </div>
+              </div>
+
+              {alert.metric === 'distroDiskPercent' && (
+                <div className="form-group">
+                  <label htmlFor="alertDistro">Distribution</label>
+                  <input
+                    id="alertDistro"
+                    type="text"
+                    value={alert.distro ?? ''}
+                    onChange={e => handleAlertChange('distro', e.target.value.trim() || null)}
+                    placeholder="Any distribution"
+                  />
+                </div>
+              )}
+
+              <div className="form-group">
+                <label>Actions</label>
+                <label className="checkbox">
+                  <input
+                    type="checkbox"
+                    checked={hasAction('notify')}
+                    onChange={e => setAction({ type: 'notify' }, e.target.checked)}
+                  />
+                  Show a notification
+                </label>
+                <label className="checkbox">
+                  <input
+                    type="checkbox"
+                    checked={hasAction('reclaimMemory')}
+                    onChange={e => setAction({ type: 'reclaimMemory' }, e.target.checked)}
+                  />
+                  Reclaim memory
+                </label>
+                <label className="checkbox">
+                  <input
+                    type="checkbox"
+                    checked={!!profileAction}
+                    onChange={e => setAction({ type: 'applyProfile', profileId: profiles[0]?.id ?? '' }, e.target.checked)}
+                  />
+                  Apply profile
+                </label>
+                {profileAction?.type === 'applyProfile' && (
+                  <select
+                    value={profileAction.profileId}
+                    onChange={e => setAction({ type: 'applyProfile', profileId: e.target.value }, true)}
+                  >
+                    {profiles.map(profile => (
+                      <option key={profile.id} value={profile.id}>{profile.name}</option>
+                    ))}
+                  </select>
+                )}
+                <label className="checkbox">
+                  <input
+                    type="checkbox"
+                    checked={!!stopAction}
+                    onChange={e => setAction({ type: 'stopDistro', name: alert.distro ?? '' }, e.target.checked)}
+                  />
+                  Stop a distribution
+                </label>
+                {stopAction?.type === 'stopDistro' && (
+                  <input
+                    type="text"
+                    value={stopAction.name}
+                    onChange={e => setAction({ type: 'stopDistro', name: e.target.value }, true)}
+                    placeholder="Distribution name"
+                  />
+                )}
+                {errors.actions && <span className="form-error">{errors.actions}</span>}
+              </div>
+            </>
+          ) : (
+            <>
+            <div className="form-group">
+              <label htmlFor="triggerValue">Trigger Value</label>
+              {formData.triggerType === 'PowerState' ? (
+                <select
+                  id="triggerValue"
+                  value={formData.triggerValue}
+                  onChange={e => handleChange('triggerValue', e.target.value)}
+                  className={errors.triggerValue ? 'error' : ''}
+                >
+                  <option value="">Select power state...</option>
+                  <option value="AC">AC Power (Plugged In)</option>
+                  <option value="Battery">Battery Power</option>
+                </select>
+              ) : (
+                <input
+                  id="triggerValue"
+                  type="text"
+                  value={formData.triggerValue}
+                  onChange={e => handleChange('triggerValue', e.target.value)}
+                  placeholder={trigger.placeholder}
+                  className={errors.triggerValue ? 'error' : ''}
+                />
+              )}
+              {errors.triggerValue && <span className="form-error">{errors.triggerValue}</span>}
+            </div>
+
+            <div className="form-group">
+              <label htmlFor="targetProfile">Target Profile</label>
               <select
-                id="triggerValue"
-                value={formData.triggerValue}
-                onChange={e => handleChange('triggerValue', e.target.value)}
-                className={errors.triggerValue ? 'error' : ''}
+                id="targetProfile"
+                value={formData.targetProfileId}
+                onChange={e => handleChange('targetProfileId', e.target.value)}
+                className={errors.targetProfileId ? 'error' : ''}
               >
-                <option value="">Select power state...</option>
-                <option value="AC">AC Power (Plugged In)</option>
-                <option value="Battery">Battery Power</option>
+                <option value="">Select profile...</option>
+                {profiles.map(profile => (
+                  <option key={profile.id} value={profile.id}>
+                    {profile.name} ({profile.memory}, {profile.processors} CPUs)
+                  </option>
+                ))}
               </select>
-            ) : (
-              <input
-                id="triggerValue"
-                type="text"
-                value={formData.triggerValue}
-                onChange={e => handleChange('triggerValue', e.target.value)}
-                placeholder={trigger.placeholder}
-                className={errors.triggerValue ? 'error' : ''}
-              />
-            )}
-            {errors.triggerValue && <span className="form-error">{errors.triggerValue}</span>}
-          </div>
-
-          <div className="form-group">
-            <label htmlFor="targetProfile">Target Profile</label>
-            <select
-              id="targetProfile"
-              value={formData.targetProfileId}
-              onChange={e => handleChange('targetProfileId', e.target.value)}
-              className={errors.targetProfileId ? 'error' : ''}
-            >
-              <option value="">Select profile...</option>
-              {profiles.map(profile => (
-                <option key={profile.id} value={profile.id}>
-                  {profile.name} ({profile.memory}, {profile.processors} CPUs)
-                </option>
-              ))}
-            </select>
-            {errors.targetProfileId && <span className="form-error">{errors.targetProfileId}</span>}
-          </div>
+              {errors.targetProfileId && <span className="form-error">{errors.targetProfileId}</span>}
+            </div>
+            </>
+          )}
 
           <div className="form-group checkbox">
             <label>
@@ -1,94 +1,152 @@
 // Configuration Page - .wslconfig and wsl.conf Editor
 
 import { useState, useEffect } from 'react';
-import ini from 'ini';
 import { wslService } from '../services';
 import { DistroConfigEditor } from '../components/DistroConfigEditor';
+import { ConfigHistory } from '../components/ConfigHistory';
 import { toErrorMessage } from '../utils/errorUtils';
-import type { WslConfig } from '../types';
+import type { ConfigDiagnostic, WslConfig, WslConfigSetting } from '../types';
 
 interface ConfigurationPageProps {
   onUnsavedChanges?: (hasChanges: boolean) => void;
 }
-// Parse INI-style .wslconfig content to WslConfig object
-function parseWslConfig(content: string): WslConfig {
-  const parsed = ini.parse(content);
-  const config: WslConfig = {};
+// "vmIdleTimeout" -> "Vm Idle Timeout"
+function labelFor(key: string): string {
+  const words = key.replace(/([a-z0-9])([A-Z])/g, '$1 $2');
+  return words.charAt(0).toUpperCase() + words.slice(1);
+}
 
-  // Map [wsl2] section
-  const wsl2 = parsed.wsl2 || {};
-  if (wsl2.memory) config.memory = String(wsl2.memory);
-  if (wsl2.processors !== undefined) config.processors = parseInt(String(wsl2.processors)) || undefined;
-  if (wsl2.swap) config.swap = String(wsl2.swap);
-  if (wsl2.swapFile) config.swapFile = String(wsl2.swapFile);
-  if (wsl2.localhostForwarding !== undefined) config.localhostForwarding = String(wsl2.localhostForwarding).toLowerCase() === 'true';
-  if (wsl2.kernelCommandLine) config.kernelCommandLine = String(wsl2.kernelCommandLine);
-  if (wsl2.safeMode !== undefined) config.safeMode = String(wsl2.safeMode).toLowerCase() === 'true';
-  if (wsl2.nestedVirtualization !== undefined) config.nestedVirtualization = String(wsl2.nestedVirtualization).toLowerCase() === 'true';
-  if (wsl2.pageReporting !== undefined) config.pageReporting = String(wsl2.pageReporting).toLowerCase() === 'true';
-  if (wsl2.debugConsole !== undefined) config.debugConsole = String(wsl2.debugConsole).toLowerCase() === 'true';
-  if (wsl2.guiApplications !== undefined) config.guiApplications = String(wsl2.guiApplications).toLowerCase() === 'true';
-  if (wsl2.networkingMode) config.networkingMode = String(wsl2.networkingMode) as 'nat' | 'mirrored' | 'bridged';
-  if (wsl2.firewall !== undefined) config.firewall = String(wsl2.firewall).toLowerCase() === 'true';
-  if (wsl2.dnsTunneling !== undefined) config.dnsTunneling = String(wsl2.dnsTunneling).toLowerCase() === 'true';
+function tooltipFor(setting: WslConfigSetting): string {
+  const notes = [setting.description];
+  if (setting.default !== null) notes.push(`Default: ${setting.default}`);
+  if (setting.minWslVersion) notes.push(`Requires WSL ${setting.minWslVersion} or later`);
+  notes.push(`[${setting.section}] ${setting.key}`);
+  return notes.join('\n');
+}
 
-  // Map [experimental] section
-  const experimental = parsed.experimental || {};
-  if (experimental.autoProxy !== undefined) config.autoProxy = String(experimental.autoProxy).toLowerCase() === 'true';
-  if (experimental.sparseVhd !== undefined) config.sparseVhd = String(experimental.sparseVhd).toLowerCase() === 'true';
+const severityIcons: Record<ConfigDiagnostic['severity'], string> = {
+  error: '⛔',
+  warning: '⚠️',
+  info: 'ℹ️',
+};
 
-  return config;
+interface SettingFieldProps {
+  setting: WslConfigSetting;
+  value: WslConfig[keyof WslConfig];
+  diagnostics: ConfigDiagnostic[];
+  onChange: (value: WslConfig[keyof WslConfig]) => void;
 }
 
-// Convert WslConfig object back to INI-style content
-function serializeWslConfig(config: WslConfig): string {
-  const iniObj: Record<string, Record<string, string | number | boolean>> = { wsl2: {} };
+function DiagnosticList({ diagnostics }: { diagnostics: ConfigDiagnostic[] }) {
+  return (
+    <>
+      {diagnostics.map((d, i) => (
+        <p key={i} className={`config-diagnostic ${d.severity}`} title={d.suggestion ?? undefined}>
+          {severityIcons[d.severity]} {d.message}
+          {d.suggestion && <span className="config-diagnostic-fix"> {d.suggestion}.</span>}
+        </p>
+      ))}
+    </>
+  );
+}
 
-  if (config.memory) iniObj.wsl2.memory = config.memory;
-  if (config.processors !== undefined) iniObj.wsl2.processors = config.processors;
-  if (config.swap) iniObj.wsl2.swap = config.swap;
-  if (config.swapFile) iniObj.wsl2.swapFile = config.swapFile;
-  if (config.localhostForwarding !== undefined) iniObj.wsl2.localhostForwarding = config.localhostForwarding;
-  if (config.kernelCommandLine) iniObj.wsl2.kernelCommandLine = config.kernelCommandLine;
-  if (config.safeMode !== undefined) iniObj.wsl2.safeMode = config.safeMode;
-  if (config.nestedVirtualization !== undefined) iniObj.wsl2.nestedVirtualization = config.nestedVirtualization;
-  if (config.pageReporting !== undefined) iniObj.wsl2.pageReporting = config.pageReporting;
-  if (config.debugConsole !== undefined) iniObj.wsl2.debugConsole = config.debugConsole;
-  if (config.guiApplications !== undefined) iniObj.wsl2.guiApplications = config.guiApplications;
-  if (config.networkingMode) iniObj.wsl2.networkingMode = config.networkingMode;
-  if (config.firewall !== undefined) iniObj.wsl2.firewall = config.firewall;
-  if (config.dnsTunneling !== undefined) iniObj.wsl2.dnsTunneling = config.dnsTunneling;
+// One .wslconfig key, rendered according to its schema type
+function SettingField({ setting, value, diagnostics, onChange }: SettingFieldProps) {
+  const id = `wslconfig-${setting.key}`;
+  const [listDraft, setListDraft] = useState(Array.isArray(value) ? value.join(', ') : '');
+  const label = (
+    <label htmlFor={id}>
+      {labelFor(setting.key)}
+      {setting.type === 'duration' && ' (ms)'}
+      <span className="tooltip" title={tooltipFor(setting)}>ⓘ</span>
+    </label>
+  );
 
-  // Experimental section
-  const hasExperimental = config.autoProxy !== undefined || config.sparseVhd !== undefined;
-  if (hasExperimental) {
-    iniObj.experimental = {};
-    if (config.autoProxy !== undefined) iniObj.experimental.autoProxy = config.autoProxy;
-    if (config.sparseVhd !== undefined) iniObj.experimental.sparseVhd = config.sparseVhd;
+  switch (setting.type) {
+    case 'bool':
+      return (
+        <div className="config-field config-toggle">
+          {label}
+          <input
+            id={id}
+            type="checkbox"
+            checked={(value as boolean | undefined) ?? setting.default === 'true'}
+            onChange={e => onChange(e.target.checked)}
+          />
+          <DiagnosticList diagnostics={diagnostics} />
+        </div>
+      );
+    case 'enum':
+      return (
+        <div className="config-field">
+          {label}
+          <select id={id} value={(value as string | undefined) ?? ''} onChange={e => onChange(e.target.value || undefined)}>
+            <option value="">Default{setting.default !== null ? ` (${setting.default})` : ''}</option>
+            {setting.values.map(v => (
+              <option key={v} value={v}>{labelFor(v)}</option>
+            ))}
+          </select>
+          <DiagnosticList diagnostics={diagnostics} />
+        </div>
+      );
+    case 'integer':
+    case 'duration':
+      return (
+        <div className="config-field">
+          {label}
+          <input
+            id={id}
+            type="number"
+            min={setting.min ?? 0}
+            max={setting.max ?? undefined}
+            value={(value as number | undefined) ?? ''}
+            onChange={e => onChange(e.target.value === '' ? undefined : parseInt(e.target.value, 10))}
+            placeholder={setting.default ?? 'Default'}
+          />
+          <DiagnosticList diagnostics={diagnostics} />
+        </div>
+      );
+    case 'list':
+      return (
+        <div className="config-field">
+          {label}
+          <input
+            id={id}
+            type="text"
+            value={listDraft}
+            onChange={e => {
+              setListDraft(e.target.value);
+              const items = e.target.value.split(',').map(v => parseInt(v.trim(), 10)).filter(n => !isNaN(n));
+              onChange(items.length > 0 ? items : undefined);
+            }}
+            placeholder="e.g., 3000, 8080"
+          />
+          <DiagnosticList diagnostics={diagnostics} />
+        </div>
+      );
+    default:
+      return (
+        <div className={`config-field${setting.type === 'path' || setting.type === 'string' ? ' full-width' : ''}`}>
+          {label}
+          <input
+            id={id}
+            type="text"
+            value={(value as string | undefined) ?? ''}
+            onChange={e => onChange(e.target.value || undefined)}
+            placeholder={setting.default ?? (setting.type === 'size' ? 'e.g., 8GB' : 'Default')}
+          />
+          <DiagnosticList diagnostics={diagnostics} />
+        </div>
+      );
   }
-
-  return ini.stringify(iniObj, { whitespace: false }).trim();
 }
 
-// Tooltip info for each setting
-const tooltips: Record<string, string> = {
-  memory: 'Maximum memory to allocate to WSL 2 (e.g., "4GB", "8GB")',
-  processors: 'Number of virtual processors to assign to WSL 2',
-  swap: 'Swap space size (e.g., "2GB"). Set to 0 to disable swap.',
-  localhostForwarding: 'Allow connections to WSL from Windows via localhost',
-  nestedVirtualization: 'Enable nested virtualization for VMs inside WSL',
-  guiApplications: 'Enable support for WSLg (GUI applications)',
-  networkingMode: 'Network mode: NAT (default), Mirrored (share host network), or Bridged',
-  firewall: 'Enable Windows Firewall rules for WSL',
-  dnsTunneling: 'Use DNS tunneling instead of NAT for DNS resolution',
-  sparseVhd: 'Allow WSL virtual disks to automatically shrink',
-  debugConsole: 'Show debug console for WSL',
-  safeMode: 'Start WSL in safe mode (minimal features)',
-};
-
 export default function ConfigurationPage({ onUnsavedChanges }: ConfigurationPageProps) {
   const [config, setConfig] = useState<WslConfig>({});
-  const [activeTab, setActiveTab] = useState<'global' | 'distro'>('global');
+  const [schema, setSchema] = useState<WslConfigSetting[]>([]);
+  const [loadCount, setLoadCount] = useState(0);
+  const [diagnostics, setDiagnostics] = useState<ConfigDiagnostic[]>([]);
+  const [activeTab, setActiveTab] = useState<'global' | 'distro' | 'history'>('global');
   const [loading, setLoading] = useState(true);
   const [saving, setSaving] = useState(false);
   const [error, setError] = useState<string | null>(null);
@@ -110,8 +168,13 @@ export default function ConfigurationPage({ onUnsavedChanges }: ConfigurationPag
     try {
       setLoading(true);
       setError(null);
-      const content = await wslService.readWslconfig();
-      setConfig(parseWslConfig(content));
+      if (schema.length === 0) {
+        setSchema(await wslService.getWslconfigSchema());
+      }
+      const loaded = await wslService.getWslconfigTyped();
+      setConfig(loaded);
+      setDiagnostics(await wslService.validateWslconfig(loaded));
+      setLoadCount(n => n + 1);
       setHasChanges(false);
     } catch (err: unknown) {
       // File might not exist yet, that's OK
@@ -130,8 +193,7 @@ export default function ConfigurationPage({ onUnsavedChanges }: ConfigurationPag
     try {
       setSaving(true);
       setError(null);
-      const content = serializeWslConfig(config);
-      await wslService.writeWslconfig(content);
+      setDiagnostics(await wslService.saveWslconfigTyped(config));
       setSuccess('Configuration saved! Restart WSL for changes to take effect.');
       setHasChanges(false);
       setTimeout(() => setSuccess(null), 5000);
@@ -151,6 +213,17 @@ export default function ConfigurationPage({ onUnsavedChanges }: ConfigurationPag
     loadConfig();
   }
 
+  async function checkConfig() {
+    try {
+      setDiagnostics(await wslService.validateWslconfig(config));
+    } catch (err: unknown) {
+      setError(`Failed to check config: ${toErrorMessage(err)}`);
+    }
+  }
+
+  // Sections in schema order
+  const categories = [...new Set(schema.map(setting => setting.category))];
+
   if (loading) {
     return (
       <div className="page-content">
@@ -183,8 +256,19 @@ export default function ConfigurationPage({ onUnsavedChanges }: ConfigurationPag
         >
           📦 Per-Distro (wsl.conf)
         </button>
+        <button
+          className={`tab ${activeTab === 'history' ? 'active' : ''}`}
+          onClick={() => setActiveTab('history')}
+        >
+          🕘 History
+        </button>
       </div>
 
+      {/* History Tab - reloads a rolled back .wslconfig unless it has unsaved edits */}
+      {activeTab === 'history' && (
+        <ConfigHistory onRollback={() => !hasChanges && loadConfig()} />
+      )}
+
       {/* Per-Distro Tab */}
       {activeTab === 'distro' && (
         <DistroConfigEditor onUnsavedChanges={setDistroHasChanges} />
@@ -210,181 +294,44 @@ export default function ConfigurationPage({ onUnsavedChanges }: ConfigurationPag
         </div>
       )}
 
+      {diagnostics.some(d => d.severity !== 'info') && (
+        <div className="alert alert-warning">
+          <span className="alert-icon">⚠️</span>
+          <span>
+            {diagnostics.filter(d => d.severity === 'error').length} error(s) and{' '}
+            {diagnostics.filter(d => d.severity === 'warning').length} warning(s) for this machine — see the marked settings below.
+          </span>
+        </div>
+      )}
+
       <div className="config-sections">
-        {/* Resource Limits Section */}
-        <section className="config-section">
-          <h2>Resource Limits</h2>
-          <div className="config-grid">
-            <div className="config-field">
-              <label htmlFor="memory">
-                Memory Limit
-                <span className="tooltip" title={tooltips.memory}>ⓘ</span>
-              </label>
-              <div className="input-with-suffix">
-                <input
-                  id="memory"
-                  type="text"
-                  value={config.memory || ''}
-                  onChange={e => updateConfig('memory', e.target.value || undefined)}
-                  placeholder="e.g., 8GB"
+        {categories.map(category => (
+          <section key={category} className="config-section">
+            <h2>{category}</h2>
+            <div className="config-grid">
+              {schema.filter(setting => setting.category === category).map(setting => (
+                <SettingField
+                  key={`${setting.key}-${loadCount}`}
+                  setting={setting}
+                  value={config[setting.key]}
+                  diagnostics={diagnostics.filter(d => d.key === setting.key)}
+                  onChange={value => updateConfig(setting.key, value)}
                 />
-              </div>
-            </div>
-
-            <div className="config-field">
-              <label htmlFor="processors">
-                Processors
-                <span className="tooltip" title={tooltips.processors}>ⓘ</span>
-              </label>
-              <input
-                id="processors"
-                type="number"
-                min="1"
-                max="128"
-                value={config.processors || ''}
-                onChange={e => updateConfig('processors', parseInt(e.target.value) || undefined)}
-                placeholder="Number of CPUs"
-              />
-            </div>
-
-            <div className="config-field">
-              <label htmlFor="swap">
-                Swap Size
-                <span className="tooltip" title={tooltips.swap}>ⓘ</span>
-              </label>
-              <input
-                id="swap"
-                type="text"
-                value={config.swap || ''}
-                onChange={e => updateConfig('swap', e.target.value || undefined)}
-                placeholder="e.g., 2GB"
-              />
-            </div>
-          </div>
-        </section>
-
-        {/* Networking Section */}
-        <section className="config-section">
-          <h2>Networking</h2>
-          <div className="config-grid">
-            <div className="config-field">
-              <label htmlFor="networkingMode">
-                Network Mode
-                <span className="tooltip" title={tooltips.networkingMode}>ⓘ</span>
-              </label>
-              <select
-                id="networkingMode"
-                value={config.networkingMode || 'nat'}
-                onChange={e => updateConfig('networkingMode', e.target.value as 'nat' | 'mirrored' | 'bridged')}
-              >
-                <option value="nat">NAT (Default)</option>
-                <option value="mirrored">Mirrored</option>
-                <option value="bridged">Bridged</option>
-              </select>
-            </div>
-
-            <div className="config-field config-toggle">
-              <label htmlFor="localhostForwarding">
-                Localhost Forwarding
-                <span className="tooltip" title={tooltips.localhostForwarding}>ⓘ</span>
-              </label>
-              <input
-                id="localhostForwarding"
-                type="checkbox"
-                checked={config.localhostForwarding ?? true}
-                onChange={e => updateConfig('localhostForwarding', e.target.checked)}
-              />
-            </div>
-
-            <div className="config-field config-toggle">
-              <label htmlFor="firewall">
-                Windows Firewall
-                <span className="tooltip" title={tooltips.firewall}>ⓘ</span>
-              </label>
-              <input
-                id="firewall"
-                type="checkbox"
-                checked={config.firewall ?? true}
-                onChange={e => updateConfig('firewall', e.target.checked)}
-              />
+              ))}
             </div>
-
-            <div className="config-field config-toggle">
-              <label htmlFor="dnsTunneling">
-                DNS Tunneling
-                <span className="tooltip" title={tooltips.dnsTunneling}>ⓘ</span>
-              </label>
-              <input
-                id="dnsTunneling"
-                type="checkbox"
-                checked={config.dnsTunneling ?? false}
-                onChange={e => updateConfig('dnsTunneling', e.target.checked)}
-              />
-            </div>
-          </div>
-        </section>
-
-        {/* Features Section */}
-        <section className="config-section">
-          <h2>Features</h2>
-          <div className="config-grid">
-            <div className="config-field config-toggle">
-              <label htmlFor="guiApplications">
-                GUI Applications (WSLg)
-                <span className="tooltip" title={tooltips.guiApplications}>ⓘ</span>
-              </label>
-              <input
-                id="guiApplications"
-                type="checkbox"
-                checked={config.guiApplications ?? true}
-                onChange={e => updateConfig('guiApplications', e.target.checked)}
-              />
-            </div>
-
-            <div className="config-field config-toggle">
-              <label htmlFor="nestedVirtualization">
-                Nested Virtualization
-                <span className="tooltip" title={tooltips.nestedVirtualization}>ⓘ</span>
-              </label>
-              <input
-                id="nestedVirtualization"
-                type="checkbox"
-                checked={config.nestedVirtualization ?? false}
-                onChange={e => updateConfig('nestedVirtualization', e.target.checked)}
-              />
-            </div>
-
-            <div className="config-field config-toggle">
-              <label htmlFor="sparseVhd">
-                Sparse VHD (Auto-shrink)
-                <span className="tooltip" title={tooltips.sparseVhd}>ⓘ</span>
-              </label>
-              <input
-                id="sparseVhd"
-                type="checkbox"
-                checked={config.sparseVhd ?? false}
-                onChange={e => updateConfig('sparseVhd', e.target.checked)}
-              />
-            </div>
-
-            <div className="config-field config-toggle">
-              <label htmlFor="debugConsole">
-                Debug Console
-                <span className="tooltip" title={tooltips.debugConsole}>ⓘ</span>
-              </label>
-              <input
-                id="debugConsole"
-                type="checkbox"
-                checked={config.debugConsole ?? false}
-                onChange={e => updateConfig('debugConsole', e.target.checked)}
-              />
-            </div>
-          </div>
-        </section>
+          </section>
+        ))}
       </div>
 
       {/* Actions */}
       <div className="config-actions">
+        <button
+          className="btn btn-secondary"
+          onClick={checkConfig}
+          disabled={saving}
+        >
+          Check
+        </button>
         <button
           className="btn btn-secondary"
           onClick={resetConfig}
@@ -2,13 +2,23 @@
 
 import { useEffect, useState } from 'react';
 import { open, save } from '@tauri-apps/plugin-dialog';
-import { wslService } from '../services';
+import { wslService, jobService, profileService } from '../services';
 import { useToast } from '../contexts/ToastContext';
 import { useConfirm } from '../contexts/ConfirmContext';
 import { useTextInput } from '../contexts/TextInputContext';
 import { SnapshotManager } from '../components/SnapshotManager';
+import { PortForwardManager } from '../components/PortForwardManager';
 import { toErrorMessage } from '../utils/errorUtils';
-import type { WslDistribution, OnlineDistribution } from '../types';
+import { EXPORT_EXTENSIONS, exportExtension, formatBytes, formatExportSize } from '../utils/formatUtils';
+import type { WslDistribution, OnlineDistribution, MoveJournal } from '../types';
+
+// What an interrupted move had finished, for the resume banner
+const moveStepLabels: Record<MoveJournal['step'], string> = {
+  exporting: 'while exporting',
+  exported: 'after exporting',
+  unregistered: 'after unregistering the original',
+  imported: 'after importing to the new location',
+};
 
 export function DistributionsPage() {
   const { showToast } = useToast();
@@ -19,11 +29,50 @@ export function DistributionsPage() {
   const [loading, setLoading] = useState(true);
   const [showOnline, setShowOnline] = useState(false);
   const [showBackups, setShowBackups] = useState(false);
+  const [showPorts, setShowPorts] = useState(false);
+  const [pendingMoves, setPendingMoves] = useState<MoveJournal[]>([]);
+  const [busyMove, setBusyMove] = useState<string | null>(null);
 
   useEffect(() => {
     loadDistributions();
+    loadPendingMoves();
   }, []);
 
+  // Moves interrupted by a crash or shutdown
+  const loadPendingMoves = async () => {
+    try {
+      setPendingMoves(await wslService.getPendingMoves());
+    } catch (error) {
+      console.error('Failed to load interrupted moves:', error);
+    }
+  };
+
+  const handleFinishMove = async (move: MoveJournal, rollback: boolean) => {
+    if (rollback) {
+      const ok = await confirm({
+        title: 'Roll Back Move',
+        message: `Put "${move.distro}" back where it was before the move to ${move.newLocation}?`,
+        confirmText: 'Roll Back'
+      });
+      if (!ok) return;
+    }
+    try {
+      setBusyMove(move.id);
+      const job = rollback ? await wslService.rollbackMove(move.id) : await wslService.resumeMove(move.id);
+      showToast('info', `${rollback ? 'Rolling back' : 'Resuming'} move of ${move.distro}...`);
+      const result = await jobService.waitForJob(job.id);
+      if (result.status === 'completed') {
+        showToast('success', rollback ? `${move.distro} restored` : `${move.distro} moved to ${move.newLocation}`);
+      }
+    } catch (err: unknown) {
+      showToast('error', `Failed to ${rollback ? 'roll back' : 'resume'} move: ` + toErrorMessage(err));
+    } finally {
+      setBusyMove(null);
+      await loadPendingMoves();
+      await loadDistributions();
+    }
+  };
+
   const loadDistributions = async () => {
     try {
       const distros = await wslService.getDistributions();
@@ -74,13 +123,19 @@ export function DistributionsPage() {
 
   const handleExport = async (name: string) => {
     try {
+      const { defaultFormat } = await profileService.getCompressionSettings();
       const path = await save({
-        defaultPath: `${name}.tar`,
-        filters: [{ name: 'TAR Archives', extensions: ['tar'] }]
+        defaultPath: `${name}.${exportExtension(defaultFormat)}`,
+        filters: [{ name: 'TAR Archives', extensions: EXPORT_EXTENSIONS }]
       });
       if (path) {
-        await wslService.exportDistribution(name, path);
-        showToast('success', 'Export completed!');
+        const job = await wslService.exportDistribution(name, path);
+        showToast('info', `Exporting ${name}...`);
+        const result = await jobService.waitForJob(job.id);
+        if (result.status === 'completed') {
+          const manifest = await wslService.getExportManifest(path);
+          showToast('success', manifest ? `Export completed: ${formatExportSize(manifest)}` : 'Export completed!');
+        }
       }
     } catch (err: unknown) {
       showToast('error', 'Failed to export: ' + toErrorMessage(err));
@@ -90,7 +145,7 @@ export function DistributionsPage() {
   const handleImport = async () => {
     try {
       const path = await open({
-        filters: [{ name: 'TAR Archives', extensions: ['tar'] }]
+        filters: [{ name: 'TAR Archives', extensions: EXPORT_EXTENSIONS }]
       });
       if (path && typeof path === 'string') {
         const name = await textInput({
@@ -103,8 +158,10 @@ export function DistributionsPage() {
         
         const location = await open({ directory: true, title: 'Select install location' });
         if (location && typeof location === 'string') {
-          await wslService.importDistribution(name, location, path);
-          showToast('success', 'Import completed!');
+          const job = await wslService.importDistribution(name, location, path);
+          showToast('info', `Importing ${name}...`);
+          const result = await jobService.waitForJob(job.id);
+          if (result.status === 'completed') showToast('success', 'Import completed!');
           await loadDistributions();
         }
       }
@@ -113,6 +170,62 @@ export function DistributionsPage() {
     }
   };
 
+  const handleCompact = async (name: string) => {
+    const ok = await confirm({
+      title: 'Compact Disk',
+      message: `Compacting stops "${name}" and asks for administrator rights. Continue?`,
+      confirmText: 'Compact'
+    });
+    if (!ok) return;
+    try {
+      const job = await wslService.compactDisk(name);
+      showToast('info', `Compacting ${name}...`);
+      const result = await jobService.waitForJob(job.id);
+      if (result.status === 'completed') {
+        const report = await wslService.getCompactionReport(name);
+        showToast('success', report
+          ? `Compacted ${name}: ${formatBytes(report.sizeBefore)} → ${formatBytes(report.sizeAfter)}`
+          : 'Compaction completed!');
+      }
+      await loadDistributions();
+    } catch (err: unknown) {
+      showToast('error', 'Failed to compact: ' + toErrorMessage(err));
+    }
+  };
+
+  const handleResize = async (name: string) => {
+    const input = await textInput({
+      title: 'Resize Disk',
+      message: `New maximum disk size for ${name}, in GB:`,
+      placeholder: 'e.g. 512',
+      confirmText: 'Preview'
+    });
+    if (!input) return;
+    const gb = Number(input);
+    if (!Number.isFinite(gb) || gb <= 0) {
+      showToast('error', 'Enter a size in GB');
+      return;
+    }
+    try {
+      const newSize = Math.round(gb * 1024) * 1024 * 1024;
+      const { plan } = await wslService.resizeDisk(name, newSize, true);
+      const ok = await confirm({
+        title: 'Resize Disk',
+        message: [...plan.steps.map((step, i) => `${i + 1}. ${step}`), ...plan.warnings.map(w => `⚠ ${w}`)].join('\n'),
+        confirmText: 'Resize'
+      });
+      if (!ok) return;
+      const { job } = await wslService.resizeDisk(name, newSize);
+      if (!job) return;
+      showToast('info', `Resizing ${name}...`);
+      const result = await jobService.waitForJob(job.id);
+      if (result.status === 'completed') showToast('success', `${name} now has ${formatBytes(newSize)}`);
+      await loadDistributions();
+    } catch (err: unknown) {
+      showToast('error', 'Failed to resize: ' + toErrorMessage(err));
+    }
+  };
+
   const handleUnregister = async (name: string) => {
     const ok = await confirm({ title: 'Unregister Distribution', message: `Are you sure you want to unregister "${name}"? This will DELETE all data!`, danger: true, confirmText: 'Unregister' });
     if (!ok) return;
@@ -126,9 +239,14 @@ export function DistributionsPage() {
 
   const handleInstall = async (name: string) => {
     try {
-      await wslService.installDistribution(name);
+      const job = await wslService.installDistribution(name);
       showToast('info', `Installing ${name}... This may take a few minutes.`);
       setShowOnline(false);
+      const result = await jobService.waitForJob(job.id);
+      if (result.status === 'completed') {
+        showToast('success', `${name} installed`);
+        await loadDistributions();
+      }
     } catch (err: unknown) {
       showToast('error', 'Failed to install: ' + toErrorMessage(err));
     }
@@ -141,6 +259,36 @@ export function DistributionsPage() {
         <p>Manage your WSL distributions</p>
       </header>
 
+      {/* Interrupted Moves */}
+      {pendingMoves.map(move => (
+        <div key={move.id} className="alert alert-warning">
+          <span className="alert-icon">⚠️</span>
+          <div className="alert-content">
+            <h3>Interrupted move of {move.distro}</h3>
+            <p>
+              Moving {move.distro} to {move.newLocation} stopped {moveStepLabels[move.step]}.
+              Resume to finish the move, or roll back to restore the original.
+            </p>
+            <div className="alert-actions">
+              <button
+                onClick={() => handleFinishMove(move, false)}
+                className="btn btn-primary"
+                disabled={busyMove !== null}
+              >
+                ▶ Resume
+              </button>
+              <button
+                onClick={() => handleFinishMove(move, true)}
+                className="btn btn-secondary"
+                disabled={busyMove !== null}
+              >
+                ↩ Roll Back
+              </button>
+            </div>
+          </div>
+        </div>
+      ))}
+
       <div className="toolbar">
         <button onClick={handleImport} className="btn btn-primary">
           📥 Import
@@ -151,6 +299,9 @@ export function DistributionsPage() {
         <button onClick={() => setShowBackups(!showBackups)} className={`btn ${showBackups ? 'btn-purple' : 'btn-secondary'}`}>
           💾 {showBackups ? 'Hide Backups' : 'Show Backups'}
         </button>
+        <button onClick={() => setShowPorts(!showPorts)} className={`btn ${showPorts ? 'btn-purple' : 'btn-secondary'}`}>
+          🔀 {showPorts ? 'Hide Port Forwards' : 'Port Forwards'}
+        </button>
       </div>
 
       {/* Backup Manager */}
@@ -163,6 +314,13 @@ export function DistributionsPage() {
         </section>
       )}
 
+      {/* Port Forwarding */}
+      {showPorts && (
+        <section className="card">
+          <PortForwardManager distributions={distributions} />
+        </section>
+      )}
+
       {/* Installed Distributions */}
       <section className="card">
         <h2>Installed Distributions</h2>
@@ -202,6 +360,16 @@ export function DistributionsPage() {
                   <button onClick={() => handleExport(distro.name)} className="btn btn-sm btn-info">
                     Export
                   </button>
+                  {distro.version === '2' && (
+                    <>
+                      <button onClick={() => handleCompact(distro.name)} className="btn btn-sm btn-secondary">
+                        Compact
+                      </button>
+                      <button onClick={() => handleResize(distro.name)} className="btn btn-sm btn-secondary">
+                        Resize
+                      </button>
+                    </>
+                  )}
                   <button onClick={() => handleUnregister(distro.name)} className="btn btn-sm btn-danger">
                     Delete
                   </button>
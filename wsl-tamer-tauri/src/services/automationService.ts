@@ -1,7 +1,21 @@
 // Automation Service - Frontend interface for automation engine
 
 import { invoke } from '@tauri-apps/api/core';
-import type { AutomationRule, TriggerType } from '../types';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { AutomationRule, TriggerType, AlertCondition, AlertEvent, AlertMetric } from '../types';
+
+/** Emitted by the backend when a resource alert fires */
+export const ALERT_FIRED_EVENT = 'alert-fired';
+
+/** Labels and units of alert metrics */
+export const alertMetrics: Record<AlertMetric, { label: string; unit: string }> = {
+  vmmemPercentOfLimit: { label: 'WSL memory (% of limit)', unit: '%' },
+  vmmemMb: { label: 'WSL memory', unit: 'MB' },
+  hostAvailableMb: { label: 'Available host memory', unit: 'MB' },
+  cpuPercent: { label: 'WSL CPU (host)', unit: '%' },
+  guestCpuPercent: { label: 'Guest CPU', unit: '%' },
+  distroDiskPercent: { label: 'Distribution disk usage', unit: '%' },
+};
 
 export interface SystemState {
   running_processes: string[];
@@ -43,6 +57,20 @@ export const automationService = {
     return invoke<string[]>('get_running_processes');
   },
 
+  /**
+   * Resource alerts that have fired and not cleared yet
+   */
+  async getActiveAlerts(): Promise<AlertEvent[]> {
+    return invoke<AlertEvent[]>('get_active_alerts');
+  },
+
+  /**
+   * Subscribe to resource alerts as they fire
+   */
+  async onAlertFired(callback: (alert: AlertEvent) => void): Promise<UnlistenFn> {
+    return listen<AlertEvent>(ALERT_FIRED_EVENT, (event) => callback(event.payload));
+  },
+
   /**
    * Convert frontend rule format to backend format.
    * Required: Tauri IPC doesn't auto-apply #[serde(rename_all)] to
@@ -56,6 +84,8 @@ export const automationService = {
       trigger_type: rule.triggerType,
       trigger_value: rule.triggerValue,
       target_profile_id: rule.targetProfileId,
+      alert: rule.alert ?? null,
+      actions: rule.actions ?? [],
     };
   },
 
@@ -70,6 +100,18 @@ export const automationService = {
     if (!rule.name.trim()) {
       errors.name = 'Name is required';
     }
+
+    // Resource alerts have a condition and actions instead of a value and profile
+    if (rule.triggerType === 'Metric') {
+      if (!rule.alert || !Number.isFinite(rule.alert.threshold)) {
+        errors.threshold = 'Threshold is required';
+      }
+      if (!rule.actions?.length) {
+        errors.actions = 'Choose at least one action';
+      }
+      return errors;
+    }
+
     if (!rule.triggerValue.trim()) {
       errors.triggerValue = 'Trigger value is required';
     }
@@ -112,6 +154,16 @@ export const automationService = {
     }
   },
 
+  /**
+   * Human-readable alert condition, e.g. "WSL CPU (host) above 90% for 120s"
+   */
+  getAlertDescription(alert: AlertCondition): string {
+    const { label, unit } = alertMetrics[alert.metric];
+    const subject = alert.distro ? `${label} of ${alert.distro}` : label;
+    const duration = alert.forSecs > 0 ? ` for ${alert.forSecs}s` : '';
+    return `${subject} ${alert.comparison} ${alert.threshold}${unit === '%' ? '%' : ` ${unit}`}${duration}`;
+  },
+
   /**
    * Format trigger value for storage
    */
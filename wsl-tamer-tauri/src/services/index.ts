@@ -4,4 +4,7 @@ export { wslService, default as wsl } from './wsl';
 export { profileService, default as profiles } from './profiles';
 export { hardwareService, default as hardware } from './hardware';
 export { automationService, default as automation } from './automationService';
+export { jobService, default as jobs } from './jobs';
+export { snapshotService, default as snapshots } from './snapshots';
+export { portForwardService, default as portForwards } from './portForwards';
 export { diskCache } from './diskCache';
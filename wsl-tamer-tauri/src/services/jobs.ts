@@ -0,0 +1,63 @@
+// Job Service - Tauri IPC wrapper for background jobs
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { Job } from '../types';
+
+/** Event emitted by the backend on every job update */
+export const JOB_PROGRESS_EVENT = 'job-progress';
+
+export const jobService = {
+  async listJobs(): Promise<Job[]> {
+    return invoke('list_jobs');
+  },
+
+  async getJob(id: string): Promise<Job | null> {
+    return invoke('get_job', { id });
+  },
+
+  async cancelJob(id: string): Promise<void> {
+    return invoke('cancel_job', { id });
+  },
+
+  async onJobProgress(callback: (job: Job) => void): Promise<UnlistenFn> {
+    return listen<Job>(JOB_PROGRESS_EVENT, (event) => callback(event.payload));
+  },
+
+  /**
+   * Resolve with the finished job, calling `onProgress` on every update.
+   * Rejects with the job error if the job failed.
+   */
+  async waitForJob(id: string, onProgress?: (job: Job) => void): Promise<Job> {
+    return new Promise<Job>((resolve, reject) => {
+      let unlisten: UnlistenFn | undefined;
+      let settled = false;
+
+      const handle = (job: Job) => {
+        if (settled || job.id !== id) return;
+        onProgress?.(job);
+        if (job.status === 'running') return;
+        settled = true;
+        unlisten?.();
+        if (job.status === 'failed') {
+          reject(job.error ?? new Error('Job failed'));
+        } else {
+          resolve(job);
+        }
+      };
+
+      jobService.onJobProgress(handle).then(async (fn) => {
+        unlisten = fn;
+        if (settled) {
+          fn();
+          return;
+        }
+        // The job may have finished before the listener was registered
+        const current = await jobService.getJob(id);
+        if (current) handle(current);
+      }).catch(reject);
+    });
+  },
+};
+
+export default jobService;
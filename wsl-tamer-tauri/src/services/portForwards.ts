@@ -0,0 +1,30 @@
+// Port Forward Service - Tauri IPC wrapper for Windows->WSL port proxies
+
+import { invoke } from '@tauri-apps/api/core';
+import type { PortForwardRule, PortForwardStatus } from '../types';
+
+export const portForwardService = {
+  async getRules(): Promise<PortForwardRule[]> {
+    return invoke('get_port_forwards');
+  },
+
+  /** Save a rule and apply all rules (may prompt for elevation) */
+  async saveRule(rule: PortForwardRule): Promise<PortForwardStatus[]> {
+    return invoke('save_port_forward', { rule });
+  },
+
+  async deleteRule(id: string): Promise<void> {
+    return invoke('delete_port_forward', { id });
+  },
+
+  /** Point all proxies at the distributions' current addresses */
+  async apply(): Promise<PortForwardStatus[]> {
+    return invoke('apply_port_forwards');
+  },
+
+  async getStatus(): Promise<PortForwardStatus[]> {
+    return invoke('get_port_forward_status');
+  },
+};
+
+export default portForwardService;
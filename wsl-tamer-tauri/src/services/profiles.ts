@@ -1,7 +1,7 @@
 // Profile Service - Tauri IPC wrapper
 
 import { invoke } from '@tauri-apps/api/core';
-import type { WslProfile, AutomationRule, AppConfig } from '../types';
+import type { WslProfile, AutomationRule, AppConfig, OperationTimeouts, CompressionSettings } from '../types';
 
 export const profileService = {
   // Profiles
@@ -58,6 +58,18 @@ export const profileService = {
   async loadConfig(config: AppConfig): Promise<void> {
     return invoke('load_app_config', { config });
   },
+
+  async setOperationTimeouts(timeouts: OperationTimeouts): Promise<void> {
+    return invoke('set_operation_timeouts', { timeouts });
+  },
+
+  async getCompressionSettings(): Promise<CompressionSettings> {
+    return invoke('get_compression_settings');
+  },
+
+  async setCompressionSettings(settings: CompressionSettings): Promise<void> {
+    return invoke('set_compression_settings', { settings });
+  },
 };
 
 export default profileService;
@@ -0,0 +1,51 @@
+// Snapshot Service - Tauri IPC wrapper for the snapshot library
+
+import { invoke } from '@tauri-apps/api/core';
+import type { Job, Snapshot, SnapshotFormat, SnapshotPolicy } from '../types';
+
+export const snapshotService = {
+  async createSnapshot(
+    distro: string,
+    name: string,
+    format: SnapshotFormat = 'tar',
+    notes?: string
+  ): Promise<Job> {
+    return invoke('create_snapshot', { distro, name, format, notes });
+  },
+
+  async listSnapshots(distro?: string): Promise<Snapshot[]> {
+    return invoke('list_snapshots', { distro });
+  },
+
+  async deleteSnapshot(id: string): Promise<void> {
+    return invoke('delete_snapshot', { id });
+  },
+
+  async restoreSnapshot(id: string): Promise<Job> {
+    return invoke('restore_snapshot', { id });
+  },
+
+  async restoreSnapshotAsNew(id: string, newName: string, location: string): Promise<Job> {
+    return invoke('restore_snapshot_as_new', { id, newName, location });
+  },
+
+  // Scheduled snapshots
+  async getPolicies(): Promise<SnapshotPolicy[]> {
+    return invoke('get_snapshot_policies');
+  },
+
+  async savePolicy(policy: SnapshotPolicy): Promise<void> {
+    return invoke('save_snapshot_policy', { policy });
+  },
+
+  async deletePolicy(distro: string): Promise<void> {
+    return invoke('delete_snapshot_policy', { distro });
+  },
+
+  /** Apply the distribution's retention policy now; resolves with the deleted snapshots */
+  async pruneSnapshots(distro: string): Promise<Snapshot[]> {
+    return invoke('prune_snapshots', { distro });
+  },
+};
+
+export default snapshotService;
@@ -1,7 +1,7 @@
 // WSL Service - Tauri IPC wrapper
 
 import { invoke } from '@tauri-apps/api/core';
-import type { WslDistribution, WslStatus, OnlineDistribution, WslProfile, SystemMetrics, DistroMetrics } from '../types';
+import type { WslDistribution, WslStatus, OnlineDistribution, WslProfile, SystemMetrics, DistroMetrics, Job, MoveJournal, ExportManifest, ExportOptions, CompactionReport, DiskResize, MetricsHistory, MetricsHistorySettings, MetricsResolution, ProcessNode, ProcessSignal, ListeningPort, WslConfig, WslConfigSetting, ConfigDiagnostic, DistroConfig, ConfigTarget, ConfigVersion, DiffLine } from '../types';
 
 export const wslService = {
   // Distribution operations
@@ -46,22 +46,58 @@ export const wslService = {
   },
 
   // Import/Export
-  async exportDistribution(name: string, path: string): Promise<void> {
-    return invoke('export_distribution', { name, path });
+  // Long-running operations start a background job; see jobService.waitForJob
+  async exportDistribution(name: string, path: string, options?: ExportOptions): Promise<Job> {
+    return invoke('export_distribution', { name, path, options: options ?? null });
   },
 
-  async importDistribution(name: string, location: string, tarPath: string): Promise<void> {
+  async importDistribution(name: string, location: string, tarPath: string): Promise<Job> {
     return invoke('import_distribution', { name, location, tarPath });
   },
 
-  async cloneDistribution(source: string, newName: string, location: string): Promise<void> {
+  async verifyExport(path: string): Promise<Job> {
+    return invoke('verify_export', { path });
+  },
+
+  async getExportManifest(path: string): Promise<ExportManifest | null> {
+    return invoke('get_export_manifest', { path });
+  },
+
+  /** Trim, stop and compact a WSL 2 distribution's virtual disk (needs elevation) */
+  async compactDisk(name: string): Promise<Job> {
+    return invoke('compact_distribution_disk', { name });
+  },
+
+  async getCompactionReport(name: string): Promise<CompactionReport | null> {
+    return invoke('get_compaction_report', { name });
+  },
+
+  /** Grow a WSL 2 distribution's virtual disk; a dry run only returns the plan */
+  async resizeDisk(name: string, newSize: number, dryRun = false): Promise<DiskResize> {
+    return invoke('resize_distribution_disk', { name, newSize, dryRun });
+  },
+
+  async cloneDistribution(source: string, newName: string, location: string): Promise<Job> {
     return invoke('clone_distribution', { source, newName, location });
   },
 
-  async moveDistribution(name: string, newLocation: string): Promise<void> {
+  async moveDistribution(name: string, newLocation: string): Promise<Job> {
     return invoke('move_distribution', { name, newLocation });
   },
 
+  // Moves interrupted by a crash or shutdown
+  async getPendingMoves(): Promise<MoveJournal[]> {
+    return invoke('get_pending_moves');
+  },
+
+  async resumeMove(id: string): Promise<Job> {
+    return invoke('resume_move', { id });
+  },
+
+  async rollbackMove(id: string): Promise<Job> {
+    return invoke('rollback_move', { id });
+  },
+
   async unregisterDistribution(name: string): Promise<void> {
     return invoke('unregister_distribution', { name });
   },
@@ -71,7 +107,7 @@ export const wslService = {
     return invoke('get_online_distributions');
   },
 
-  async installDistribution(name: string): Promise<void> {
+  async installDistribution(name: string): Promise<Job> {
     return invoke('install_distribution', { name });
   },
 
@@ -89,6 +125,23 @@ export const wslService = {
     return invoke('write_wslconfig', { content });
   },
 
+  async getWslconfigSchema(): Promise<WslConfigSetting[]> {
+    return invoke('get_wslconfig_schema');
+  },
+
+  async getWslconfigTyped(): Promise<WslConfig> {
+    return invoke('get_wslconfig_typed');
+  },
+
+  async validateWslconfig(config: WslConfig): Promise<ConfigDiagnostic[]> {
+    return invoke('validate_wslconfig', { config });
+  },
+
+  // Returns diagnostics; the file is written either way
+  async saveWslconfigTyped(config: WslConfig): Promise<ConfigDiagnostic[]> {
+    return invoke('save_wslconfig_typed', { config });
+  },
+
   async applyProfile(profile: WslProfile): Promise<void> {
     return invoke('apply_wsl_profile', { profile });
   },
@@ -101,6 +154,29 @@ export const wslService = {
     return invoke('write_distro_config', { name, content });
   },
 
+  async getDistroConfigTyped(name: string): Promise<DistroConfig> {
+    return invoke('get_distro_config_typed', { name });
+  },
+
+  // Returns diagnostics; the file is written either way
+  async saveDistroConfigTyped(name: string, config: DistroConfig): Promise<ConfigDiagnostic[]> {
+    return invoke('save_distro_config_typed', { name, config });
+  },
+
+  // Configuration history: every write keeps the version it replaced
+  async getConfigHistory(target?: ConfigTarget): Promise<ConfigVersion[]> {
+    return invoke('get_config_history', { target: target ?? null });
+  },
+
+  // Without toId the version is compared with the current file
+  async diffConfigVersions(fromId: string, toId?: string): Promise<DiffLine[]> {
+    return invoke('diff_config_versions', { fromId, toId: toId ?? null });
+  },
+
+  async rollbackConfig(versionId: string): Promise<void> {
+    return invoke('rollback_config', { versionId });
+  },
+
   // Monitoring
   async getSystemMetrics(): Promise<SystemMetrics> {
     return invoke('get_system_metrics');
@@ -109,6 +185,33 @@ export const wslService = {
   async getDistroMetrics(): Promise<DistroMetrics[]> {
     return invoke('get_distro_metrics');
   },
+
+  /** Metrics recorded over the last `range` seconds */
+  async getMetricsHistory(range: number, resolution?: MetricsResolution): Promise<MetricsHistory> {
+    return invoke('get_metrics_history', { range, resolution });
+  },
+
+  async getMetricsHistorySettings(): Promise<MetricsHistorySettings> {
+    return invoke('get_metrics_history_settings');
+  },
+
+  async setMetricsHistorySettings(settings: MetricsHistorySettings): Promise<void> {
+    return invoke('set_metrics_history_settings', { settings });
+  },
+
+  /** Process tree of a running distribution */
+  async getDistroProcesses(name: string): Promise<ProcessNode[]> {
+    return invoke('get_distro_processes', { name });
+  },
+
+  async killDistroProcess(name: string, pid: number, signal: ProcessSignal = 'TERM'): Promise<void> {
+    return invoke('kill_distro_process', { name, pid, signal });
+  },
+
+  /** Listening ports of all running distributions */
+  async getListeningPorts(): Promise<ListeningPort[]> {
+    return invoke('get_listening_ports');
+  },
 };
 
 export default wslService;
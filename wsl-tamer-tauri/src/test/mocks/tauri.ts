@@ -59,14 +59,40 @@ export const invoke = vi.fn(async (command: string, args?: unknown) => {
         wslMemoryUsageMb: 2048,
         wslMemoryLimitMb: 8192,
         wslCpuPercent: 15.5,
+        guestCpuPercent: 42.0,
         totalSystemMemoryMb: 32768,
         availableSystemMemoryMb: 16384,
         timestamp: Date.now(),
       };
     case 'get_distro_metrics':
       return [
-        { name: 'Ubuntu', diskUsageMb: 5120, diskSizeMb: 10240, isRunning: true },
+        {
+          name: 'Ubuntu',
+          diskUsageMb: 5120,
+          diskSizeMb: 10240,
+          virtualSizeMb: 1048576,
+          reclaimableMb: 5120,
+          vhdPath: 'C:\\Users\\me\\AppData\\Local\\Ubuntu\\ext4.vhdx',
+          isRunning: true,
+        },
       ];
+    case 'get_metrics_history':
+      return { resolution: 'minute', points: [] };
+    case 'get_metrics_history_settings':
+      return { enabled: false, intervalSecs: 5, persist: false };
+    case 'get_distro_processes':
+    case 'get_listening_ports':
+    case 'get_port_forwards':
+    case 'get_port_forward_status':
+    case 'get_wslconfig_schema':
+    case 'validate_wslconfig':
+    case 'get_config_history':
+    case 'get_pending_moves':
+    case 'diff_config_versions':
+      return [];
+    case 'get_wslconfig_typed':
+    case 'get_distro_config_typed':
+      return {};
     default:
       console.warn(`Unmocked Tauri command: ${command}`, args);
       return null;
@@ -6,6 +6,22 @@ export interface WslDistribution {
   state: DistributionState;
   version: string;
   isDefault: boolean;
+  /** Metadata from the Lxss registry key, when it could be read */
+  registration?: DistroRegistration;
+}
+
+/** A distribution's entry under HKCU\Software\Microsoft\Windows\CurrentVersion\Lxss */
+export interface DistroRegistration {
+  /** Registry key GUID, including braces */
+  id: string;
+  name: string;
+  basePath: string;
+  vhdFileName?: string;
+  defaultUid?: number;
+  flags?: number;
+  version?: number;
+  packageFamilyName?: string;
+  defaultEnvironment: string[];
 }
 
 export type DistributionState = 'Running' | 'Stopped' | 'Installing' | 'Unknown';
@@ -36,7 +52,7 @@ export interface WslProfile {
   debugConsole: boolean;
 }
 
-export type TriggerType = 'Time' | 'Process' | 'PowerState' | 'Network';
+export type TriggerType = 'Time' | 'Process' | 'PowerState' | 'Network' | 'Metric';
 
 export interface AutomationRule {
   id: string;
@@ -45,6 +61,52 @@ export interface AutomationRule {
   triggerType: TriggerType;
   triggerValue: string;
   targetProfileId: string;
+  /** Condition of a 'Metric' trigger */
+  alert?: AlertCondition | null;
+  /** What a 'Metric' rule does when it fires */
+  actions?: AlertAction[];
+}
+
+// Resource alert types
+export type AlertMetric =
+  | 'vmmemPercentOfLimit'
+  | 'vmmemMb'
+  | 'hostAvailableMb'
+  | 'cpuPercent'
+  | 'guestCpuPercent'
+  | 'distroDiskPercent';
+
+export type AlertComparison = 'above' | 'below';
+
+export interface AlertCondition {
+  metric: AlertMetric;
+  comparison: AlertComparison;
+  threshold: number;
+  /** Seconds the threshold must stay crossed before firing */
+  forSecs: number;
+  /** How far back the value must go before the alert clears */
+  hysteresis: number;
+  /** Minimum seconds between two firings */
+  cooldownSecs: number;
+  /** For 'distroDiskPercent'; any distribution when null */
+  distro: string | null;
+}
+
+export type AlertAction =
+  | { type: 'notify' }
+  | { type: 'reclaimMemory' }
+  | { type: 'applyProfile'; profileId: string }
+  | { type: 'stopDistro'; name: string };
+
+export interface AlertEvent {
+  ruleId: string;
+  ruleName: string;
+  metric: AlertMetric;
+  value: number;
+  threshold: number;
+  distro: string | null;
+  firedAt: number;
+  message: string;
 }
 
 // Hardware types
@@ -78,6 +140,16 @@ export interface AppConfig {
   startWithWindows: boolean;
   startMinimized: boolean;
   theme: Theme;
+  operationTimeouts?: OperationTimeouts;
+  snapshotPolicies?: SnapshotPolicy[];
+  exportCompression?: CompressionSettings;
+}
+
+/** Time limits for long-running WSL operations, in seconds (0 = no limit) */
+export interface OperationTimeouts {
+  exportSecs: number;
+  importSecs: number;
+  installSecs: number;
 }
 
 export type Theme = 'Light' | 'Dark' | 'System';
@@ -100,6 +172,46 @@ export interface WslConfig {
   dnsTunneling?: boolean;
   autoProxy?: boolean;
   sparseVhd?: boolean;
+  kernel?: string;
+  kernelModules?: string;
+  vmIdleTimeout?: number;
+  defaultVhdSize?: string;
+  maxCrashDumpCount?: number;
+  vmSwitch?: string;
+  dnsProxy?: boolean;
+  ipv6?: boolean;
+  autoMemoryReclaim?: 'disabled' | 'gradual' | 'dropcache';
+  hostAddressLoopback?: boolean;
+  ignoredPorts?: number[];
+  bestEffortDnsParsing?: boolean;
+  dnsTunnelingIpAddress?: string;
+  initialAutoProxyTimeout?: number;
+}
+
+export type WslConfigSettingType = 'bool' | 'integer' | 'size' | 'path' | 'enum' | 'list' | 'duration' | 'string';
+
+// Schema entry for one .wslconfig key; `key` is also the WslConfig field name
+export interface WslConfigSetting {
+  section: string;
+  key: keyof WslConfig;
+  type: WslConfigSettingType;
+  default: string | null;
+  values: string[];
+  min: number | null;
+  max: number | null;
+  minWslVersion: string | null;
+  category: string;
+  description: string;
+}
+
+export type DiagnosticSeverity = 'info' | 'warning' | 'error';
+
+// A problem found in a configuration file
+export interface ConfigDiagnostic {
+  severity: DiagnosticSeverity;
+  key: string | null;
+  message: string;
+  suggestion: string | null;
 }
 
 export interface DistroConfig {
@@ -115,7 +227,40 @@ export interface DistroConfig {
   appendWindowsPath?: boolean;
   // Automount section
   automountEnabled?: boolean;
+  automountRoot?: string;
   automountOptions?: string;
+  mountFsTab?: boolean;
+  // User section
+  defaultUser?: string;
+  // GPU section
+  gpuEnabled?: boolean;
+}
+
+// Configuration file a stored version belongs to
+export type ConfigTarget =
+  | { kind: 'wslconfig' }
+  | { kind: 'distroConfig'; distro: string };
+
+// What made a change to a configuration file
+export type ConfigOrigin =
+  | { type: 'manual' }
+  | { type: 'profile'; profileId: string; profileName: string }
+  | { type: 'automationRule'; ruleId: string; ruleName: string }
+  | { type: 'rollback'; versionId: string };
+
+// Earlier version of a configuration file, saved before a write replaced it
+export interface ConfigVersion {
+  id: string;
+  target: ConfigTarget;
+  replacedAt: number;
+  replacedBy: ConfigOrigin;
+  existed: boolean;
+  sizeBytes: number;
+}
+
+export interface DiffLine {
+  kind: 'unchanged' | 'added' | 'removed';
+  text: string;
 }
 
 // Monitoring types
@@ -134,7 +279,10 @@ export interface SystemMetrics {
   vmmemMemoryMb: number;
   wslMemoryLimitMb: number;
   wslMemory: WslMemoryBreakdown | null;
+  /** Share of all host cores used by the VM since the previous sample */
   wslCpuPercent: number;
+  /** Busy share of the VM's cores, from /proc/stat (null when stopped) */
+  guestCpuPercent: number | null;
   totalSystemMemoryMb: number;
   availableSystemMemoryMb: number;
   timestamp: number;
@@ -142,10 +290,269 @@ export interface SystemMetrics {
 
 export interface DistroMetrics {
   name: string;
-  diskUsageMb: number;
+  /** Used inside the guest file system (last known if stopped; null if never measured) */
+  diskUsageMb: number | null;
+  /** Size of the VHDX file on the host */
+  diskSizeMb: number;
+  /** Maximum size of the virtual disk */
+  virtualSizeMb: number;
+  /** Host space a compaction could give back */
+  reclaimableMb: number | null;
+  vhdPath: string | null;
+  isRunning: boolean;
+}
+
+export interface MetricsHistorySettings {
+  enabled: boolean;
+  /** Seconds between samples (1–600) */
+  intervalSecs: number;
+  /** Keep the history across restarts */
+  persist: boolean;
+}
+
+/** 'second': kept for 10 minutes, 'minute': averages kept for 24 hours */
+export type MetricsResolution = 'second' | 'minute';
+
+export interface DistroMetricsPoint {
+  name: string;
+  diskUsageMb: number | null;
   diskSizeMb: number;
   isRunning: boolean;
 }
 
+export interface MetricsPoint {
+  /** Unix seconds (start of the minute for per-minute averages) */
+  timestamp: number;
+  vmmemMemoryMb: number;
+  wslMemoryUsedMb: number | null;
+  wslCpuPercent: number;
+  guestCpuPercent: number | null;
+  availableSystemMemoryMb: number;
+  distros: DistroMetricsPoint[];
+}
+
+export interface MetricsHistory {
+  resolution: MetricsResolution;
+  /** Oldest first */
+  points: MetricsPoint[];
+}
+
+/** A process inside a distribution */
+export interface DistroProcess {
+  pid: number;
+  ppid: number;
+  user: string;
+  name: string;
+  command: string;
+  state: string;
+  /** CPU time over the process's lifetime */
+  cpuPercent: number;
+  rssBytes: number;
+  threads: number;
+  /** Unix timestamp (seconds) */
+  startTime: number;
+  cgroup: string | null;
+}
+
+export interface ProcessNode extends DistroProcess {
+  children: ProcessNode[];
+}
+
+export type ProcessSignal = 'TERM' | 'KILL' | 'INT' | 'HUP' | 'STOP' | 'CONT';
+
+export type SocketProtocol = 'tcp' | 'udp';
+
+/** How Windows can reach a port opened inside a distribution */
+export type WindowsReachability = 'localhost' | 'vmAddress' | 'unreachable';
+
+export interface ListeningPort {
+  distro: string;
+  protocol: SocketProtocol;
+  /** `0.0.0.0` / `::` when bound to all interfaces */
+  address: string;
+  port: number;
+  pid: number | null;
+  process: string | null;
+  reachability: WindowsReachability;
+}
+
+/** A Windows port proxied into a distribution (NAT networking) */
+export interface PortForwardRule {
+  id: string;
+  enabled: boolean;
+  /** `0.0.0.0` for all interfaces */
+  listenAddress: string;
+  listenPort: number;
+  distro: string;
+  targetPort: number;
+  openFirewall: boolean;
+}
+
+export interface PortForwardStatus {
+  ruleId: string;
+  /** Address the Windows port is proxied to, if a proxy exists */
+  connectAddress: string | null;
+  /** The proxy points at the distribution's current address */
+  active: boolean;
+}
+
+export type CompactionMethod = 'optimizeVhd' | 'diskpart';
+
+/** Outcome of compacting a distribution's virtual disk */
+export interface CompactionReport {
+  distro: string;
+  vhdPath: string;
+  method: CompactionMethod;
+  /** fstrim succeeded inside the distribution */
+  trimmed: boolean;
+  sizeBefore: number;
+  sizeAfter: number;
+  reclaimedBytes: number;
+  startedAt: number;
+  finishedAt: number;
+}
+
+export type ResizeMethod = 'resizeVhd' | 'diskpart';
+
+/** Validated plan for growing a distribution's virtual disk */
+export interface ResizePlan {
+  distro: string;
+  vhdPath: string;
+  method: ResizeMethod;
+  currentSize: number;
+  newSize: number;
+  fileSize: number;
+  hostFreeBytes: number | null;
+  /** Steps in the order they run */
+  steps: string[];
+  warnings: string[];
+}
+
+export interface DiskResize {
+  plan: ResizePlan;
+  /** Absent for a dry run */
+  job: Job | null;
+}
+
+// Background job types
+export type JobKind = 'export' | 'import' | 'clone' | 'move' | 'moveRollback' | 'install' | 'snapshot' | 'restore' | 'verify' | 'compact' | 'resize';
+export type JobPhase =
+  | 'queued'
+  | 'exporting'
+  | 'importing'
+  | 'installing'
+  | 'verifying'
+  | 'checksumming'
+  | 'unregistering'
+  | 'trimming'
+  | 'stopping'
+  | 'compacting'
+  | 'resizing'
+  | 'expandingFilesystem'
+  | 'cleaningUp';
+export type JobStatus = 'running' | 'completed' | 'failed' | 'cancelled';
+
+export interface Job {
+  id: string;
+  kind: JobKind;
+  target: string;
+  status: JobStatus;
+  phase: JobPhase;
+  bytesWritten: number;
+  startedAt: number;
+  finishedAt?: number;
+  error?: { code: string; message: string };
+}
+
+/** Persisted state of a move interrupted mid-way */
+export interface MoveJournal {
+  id: string;
+  distro: string;
+  newLocation: string;
+  originalLocation?: string;
+  tarPath: string;
+  step: 'exporting' | 'exported' | 'unregistered' | 'imported';
+  startedAt: number;
+  updatedAt: number;
+}
+
+export type Compression = 'none' | 'gzip' | 'zstd' | 'xz';
+
+/** Default export format and per-codec compression levels */
+export interface CompressionSettings {
+  defaultFormat: Compression;
+  gzipLevel: number;
+  zstdLevel: number;
+  xzLevel: number;
+}
+
+/** Per-export overrides; compression defaults to the file extension */
+export interface ExportOptions {
+  compression?: Compression;
+  level?: number;
+}
+
+/** Sidecar manifest written next to an exported tarball */
+export interface ExportManifest {
+  distro: string;
+  fileName: string;
+  sizeBytes: number;
+  sha256: string;
+  createdAt: number;
+  compression: Compression;
+  /** Size of the tar before compression */
+  uncompressedBytes: number;
+}
+
+// Snapshot types
+export type SnapshotFormat = 'tar' | 'vhd';
+
+/** Manifest of a snapshot in the managed library */
+export interface Snapshot {
+  id: string;
+  name: string;
+  distro: string;
+  createdAt: number;
+  format: SnapshotFormat;
+  fileName: string;
+  sizeBytes: number;
+  sha256: string;
+  wslVersion: string;
+  notes?: string;
+  /** Taken by the scheduler; only automatic snapshots are pruned */
+  automatic: boolean;
+}
+
+/** Which automatic snapshots to keep (unset rules are ignored) */
+export interface RetentionPolicy {
+  keepLast?: number;
+  keepDaily?: number;
+  keepWeekly?: number;
+  maxTotalBytes?: number;
+}
+
+/** Automatic snapshot schedule for one distribution */
+export interface SnapshotPolicy {
+  distro: string;
+  enabled: boolean;
+  /** Cron expression, e.g. "0 3 * * *" */
+  schedule: string;
+  format: SnapshotFormat;
+  retention: RetentionPolicy;
+  lastRun?: number;
+}
+
 // Page type for navigation
 export type Page = 'general' | 'distributions' | 'profiles' | 'configuration' | 'hardware' | 'automation' | 'settings' | 'about';
+
+/** Structured error returned by backend commands */
+export interface TamerError {
+  code: string;
+  message: string;
+  program?: string;
+  command?: string;
+  exitCode?: number | null;
+  stderr?: string;
+  name?: string;
+  cause?: TamerError;
+}
@@ -1,3 +1,17 @@
+import type { TamerError } from '../types';
+
+/**
+ * Check whether a value is a structured error returned by a backend command.
+ */
+export function isTamerError(err: unknown): err is TamerError {
+  return (
+    typeof err === 'object' &&
+    err !== null &&
+    typeof (err as TamerError).code === 'string' &&
+    typeof (err as TamerError).message === 'string'
+  );
+}
+
 /**
  * Convert unknown error values to a human-readable message string.
  * Replaces `catch (err: any)` patterns throughout the codebase.
@@ -5,5 +19,6 @@
 export function toErrorMessage(err: unknown): string {
   if (err instanceof Error) return err.message;
   if (typeof err === 'string') return err;
+  if (isTamerError(err)) return err.message;
   return String(err);
 }
@@ -1,3 +1,5 @@
+import type { Compression, ExportManifest } from '../types';
+
 /**
  * Format a byte count to a human-readable string (e.g., "1.5 GB").
  * Returns "0 B" for falsy, zero, negative, or non-finite inputs.
@@ -9,3 +11,23 @@ export function formatBytes(bytes: number): string {
   const i = Math.floor(Math.log(bytes) / Math.log(k));
   return `${(bytes / Math.pow(k, i)).toFixed(1)} ${sizes[i]}`;
 }
+
+/** File extensions accepted for exported distributions */
+export const EXPORT_EXTENSIONS = ['tar', 'tar.gz', 'tgz', 'tar.zst', 'tar.xz'];
+
+/** File extension for an export in the given format */
+export function exportExtension(compression: Compression): string {
+  return { none: 'tar', gzip: 'tar.gz', zstd: 'tar.zst', xz: 'tar.xz' }[compression];
+}
+
+/**
+ * Describe the size of a finished export, e.g. "1.2 GB (4.1x smaller)".
+ */
+export function formatExportSize(manifest: ExportManifest): string {
+  const size = formatBytes(manifest.sizeBytes);
+  if (manifest.compression === 'none' || !manifest.sizeBytes || !manifest.uncompressedBytes) {
+    return size;
+  }
+  const ratio = manifest.uncompressedBytes / manifest.sizeBytes;
+  return `${size} (${ratio.toFixed(1)}x smaller)`;
+}